@@ -0,0 +1,70 @@
+// Writes the output with many files in flight at once, enabled with the `async-io` feature.
+//
+// On network filesystems (NFS, FUSE mounts) every file write is a round trip to the server, so
+// writing the thousands of small artifacts of a large log one after the other can take longer
+// than parsing it.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context;
+use fxhash::FxHashSet;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::types::ParseOutput;
+
+/// Writes `output` under `output_dir` with up to `max_in_flight` files being written at once
+pub fn write_output(
+    output: ParseOutput,
+    output_dir: &Path,
+    max_in_flight: usize,
+) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(write_output_async(output, output_dir, max_in_flight))
+}
+
+pub async fn write_output_async(
+    output: ParseOutput,
+    output_dir: &Path,
+    max_in_flight: usize,
+) -> anyhow::Result<()> {
+    let semaphore = Arc::new(Semaphore::new(max_in_flight.max(1)));
+
+    // Create each directory once up front, rather than before every file in it
+    let dirs: FxHashSet<PathBuf> = output
+        .iter()
+        .filter_map(|(filename, _)| output_dir.join(filename).parent().map(Path::to_path_buf))
+        .collect();
+    let mut tasks = JoinSet::new();
+    for dir in dirs {
+        let permit = semaphore.clone().acquire_owned().await?;
+        tasks.spawn(async move {
+            let result = tokio::fs::create_dir_all(&dir)
+                .await
+                .with_context(|| format!("Failed to create {}", dir.display()));
+            drop(permit);
+            result
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        result??;
+    }
+
+    for (filename, content) in output {
+        let path = output_dir.join(filename);
+        let permit = semaphore.clone().acquire_owned().await?;
+        tasks.spawn(async move {
+            let result = tokio::fs::write(&path, content)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()));
+            drop(permit);
+            result
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        result??;
+    }
+    Ok(())
+}
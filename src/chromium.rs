@@ -0,0 +1,117 @@
+use fxhash::{FxHashMap, FxHashSet};
+use serde_json::{json, Value};
+
+use crate::types::{FxIndexMap, Stats};
+
+/// Make chromium_events.json loadable in Perfetto, which rejects the whole file over a single bad
+/// event.  Events that can't be repaired and exact duplicates are dropped, events missing `ph`,
+/// `name`, `pid` or `tid` get defaults, and negative timestamps and durations, as well as ends of
+/// durations before their beginning, are clamped.
+pub fn repair_chromium_events(events: &mut Vec<Value>, stats: &mut Stats) {
+    let mut seen: FxHashSet<String> = FxHashSet::default();
+    // Timestamps of the unclosed "B" events of each (pid, tid)
+    let mut open: FxHashMap<(String, String), Vec<f64>> = FxHashMap::default();
+    events.retain_mut(|event| {
+        let Some(obj) = event.as_object_mut() else {
+            stats.chromium_events_dropped += 1;
+            return false;
+        };
+        let mut repaired = false;
+        if !obj.get("ph").is_some_and(|ph| ph.is_string()) {
+            // Complete events are the only ones with a duration
+            let ph = if obj.contains_key("dur") { "X" } else { "i" };
+            obj.insert("ph".to_string(), json!(ph));
+            repaired = true;
+        }
+        let ph = obj["ph"].as_str().unwrap_or_default().to_string();
+        // Metadata events don't need a timestamp
+        if ph != "M" {
+            match obj.get("ts").and_then(|ts| ts.as_f64()) {
+                None => {
+                    stats.chromium_events_dropped += 1;
+                    return false;
+                }
+                Some(ts) if ts < 0.0 => {
+                    obj.insert("ts".to_string(), json!(0));
+                    repaired = true;
+                }
+                Some(_) => {}
+            }
+        }
+        if obj
+            .get("dur")
+            .and_then(|dur| dur.as_f64())
+            .is_some_and(|dur| dur < 0.0)
+        {
+            obj.insert("dur".to_string(), json!(0));
+            repaired = true;
+        }
+        for (key, default) in [
+            ("name", json!("(unnamed)")),
+            ("pid", json!(0)),
+            ("tid", json!(0)),
+        ] {
+            if obj.get(key).is_none_or(|v| v.is_null()) {
+                obj.insert(key.to_string(), default);
+                repaired = true;
+            }
+        }
+        if !seen.insert(event.to_string()) {
+            stats.chromium_events_deduplicated += 1;
+            return false;
+        }
+
+        let Some(obj) = event.as_object_mut() else {
+            return false;
+        };
+        let track = (obj["pid"].to_string(), obj["tid"].to_string());
+        let ts = obj.get("ts").and_then(|ts| ts.as_f64()).unwrap_or(0.0);
+        match ph.as_str() {
+            "B" => open.entry(track).or_default().push(ts),
+            "E" => {
+                if let Some(begin) = open.get_mut(&track).and_then(|stack| stack.pop()) {
+                    if ts < begin {
+                        obj.insert("ts".to_string(), json!(begin));
+                        repaired = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+        stats.chromium_events_repaired += repaired as u64;
+        true
+    });
+}
+
+/// Splits (repaired) events by the compile id in their args, e.g. "0/0" or "!0/1/0_1".  Ends of
+/// durations without a compile id go with their beginning, so that every sub-trace is balanced.
+pub fn chromium_events_by_compile_id(events: &[Value]) -> FxIndexMap<String, Vec<Value>> {
+    let mut by_compile_id: FxIndexMap<String, Vec<Value>> = FxIndexMap::default();
+    // Compile ids of the unclosed "B" events of each (pid, tid)
+    let mut open: FxHashMap<(String, String), Vec<Option<String>>> = FxHashMap::default();
+    for event in events {
+        let compile_id = event["args"]["compile_id"]
+            .as_str()
+            .filter(|c| *c != "None")
+            .map(|c| c.to_string());
+        let track = (event["pid"].to_string(), event["tid"].to_string());
+        let compile_id = match event["ph"].as_str() {
+            Some("B") => {
+                open.entry(track).or_default().push(compile_id.clone());
+                compile_id
+            }
+            Some("E") => {
+                let begin = open.get_mut(&track).and_then(|stack| stack.pop()).flatten();
+                compile_id.or(begin)
+            }
+            _ => compile_id,
+        };
+        if let Some(compile_id) = compile_id {
+            by_compile_id
+                .entry(compile_id)
+                .or_default()
+                .push(event.clone());
+        }
+    }
+    by_compile_id
+}
@@ -1,24 +1,143 @@
-use clap::Parser;
+use clap::{Args, CommandFactory, Parser, Subcommand};
 
 use anyhow::{bail, Context};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::hash::BuildHasher;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use fxhash::{FxHashMap, FxHashSet};
 use tlparse::{
-    analyze_graph_runtime_deltas, generate_multi_rank_html, parse_path,
-    read_chromium_events_with_pid, ArtifactFlags, Diagnostics, DivergenceFlags, DivergenceGroup,
-    ParseConfig, RankMetaData,
+    analyze_compile_time_skew, analyze_graph_memory_deltas, analyze_graph_runtime_deltas,
+    expand_log_paths, extract_artifact, generate_compare_runs_html,
+    generate_cross_rank_search_html, generate_multi_rank_html, load_template_overrides,
+    parse_path_to_directory, parse_path_with_summary, prometheus_metrics,
+    read_chromium_events_with_pid, theme_css, write_output, ArtifactFlags, CompileId,
+    CompileSummary, CompileThresholds, Diagnostics, DirectoryLayout, DivergenceFlags,
+    DivergenceGroup, GraphContentDivergence, MeasuredDurations, ParseConfig, PayloadVerification,
+    Progress, RankParseSummary, RankStats, RankSummary, SourceRoot, Theme, TrainingSteps,
 };
 
+/// Parse TORCH_LOG logs produced by PyTorch torch.compile.  `tlparse LOG` is short for
+/// `tlparse parse LOG`, `tlparse DIR --all-ranks-html` for `tlparse multi-rank DIR`, and
+/// `tlparse --runs LOG LOG... --compare-runs` for `tlparse compare-runs LOG LOG...`.
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
-    path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(required_unless_present_any = ["manifest", "runs"])]
+    path: Option<PathBuf>,
+    /// More fragments of the same log, see `tlparse parse --help`
+    more_paths: Vec<PathBuf>,
     /// Parse most recent log
     #[arg(long)]
     latest: bool,
+    /// For export specific logs
+    #[arg(short, long)]
+    export: bool,
+    /// Parse all ranks and create a unified multi-rank report
+    #[arg(long)]
+    all_ranks_html: bool,
+    /// With --compare-runs, logs of the same program from different runs
+    #[arg(long, num_args = 2.., requires = "compare_runs", conflicts_with = "path")]
+    runs: Vec<PathBuf>,
+    /// Parse the logs of --runs and create a report comparing their compilations
+    #[arg(long, requires = "runs", conflicts_with = "all_ranks_html")]
+    compare_runs: bool,
+    #[command(flatten)]
+    report: ReportArgs,
+    #[command(flatten)]
+    checks: CheckArgs,
+    #[command(flatten)]
+    ranks: RankArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parse a log into an HTML report
+    Parse(ParseArgs),
+    /// Parse the logs of all ranks of a distributed job into a unified report
+    MultiRank(MultiRankArgs),
+    /// Parse logs of the same program from different runs, e.g. nightly runs, into a report that
+    /// lines up their compilations
+    CompareRuns(CompareRunsArgs),
+    /// Compare the compile summaries of two runs, each a log or a compile_summary.json
+    Diff { baseline: PathBuf, path: PathBuf },
+    /// Print the headline numbers of a log, e.g. over SSH, without writing a report
+    Summarize { path: PathBuf },
+    /// Run a single parser over a log, e.g. `tlparse extract LOG dynamo_output_graph
+    /// --compile-id 3/1`, and print what it outputs without writing a report
+    Extract {
+        path: PathBuf,
+        /// The parser to run, e.g. dynamo_output_graph or inductor_output_code
+        parser: String,
+        /// Only parse the records of this compile id, e.g. 3/1 for any of its attempts or 3/1_2
+        #[arg(long)]
+        compile_id: Option<CompileId>,
+        /// Write the outputs under this directory rather than printing them
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Serve a report over HTTP, e.g. to look at it through an SSH tunnel
+    Serve {
+        /// Report directory
+        #[arg(default_value = "tl_out")]
+        dir: PathBuf,
+        #[arg(long, default_value_t = 8000)]
+        port: u16,
+    },
+    /// Print a completion script for a shell, e.g. `tlparse completions bash > ~/.tlparse.bash`
+    Completions { shell: clap_complete::Shell },
+}
+
+#[derive(Args)]
+struct ParseArgs {
+    /// Log to parse, or a pattern like 'logs/trace.*.log' of the fragments of one log
+    path: PathBuf,
+    /// More fragments of the same log, e.g. rotated files, which are parsed together with PATH
+    /// in the order they were written
+    more_paths: Vec<PathBuf>,
+    /// Parse most recent log in the directory PATH
+    #[arg(long)]
+    latest: bool,
+    /// For export specific logs
+    #[arg(short, long)]
+    export: bool,
+    #[command(flatten)]
+    report: ReportArgs,
+    #[command(flatten)]
+    checks: CheckArgs,
+}
+
+#[derive(Args)]
+struct MultiRankArgs {
+    /// Directory of rank logs
+    #[arg(required_unless_present = "manifest")]
+    path: Option<PathBuf>,
+    #[command(flatten)]
+    report: ReportArgs,
+    #[command(flatten)]
+    ranks: RankArgs,
+}
+
+#[derive(Args)]
+struct CompareRunsArgs {
+    /// Logs of the runs, oldest first
+    #[arg(num_args = 2.., required = true)]
+    runs: Vec<PathBuf>,
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+/// Options of the report, shared by all commands that write one
+#[derive(Args)]
+struct ReportArgs {
     /// Output directory, defaults to `tl_out`
     #[arg(short, default_value = "tl_out")]
     out: PathBuf,
@@ -46,75 +165,800 @@ pub struct Cli {
     /// Enabiling this option will enforce output as plain text for easier diffing
     #[arg(short, long)]
     plain_text: bool,
-    /// For export specific logs
-    #[arg(short, long)]
-    export: bool,
-    /// For inductor provenance tracking highlighter
-    #[arg(short, long)]
+    /// Expect inductor provenance tracking: the highlighter is generated if the log has
+    /// provenance node mappings anyway, and otherwise index.html says how to log them
+    #[arg(short, long, conflicts_with = "no_inductor_provenance")]
     inductor_provenance: bool,
-    /// Parse all ranks and create a unified multi-rank report
+    /// Never generate the inductor provenance tracking highlighter
     #[arg(long)]
-    all_ranks_html: bool,
+    no_inductor_provenance: bool,
+    /// Directory of templates (e.g. index.html, compilation_metrics.html) that replace the
+    /// built-in templates of the same name
+    #[arg(long)]
+    template_dir: Option<PathBuf>,
+    /// Color theme for the report: light, dark or high-contrast
+    #[arg(long, default_value = "light")]
+    theme: Theme,
+    /// CSS file appended to the selected theme, for further customizing the report
+    #[arg(long)]
+    theme_css: Option<PathBuf>,
+    /// Don't check payloads against their md5 hash.  Saves time on very large logs
+    #[arg(long, conflicts_with = "verify_payloads_every")]
+    no_verify_payloads: bool,
+    /// Put payloads interleaved with other threads' log lines back together, and write payloads
+    /// that are still malformed, e.g. truncated by a crash, to corrupt_payloads/ instead of
+    /// parsing them
+    #[arg(long, conflicts_with = "no_verify_payloads")]
+    recover_payloads: bool,
+    /// Only check every Nth payload against its md5 hash
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    verify_payloads_every: Option<u32>,
+    /// Number output files by their position in the log, like older versions of tlparse did
+    #[arg(long)]
+    legacy_artifact_numbering: bool,
+    /// Write identical payloads (e.g. the same graph across restarts) only once, under blobs/
+    #[arg(long)]
+    dedup_payloads: bool,
+    /// Only keep the artifacts of the N most expensive compile ids, summarizing the rest in
+    /// overflow_compile_ids.html
+    #[arg(long)]
+    max_compile_ids: Option<usize>,
+    /// Stop parsing at the first compilation that failed, and only write its artifacts and
+    /// first_failure.html, e.g. for CI jobs that only need to surface the error
+    #[arg(long)]
+    first_failure: bool,
+    /// Write the filenames of stack frames into raw.jsonl, instead of their index in the string
+    /// table on its first line, so that it can be read on its own
+    #[arg(long)]
+    raw_jsonl_filenames: bool,
+    /// Cut renderings over N bytes, e.g. of huge output code, short with a link to the full
+    /// payload, so that the pages stay small enough for browsers
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_artifact_bytes: Option<u64>,
+    /// List at most N compile ids on index.html, continuing on index_1.html, index_2.html, ...
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    index_page_size: Option<u64>,
+    /// Write up to N output files at once, which is much faster on network filesystems
+    #[cfg(feature = "async-io")]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    parallel_writes: Option<u64>,
+    /// Upload the report to this S3 location once it's written, e.g. s3://bucket/reports/1234,
+    /// and print the URL to share
+    #[cfg(feature = "upload")]
+    #[arg(long)]
+    upload: Option<String>,
+    /// Don't run these parsers, e.g. inductor_output_code to skip highlighting generated code
+    #[arg(long, value_delimiter = ',')]
+    disable_parser: Vec<String>,
+    /// Only run these parsers, e.g. dynamo_guards,compilation_metrics
+    #[arg(long, value_delimiter = ',', conflicts_with = "disable_parser")]
+    enable_only: Option<Vec<String>>,
+    /// Profiler trace (e.g. Kineto's chrome trace JSON) of the compiled model, to compare
+    /// Inductor's runtime estimations against the measured kernel durations
+    #[arg(long)]
+    profile_trace: Option<PathBuf>,
+    /// JSON file of when each training step started, e.g. exported from wandb or MLflow, to
+    /// annotate compilations and failures with the step they happened at
+    #[arg(long)]
+    steps: Option<PathBuf>,
+    /// When to show progress bars: auto (if stderr is a terminal), always or never
+    #[arg(long, default_value = "auto")]
+    progress: Progress,
+    /// Also write FX graphs of up to 200 nodes as Graphviz .dot files, e.g. to render with
+    /// `dot -Tsvg`
+    #[arg(long)]
+    graph_dot: bool,
+    /// How to name the directories of compile ids: flat (e.g. -_0_0_0/) or nested (e.g.
+    /// frame_0/compile_0/attempt_0/)
+    #[arg(long, default_value = "flat")]
+    directory_layout: DirectoryLayout,
+    /// Fail if compile_directory.json, compile_summary.json or any other JSON file of the output
+    /// doesn't match its schema, e.g. in CI of tools that read them
+    #[arg(long)]
+    validate_output: bool,
+    /// Also write the compilations as OpenTelemetry spans to otel_spans.json, with their dynamo,
+    /// AOT and inductor phases nested under them
+    #[arg(long)]
+    otel_spans: bool,
+    /// Send the spans of --otel-spans to this OTLP/HTTP collector, e.g. http://localhost:4318,
+    /// using curl
+    #[cfg(feature = "otlp")]
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+    /// URL the report will be hosted under, e.g. https://bucket.s3.amazonaws.com/reports/1234/,
+    /// to make the links of its pages absolute
+    #[arg(long)]
+    url_prefix: Option<String>,
+    /// Make the report the same on every run over the same log, e.g. for snapshot tests: log
+    /// timestamps, which lack a year, get a fixed one, and --sample-ranks picks the same ranks
+    #[arg(long)]
+    deterministic: bool,
+    /// Local checkout of the logged code, to show the source around each frame of user stacks
+    /// on the compilation metrics and guard pages: DIR, to look files up by the tail of their
+    /// path, or PREFIX=DIR, to read files under PREFIX, where the code was on the machine that
+    /// wrote the log, from under DIR.  Can be repeated
+    #[arg(long)]
+    source_root: Vec<SourceRoot>,
+    /// Commit the run is at, recorded in trend_record.json
+    #[arg(long)]
+    git_sha: Option<String>,
+    /// Id of the job of the run, e.g. the CI job, recorded in trend_record.json
+    #[arg(long)]
+    job_id: Option<String>,
+    /// Also append trend_record.json, a one-line snapshot of the run's compile metrics, to this
+    /// JSONL file, e.g. to load the trend of nightly runs into a dashboard.  With several ranks
+    /// or runs, the record of each is appended
+    #[arg(long)]
+    append_trend: Option<PathBuf>,
+    /// Also write the compilation metrics to this file as CSV, one row per compilation with
+    /// every numeric field as a column, e.g. to analyze compile times in pandas.  With several
+    /// ranks or runs, each gets its own file, e.g. metrics_rank_0.csv for metrics.csv
+    #[arg(long)]
+    metrics_csv: Option<PathBuf>,
+    /// Don't show progress bars, same as --progress never
+    #[arg(short, long, conflicts_with = "progress")]
+    quiet: bool,
+}
+
+/// Compile health checks, for single logs
+#[derive(Args)]
+struct CheckArgs {
+    /// Also write a Markdown summary of the compilations to this file, e.g. for CI to post as a
+    /// PR comment
+    #[arg(long)]
+    report_md: Option<PathBuf>,
+    /// Exit with an error if there are more than N recompiles
+    #[arg(long)]
+    fail_on_recompiles: Option<u64>,
+    /// Exit with an error if there are more than N graph breaks
+    #[arg(long)]
+    fail_on_graph_breaks: Option<u64>,
+    /// Exit with an error if more than this fraction (0 to 1) of cache lookups miss
+    #[arg(long)]
+    fail_on_cache_miss_rate: Option<f64>,
+    /// compile_summary.json of an earlier run to compare this one against
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Exit with an error if compiling takes more than this fraction longer than in --baseline,
+    /// e.g. 0.1 for 10%
+    #[arg(long, requires = "baseline")]
+    fail_on_compile_time_regression: Option<f64>,
+    /// Exit with an error if there are more than N more compiles than in --baseline
+    #[arg(long, requires = "baseline")]
+    fail_on_compile_count_increase: Option<u64>,
+}
+
+impl CheckArgs {
+    fn is_empty(&self) -> bool {
+        self.report_md.is_none() && self.baseline.is_none() && self.thresholds().is_empty()
+    }
+
+    fn thresholds(&self) -> CompileThresholds {
+        CompileThresholds {
+            recompiles: self.fail_on_recompiles,
+            graph_breaks: self.fail_on_graph_breaks,
+            cache_miss_rate: self.fail_on_cache_miss_rate,
+            compile_time_regression: self.fail_on_compile_time_regression,
+            compile_count_increase: self.fail_on_compile_count_increase,
+        }
+    }
+}
+
+/// Which ranks to parse, for multi-rank reports
+#[derive(Args)]
+struct RankArgs {
+    /// With --all-ranks-html, a JSON file mapping rank numbers to the paths of their logs, e.g.
+    /// {"0": "/mnt/a/rank0.log", "1": "rank1.log"}, instead of a directory of rank logs.
+    /// Relative paths are relative to the manifest.
+    #[arg(long, conflicts_with = "path")]
+    manifest: Option<PathBuf>,
+    /// With --all-ranks-html, only parse these ranks, e.g. 0,7,63
+    #[arg(long, value_delimiter = ',')]
+    ranks: Option<Vec<u32>>,
+    /// With --all-ranks-html, only parse N randomly chosen ranks
+    #[arg(long, conflicts_with = "ranks")]
+    sample_ranks: Option<usize>,
+    /// With --all-ranks-html, highlight ranks whose total compile time deviates from the median
+    /// by more than this fraction
+    #[arg(long, default_value_t = 0.2)]
+    compile_time_skew_threshold: f64,
+}
+
+impl RankArgs {
+    // The threshold has a default, so it can't tell whether it was passed
+    fn is_empty(&self) -> bool {
+        self.manifest.is_none() && self.ranks.is_none() && self.sample_ranks.is_none()
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
-
-    // Early validation of incompatible flags
-    if cli.all_ranks_html && cli.latest {
-        bail!("--latest cannot be used with --all-ranks-html");
+    match cli.command {
+        Some(Command::Parse(args)) => parse(args),
+        Some(Command::MultiRank(args)) => multi_rank(args),
+        Some(Command::CompareRuns(args)) => compare_runs(args),
+        Some(Command::Diff { baseline, path }) => diff(&baseline, &path),
+        Some(Command::Summarize { path }) => summarize(&path),
+        Some(Command::Extract {
+            path,
+            parser,
+            compile_id,
+            out,
+        }) => extract(&path, &parser, compile_id.as_ref(), out.as_deref()),
+        Some(Command::Serve { dir, port }) => serve(&dir, port),
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "tlparse",
+                &mut std::io::stdout(),
+            );
+            Ok(())
+        }
+        // The flat invocation of older versions
+        None if cli.all_ranks_html => {
+            if cli.latest {
+                bail!("--latest cannot be used with --all-ranks-html");
+            }
+            if !cli.checks.is_empty() || cli.export {
+                bail!(
+                    "--report-md, --baseline and --fail-on-* cannot be used with --all-ranks-html or --export"
+                );
+            }
+            if !cli.more_paths.is_empty() {
+                bail!("--all-ranks-html takes a single directory of rank logs");
+            }
+            multi_rank(MultiRankArgs {
+                path: cli.path,
+                report: cli.report,
+                ranks: cli.ranks,
+            })
+        }
+        None if cli.compare_runs => {
+            if cli.latest || cli.export || !cli.more_paths.is_empty() {
+                bail!("--compare-runs takes the logs of the runs with --runs");
+            }
+            if !cli.checks.is_empty() || !cli.ranks.is_empty() {
+                bail!(
+                    "--report-md, --baseline, --fail-on-* and rank options cannot be used with --compare-runs"
+                );
+            }
+            compare_runs(CompareRunsArgs {
+                runs: cli.runs,
+                report: cli.report,
+            })
+        }
+        None => {
+            if !cli.ranks.is_empty() {
+                bail!("--manifest, --ranks and --sample-ranks need --all-ranks-html");
+            }
+            if cli.export && !cli.checks.is_empty() {
+                bail!(
+                    "--report-md, --baseline and --fail-on-* cannot be used with --all-ranks-html or --export"
+                );
+            }
+            parse(ParseArgs {
+                path: cli.path.unwrap_or_default(),
+                more_paths: cli.more_paths,
+                latest: cli.latest,
+                export: cli.export,
+                report: cli.report,
+                checks: cli.checks,
+            })
+        }
     }
+}
 
-    let path = if cli.latest {
-        let input_path = cli.path;
-        // Path should be a directory
-        if !input_path.is_dir() {
-            bail!(
-                "Input path {} is not a directory (required when using --latest)",
-                input_path.display()
+fn read_file<T>(
+    path: &Option<PathBuf>,
+    what: &str,
+    load: impl FnOnce(&str) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<T>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Couldn't read {what} {}", path.display()))?;
+    load(&contents)
+        .with_context(|| format!("Couldn't load {what} {}", path.display()))
+        .map(Some)
+}
+
+fn parse_config(
+    report: ReportArgs,
+    export: bool,
+    baseline: Option<CompileSummary>,
+) -> anyhow::Result<ParseConfig> {
+    let template_overrides = match &report.template_dir {
+        Some(dir) => load_template_overrides(dir)?,
+        None => FxHashMap::default(),
+    };
+    let custom_css =
+        read_file(&report.theme_css, "theme css", |css| Ok(css.to_string()))?.unwrap_or_default();
+    let profile = read_file(
+        &report.profile_trace,
+        "profiler trace",
+        MeasuredDurations::from_trace,
+    )?;
+    let steps = read_file(&report.steps, "steps file", TrainingSteps::from_json)?;
+
+    #[cfg(feature = "async-io")]
+    let max_parallel_writes = report.parallel_writes.map(|n| n as usize);
+    #[cfg(not(feature = "async-io"))]
+    let max_parallel_writes = None;
+    #[cfg(feature = "otlp")]
+    let otel_spans = report.otel_spans || report.otlp_endpoint.is_some();
+    #[cfg(not(feature = "otlp"))]
+    let otel_spans = report.otel_spans;
+    Ok(ParseConfig {
+        strict: report.strict,
+        strict_compile_id: report.strict_compile_id,
+        custom_parsers: Vec::new(),
+        custom_header_html: report.custom_header_html,
+        verbose: report.verbose,
+        plain_text: report.plain_text,
+        export,
+        inductor_provenance: report.inductor_provenance,
+        no_inductor_provenance: report.no_inductor_provenance,
+        template_overrides,
+        theme: report.theme,
+        custom_css,
+        verify_payloads: if report.no_verify_payloads {
+            PayloadVerification::None
+        } else if let Some(n) = report.verify_payloads_every {
+            PayloadVerification::Sample(n)
+        } else {
+            PayloadVerification::All
+        },
+        legacy_artifact_numbering: report.legacy_artifact_numbering,
+        dedup_payloads: report.dedup_payloads,
+        max_compile_ids: report.max_compile_ids,
+        first_failure: report.first_failure,
+        raw_jsonl_filenames: report.raw_jsonl_filenames,
+        max_artifact_bytes: report.max_artifact_bytes.map(|n| n as usize),
+        source_roots: report.source_root,
+        git_sha: report.git_sha,
+        job_id: report.job_id,
+        rank: None,
+        compare_runs: false,
+        summary_only: false,
+        index_page_size: report.index_page_size.map(|n| n as usize),
+        max_parallel_writes,
+        recover_payloads: report.recover_payloads,
+        disabled_parsers: report.disable_parser,
+        enabled_parsers: report.enable_only,
+        baseline,
+        profile,
+        steps,
+        progress: if report.quiet {
+            Progress::Never
+        } else {
+            report.progress
+        },
+        deterministic: report.deterministic,
+        graph_dot: report.graph_dot,
+        directory_layout: report.directory_layout,
+        validate_output: report.validate_output,
+        otel_spans,
+        metrics_csv: report.metrics_csv.is_some(),
+        url_prefix: report.url_prefix.map(|prefix| {
+            if prefix.ends_with('/') {
+                prefix
+            } else {
+                format!("{prefix}/")
+            }
+        }),
+    })
+}
+
+fn parse(args: ParseArgs) -> anyhow::Result<()> {
+    if args.export && !args.checks.is_empty() {
+        bail!("--report-md, --baseline and --fail-on-* cannot be used with --export");
+    }
+    let baseline = read_file(&args.checks.baseline, "baseline", |json| {
+        serde_json::from_str::<CompileSummary>(json).context("Not a compile_summary.json")
+    })?;
+    let thresholds = args.checks.thresholds();
+    let out = args.report.out.clone();
+    let open_browser = !args.report.no_browser;
+    let overwrite = args.report.overwrite;
+    #[cfg(feature = "upload")]
+    let upload = args.report.upload.clone();
+    #[cfg(feature = "otlp")]
+    let otlp_endpoint = args.report.otlp_endpoint.clone();
+    let append_trend = args.report.append_trend.clone();
+    let metrics_csv = args.report.metrics_csv.clone();
+    let config = parse_config(args.report, args.export, baseline)?;
+    let paths = expand_log_paths(&[vec![args.path], args.more_paths].concat())?;
+    if args.latest && paths.len() > 1 {
+        bail!("--latest cannot be used with several logs");
+    }
+    let summary = handle_one_rank(
+        &config,
+        paths,
+        args.latest,
+        out.clone(),
+        open_browser,
+        overwrite,
+    )?
+    .compile_summary;
+    if let Some(report_md) = &args.checks.report_md {
+        fs::write(report_md, summary.to_markdown())
+            .with_context(|| format!("Couldn't write {}", report_md.display()))?;
+    }
+    copy_metrics_csv(&out, metrics_csv.as_deref())?;
+    append_trends(&out, append_trend.as_deref())?;
+    if config.first_failure {
+        let page = out.join("first_failure.html");
+        if page.exists() {
+            println!(
+                "Stopped at the first failed compilation, see {}",
+                page.display()
             );
+        } else {
+            println!("No compilation failed");
         }
+    }
+    #[cfg(feature = "otlp")]
+    export_spans(&out, otlp_endpoint.as_deref())?;
+    // Upload reports that fail the health checks too, they are the ones worth sharing
+    #[cfg(feature = "upload")]
+    upload_report(&out, upload.as_deref())?;
+    let violations = thresholds.violations(&summary, config.baseline.as_ref());
+    if !violations.is_empty() {
+        bail!("Compile health check failed: {}", violations.join(", "));
+    }
+    Ok(())
+}
 
-        let last_modified_file = std::fs::read_dir(&input_path)
-            .with_context(|| format!("Couldn't access directory {}", input_path.display()))?
-            .flatten()
-            .filter(|f| f.metadata().unwrap().is_file())
-            .max_by_key(|x| x.metadata().unwrap().modified().unwrap());
+/// Appends the trend_record.json of the report in `out`, or of each of its ranks or runs, to the
+/// JSONL file `trends`
+fn append_trends(out: &Path, trends: Option<&Path>) -> anyhow::Result<()> {
+    let Some(trends) = trends else {
+        return Ok(());
+    };
+    let mut records = vec![out.join("trend_record.json")];
+    records.extend(
+        report_subdirs(out)?
+            .iter()
+            .map(|d| d.join("trend_record.json")),
+    );
+    for record in records.iter().filter(|r| r.is_file()) {
+        append_trend(record, trends)?;
+    }
+    Ok(())
+}
 
-        let Some(last_modified_file) = last_modified_file else {
-            bail!("No files found in directory {}", input_path.display());
-        };
-        last_modified_file.path()
-    } else {
-        cli.path
+/// Copies the compilation_metrics.csv of the report in `out` to `dest`, or that of each of its
+/// ranks or runs next to it, e.g. to metrics_rank_0.csv for metrics.csv
+fn copy_metrics_csv(out: &Path, dest: Option<&Path>) -> anyhow::Result<()> {
+    let Some(dest) = dest else {
+        return Ok(());
+    };
+    let mut copies = vec![(out.join("compilation_metrics.csv"), dest.to_path_buf())];
+    for subdir in report_subdirs(out)? {
+        let mut name = dest.file_stem().unwrap_or_default().to_os_string();
+        name.push("_");
+        name.push(subdir.file_name().unwrap_or_default());
+        copies.push((
+            subdir.join("compilation_metrics.csv"),
+            dest.with_file_name(name).with_extension("csv"),
+        ));
+    }
+    for (csv, dest) in copies.iter().filter(|(csv, _)| csv.is_file()) {
+        fs::copy(csv, dest).with_context(|| format!("Couldn't write {}", dest.display()))?;
+    }
+    Ok(())
+}
+
+/// The rank_N/ or run_N/ directories of a multi-rank or compare-runs report, by their number
+fn report_subdirs(out: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut subdirs: Vec<(u32, PathBuf)> = fs::read_dir(out)?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let n = name
+                .strip_prefix("rank_")
+                .or_else(|| name.strip_prefix("run_"))?
+                .parse()
+                .ok()?;
+            Some((n, entry.path()))
+        })
+        .collect();
+    subdirs.sort();
+    Ok(subdirs.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Appends the one-line `record` to the JSONL file `trends`.  The line is written with a single
+/// write to a file opened for appending, so that jobs appending at the same time don't interleave.
+fn append_trend(record: &Path, trends: &Path) -> anyhow::Result<()> {
+    let mut line = fs::read_to_string(record)
+        .with_context(|| format!("Couldn't read {}", record.display()))?;
+    line.push('\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(trends)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Couldn't append to {}", trends.display()))
+}
+
+fn multi_rank(args: MultiRankArgs) -> anyhow::Result<()> {
+    let rank_logs = match &args.ranks.manifest {
+        Some(manifest) => read_rank_manifest(manifest)?,
+        None => discover_rank_logs(&args.path.unwrap_or_default())?,
     };
+    let out = args.report.out.clone();
+    let open_browser = !args.report.no_browser;
+    let overwrite = args.report.overwrite;
+    #[cfg(feature = "upload")]
+    let upload = args.report.upload.clone();
+    #[cfg(feature = "otlp")]
+    let otlp_endpoint = args.report.otlp_endpoint.clone();
+    let append_trend = args.report.append_trend.clone();
+    let metrics_csv = args.report.metrics_csv.clone();
+    let config = parse_config(args.report, false, None)?;
+    let (rank_logs, skipped_ranks) = select_rank_logs(
+        rank_logs,
+        args.ranks.ranks.as_deref(),
+        args.ranks.sample_ranks,
+        config.deterministic,
+    )?;
+    handle_all_ranks(
+        config,
+        rank_logs,
+        skipped_ranks,
+        out.clone(),
+        overwrite,
+        open_browser,
+        args.ranks.compile_time_skew_threshold,
+    )?;
+    copy_metrics_csv(&out, metrics_csv.as_deref())?;
+    append_trends(&out, append_trend.as_deref())?;
+    #[cfg(feature = "otlp")]
+    export_spans(&out, otlp_endpoint.as_deref())?;
+    #[cfg(feature = "upload")]
+    upload_report(&out, upload.as_deref())?;
+    Ok(())
+}
+
+fn compare_runs(args: CompareRunsArgs) -> anyhow::Result<()> {
+    let out = args.report.out.clone();
+    let open_browser = !args.report.no_browser;
+    let overwrite = args.report.overwrite;
+    #[cfg(feature = "upload")]
+    let upload = args.report.upload.clone();
+    let append_trend = args.report.append_trend.clone();
+    let metrics_csv = args.report.metrics_csv.clone();
+    let mut cfg = parse_config(args.report, false, None)?;
+    cfg.compare_runs = true;
+    setup_output_directory(&out, overwrite)?;
+    let url_prefix = cfg.url_prefix.take();
+    let mut runs: Vec<(String, RankParseSummary)> = Vec::new();
+    for (run, log_path) in args.runs.into_iter().enumerate() {
+        let subdir = out.join(format!("run_{run}"));
+        println!("Processing {} → {}", log_path.display(), subdir.display());
+        // Each run's pages are under its own directory of the hosted report
+        cfg.url_prefix = url_prefix
+            .as_ref()
+            .map(|prefix| format!("{prefix}run_{run}/"));
+        let name = log_path
+            .file_name()
+            .map_or(log_path.display().to_string(), |f| {
+                f.to_string_lossy().to_string()
+            });
+        let summary = handle_one_rank(&cfg, vec![log_path], false, subdir, false, overwrite)?;
+        runs.push((name, summary));
+    }
+    cfg.url_prefix = url_prefix;
+    let (landing_page_path, landing_html) = generate_compare_runs_html(&out, &runs, &cfg)?;
+    fs::write(&landing_page_path, landing_html)?;
+    fs::write(out.join("theme.css"), theme_css(cfg.theme, &cfg.custom_css))?;
+    println!(
+        "Run comparison generated under {}\nIndividual pages: run_*/index.html",
+        out.display()
+    );
+    copy_metrics_csv(&out, metrics_csv.as_deref())?;
+    append_trends(&out, append_trend.as_deref())?;
+    #[cfg(feature = "upload")]
+    upload_report(&out, upload.as_deref())?;
+    if open_browser {
+        opener::open(&landing_page_path)?;
+    }
+    Ok(())
+}
+
+/// Sends the otel_spans.json of the report in `out`, or of each of its ranks, to `endpoint`
+#[cfg(feature = "otlp")]
+fn export_spans(out: &Path, endpoint: Option<&str>) -> anyhow::Result<()> {
+    let Some(endpoint) = endpoint else {
+        return Ok(());
+    };
+    let mut files = vec![out.join("otel_spans.json")];
+    files.extend(
+        report_subdirs(out)?
+            .iter()
+            .map(|d| d.join("otel_spans.json")),
+    );
+    for file in files.iter().filter(|f| f.is_file()) {
+        tlparse::export_spans(file, endpoint)?;
+        println!("Sent {} to {endpoint}", file.display());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+fn upload_report(out: &Path, destination: Option<&str>) -> anyhow::Result<()> {
+    if let Some(destination) = destination {
+        let url = tlparse::upload_report(out, destination)?;
+        println!("Uploaded the report to {url}");
+    }
+    Ok(())
+}
 
+/// Only runs the parsers needed for the compile summary, and renders and writes nothing
+fn compile_summary(path: &PathBuf) -> anyhow::Result<CompileSummary> {
     let config = ParseConfig {
-        strict: cli.strict,
-        strict_compile_id: cli.strict_compile_id,
-        custom_parsers: Vec::new(),
-        custom_header_html: cli.custom_header_html,
-        verbose: cli.verbose,
-        plain_text: cli.plain_text,
-        export: cli.export,
-        inductor_provenance: cli.inductor_provenance,
+        summary_only: true,
+        enabled_parsers: Some(vec![
+            "compilation_metrics".to_string(),
+            // For the cache hits and misses
+            "artifact".to_string(),
+        ]),
+        ..Default::default()
     };
+    Ok(parse_path_with_summary(path, &config)?.1.compile_summary)
+}
 
-    if cli.all_ranks_html {
-        handle_all_ranks(&config, path, cli.out, cli.overwrite, !cli.no_browser)?;
-    } else {
-        handle_one_rank(
-            &config,
-            path,
-            cli.latest,
-            cli.out,
-            !cli.no_browser,
-            cli.overwrite,
-        )?;
+fn summarize(path: &PathBuf) -> anyhow::Result<()> {
+    print!("{}", compile_summary(path)?.to_table());
+    Ok(())
+}
+
+fn extract(
+    path: &Path,
+    parser: &str,
+    compile_id: Option<&CompileId>,
+    out: Option<&Path>,
+) -> anyhow::Result<()> {
+    let output = extract_artifact(path, parser, compile_id)?;
+    if output.is_empty() {
+        bail!(
+            "{parser} found nothing to extract{}",
+            compile_id.map_or(String::new(), |c| format!(" for compile id {c}"))
+        );
+    }
+    match out {
+        Some(out) => {
+            for (filename, content) in output {
+                let path = out.join(filename);
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(&path, content)?;
+                println!("{}", path.display());
+            }
+        }
+        None => {
+            for (_, content) in output {
+                println!("{content}");
+            }
+        }
     }
     Ok(())
 }
 
+fn diff(baseline: &PathBuf, path: &PathBuf) -> anyhow::Result<()> {
+    // A compile_summary.json of an earlier report, or a log to summarize
+    let summary_of = |path: &PathBuf| -> anyhow::Result<CompileSummary> {
+        if path.extension().is_some_and(|e| e == "json") {
+            serde_json::from_str(&fs::read_to_string(path)?)
+                .with_context(|| format!("{} is not a compile_summary.json", path.display()))
+        } else {
+            compile_summary(path)
+        }
+    };
+    let baseline = summary_of(baseline)?;
+    print!("{}", summary_of(path)?.compare_table(&baseline));
+    Ok(())
+}
+
+// How long `serve` waits for the request of a connection, so that an idle one doesn't block the
+// others
+const SERVE_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serves `dir` on localhost until interrupted.  Requests are handled one at a time, which is
+/// plenty for one person looking at a report.
+fn serve(dir: &Path, port: u16) -> anyhow::Result<()> {
+    if !dir.join("index.html").is_file() {
+        bail!(
+            "{} has no index.html; is it a tlparse report?",
+            dir.display()
+        );
+    }
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .with_context(|| format!("Couldn't listen on port {port}"))?;
+    println!(
+        "Serving {} at http://{}/",
+        dir.display(),
+        listener.local_addr()?
+    );
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        stream.set_read_timeout(Some(SERVE_READ_TIMEOUT))?;
+        if let Err(e) = serve_request(dir, &mut stream) {
+            eprintln!("Error serving request: {e:#}");
+        }
+    }
+    Ok(())
+}
+
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn serve_request(dir: &Path, stream: &mut TcpStream) -> anyhow::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&mut *stream).read_line(&mut request_line)?;
+    // e.g. GET /-_0_0_0/dynamo_output_graph_0.txt HTTP/1.1
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let target = target.split(['?', '#']).next().unwrap_or_default();
+    let relative = PathBuf::from(percent_decode(target.trim_start_matches('/')));
+    let file = if relative.as_os_str().is_empty() {
+        dir.join("index.html")
+    } else {
+        dir.join(&relative)
+    };
+    // Don't serve anything outside of the report
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    let (status, content_type, body) = match fs::read(&file) {
+        Ok(body) if inside => {
+            let content_type = match file.extension().and_then(|e| e.to_str()) {
+                Some("html") => "text/html; charset=utf-8",
+                Some("json") | Some("jsonl") => "application/json",
+                Some("css") => "text/css",
+                Some("js") => "text/javascript",
+                Some("svg") => "image/svg+xml",
+                _ => "text/plain; charset=utf-8",
+            };
+            ("200 OK", content_type, body)
+        }
+        _ => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            b"Not found".to_vec(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    Ok(())
+}
+
 /// Create the output directory
 fn setup_output_directory(out_path: &PathBuf, overwrite: bool) -> anyhow::Result<()> {
     if out_path.exists() {
@@ -130,34 +974,29 @@ fn setup_output_directory(out_path: &PathBuf, overwrite: bool) -> anyhow::Result
     Ok(())
 }
 
-/// Parse a log file and write the rendered artefacts into `output_dir`.
+/// Parse a log file, or the fragments of one, and write the rendered artefacts into `output_dir`.
 fn parse_and_write_output(
     config: &ParseConfig,
-    log_path: &PathBuf,
+    log_paths: &[PathBuf],
     output_dir: &PathBuf,
-) -> anyhow::Result<PathBuf> {
-    let output = parse_path(log_path, config)?;
-
-    for (filename, content) in output {
-        let out_path = output_dir.join(&filename);
-        if let Some(dir) = out_path.parent() {
-            fs::create_dir_all(dir)?;
-        }
-        fs::write(out_path, content)?;
-    }
-    Ok(output_dir.join("index.html"))
+) -> anyhow::Result<(PathBuf, RankParseSummary)> {
+    // raw.jsonl and the outputs parsers stream are written as the log is parsed
+    let (output, summary) = parse_path_to_directory(log_paths, config, output_dir)?;
+    write_output(output, output_dir, config)?;
+    Ok((output_dir.join("index.html"), summary))
 }
 
 fn handle_one_rank(
     cfg: &ParseConfig,
-    input_path: PathBuf,
+    input_paths: Vec<PathBuf>,
     latest: bool,
     out_dir: PathBuf,
     open_browser: bool,
     overwrite: bool,
-) -> anyhow::Result<()> {
-    // Resolve which log file we should parse
-    let log_path = if latest {
+) -> anyhow::Result<RankParseSummary> {
+    // Resolve which log files we should parse
+    let log_paths = if latest {
+        let input_path = &input_paths[0];
         if !input_path.is_dir() {
             bail!(
                 "Input path {} is not a directory (required with --latest)",
@@ -169,28 +1008,97 @@ fn handle_one_rank(
             .filter(|e| e.metadata().ok().map_or(false, |m| m.is_file()))
             .max_by_key(|e| e.metadata().unwrap().modified().unwrap())
             .map(|e| e.path())
+            .map(|path| vec![path])
             .context("No files found in directory for --latest")?
     } else {
-        input_path.clone()
+        input_paths
     };
 
     setup_output_directory(&out_dir, overwrite)?;
-    let main_output_file = parse_and_write_output(cfg, &log_path, &out_dir)?;
+    let (main_output_file, summary) = parse_and_write_output(cfg, &log_paths, &out_dir)?;
 
     if open_browser {
         opener::open(&main_output_file)?;
     }
-    Ok(())
+    Ok(summary)
 }
 
-fn handle_all_ranks(
-    cfg: &ParseConfig,
-    path: PathBuf,
-    out_path: PathBuf,
-    overwrite: bool,
-    open_browser: bool,
-) -> anyhow::Result<()> {
-    let input_dir = path;
+/// Groups of ranks that agree on a sequence, if they don't all agree.  Groups are ordered by
+/// their lowest rank so the landing page doesn't change between runs.
+fn divergence_groups(groups: &FxHashMap<String, Vec<u32>>) -> Vec<DivergenceGroup> {
+    if groups.len() <= 1 {
+        return Vec::new();
+    }
+    let mut groups: Vec<(&String, Vec<u32>)> = groups
+        .iter()
+        .map(|(seq, ranks)| {
+            let mut ranks = ranks.clone();
+            ranks.sort_unstable();
+            (seq, ranks)
+        })
+        .collect();
+    groups.sort_by_key(|(_, ranks)| ranks.first().copied());
+    groups
+        .into_iter()
+        .map(|(seq, ranks)| DivergenceGroup {
+            sequence: seq.clone(),
+            ranks: ranks
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        })
+        .collect()
+}
+
+/// Graphs of the same compile id whose content differs across ranks, grouping the ranks by content
+fn graph_content_divergence(
+    rank_metadata: &[(u32, RankParseSummary)],
+) -> Vec<GraphContentDivergence> {
+    let mut by_graph: BTreeMap<&(String, String), BTreeMap<&str, Vec<u32>>> = BTreeMap::new();
+    for (rank, md) in rank_metadata {
+        for (graph, hash) in &md.graph_hashes {
+            by_graph
+                .entry(graph)
+                .or_default()
+                .entry(hash)
+                .or_default()
+                .push(*rank);
+        }
+    }
+    by_graph
+        .into_iter()
+        .filter(|(_, hashes)| hashes.len() > 1)
+        .map(|((compile_id, artifact), hashes)| {
+            let mut groups: Vec<DivergenceGroup> = hashes
+                .into_iter()
+                .map(|(hash, mut ranks)| {
+                    ranks.sort_unstable();
+                    DivergenceGroup {
+                        sequence: hash.to_string(),
+                        ranks: ranks
+                            .iter()
+                            .map(|r| r.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    }
+                })
+                .collect();
+            groups.sort_by(|a, b| a.ranks.cmp(&b.ranks));
+            GraphContentDivergence {
+                compile_id: compile_id.clone(),
+                artifact: artifact.clone(),
+                groups,
+            }
+        })
+        .collect()
+}
+
+/// Paths of rank logs and their ranks
+type RankLogs = Vec<(PathBuf, u32)>;
+
+/// Finds the rank logs in a directory, i.e. dedicated_log_torch_trace_rank_<N>[_*].log
+fn discover_rank_logs(input_dir: &PathBuf) -> anyhow::Result<RankLogs> {
     if !input_dir.is_dir() {
         bail!(
             "Input path {} must be a directory when using --all-ranks-html",
@@ -198,10 +1106,7 @@ fn handle_all_ranks(
         );
     }
 
-    setup_output_directory(&out_path, overwrite)?;
-
-    // Discover rank log files
-    let rank_logs: Vec<_> = std::fs::read_dir(&input_dir)?
+    let rank_logs: Vec<_> = std::fs::read_dir(input_dir)?
         .flatten()
         .filter_map(|entry| {
             let path = entry.path();
@@ -226,102 +1131,157 @@ fn handle_all_ranks(
             input_dir.display()
         );
     }
+    Ok(rank_logs)
+}
+
+/// Reads the rank logs listed in a `--manifest`
+fn read_rank_manifest(manifest: &PathBuf) -> anyhow::Result<RankLogs> {
+    let content = fs::read_to_string(manifest)
+        .with_context(|| format!("Couldn't read manifest {}", manifest.display()))?;
+    let entries: BTreeMap<String, PathBuf> = serde_json::from_str(&content).with_context(|| {
+        format!(
+            "{} should map rank numbers to log paths",
+            manifest.display()
+        )
+    })?;
+    let base_dir = manifest.parent().unwrap_or(Path::new(""));
+    let rank_logs: RankLogs = entries
+        .into_iter()
+        .map(|(rank, log_path)| {
+            let rank_num = rank
+                .parse::<u32>()
+                .with_context(|| format!("Manifest key {rank:?} is not a rank number"))?;
+            let log_path = base_dir.join(log_path);
+            if !log_path.is_file() {
+                bail!(
+                    "Log of rank {rank_num} in manifest, {}, is not a file",
+                    log_path.display()
+                );
+            }
+            Ok((log_path, rank_num))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    if rank_logs.is_empty() {
+        bail!("No rank logs listed in manifest {}", manifest.display());
+    }
+    Ok(rank_logs)
+}
+
+/// Picks the rank logs to parse with `--ranks` or `--sample-ranks`, returning them and the ranks
+/// that were left out
+fn select_rank_logs(
+    rank_logs: RankLogs,
+    ranks: Option<&[u32]>,
+    sample_ranks: Option<usize>,
+    deterministic: bool,
+) -> anyhow::Result<(RankLogs, Vec<u32>)> {
+    let selected: FxHashSet<u32> = if let Some(ranks) = ranks {
+        let found: FxHashSet<u32> = rank_logs.iter().map(|(_, rank)| *rank).collect();
+        if let Some(missing) = ranks.iter().find(|rank| !found.contains(rank)) {
+            bail!("No log found for rank {missing} passed to --ranks");
+        }
+        ranks.iter().copied().collect()
+    } else if let Some(n) = sample_ranks {
+        // Ordering by a randomly keyed hash shuffles the ranks, and by an unkeyed one shuffles
+        // them the same way every time
+        let state = std::collections::hash_map::RandomState::new();
+        let mut shuffled: Vec<u32> = rank_logs.iter().map(|(_, rank)| *rank).collect();
+        shuffled.sort_by_key(|rank| {
+            if deterministic {
+                fxhash::hash64(rank)
+            } else {
+                state.hash_one(rank)
+            }
+        });
+        shuffled.into_iter().take(n).collect()
+    } else {
+        return Ok((rank_logs, Vec::new()));
+    };
+    let (rank_logs, skipped): (Vec<_>, Vec<_>) = rank_logs
+        .into_iter()
+        .partition(|(_, rank)| selected.contains(rank));
+    let mut skipped_ranks: Vec<u32> = skipped.into_iter().map(|(_, rank)| rank).collect();
+    skipped_ranks.sort_unstable();
+    Ok((rank_logs, skipped_ranks))
+}
 
-    // Extract rank numbers, sort numerically, then convert to strings for HTML generation
+fn handle_all_ranks(
+    mut cfg: ParseConfig,
+    rank_logs: RankLogs,
+    skipped_ranks: Vec<u32>,
+    out_path: PathBuf,
+    overwrite: bool,
+    open_browser: bool,
+    compile_time_skew_threshold: f64,
+) -> anyhow::Result<()> {
+    setup_output_directory(&out_path, overwrite)?;
+
+    // Extract rank numbers and sort numerically
     let mut rank_nums: Vec<u32> = rank_logs.iter().map(|(_, rank)| *rank).collect();
     rank_nums.sort_unstable();
-    let sorted_ranks: Vec<String> = rank_nums.iter().map(|r| r.to_string()).collect();
     let mut all_chromium_events: Vec<serde_json::Value> = Vec::new();
-    let mut rank_metadata: Vec<RankMetaData> = Vec::new();
+    let mut rank_metadata: Vec<(u32, RankParseSummary)> = Vec::new();
+    let url_prefix = cfg.url_prefix.take();
 
     for (log_path, rank_num) in rank_logs {
         let subdir = out_path.join(format!("rank_{rank_num}"));
         println!("Processing rank {rank_num} → {}", subdir.display());
         let chromium_events_path = subdir.join("chromium_events.json");
-        let compile_dir_json = subdir.join("compile_directory.json");
 
-        handle_one_rank(cfg, log_path, false, subdir, false, overwrite)?;
-
-        // extract compile IDs and cache sequence from compile_directory.json
-        let mut compile_ids: FxHashSet<String> = FxHashSet::default();
-        let content = fs::read_to_string(&compile_dir_json)?;
-        let mut artifact_entries: Vec<(u64, String)> = Vec::new();
-
-        if let Ok(serde_json::Value::Object(map)) =
-            serde_json::from_str::<serde_json::Value>(&content)
-        {
-            for (key, val) in map.iter() {
-                if key != "unknown" && !key.starts_with("unknown_") {
-                    compile_ids.insert(key.clone());
-                }
-                if let Some(arr) = val.get("artifacts").and_then(|v| v.as_array()) {
-                    for art in arr {
-                        let suffix = art.get("suffix").and_then(|s| s.as_str()).unwrap_or("");
-                        if suffix.is_empty() {
-                            continue;
-                        }
-                        if let Some(num) = art.get("number").and_then(|n| n.as_u64()) {
-                            artifact_entries.push((num, suffix.to_string()));
-                        }
-                    }
-                }
-            }
-        }
-
-        artifact_entries.sort_by_key(|(n, _)| *n);
-        let cache_sequence: String = artifact_entries.into_iter().map(|(_, s)| s).collect();
-
-        rank_metadata.push(RankMetaData {
-            rank: rank_num,
-            compile_ids,
-            cache_sequence,
-        });
+        // Each rank's pages are under its own directory of the hosted report
+        cfg.url_prefix = url_prefix
+            .as_ref()
+            .map(|prefix| format!("{prefix}rank_{rank_num}/"));
+        cfg.rank = Some(rank_num);
+        let summary = handle_one_rank(&cfg, vec![log_path], false, subdir, false, overwrite)?;
 
         // collect chromium events for each rank
-        if chromium_events_path.exists() {
+        if summary.num_chromium_events > 0 {
             let events = read_chromium_events_with_pid(&chromium_events_path, rank_num)?;
             all_chromium_events.extend(events);
         }
+        rank_metadata.push((rank_num, summary));
     }
 
     // Determine if there is any divergence in compile IDs across ranks
-    let compile_id_divergence = if let Some(first) = rank_metadata.first() {
+    let compile_id_divergence = if let Some((_, first)) = rank_metadata.first() {
         rank_metadata
             .iter()
-            .any(|md| md.compile_ids != first.compile_ids)
+            .any(|(_, md)| md.compile_ids != first.compile_ids)
     } else {
         false
     };
 
+    let graph_content_divergence = graph_content_divergence(&rank_metadata);
+    let mut compile_times: Vec<(u32, f64)> = rank_metadata
+        .iter()
+        .map(|(rank, md)| (*rank, md.compile_summary.cumulative_compile_time_s))
+        .collect();
+    compile_times.sort_by_key(|(rank, _)| *rank);
+    let mut rank_compile_summaries: Vec<(Option<u32>, &CompileSummary)> = rank_metadata
+        .iter()
+        .map(|(rank, md)| (Some(*rank), &md.compile_summary))
+        .collect();
+    rank_compile_summaries.sort_by_key(|(rank, _)| *rank);
+    fs::write(
+        out_path.join("metrics.prom"),
+        prometheus_metrics(&rank_compile_summaries),
+    )?;
+
     // Group ranks by their cache hit/miss sequence
     let cache_seq_groups: FxHashMap<String, Vec<u32>> =
         rank_metadata
-            .into_iter()
-            .fold(FxHashMap::default(), |mut acc, md| {
-                acc.entry(md.cache_sequence).or_default().push(md.rank);
+            .iter()
+            .fold(FxHashMap::default(), |mut acc, (rank, md)| {
+                acc.entry(md.cache_sequence.clone())
+                    .or_default()
+                    .push(*rank);
                 acc
             });
 
     // Build groups describing cache hit/miss patterns per rank
-    let cache_divergence_groups: Vec<DivergenceGroup> = if cache_seq_groups.len() > 1 {
-        cache_seq_groups
-            .iter()
-            .map(|(seq, ranks_vec)| {
-                let mut sorted_ranks = ranks_vec.clone();
-                sorted_ranks.sort_unstable();
-                DivergenceGroup {
-                    sequence: seq.clone(),
-                    ranks: sorted_ranks
-                        .iter()
-                        .map(|r| r.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                }
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let cache_divergence_groups = divergence_groups(&cache_seq_groups);
 
     // combine chromium events from all ranks
     if !all_chromium_events.is_empty() {
@@ -448,6 +1408,8 @@ fn handle_all_ranks(
         None
     };
 
+    let memory_estimations = tlparse::parsers::read_memory_estimations(&out_path, &rank_nums)?;
+
     // Process collective schedules from all ranks
     let collective_schedules = tlparse::parsers::read_collective_schedules(&out_path, &rank_nums)?;
     if !collective_schedules.is_empty() {
@@ -485,25 +1447,7 @@ fn handle_all_ranks(
         }
     }
 
-    let tensor_meta_divergence_groups: Vec<DivergenceGroup> = if tensor_meta_groups.len() > 1 {
-        tensor_meta_groups
-            .iter()
-            .map(|(seq, ranks_vec)| {
-                let mut sorted_ranks = ranks_vec.clone();
-                sorted_ranks.sort_unstable();
-                DivergenceGroup {
-                    sequence: seq.clone(),
-                    ranks: sorted_ranks
-                        .iter()
-                        .map(|r| r.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                }
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let tensor_meta_divergence_groups = divergence_groups(&tensor_meta_groups);
 
     // Group ranks by their collective op sequence
     let mut collective_seq_groups: FxHashMap<String, Vec<u32>> = FxHashMap::default();
@@ -522,25 +1466,7 @@ fn handle_all_ranks(
         }
     }
 
-    let collective_divergence_groups: Vec<DivergenceGroup> = if collective_seq_groups.len() > 1 {
-        collective_seq_groups
-            .iter()
-            .map(|(seq, ranks_vec)| {
-                let mut sorted_ranks = ranks_vec.clone();
-                sorted_ranks.sort_unstable();
-                DivergenceGroup {
-                    sequence: seq.clone(),
-                    ranks: sorted_ranks
-                        .iter()
-                        .map(|r| r.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                }
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let collective_divergence_groups = divergence_groups(&collective_seq_groups);
 
     println!(
         "Multi-rank report generated under {}\nIndividual pages: rank_*/index.html",
@@ -552,6 +1478,7 @@ fn handle_all_ranks(
             cache: cache_seq_groups.len() > 1,
             collective: collective_seq_groups.len() > 1,
             tensor_meta: tensor_meta_groups.len() > 1,
+            graph_content: !graph_content_divergence.is_empty(),
         },
         artifacts: ArtifactFlags {
             runtime_trace: !runtime_estimations.is_empty(),
@@ -560,21 +1487,68 @@ fn handle_all_ranks(
         cache_groups: cache_divergence_groups.clone(),
         collective_groups: collective_divergence_groups.clone(),
         tensor_meta_groups: tensor_meta_divergence_groups.clone(),
+        graph_content_divergence,
+        compile_time_skew: analyze_compile_time_skew(&compile_times, compile_time_skew_threshold),
+        memory_analysis: analyze_graph_memory_deltas(&memory_estimations),
+        skipped_ranks: skipped_ranks
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
     };
 
+    let mut rank_stats: FxHashMap<u32, RankStats> = rank_metadata
+        .into_iter()
+        .map(|(rank, md)| {
+            let summary = md.compile_summary;
+            let stats = RankStats {
+                log_entries: md.stats.ok,
+                compiles: summary.total_compiles,
+                failures: summary.failures,
+                compile_time_s: summary.cumulative_compile_time_s,
+                cache_hit_rate: summary.cache_hit_rate.map(|r| format!("{:.1}%", r * 100.0)),
+            };
+            (rank, stats)
+        })
+        .collect();
+    let mut distributed_info = tlparse::parsers::read_distributed_info(&out_path, &rank_nums)?;
+    let rank_summaries: Vec<RankSummary> = rank_nums
+        .iter()
+        .map(|&rank| {
+            let info = distributed_info.remove(&rank);
+            RankSummary {
+                rank,
+                stats: rank_stats.remove(&rank),
+                rank_mismatch: info
+                    .as_ref()
+                    .and_then(|i| i.rank)
+                    .is_some_and(|r| r != rank),
+                info,
+            }
+        })
+        .collect();
+
+    cfg.url_prefix = url_prefix;
     let (landing_page_path, landing_html) = generate_multi_rank_html(
         &out_path,
-        sorted_ranks,
-        cfg,
+        rank_summaries,
+        &cfg,
         !all_chromium_events.is_empty(),
         compile_id_divergence
             || diagnostics.divergence.cache
             || diagnostics.divergence.collective
-            || diagnostics.divergence.tensor_meta,
+            || diagnostics.divergence.tensor_meta
+            || diagnostics.divergence.graph_content,
         compile_id_divergence,
         diagnostics,
     )?;
     fs::write(&landing_page_path, landing_html)?;
+    let (search_path, search_html) = generate_cross_rank_search_html(&out_path, &rank_nums, &cfg)?;
+    fs::write(search_path, search_html)?;
+    fs::write(
+        out_path.join("theme.css"),
+        theme_css(cfg.theme, &cfg.custom_css),
+    )?;
     if open_browser {
         opener::open(&landing_page_path)?;
     }
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::payload::LazyPayload;
+use crate::types::{CompileId, Envelope, FxIndexMap};
+
+// Artifacts whose payload is generated code
+const AOT_CODE_ARTIFACTS: &[&str] = &["inductor_aot_wrapper_code", "inductor_aot_kernel_code"];
+
+const TILE_COLORS: &[&str] = &[
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+];
+
+/// Bytes of generated code per compile id, broken down by the kind of artifact it came from
+#[derive(Debug, Default)]
+pub struct CodeSizeIndex {
+    sizes: FxIndexMap<Option<CompileId>, FxIndexMap<&'static str, u64>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileCodeSize {
+    pub compile_id: String,
+    pub total_bytes: u64,
+    pub artifacts: BTreeMap<&'static str, u64>,
+}
+
+/// Contents of code_size.json
+#[derive(Debug, Serialize)]
+pub struct CodeSizeSummary {
+    pub total_bytes: u64,
+    /// Largest first
+    pub compile_ids: Vec<CompileCodeSize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TreemapTile {
+    // Position and size, as a percentage of the treemap
+    pub left: String,
+    pub top: String,
+    pub width: String,
+    pub height: String,
+    pub color: &'static str,
+    pub compile_id: String,
+    pub size: String,
+}
+
+impl CodeSizeIndex {
+    pub fn record(&mut self, e: &Envelope, payload: &LazyPayload, compile_id: &Option<CompileId>) {
+        let kind = if e.inductor_output_code.is_some() {
+            "inductor_output_code"
+        } else if let Some(name) = e
+            .artifact
+            .as_ref()
+            .and_then(|a| AOT_CODE_ARTIFACTS.iter().find(|n| **n == a.name))
+        {
+            name
+        } else {
+            return;
+        };
+        *self
+            .sizes
+            .entry(compile_id.clone())
+            .or_default()
+            .entry(kind)
+            .or_default() += payload.len() as u64;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    pub fn summary(&self) -> CodeSizeSummary {
+        let mut compile_ids: Vec<CompileCodeSize> = self
+            .sizes
+            .iter()
+            .map(|(compile_id, artifacts)| CompileCodeSize {
+                compile_id: compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                total_bytes: artifacts.values().sum(),
+                artifacts: artifacts.iter().map(|(k, v)| (*k, *v)).collect(),
+            })
+            .collect();
+        compile_ids.sort_by_key(|c| std::cmp::Reverse(c.total_bytes));
+        CodeSizeSummary {
+            total_bytes: compile_ids.iter().map(|c| c.total_bytes).sum(),
+            compile_ids,
+        }
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+/// Lay out one tile per compile id with the squarified treemap algorithm (Bruls et al.), so that
+/// the area of a tile is proportional to the amount of code generated for it.
+pub fn treemap_tiles(summary: &CodeSizeSummary) -> Vec<TreemapTile> {
+    let sized: Vec<&CompileCodeSize> = summary
+        .compile_ids
+        .iter()
+        .filter(|c| c.total_bytes > 0)
+        .collect();
+    // Work in a 100x100 square so that coordinates are already percentages
+    let scale = 100.0 * 100.0 / summary.total_bytes.max(1) as f64;
+    let areas: Vec<f64> = sized.iter().map(|c| c.total_bytes as f64 * scale).collect();
+    let rects = squarify(
+        &areas,
+        Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 100.0,
+            h: 100.0,
+        },
+    );
+    sized
+        .iter()
+        .zip(rects)
+        .enumerate()
+        .map(|(i, (c, r))| TreemapTile {
+            left: format!("{:.3}%", r.x),
+            top: format!("{:.3}%", r.y),
+            width: format!("{:.3}%", r.w),
+            height: format!("{:.3}%", r.h),
+            color: TILE_COLORS[i % TILE_COLORS.len()],
+            compile_id: c.compile_id.clone(),
+            size: format_bytes(c.total_bytes),
+        })
+        .collect()
+}
+
+// Worst aspect ratio of a row of areas laid out along a side of the given length
+fn worst_ratio(row: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+    f64::max(
+        side * side * max / (sum * sum),
+        sum * sum / (side * side * min),
+    )
+}
+
+// `areas` must be sorted in decreasing order and sum up to the area of `rect`
+fn squarify(areas: &[f64], rect: Rect) -> Vec<Rect> {
+    let mut result = Vec::with_capacity(areas.len());
+    let mut rect = rect;
+    let mut start = 0;
+    while start < areas.len() {
+        let side = rect.w.min(rect.h);
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst_ratio(&areas[start..=end], side) <= worst_ratio(&areas[start..end], side)
+        {
+            end += 1;
+        }
+        let row = &areas[start..end];
+        let row_sum: f64 = row.iter().sum();
+        if rect.w >= rect.h {
+            // Fill a column on the left
+            let col_w = row_sum / rect.h;
+            let mut y = rect.y;
+            for area in row {
+                let h = area / col_w;
+                result.push(Rect {
+                    x: rect.x,
+                    y,
+                    w: col_w,
+                    h,
+                });
+                y += h;
+            }
+            rect.x += col_w;
+            rect.w -= col_w;
+        } else {
+            // Fill a row at the top
+            let row_h = row_sum / rect.w;
+            let mut x = rect.x;
+            for area in row {
+                let w = area / row_h;
+                result.push(Rect {
+                    x,
+                    y: rect.y,
+                    w,
+                    h: row_h,
+                });
+                x += w;
+            }
+            rect.y += row_h;
+            rect.h -= row_h;
+        }
+        start = end;
+    }
+    result
+}
@@ -0,0 +1,191 @@
+// Compare runs of the same program, e.g. nightly runs of a benchmark: the compilations of each run
+// are aligned by the frame they compile and the user stack that led to them, since compile ids
+// depend on the order frames happen to be compiled in.  Compilations without an exact match are
+// aligned by the files and functions of their frame and stack, since edits elsewhere in a file
+// move the lines of everything below them.
+use fxhash::FxHashMap;
+
+use crate::templates::{TEMPLATE_FAILURES_CSS, TEMPLATE_QUERY_PARAM_SCRIPT};
+use crate::types::{
+    CompareRunsContext, ComparedCompile, ComparedCompileCell, ComparedRun, CompilationMetricsIndex,
+    CompileId, FxIndexMap, InternTable, OutputFile, RankParseSummary, RunCompile, StackIndex,
+};
+
+// The frame and stack of a row, with the compilation of each run on it
+type AlignedCompiles<'a> = Vec<((&'a str, &'a [String]), Vec<Option<&'a RunCompile>>)>;
+
+// A frame or stack frame without its line, e.g. `forward (/home/me/model.py)` for
+// `forward (/home/me/model.py:12)`, or `/home/me/model.py in main` for
+// `/home/me/model.py:30 in main`
+fn without_line(frame: &str) -> String {
+    let (location, rest) = match frame.split_once(" in ") {
+        Some((location, name)) => (location, format!(" in {name}")),
+        None => match frame.strip_suffix(')') {
+            Some(frame) => (frame, ")".to_string()),
+            None => (frame, String::new()),
+        },
+    };
+    match location.rsplit_once(':') {
+        Some((file, line)) if line.chars().all(|c| c.is_ascii_digit()) => format!("{file}{rest}"),
+        _ => frame.to_string(),
+    }
+}
+
+/// The compilations of a log, in the order of its compilation metrics
+pub fn run_compiles(
+    metrics_index: &CompilationMetricsIndex,
+    stack_index: &StackIndex,
+    intern_table: &InternTable,
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+) -> Vec<RunCompile> {
+    metrics_index
+        .iter()
+        .map(|(compile_id, metrics)| {
+            let co_name = metrics.iter().find_map(|m| m.co_name.as_deref());
+            let location = metrics.iter().find_map(|m| {
+                let filename = m.co_filename.as_ref()?;
+                Some(format!(
+                    "{filename}:{}",
+                    m.co_firstlineno.unwrap_or_default()
+                ))
+            });
+            let frame = match (co_name, location) {
+                (Some(name), Some(location)) => format!("{name} ({location})"),
+                (name, location) => name
+                    .or(location.as_deref())
+                    .unwrap_or("(unknown)")
+                    .to_string(),
+            };
+            // dynamo_start is logged once, by the first attempt of the compile
+            let first_attempt = compile_id.as_ref().map(|c| CompileId {
+                attempt: Some(0),
+                ..c.clone()
+            });
+            let stack = stack_index
+                .get(compile_id)
+                .or_else(|| stack_index.get(&first_attempt))
+                .map(|stack| {
+                    stack
+                        .iter()
+                        .map(|f| format!("{}:{} in {}", f.filename(intern_table), f.line, f.name))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let files = directory.get(compile_id).map_or(&[][..], |f| f.as_slice());
+            let compile_times = metrics.iter().filter_map(|m| m.entire_frame_compile_time_s);
+            RunCompile {
+                compile_id: compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                frame,
+                stack,
+                url: files
+                    .iter()
+                    .find(|f| f.name.contains("compilation_metrics"))
+                    .map(|f| f.url.clone()),
+                compile_time_s: compile_times.reduce(|a, b| a + b),
+                cache: files.iter().map(|f| f.suffix.as_str()).collect(),
+                fail_type: metrics.iter().rev().find_map(|m| m.fail_type.clone()),
+            }
+        })
+        .collect()
+}
+
+/// The context of compare_runs.html of `runs`, each a name and the summary of its log, whose reports are in the
+/// directories run_0/, run_1/, ...
+pub fn compare_runs(
+    runs: &[(String, RankParseSummary)],
+    custom_header_html: &str,
+) -> CompareRunsContext {
+    // Each compilation is identified by its frame, its stack, and how many compilations of the
+    // same frame and stack came before it, e.g. the second recompile of a frame
+    let mut rows: AlignedCompiles = Vec::new();
+    let mut exact: FxHashMap<(&str, &[String], usize), usize> = FxHashMap::default();
+    // Rows by the files and functions of their frame and stack, in order
+    let mut by_function: FxHashMap<(String, Vec<String>), Vec<usize>> = FxHashMap::default();
+    for (run, (_, summary)) in runs.iter().enumerate() {
+        let mut seen: FxHashMap<(&str, &[String]), usize> = FxHashMap::default();
+        let mut unmatched = Vec::new();
+        for compile in &summary.compiles {
+            let occurrence = seen.entry((&compile.frame, &compile.stack)).or_default();
+            let key = (
+                compile.frame.as_str(),
+                compile.stack.as_slice(),
+                *occurrence,
+            );
+            *occurrence += 1;
+            match exact.get(&key) {
+                Some(&row) => rows[row].1[run] = Some(compile),
+                None => unmatched.push((key, compile)),
+            }
+        }
+        for (key, compile) in unmatched {
+            let function_key = (
+                without_line(&compile.frame),
+                compile.stack.iter().map(|f| without_line(f)).collect(),
+            );
+            let candidates = by_function.entry(function_key).or_default();
+            match candidates.iter().find(|row| rows[**row].1[run].is_none()) {
+                Some(&row) => rows[row].1[run] = Some(compile),
+                None => {
+                    let mut cells = vec![None; runs.len()];
+                    cells[run] = Some(compile);
+                    candidates.push(rows.len());
+                    exact.insert(key, rows.len());
+                    rows.push(((key.0, key.1), cells));
+                }
+            }
+        }
+    }
+    let rows = rows
+        .into_iter()
+        .map(|((frame, stack), compiles)| {
+            let outcome =
+                |c: &Option<&RunCompile>| c.map(|c| (c.cache.clone(), c.fail_type.is_some()));
+            let differs = compiles.iter().any(|c| outcome(c) != outcome(&compiles[0]));
+            ComparedCompile {
+                frame: frame.to_string(),
+                stack: stack.join("\n"),
+                differs,
+                cells: compiles
+                    .into_iter()
+                    .enumerate()
+                    .map(|(run, c)| match c {
+                        Some(c) => ComparedCompileCell {
+                            present: true,
+                            compile_id: c.compile_id.clone(),
+                            url: c.url.as_ref().map(|url| format!("run_{run}/{url}")),
+                            compile_time_s: c
+                                .compile_time_s
+                                .map_or(String::new(), |t| format!("{t:.3}")),
+                            cache: c.cache.clone(),
+                            fail_type: c.fail_type.clone(),
+                        },
+                        None => ComparedCompileCell::default(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    CompareRunsContext {
+        css: TEMPLATE_FAILURES_CSS,
+        custom_header_html: custom_header_html.to_string(),
+        runs: runs
+            .iter()
+            .enumerate()
+            .map(|(run, (name, summary))| ComparedRun {
+                name: name.clone(),
+                url: format!("run_{run}/index.html"),
+                compiles: summary.compiles.len(),
+                failures: summary
+                    .compiles
+                    .iter()
+                    .filter(|c| c.fail_type.is_some())
+                    .count(),
+                compile_time_s: format!("{:.3}", summary.compile_summary.cumulative_compile_time_s),
+            })
+            .collect(),
+        rows,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    }
+}
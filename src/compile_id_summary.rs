@@ -0,0 +1,100 @@
+// summary.html of each compile id: the key facts about one compilation on one page, so that a
+// link to it has the whole story rather than a list of artifacts.
+use std::path::Path;
+
+use crate::directory_layout::DirectoryLayout;
+use crate::templates::{TEMPLATE_FAILURES_CSS, TEMPLATE_QUERY_PARAM_SCRIPT};
+use crate::types::{
+    CompilationMetricsMetadata, CompileId, CompileIdSummaryContext, OutputFile, SummaryArtifact,
+    SummaryCacheStatus,
+};
+use crate::url_prefix;
+
+fn cache_status(suffix: &str) -> Option<&'static str> {
+    match suffix {
+        "✅" => Some("hit"),
+        "❌" => Some("miss"),
+        "❓" => Some("bypass"),
+        _ => None,
+    }
+}
+
+fn file_name(url: &str) -> String {
+    Path::new(url)
+        .file_name()
+        .map_or(url.to_string(), |f| f.to_string_lossy().to_string())
+}
+
+/// The link to the output `url` from a page `root` below the top of the report.  Links of the
+/// link parser, e.g. to https://..., are left alone.
+fn output_link(root: &str, url: &str) -> String {
+    if url_prefix::is_relative(url) {
+        format!("{root}{url}")
+    } else {
+        url.to_string()
+    }
+}
+
+/// The facts of `compile_id` for its summary.html, from its compilation metrics (the last ones if
+/// it logged several), the number of nodes of its Dynamo graph and its artifacts.  The page is in
+/// the directory of `compile_id` under `layout`.
+pub fn compile_id_summary(
+    compile_id: &CompileId,
+    layout: DirectoryLayout,
+    metrics: Option<&CompilationMetricsMetadata>,
+    graph_nodes: Option<usize>,
+    files: &[OutputFile],
+) -> CompileIdSummaryContext {
+    let status = match metrics {
+        Some(m) => match &m.fail_type {
+            Some(fail_type) => format!("Failed with {fail_type}"),
+            None => "Compiled".to_string(),
+        },
+        None => "No compilation metrics were logged".to_string(),
+    };
+    let root = layout.root_link(Some(compile_id));
+    let cache = files
+        .iter()
+        .filter_map(|f| {
+            Some(SummaryCacheStatus {
+                artifact: file_name(&f.name),
+                url: output_link(&root, &f.url),
+                status: cache_status(&f.suffix)?,
+            })
+        })
+        .collect();
+    CompileIdSummaryContext {
+        css: TEMPLATE_FAILURES_CSS,
+        compile_id: compile_id.to_string(),
+        co_name: metrics.and_then(|m| m.co_name.clone()),
+        location: metrics.and_then(|m| {
+            m.co_filename
+                .as_ref()
+                .map(|f| format!("{f}:{}", m.co_firstlineno.unwrap_or_default()))
+        }),
+        failed: metrics.is_some_and(|m| m.fail_type.is_some()),
+        status,
+        fail_reason: metrics.and_then(|m| m.fail_reason.clone()),
+        compile_time_s: metrics
+            .and_then(|m| m.entire_frame_compile_time_s)
+            .map(|t| format!("{t:.3}")),
+        guard_count: metrics.and_then(|m| m.guard_count).map(|n| n.to_string()),
+        graph_nodes: graph_nodes
+            .or_else(|| metrics.and_then(|m| m.graph_node_count).map(|n| n as usize))
+            .map(|n| n.to_string()),
+        restart_reasons: metrics
+            .and_then(|m| m.restart_reasons.clone())
+            .unwrap_or_default(),
+        recompile_reason: metrics.and_then(|m| m.recompile_reason.clone()),
+        cache,
+        artifacts: files
+            .iter()
+            .map(|f| SummaryArtifact {
+                name: file_name(&f.name),
+                url: output_link(&root, &f.url),
+            })
+            .collect(),
+        root,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    }
+}
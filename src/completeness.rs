@@ -0,0 +1,82 @@
+// Checks that a log has the records its report needs, e.g. that a log with compilation metrics
+// also has the graphs they were for.  Each gap comes with the setting that fills it, since the
+// usual cause is a run without the right TORCH_TRACE or Inductor config.
+use fxhash::FxHashSet;
+use serde::Serialize;
+
+use crate::types::EnvelopeTypeStats;
+use crate::ParseConfig;
+
+/// What the rules check a log for
+pub struct CompletenessFacts<'a> {
+    /// Record types of the log, as in envelope_stats.json, e.g. artifact:fx_graph_cache_hit
+    record_types: FxHashSet<&'a str>,
+    inductor_provenance_requested: bool,
+}
+
+impl CompletenessFacts<'_> {
+    fn has(&self, record_type: &str) -> bool {
+        self.record_types.contains(record_type)
+    }
+}
+
+struct Rule {
+    applies: fn(&CompletenessFacts) -> bool,
+    title: &'static str,
+    hint: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        applies: |f| {
+            f.inductor_provenance_requested
+                && !f.has("artifact:inductor_provenance_tracking_node_mappings")
+        },
+        title: "Provenance tracking was requested, but the log has no node mappings",
+        hint: "Inductor only logs the mappings between graph nodes and kernels with <code>torch._inductor.config.trace.provenance_tracking</code> enabled (<code>provenance_tracking_level = 1</code> in newer PyTorch), so no provenance pages were written.",
+    },
+    Rule {
+        applies: |f| f.has("compilation_metrics") && !f.has("dynamo_output_graph"),
+        title: "The log has compilation metrics but no graphs",
+        hint: "Graphs are logged with the rest of the structured trace by setting <code>TORCH_TRACE=/path/to/dir</code>; logs from a metrics-only logger can't show what was compiled.",
+    },
+    Rule {
+        applies: |f| f.has("compilation_metrics") && !f.has("dynamo_start"),
+        title: "The log has no dynamo_start records",
+        hint: "Without them compiles can't be placed in the stack trie.  The log was likely cut short at the start, e.g. because <code>TORCH_TRACE</code> was set after the first compile.",
+    },
+    Rule {
+        applies: |f| f.has("compilation_metrics") && !f.has("chromium_event"),
+        title: "The log has no chromium events",
+        hint: "The compile time trace (chromium_events.json) needs a PyTorch recent enough to log chromium events under <code>TORCH_TRACE</code>.",
+    },
+];
+
+/// A gap in a log, and how to fill it
+#[derive(Debug, Serialize)]
+pub struct CompletenessHint {
+    pub title: &'static str,
+    pub hint: &'static str,
+}
+
+/// Hints for the gaps in a log with the record types of `envelope_stats`
+pub fn completeness_hints(
+    envelope_stats: &[EnvelopeTypeStats],
+    config: &ParseConfig,
+) -> Vec<CompletenessHint> {
+    let facts = CompletenessFacts {
+        record_types: envelope_stats
+            .iter()
+            .map(|s| s.envelope_type.as_str())
+            .collect(),
+        inductor_provenance_requested: config.inductor_provenance,
+    };
+    RULES
+        .iter()
+        .filter(|rule| (rule.applies)(&facts))
+        .map(|rule| CompletenessHint {
+            title: rule.title,
+            hint: rule.hint,
+        })
+        .collect()
+}
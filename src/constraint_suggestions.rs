@@ -0,0 +1,399 @@
+use std::collections::HashSet;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{
+    ConstraintSuggestion, FrameLocals, SymExprInfoIndex, SymbolicShapePropagateRealTensorMetadata,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Relation {
+    // Method names recorded by expression_created for sympy relations
+    fn from_method(method: &str) -> Option<Self> {
+        match method {
+            "eq" => Some(Relation::Eq),
+            "ne" => Some(Relation::Ne),
+            "lt" => Some(Relation::Lt),
+            "le" => Some(Relation::Le),
+            "gt" => Some(Relation::Gt),
+            "ge" => Some(Relation::Ge),
+            _ => None,
+        }
+    }
+
+    fn python(self) -> &'static str {
+        match self {
+            Relation::Eq => "==",
+            Relation::Ne => "!=",
+            Relation::Lt => "<",
+            Relation::Le => "<=",
+            Relation::Gt => ">",
+            Relation::Ge => ">=",
+        }
+    }
+
+    fn negate(self) -> Self {
+        match self {
+            Relation::Eq => Relation::Ne,
+            Relation::Ne => Relation::Eq,
+            Relation::Lt => Relation::Ge,
+            Relation::Le => Relation::Gt,
+            Relation::Gt => Relation::Le,
+            Relation::Ge => Relation::Lt,
+        }
+    }
+
+    // Same relation with the two sides swapped
+    fn flip(self) -> Self {
+        match self {
+            Relation::Lt => Relation::Gt,
+            Relation::Le => Relation::Ge,
+            Relation::Gt => Relation::Lt,
+            Relation::Ge => Relation::Le,
+            r => r,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Comparison {
+    relation: Relation,
+    lhs: String,
+    rhs: String,
+}
+
+/// Suggest `dynamic_shapes` constraints or `torch._check` calls that would let export proceed
+/// without specializing on the guard.  The guard's expression tree (from `sym_expr_info_index`)
+/// tells us which relation was evaluated and which symbols it depends on; frame locals let us
+/// phrase the suggestion in terms of the user's own variables.
+pub fn suggest_constraints(
+    guard: &SymbolicShapePropagateRealTensorMetadata,
+    sym_expr_info_index: &SymExprInfoIndex,
+) -> Vec<ConstraintSuggestion> {
+    let Some(mut comparison) = comparison(guard, sym_expr_info_index) else {
+        return Vec::new();
+    };
+    // Data dependent errors record what the expression evaluated to on the real tensors; guards
+    // that were simply added must hold.
+    if guard.result.as_deref() == Some("False") {
+        comparison.relation = comparison.relation.negate();
+    }
+    let symbols = free_symbols(guard, sym_expr_info_index);
+    let has_unbacked = symbols.iter().any(|s| s.starts_with('u'));
+
+    let mut suggestions = Vec::new();
+    if !has_unbacked {
+        suggestions.extend(dim_suggestion(&comparison, guard));
+    }
+    if suggestions.is_empty() {
+        suggestions.push(check_suggestion(
+            &comparison,
+            guard,
+            &symbols,
+            sym_expr_info_index,
+        ));
+    }
+    suggestions
+}
+
+fn comparison(
+    guard: &SymbolicShapePropagateRealTensorMetadata,
+    sym_expr_info_index: &SymExprInfoIndex,
+) -> Option<Comparison> {
+    let root = guard
+        .expr_node_id
+        .and_then(|id| sym_expr_info_index.get(&id));
+    if let Some(root) = root {
+        if let (Some(relation), Some([lhs, rhs])) = (
+            root.method.as_deref().and_then(Relation::from_method),
+            root.arguments.as_deref(),
+        ) {
+            return Some(Comparison {
+                relation,
+                lhs: lhs.clone(),
+                rhs: rhs.clone(),
+            });
+        }
+    }
+    parse_comparison(guard.expr.as_deref()?)
+}
+
+// Fallback for guards whose expression tree wasn't logged: parse the printed sympy relation,
+// e.g. "Eq(s0, 3)" or "(((-u0)//3)) + 5 < 2*s0"
+fn parse_comparison(expr: &str) -> Option<Comparison> {
+    for (prefix, relation) in [("Eq(", Relation::Eq), ("Ne(", Relation::Ne)] {
+        if let Some(inner) = expr.strip_prefix(prefix).and_then(|e| e.strip_suffix(')')) {
+            let (lhs, rhs) = split_top_level(inner, ",")?;
+            return Some(Comparison {
+                relation,
+                lhs: lhs.trim().to_string(),
+                rhs: rhs.trim().to_string(),
+            });
+        }
+    }
+    for (op, relation) in [
+        (" >= ", Relation::Ge),
+        (" <= ", Relation::Le),
+        (" > ", Relation::Gt),
+        (" < ", Relation::Lt),
+    ] {
+        if let Some((lhs, rhs)) = split_top_level(expr, op) {
+            return Some(Comparison {
+                relation,
+                lhs: lhs.trim().to_string(),
+                rhs: rhs.trim().to_string(),
+            });
+        }
+    }
+    None
+}
+
+fn split_top_level<'a>(s: &'a str, sep: &str) -> Option<(&'a str, &'a str)> {
+    let mut depth = 0i32;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ if depth == 0 && s[i..].starts_with(sep) => {
+                return Some((&s[..i], &s[i + sep.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// A symbol, e.g. s0 or u1
+static SYMBOL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]+\d+$").unwrap());
+// The symbols of a printed expression
+static EXPR_SYMBOL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[a-z]+\d+\b").unwrap());
+// The dimensions of a printed tensor shape
+static TORCH_SIZE: Lazy<Regex> = Lazy::new(|| Regex::new(r"torch\.Size\(\[(.*?)\]\)").unwrap());
+
+// Leaves of the expression tree that are symbols, in the order they are first reached
+fn free_symbols(
+    guard: &SymbolicShapePropagateRealTensorMetadata,
+    sym_expr_info_index: &SymExprInfoIndex,
+) -> Vec<String> {
+    let mut symbols = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack: Vec<u64> = guard.expr_node_id.into_iter().collect();
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let Some(node) = sym_expr_info_index.get(&id) else {
+            continue;
+        };
+        match node.argument_ids.as_deref() {
+            Some(ids) if !ids.is_empty() => stack.extend(ids.iter().rev()),
+            _ => {
+                if let Some(result) = node.result.as_ref() {
+                    if is_symbol(result) && !symbols.contains(result) {
+                        symbols.push(result.clone());
+                    }
+                }
+            }
+        }
+    }
+    if symbols.is_empty() {
+        // The tree wasn't logged; fall back to the symbols in the printed expression
+        for m in EXPR_SYMBOL.find_iter(guard.expr.as_deref().unwrap_or_default()) {
+            if !symbols.iter().any(|s| s == m.as_str()) {
+                symbols.push(m.as_str().to_string());
+            }
+        }
+    }
+    symbols
+}
+
+fn is_symbol(s: &str) -> bool {
+    SYMBOL.is_match(s)
+}
+
+fn symbol_source(guard: &SymbolicShapePropagateRealTensorMetadata, symbol: &str) -> String {
+    guard
+        .symbol_to_sources
+        .as_ref()
+        .and_then(|s| s.get(symbol).cloned())
+        .or_else(|| {
+            guard
+                .frame_locals
+                .as_ref()
+                .and_then(|l| l.symbols.as_ref())
+                .and_then(|s| s.get(symbol).cloned().flatten())
+        })
+        .map_or(symbol.to_string(), |source| format!("{source} ({symbol})"))
+}
+
+// Constraints on backed sizes are best expressed through the Dims passed as dynamic_shapes
+fn dim_suggestion(
+    comparison: &Comparison,
+    guard: &SymbolicShapePropagateRealTensorMetadata,
+) -> Option<ConstraintSuggestion> {
+    let (symbol, relation, other) = if is_symbol(&comparison.lhs) {
+        (&comparison.lhs, comparison.relation, &comparison.rhs)
+    } else if is_symbol(&comparison.rhs) {
+        (&comparison.rhs, comparison.relation.flip(), &comparison.lhs)
+    } else {
+        return None;
+    };
+    let source = symbol_source(guard, symbol);
+
+    if let Ok(value) = other.parse::<i64>() {
+        let (code, explanation) = match relation {
+            Relation::Eq => (
+                "Dim.STATIC".to_string(),
+                format!(
+                    "{source} must be {value}. Mark this dimension as static in dynamic_shapes, \
+                     or change the model so it does not require this size."
+                ),
+            ),
+            Relation::Ge | Relation::Gt => {
+                let min = if relation == Relation::Gt {
+                    value + 1
+                } else {
+                    value
+                };
+                (
+                    format!("Dim(\"{symbol}\", min={min})"),
+                    format!("{source} must be at least {min}. Use a Dim with this lower bound."),
+                )
+            }
+            Relation::Le | Relation::Lt => {
+                let max = if relation == Relation::Lt {
+                    value - 1
+                } else {
+                    value
+                };
+                (
+                    format!("Dim(\"{symbol}\", max={max})"),
+                    format!("{source} must be at most {max}. Use a Dim with this upper bound."),
+                )
+            }
+            Relation::Ne => return None,
+        };
+        return Some(ConstraintSuggestion {
+            kind: "dynamic_shapes",
+            code,
+            explanation,
+        });
+    }
+
+    if relation == Relation::Eq && is_symbol(other) {
+        return Some(ConstraintSuggestion {
+            kind: "dynamic_shapes",
+            code: format!("{symbol} = Dim(\"{symbol}\")"),
+            explanation: format!(
+                "{source} and {} must be equal. Use the same Dim for both dimensions.",
+                symbol_source(guard, other)
+            ),
+        });
+    }
+    None
+}
+
+fn check_suggestion(
+    comparison: &Comparison,
+    guard: &SymbolicShapePropagateRealTensorMetadata,
+    symbols: &[String],
+    sym_expr_info_index: &SymExprInfoIndex,
+) -> ConstraintSuggestion {
+    let locals = guard.frame_locals.as_ref();
+    let code = format!(
+        "torch._check({} {} {})",
+        user_expression(&comparison.lhs, locals),
+        comparison.relation.python(),
+        user_expression(&comparison.rhs, locals),
+    );
+
+    let mut explanation = match locals.and_then(|l| l.loc.as_ref()) {
+        Some(loc) => format!("Add this before `{loc}`"),
+        None => "Add this before the line that failed".to_string(),
+    };
+    explanation.push_str(
+        " so that export assumes it holds for all inputs instead of specializing on the example \
+         input.",
+    );
+    // Point at where the unbacked symbols came from, which is usually where the check belongs
+    for symbol in symbols.iter().filter(|s| s.starts_with('u')) {
+        let origin = sym_expr_info_index
+            .values()
+            .find(|n| n.method.is_none() && n.result.as_ref() == Some(symbol))
+            .and_then(|n| n.user_stack.as_ref())
+            .and_then(|s| s.last())
+            .and_then(|f| f.loc.as_ref());
+        if let Some(origin) = origin {
+            explanation.push_str(&format!(" {symbol} comes from `{origin}`."));
+        }
+    }
+
+    ConstraintSuggestion {
+        kind: "torch._check",
+        code,
+        explanation,
+    }
+}
+
+// Rewrite one side of a comparison in terms of the user's locals: either a scalar local whose
+// value is exactly this expression, or a dimension of a tensor local.
+fn user_expression(expr: &str, locals: Option<&FrameLocals>) -> String {
+    let expr = strip_parens(expr);
+    let Some(locals) = locals.and_then(|l| l.locals.as_ref()) else {
+        return expr.to_string();
+    };
+    let mut names: Vec<&String> = locals.keys().collect();
+    names.sort();
+    for name in &names {
+        let Some(value) = locals[*name].as_deref() else {
+            continue;
+        };
+        if !value.starts_with("Tensor(") && strip_parens(value) == expr {
+            return name.to_string();
+        }
+    }
+    for name in &names {
+        let Some(value) = locals[*name].as_deref() else {
+            continue;
+        };
+        let Some(dims) = TORCH_SIZE.captures(value).map(|c| c[1].to_string()) else {
+            continue;
+        };
+        if let Some(i) = dims.split(", ").position(|d| strip_parens(d) == expr) {
+            return format!("{name}.shape[{i}]");
+        }
+    }
+    expr.to_string()
+}
+
+// Drop parentheses that wrap the whole expression
+fn strip_parens(expr: &str) -> &str {
+    let mut expr = expr.trim();
+    while let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        // "(a) + (b)" starts and ends with parentheses that don't match each other
+        let mut depth = 0i32;
+        let balanced = inner.chars().all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth >= 0
+        });
+        if !balanced {
+            break;
+        }
+        expr = inner.trim();
+    }
+    expr
+}
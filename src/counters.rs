@@ -0,0 +1,69 @@
+// Counter artifacts (dynamo counters, wait counters) are snapshots of running totals, so what a
+// compile did is the difference from the snapshot before it.
+use fxhash::FxHashMap;
+
+use crate::templates::{TEMPLATE_FAILURES_CSS, TEMPLATE_QUERY_PARAM_SCRIPT};
+use crate::types::{CompileCounters, CounterRow, CountersContext, CountersIndex, GlobalCounterRow};
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value:.3}")
+    }
+}
+
+/// The context of counters.html
+pub fn counters_context(counters_index: &CountersIndex) -> CountersContext {
+    // The last value of each counter and how often it changed, by artifact
+    let mut global: FxHashMap<(&str, &str), (f64, usize)> = FxHashMap::default();
+    let mut compiles = Vec::new();
+    for snapshot in counters_index {
+        let mut rows = Vec::new();
+        for (counter, &value) in &snapshot.values {
+            let key = (snapshot.source.as_str(), counter.as_str());
+            let (last, num_changes) = global.entry(key).or_default();
+            let delta = value - *last;
+            *last = value;
+            if delta != 0.0 {
+                *num_changes += 1;
+            }
+            rows.push(CounterRow {
+                counter: counter.clone(),
+                value: format_value(value),
+                delta: match delta {
+                    d if d > 0.0 => format!("+{}", format_value(d)),
+                    d if d < 0.0 => format_value(d),
+                    _ => String::new(),
+                },
+            });
+        }
+        compiles.push(CompileCounters {
+            compile_id: snapshot
+                .compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            source: snapshot.source.clone(),
+            num_changed: rows.iter().filter(|r| !r.delta.is_empty()).count(),
+            rows,
+        });
+    }
+    let mut global: Vec<GlobalCounterRow> = global
+        .into_iter()
+        .map(
+            |((source, counter), (value, num_changes))| GlobalCounterRow {
+                source: source.to_string(),
+                counter: counter.to_string(),
+                value: format_value(value),
+                num_changes,
+            },
+        )
+        .collect();
+    global.sort_by(|a, b| (&a.source, &a.counter).cmp(&(&b.source, &b.counter)));
+    CountersContext {
+        css: TEMPLATE_FAILURES_CSS,
+        global,
+        compiles,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    }
+}
@@ -0,0 +1,105 @@
+// Deep links to a line or row of any page of the report, e.g. for bug reports:
+//
+//   inductor_output_code_abc_0.html?line=12-20  lines 12 to 20 of code pages (id L12..)
+//   dynamo_output_graph_0.html?line=12-20       lines of a graph dump, see `add_line_views`
+//   compilation_metrics_0.html?row=graph-metrics  the element of that id, e.g. a table row
+//   provenance_tracking.html?row=postGradGraph-L12  line 12 of a pane of the provenance page
+//   index.html?artifact=fx_graph_cache_miss     the links to artifacts whose name contains it
+//
+// The script that highlights them is added to every page once the output is complete, rather
+// than to each template.  Unlike other query parameters, TEMPLATE_QUERY_PARAM_SCRIPT doesn't pass
+// them on to the links of the page.
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use html_escape::encode_text;
+
+use crate::types::ParseOutput;
+
+const DEEP_LINK_SCRIPT: &str = r#"<style>
+.deep-link-target { background-color: #fff5b1; outline: 2px solid #f0c000; }
+</style>
+<script id="deep-link-script">
+document.addEventListener('DOMContentLoaded', function() {
+    const params = new URLSearchParams(window.location.search);
+    const targets = [];
+    const line = params.get('line');
+    if (line) {
+        const [start, end] = line.split('-').map(Number);
+        for (let i = start; i <= (end || start); i++) {
+            const el = document.getElementById('L' + i);
+            if (el) targets.push(el);
+        }
+    }
+    const row = params.get('row');
+    if (row && document.getElementById(row)) {
+        targets.push(document.getElementById(row));
+    }
+    const artifact = params.get('artifact');
+    if (artifact) {
+        document.querySelectorAll('a[href]').forEach(function(a) {
+            if (a.textContent.includes(artifact)) targets.push(a);
+        });
+    }
+    targets.forEach(function(el) { el.classList.add('deep-link-target'); });
+    if (targets.length > 0) targets[0].scrollIntoView({block: 'center'});
+});
+</script>
+"#;
+
+// Graph dumps, e.g. dynamo_output_graph_0.txt or aot_forward_graph_3.txt
+fn is_graph_dump(path: &Path) -> bool {
+    path.extension() == Some("txt".as_ref())
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.rsplit_once('_'))
+            .is_some_and(|(name, n)| name.ends_with("_graph") && n.parse::<usize>().is_ok())
+}
+
+/// Adds a page next to each graph dump of `output` with its lines, which `?line=` can link to
+/// unlike the plain text, e.g. dynamo_output_graph_0.html for dynamo_output_graph_0.txt
+pub fn add_line_views(mut output: ParseOutput) -> ParseOutput {
+    let paths: HashSet<PathBuf> = output.iter().map(|(path, _)| path.clone()).collect();
+    let mut views = Vec::new();
+    for (path, content) in &output {
+        let view = path.with_extension("html");
+        if !is_graph_dump(path) || paths.contains(&view) {
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut html = format!(
+            "<html>\n<head><meta charset=\"utf-8\"><title>{name}</title></head>\n<body>\n\
+             <p><a href=\"{name}\">{name}</a></p>\n<pre>"
+        );
+        for (i, line) in content.lines().enumerate() {
+            html.push_str(&format!(
+                "<span id=\"L{}\">{}\n</span>",
+                i + 1,
+                encode_text(line)
+            ));
+        }
+        html.push_str("</pre>\n</body>\n</html>\n");
+        views.push((view, html));
+    }
+    output.extend(views);
+    output
+}
+
+/// Adds the deep link script to every html page of `output`
+pub fn add_deep_link_script(output: ParseOutput) -> ParseOutput {
+    output
+        .into_iter()
+        .map(|(path, content)| {
+            if path.extension() != Some("html".as_ref()) {
+                return (path, content);
+            }
+            let mut content = content;
+            match content.rfind("</body>") {
+                Some(at) => content.insert_str(at, DEEP_LINK_SCRIPT),
+                None => content.push_str(DEEP_LINK_SCRIPT),
+            }
+            (path, content)
+        })
+        .collect()
+}
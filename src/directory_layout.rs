@@ -0,0 +1,90 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::types::CompileId;
+
+/// How the directories of compile ids are named
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectoryLayout {
+    /// One directory per compile id, e.g. -_0_0_0/ for [0/0]
+    #[default]
+    Flat,
+    /// A directory per frame, compile and attempt, e.g. frame_0/compile_0/attempt_0/ for [0/0],
+    /// under compiled_autograd_1/ for [!1/0/0]
+    Nested,
+}
+
+impl FromStr for DirectoryLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(DirectoryLayout::Flat),
+            "nested" => Ok(DirectoryLayout::Nested),
+            _ => Err(format!(
+                "unknown directory layout {s}; expected one of: flat, nested"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DirectoryLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DirectoryLayout::Flat => "flat",
+            DirectoryLayout::Nested => "nested",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl DirectoryLayout {
+    /// The directory of the artifacts of `compile_id`, relative to the root of the report
+    pub fn directory_name(&self, compile_id: &CompileId) -> String {
+        match self {
+            DirectoryLayout::Flat => compile_id.as_directory_name(),
+            DirectoryLayout::Nested => {
+                let id = |id: Option<u32>| id.map_or("unknown".to_string(), |id| id.to_string());
+                let mut directory = String::new();
+                if let Some(compiled_autograd_id) = compile_id.compiled_autograd_id {
+                    directory.push_str(&format!("compiled_autograd_{compiled_autograd_id}/"));
+                }
+                directory.push_str(&format!(
+                    "frame_{}/compile_{}/attempt_{}",
+                    id(compile_id.frame_id),
+                    id(compile_id.frame_compile_id),
+                    id(compile_id.attempt)
+                ));
+                directory
+            }
+        }
+    }
+
+    /// The directory of the artifacts of the record at `lineno`; records without a compile id get
+    /// one of their own
+    pub fn compile_id_dir(&self, compile_id: Option<&CompileId>, lineno: usize) -> PathBuf {
+        compile_id
+            .map_or(format!("unknown_{lineno}"), |cid| self.directory_name(cid))
+            .into()
+    }
+
+    /// The link from the directory of `compile_id` (see `compile_id_dir`) back up to the root of
+    /// the report, e.g. ../
+    pub fn root_link(&self, compile_id: Option<&CompileId>) -> String {
+        let depth = compile_id.map_or(1, |cid| self.directory_name(cid).split('/').count());
+        "../".repeat(depth)
+    }
+
+    /// Where this layout puts `path`, written by a parser under the flat directory of the record's
+    /// `compile_id` as `build_file_path` does
+    pub(crate) fn relocate(&self, path: PathBuf, compile_id: Option<&CompileId>) -> PathBuf {
+        let Some(cid) = compile_id.filter(|_| *self != DirectoryLayout::Flat) else {
+            return path;
+        };
+        match path.strip_prefix(cid.as_directory_name()) {
+            Ok(rest) => Path::new(&self.directory_name(cid)).join(rest),
+            Err(_) => path,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt::Write;
+
+use crate::fx_graph::FxGraph;
+
+// Bigger graphs are unreadable once laid out, their text dump is easier to follow
+pub const MAX_DOT_NODES: usize = 200;
+
+/// `f32[1, 320, 128, 128][5242880, 1, 40960, 320]cuda:0` without the strides and device, to
+/// keep the boxes narrow
+fn short_type(ty: &str) -> &str {
+    ty.find(']').map_or(ty, |end| &ty[..=end])
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `graph` as a Graphviz graph, or None if it has more than `MAX_DOT_NODES` nodes
+pub fn fx_graph_to_dot(graph: &FxGraph) -> Option<String> {
+    if graph.nodes.len() > MAX_DOT_NODES {
+        return None;
+    }
+    let mut dot = String::new();
+    writeln!(dot, "digraph G {{").unwrap();
+    writeln!(
+        dot,
+        "    node [shape=box, fontname=\"monospace\", fontsize=10];"
+    )
+    .unwrap();
+    for node in &graph.nodes {
+        let mut label = node.name.clone();
+        let ty = node.ty.as_deref().map(short_type);
+        for line in node.target.as_deref().into_iter().chain(ty) {
+            write!(label, "\n{line}").unwrap();
+        }
+        let shape = if node.target.is_none() {
+            ", shape=ellipse"
+        } else {
+            ""
+        };
+        writeln!(
+            dot,
+            "    \"{}\" [label=\"{}\"{shape}];",
+            escape(&node.name),
+            escape(&label).replace('\n', "\\n")
+        )
+        .unwrap();
+        for input in &node.inputs {
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\";",
+                escape(input),
+                escape(&node.name)
+            )
+            .unwrap();
+        }
+    }
+    if !graph.outputs.is_empty() {
+        writeln!(dot, "    \"output\" [shape=doubleoctagon];").unwrap();
+        for output in &graph.outputs {
+            writeln!(dot, "    \"{}\" -> \"output\";", escape(output)).unwrap();
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+    Some(dot)
+}
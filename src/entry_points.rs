@@ -0,0 +1,141 @@
+use fxhash::FxHashSet;
+use serde::Serialize;
+
+use crate::types::{
+    simplify_filename, CompilationMetricsIndex, CompileId, FxIndexMap, InternTable, StackIndex,
+    StackSummary,
+};
+
+// How many entry points to list on the index
+const NUM_HOT_ENTRY_POINTS: usize = 10;
+
+/// A user function Dynamo compiled, as named by compilation_metrics
+#[derive(Debug, Serialize)]
+pub struct EntryPoint {
+    pub name: String,
+    pub filename: String,
+    pub line: i32,
+    pub num_compiles: usize,
+    /// Distinct dynamo_start stacks the function was compiled from, more than one if it has
+    /// several callers
+    pub num_stacks: usize,
+    pub compile_time_s: f64,
+    pub compile_ids: Vec<String>,
+}
+
+// (filename, function name, line)
+type Location = (String, String, i32);
+
+fn compile_id_key(compile_id: &Option<CompileId>) -> impl Ord {
+    compile_id.as_ref().map(|c| {
+        (
+            c.compiled_autograd_id,
+            c.frame_id,
+            c.frame_compile_id,
+            c.attempt,
+        )
+    })
+}
+
+/// The functions that triggered the most compilations, by (filename, function), most compiled
+/// first and then by time spent compiling them.  Compilations that logged no metrics, e.g.
+/// because they crashed, are attributed to the innermost frame of their stack instead.
+pub fn hot_entry_points(
+    stack_index: &StackIndex,
+    metrics_index: &CompilationMetricsIndex,
+    intern_table: &InternTable,
+) -> Vec<EntryPoint> {
+    // Stacks are logged when Dynamo starts on a frame, i.e. by the first attempt
+    let first_attempt = |compile_id: &Option<CompileId>| {
+        let mut compile_id = compile_id.clone();
+        if let Some(c) = compile_id.as_mut() {
+            c.attempt = Some(0);
+        }
+        compile_id
+    };
+    let stack_of = |compile_id: &Option<CompileId>| stack_index.get(&first_attempt(compile_id));
+    let mut compiles: Vec<(&Option<CompileId>, Location, f64)> = Vec::new();
+    let mut with_metrics = FxHashSet::default();
+    for (compile_id, metrics) in metrics_index {
+        let Some(m) = metrics.iter().find(|m| m.co_name.is_some()) else {
+            continue;
+        };
+        let compile_time_s = metrics
+            .iter()
+            .filter_map(|m| m.entire_frame_compile_time_s)
+            .sum::<f64>();
+        let filename = m.co_filename.as_deref().unwrap_or("(unknown)");
+        compiles.push((
+            compile_id,
+            (
+                simplify_filename(filename).to_string(),
+                m.co_name.clone().unwrap_or_default(),
+                m.co_firstlineno.unwrap_or_default(),
+            ),
+            compile_time_s,
+        ));
+        with_metrics.insert(first_attempt(compile_id));
+    }
+    for (compile_id, stack) in stack_index {
+        if with_metrics.contains(compile_id) {
+            continue;
+        }
+        let Some(frame) = stack.last() else {
+            continue;
+        };
+        compiles.push((
+            compile_id,
+            (
+                simplify_filename(frame.filename(intern_table)).to_string(),
+                frame.name.clone(),
+                frame.line,
+            ),
+            0.0,
+        ));
+    }
+    compiles.sort_by_key(|(compile_id, _, _)| compile_id_key(compile_id));
+
+    let mut entry_points: FxIndexMap<(String, String), (EntryPoint, FxHashSet<&StackSummary>)> =
+        FxIndexMap::default();
+    for (compile_id, (filename, name, line), compile_time_s) in compiles {
+        let (entry_point, stacks) = entry_points
+            .entry((filename.clone(), name.clone()))
+            .or_insert_with(|| {
+                (
+                    EntryPoint {
+                        name,
+                        filename,
+                        line,
+                        num_compiles: 0,
+                        num_stacks: 0,
+                        compile_time_s: 0.0,
+                        compile_ids: Vec::new(),
+                    },
+                    FxHashSet::default(),
+                )
+            });
+        entry_point.num_compiles += 1;
+        entry_point.compile_time_s += compile_time_s;
+        entry_point.compile_ids.push(
+            compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+        );
+        stacks.extend(stack_of(compile_id));
+    }
+    let mut entry_points: Vec<EntryPoint> = entry_points
+        .into_values()
+        .map(|(mut entry_point, stacks)| {
+            entry_point.num_stacks = stacks.len();
+            entry_point.compile_time_s = (entry_point.compile_time_s * 1000.0).round() / 1000.0;
+            entry_point
+        })
+        .collect();
+    entry_points.sort_by(|a, b| {
+        b.num_compiles
+            .cmp(&a.num_compiles)
+            .then(b.compile_time_s.total_cmp(&a.compile_time_s))
+    });
+    entry_points.truncate(NUM_HOT_ENTRY_POINTS);
+    entry_points
+}
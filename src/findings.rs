@@ -0,0 +1,266 @@
+// Rule-based checks over a parsed log for the usual suspects of slow or flaky compilation, shown
+// as "Findings" at the top of index.html, most severe first.
+use fxhash::FxHashSet;
+use serde::Serialize;
+
+use crate::types::{
+    CompilationMetricsIndex, CompileId, CompileTimeSkew, CudagraphSkip, FxIndexMap, OutputFile,
+};
+
+// Compiles of one frame that are worth a look, and Dynamo's default cache_size_limit, past which
+// it gives up on compiling the frame
+const MANY_COMPILES: usize = 3;
+const CACHE_SIZE_LIMIT: usize = 8;
+// Guards of one compile, past which checking them adds noticeable overhead to every call
+const MANY_GUARDS: u64 = 1000;
+// A compile is an outlier if it takes this many times the median compile time, and at least
+// MIN_OUTLIER_S seconds
+const OUTLIER_FACTOR: f64 = 3.0;
+const MIN_OUTLIER_S: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    High,
+    Medium,
+    Low,
+}
+
+/// A link to what a finding is based on, e.g. the compilation metrics of a compile id
+#[derive(Debug, Serialize)]
+pub struct Evidence {
+    pub text: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub title: String,
+    pub detail: String,
+    pub evidence: Vec<Evidence>,
+}
+
+fn compile_id_name(compile_id: &Option<CompileId>) -> String {
+    compile_id
+        .as_ref()
+        .map_or("(unknown)".to_string(), |c| c.to_string())
+}
+
+/// Evidence for `compile_id`: its first file whose name contains `artifact`, or else its entry
+/// on the index
+fn evidence(
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    compile_id: &Option<CompileId>,
+    artifact: &str,
+) -> Evidence {
+    let text = compile_id_name(compile_id);
+    let url = directory
+        .get(compile_id)
+        .and_then(|files| files.iter().find(|f| f.name.contains(artifact)))
+        .map_or_else(|| format!("#{text}"), |f| f.url.clone());
+    Evidence { text, url }
+}
+
+// The name of the function of each (compiled autograd id, frame id), and its compile ids
+type FrameCompiles<'a> =
+    FxIndexMap<(Option<u32>, Option<u32>), (Option<String>, Vec<&'a Option<CompileId>>)>;
+
+fn recompile_findings(
+    metrics_index: &CompilationMetricsIndex,
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+) -> Vec<Finding> {
+    let mut frames: FrameCompiles = FxIndexMap::default();
+    let mut seen = FxHashSet::default();
+    for (compile_id, metrics) in metrics_index {
+        let Some(c) = compile_id else {
+            continue;
+        };
+        let frame = (c.compiled_autograd_id, c.frame_id);
+        if !seen.insert((frame, c.frame_compile_id)) {
+            // Another attempt of the same compile
+            continue;
+        }
+        let (co_name, compile_ids) = frames.entry(frame).or_default();
+        if co_name.is_none() {
+            *co_name = metrics.iter().find_map(|m| m.co_name.clone());
+        }
+        compile_ids.push(compile_id);
+    }
+    frames
+        .into_values()
+        .filter(|(_, compile_ids)| compile_ids.len() >= MANY_COMPILES)
+        .map(|(co_name, compile_ids)| {
+            let name = co_name.unwrap_or("(unknown)".to_string());
+            let (severity, consequence) = if compile_ids.len() >= CACHE_SIZE_LIMIT {
+                (
+                    Severity::High,
+                    "which reaches Dynamo's default cache_size_limit, past which it stops compiling the frame and runs it eagerly",
+                )
+            } else {
+                (
+                    Severity::Medium,
+                    "each recompile costs compile time and adds guards to check on every call",
+                )
+            };
+            Finding {
+                severity,
+                title: format!("`{name}` was compiled {} times", compile_ids.len()),
+                detail: format!(
+                    "The frame was recompiled {} times, {consequence}.  The guard failures of its recompiles show what keeps changing.",
+                    compile_ids.len() - 1
+                ),
+                evidence: compile_ids
+                    .into_iter()
+                    .map(|c| evidence(directory, c, "compilation_metrics"))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn guard_count_findings(
+    metrics_index: &CompilationMetricsIndex,
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+) -> Vec<Finding> {
+    metrics_index
+        .iter()
+        .filter_map(|(compile_id, metrics)| {
+            let guard_count = metrics.iter().filter_map(|m| m.guard_count).max()?;
+            (guard_count > MANY_GUARDS).then(|| Finding {
+                severity: Severity::Medium,
+                title: format!(
+                    "{} installed {guard_count} guards",
+                    compile_id_name(compile_id)
+                ),
+                detail: format!(
+                    "Guards are checked on every call of the compiled frame; more than {MANY_GUARDS} add noticeable overhead.  Large pytrees or many parameters passed as inputs are the usual cause."
+                ),
+                evidence: vec![evidence(directory, compile_id, "dynamo_guards")],
+            })
+        })
+        .collect()
+}
+
+fn compile_time_findings(
+    metrics_index: &CompilationMetricsIndex,
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+) -> Vec<Finding> {
+    let compile_times: Vec<(&Option<CompileId>, f64)> = metrics_index
+        .iter()
+        .filter_map(|(compile_id, metrics)| {
+            let times = metrics.iter().filter_map(|m| m.entire_frame_compile_time_s);
+            Some((compile_id, times.reduce(|a, b| a + b)?))
+        })
+        .collect();
+    if compile_times.len() < 3 {
+        return Vec::new();
+    }
+    let mut sorted: Vec<f64> = compile_times.iter().map(|(_, t)| *t).collect();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+    compile_times
+        .into_iter()
+        .filter(|(_, t)| *t >= MIN_OUTLIER_S && *t > OUTLIER_FACTOR * median)
+        .map(|(compile_id, t)| Finding {
+            severity: Severity::Medium,
+            title: format!("{} took {t:.1} s to compile", compile_id_name(compile_id)),
+            detail: format!(
+                "That is {:.0}x the median compile time of {median:.2} s.  The chromium events and Inductor pass timings break it down by phase.",
+                t / median.max(f64::EPSILON)
+            ),
+            evidence: vec![evidence(directory, compile_id, "compilation_metrics")],
+        })
+        .collect()
+}
+
+fn cudagraph_findings(cudagraph_skips: &[CudagraphSkip]) -> Vec<Finding> {
+    if cudagraph_skips.is_empty() {
+        return Vec::new();
+    }
+    vec![Finding {
+        severity: Severity::Medium,
+        title: format!(
+            "CUDA graphs were skipped for {} graph(s)",
+            cudagraph_skips.len()
+        ),
+        detail: format!(
+            "Graphs without CUDA graphs pay the launch overhead of every kernel, e.g. because: {}",
+            cudagraph_skips[0].reason
+        ),
+        evidence: cudagraph_skips
+            .iter()
+            .map(|skip| Evidence {
+                text: skip.compile_id.clone(),
+                url: skip.url.clone(),
+            })
+            .collect(),
+    }]
+}
+
+fn cache_bypass_findings(
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+) -> Vec<Finding> {
+    // The suffix the index marks cache bypasses with
+    let bypasses: Vec<Evidence> = directory
+        .iter()
+        .flat_map(|(compile_id, files)| {
+            files
+                .iter()
+                .filter(|f| f.suffix == "❓")
+                .map(move |f| Evidence {
+                    text: compile_id_name(compile_id),
+                    url: f.url.clone(),
+                })
+        })
+        .collect();
+    if bypasses.is_empty() {
+        return Vec::new();
+    }
+    vec![Finding {
+        severity: Severity::Low,
+        title: format!("The compile caches were bypassed {} time(s)", bypasses.len()),
+        detail: "Bypassed graphs are compiled from scratch on every run; the bypass artifacts say which input or option can't be cached.".to_string(),
+        evidence: bypasses,
+    }]
+}
+
+/// The findings of a log, most severe first
+pub fn findings(
+    metrics_index: &CompilationMetricsIndex,
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    cudagraph_skips: &[CudagraphSkip],
+) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = recompile_findings(metrics_index, directory)
+        .into_iter()
+        .chain(guard_count_findings(metrics_index, directory))
+        .chain(compile_time_findings(metrics_index, directory))
+        .chain(cudagraph_findings(cudagraph_skips))
+        .chain(cache_bypass_findings(directory))
+        .collect();
+    findings.sort_by_key(|f| f.severity);
+    findings
+}
+
+/// Findings of a multi-rank report: ranks that compile much longer than the others
+pub fn straggler_findings(skew: &CompileTimeSkew) -> Vec<Finding> {
+    let stragglers: Vec<_> = skew.ranks.iter().filter(|r| r.straggler).collect();
+    if stragglers.is_empty() {
+        return Vec::new();
+    }
+    vec![Finding {
+        severity: Severity::High,
+        title: format!("{} rank(s) are compile time stragglers", stragglers.len()),
+        detail: format!(
+            "Their compile time deviates from the median of {} s by more than {}%, which stalls the collectives of the other ranks until they catch up.",
+            skew.median_s, skew.threshold_pct
+        ),
+        evidence: stragglers
+            .into_iter()
+            .map(|r| Evidence {
+                text: format!("rank {} ({} s)", r.rank, r.compile_time_s),
+                url: format!("rank_{}/index.html", r.rank),
+            })
+            .collect(),
+    }]
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use html_escape::encode_text;
+
+use crate::types::{
+    simplify_filename, CompilationMetricsIndex, FrameSummary, InternTable, StackIndex,
+};
+
+/// A user stack (outermost frame first, ending with the compile id) and the time in milliseconds
+/// spent compiling it.
+pub type FoldedStack = (Vec<String>, u64);
+
+const IMAGE_WIDTH: f64 = 1200.0;
+const FRAME_HEIGHT: f64 = 16.0;
+const TITLE_HEIGHT: f64 = 32.0;
+// Rough width of a character at font-size 12, used to decide how much of a label fits
+const CHAR_WIDTH: f64 = 7.0;
+
+fn frame_label(frame: &FrameSummary, intern_table: &InternTable) -> String {
+    // Semicolons separate frames in the folded format
+    format!(
+        "{} ({}:{})",
+        frame.name,
+        simplify_filename(frame.filename(intern_table)),
+        frame.line
+    )
+    .replace(';', ":")
+}
+
+/// Pair up the stack that triggered each compilation with its entire_frame_compile_time_s.
+/// Compilations without timing information are left out.
+pub fn folded_stacks(
+    stack_index: &StackIndex,
+    metrics_index: &CompilationMetricsIndex,
+    intern_table: &InternTable,
+) -> Vec<FoldedStack> {
+    let mut stacks: Vec<FoldedStack> = stack_index
+        .iter()
+        .filter_map(|(compile_id, stack)| {
+            let compile_time_s: f64 = metrics_index
+                .get(compile_id)?
+                .iter()
+                .filter_map(|m| m.entire_frame_compile_time_s)
+                .fold(0.0, |acc, t| acc + t);
+            let compile_time_ms = (compile_time_s * 1000.0).round() as u64;
+            if compile_time_ms == 0 {
+                return None;
+            }
+            let mut frames: Vec<String> = stack
+                .iter()
+                .map(|frame| frame_label(frame, intern_table))
+                .collect();
+            frames.push(
+                compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+            );
+            Some((frames, compile_time_ms))
+        })
+        .collect();
+    stacks.sort();
+    stacks
+}
+
+/// Folded stacks format, as consumed by inferno-flamegraph and flamegraph.pl
+pub fn to_folded(stacks: &[FoldedStack]) -> String {
+    let mut out = String::new();
+    for (frames, compile_time_ms) in stacks {
+        writeln!(out, "{} {}", frames.join(";"), compile_time_ms).unwrap();
+    }
+    out
+}
+
+#[derive(Default)]
+struct FlameNode {
+    value: u64,
+    children: BTreeMap<String, FlameNode>,
+}
+
+impl FlameNode {
+    fn depth(&self) -> usize {
+        self.children
+            .values()
+            .map(|c| c.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Render the stacks as a flame graph, where the width of a frame is the time spent compiling
+/// everything called from it.
+pub fn render_svg(stacks: &[FoldedStack], title: &str) -> String {
+    let mut root = FlameNode::default();
+    for (frames, compile_time_ms) in stacks {
+        root.value += compile_time_ms;
+        let mut cur = &mut root;
+        for frame in frames {
+            cur = cur.children.entry(frame.clone()).or_default();
+            cur.value += compile_time_ms;
+        }
+    }
+
+    let height = TITLE_HEIGHT + FRAME_HEIGHT * root.depth() as f64;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r##"<?xml version="1.0" standalone="no"?>
+<svg version="1.1" width="{IMAGE_WIDTH}" height="{height}" xmlns="http://www.w3.org/2000/svg" font-family="Verdana, sans-serif" font-size="12">
+<rect width="100%" height="100%" fill="#f8f8f8"/>
+<text x="{}" y="20" text-anchor="middle" font-size="16">{}</text>"##,
+        IMAGE_WIDTH / 2.0,
+        encode_text(title),
+    )
+    .unwrap();
+    if root.value > 0 {
+        render_children(&mut svg, &root, root.value, 0.0, height - FRAME_HEIGHT);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn render_children(svg: &mut String, node: &FlameNode, total: u64, x: f64, y: f64) {
+    let mut x = x;
+    for (label, child) in &node.children {
+        let width = IMAGE_WIDTH * child.value as f64 / total as f64;
+        let percent = 100.0 * child.value as f64 / total as f64;
+        let max_chars = ((width - 6.0) / CHAR_WIDTH).max(0.0) as usize;
+        let text = if label.chars().count() <= max_chars {
+            label.clone()
+        } else if max_chars > 2 {
+            format!(
+                "{}..",
+                label.chars().take(max_chars - 2).collect::<String>()
+            )
+        } else {
+            String::new()
+        };
+        writeln!(
+            svg,
+            r#"<g><title>{} ({:.3}s, {percent:.2}%)</title><rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{}" fill="{}" rx="2"/><text x="{:.2}" y="{:.2}">{}</text></g>"#,
+            encode_text(label),
+            child.value as f64 / 1000.0,
+            FRAME_HEIGHT - 1.0,
+            frame_color(label),
+            x + 3.0,
+            y + FRAME_HEIGHT - 4.0,
+            encode_text(&text),
+        )
+        .unwrap();
+        render_children(svg, child, total, x, y - FRAME_HEIGHT);
+        x += width;
+    }
+}
+
+// Warm colors in the style of flamegraph.pl, stable per frame so that re-running tlparse
+// doesn't reshuffle the picture
+fn frame_color(label: &str) -> String {
+    let hash = label
+        .bytes()
+        .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    let r = 205 + hash % 50;
+    let g = (hash / 50) % 230;
+    let b = (hash / 11500) % 55;
+    format!("rgb({r},{g},{b})")
+}
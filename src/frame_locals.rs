@@ -0,0 +1,152 @@
+// Rendering of the frame locals that Dynamo captures with guards, which are the reprs of the
+// values: tensors get badges for their shape, dtype and device, containers can be expanded item
+// by item, and long reprs are cut short.
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use html_escape::encode_text;
+
+use crate::types::FrameLocals;
+
+// Reprs longer than this are cut short, with the rest shown on click
+const MAX_REPR_CHARS: usize = 120;
+
+// e.g. Tensor(shape: torch.Size([2*s0]), stride: (1,), storage_offset: 0),
+// FakeTensor(..., device='cuda:0', size=(4, 3), dtype=torch.float16) or tensor([1., 2.])
+static TENSOR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:FakeTensor|Tensor|tensor|Parameter)\(").unwrap());
+static SHAPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:shape: torch\.Size\(\[([^\]]*)\]\)|size=\(([^)]*?),?\))").unwrap()
+});
+static DTYPE: Lazy<Regex> = Lazy::new(|| Regex::new(r"dtype[=:] ?torch\.(\w+)").unwrap());
+static DEVICE: Lazy<Regex> = Lazy::new(|| Regex::new(r"device[=:] ?'?([\w:]+)'?").unwrap());
+
+fn badge(text: &str) -> String {
+    format!("<span class='badge'>{}</span>", encode_text(text))
+}
+
+fn render_tensor(repr: &str) -> String {
+    let kind = repr.split('(').next().unwrap_or_default();
+    let mut html = format!(
+        "<span class='tensor' title='{}'>{kind}</span>",
+        html_escape::encode_single_quoted_attribute(repr)
+    );
+    if let Some(c) = SHAPE.captures(repr) {
+        let shape = c.get(1).or(c.get(2)).map_or("", |m| m.as_str());
+        html.push_str(&badge(&format!("[{shape}]")));
+    }
+    for re in [&DTYPE, &DEVICE] {
+        if let Some(c) = re.captures(repr) {
+            html.push_str(&badge(&c[1]));
+        }
+    }
+    html
+}
+
+// The items of a list, tuple, set or dict repr, split at its top-level commas
+fn container_items(repr: &str) -> Option<Vec<&str>> {
+    let (open, close) = match repr.chars().next()? {
+        '[' => ('[', ']'),
+        '(' => ('(', ')'),
+        '{' => ('{', '}'),
+        _ => return None,
+    };
+    let inner = repr.strip_prefix(open)?.strip_suffix(close)?;
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(' | '{') => depth += 1,
+            (None, ']' | ')' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                items.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(inner[start..].trim());
+    items.retain(|item| !item.is_empty());
+    Some(items)
+}
+
+/// Renders the repr of a local as html
+pub fn render_value(repr: &str) -> String {
+    if TENSOR.is_match(repr) {
+        return render_tensor(repr);
+    }
+    if let Some(items) = container_items(repr) {
+        // Short containers of plain values read fine as they are
+        let nested = items
+            .iter()
+            .any(|item| TENSOR.is_match(item) || container_items(item).is_some());
+        if nested || repr.len() > MAX_REPR_CHARS {
+            let mut html = format!(
+                "<details><summary><code>{}</code> of {} item(s)</summary><ul>",
+                &repr[..1],
+                items.len()
+            );
+            for item in items {
+                html.push_str("<li>");
+                // Dict items are `key: value`
+                match item.split_once(": ").filter(|_| repr.starts_with('{')) {
+                    Some((key, value)) => {
+                        html.push_str(&format!("<code>{}</code>: ", encode_text(key)));
+                        html.push_str(&render_value(value));
+                    }
+                    None => html.push_str(&render_value(item)),
+                }
+                html.push_str("</li>");
+            }
+            html.push_str("</ul></details>");
+            return html;
+        }
+    }
+    if repr.chars().count() > MAX_REPR_CHARS {
+        let cut: String = repr.chars().take(MAX_REPR_CHARS).collect();
+        return format!(
+            "<details class='long-repr'><summary><code>{}</code>…</summary><pre>{}</pre></details>",
+            encode_text(&cut),
+            encode_text(repr)
+        );
+    }
+    format!("<code>{}</code>", encode_text(repr))
+}
+
+/// A table of locals (or symbols) and their values, sorted by name
+pub fn render_locals<'a>(locals: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut locals: Vec<(&str, &str)> = locals.into_iter().collect();
+    locals.sort();
+    let mut html = String::from("<table class='frame-locals'>");
+    for (name, value) in locals {
+        html.push_str(&format!(
+            "<tr><td><code>{}</code></td><td>{}</td></tr>",
+            encode_text(name),
+            render_value(value)
+        ));
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// The locals and symbols captured with a guard, as html
+pub fn render_frame_locals(frame_locals: &FrameLocals) -> String {
+    let mut html = String::new();
+    for (caption, values) in [
+        ("Locals", &frame_locals.locals),
+        ("Symbols", &frame_locals.symbols),
+    ] {
+        if let Some(values) = values {
+            let values = values
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.as_deref()?)));
+            html.push_str(&format!("{caption}:{}", render_locals(values)));
+        }
+    }
+    html
+}
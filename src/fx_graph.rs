@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use fxhash::{FxHashMap, FxHashSet};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::GraphStats;
+
+static NODE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^([A-Za-z_]\w*)(?:\s*:\s*"([^"]*)")?\s*=\s*(.*)$"#).unwrap());
+static IDENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z_][\w.]*").unwrap());
+
+pub struct FxNode {
+    pub name: String,
+    /// What the node calls, e.g. aten.mul.Tensor, or None for inputs
+    pub target: Option<String>,
+    /// e.g. f32[1, 320, 128, 128][5242880, 1, 40960, 320]cuda:0
+    pub ty: Option<String>,
+    /// Nodes this one uses
+    pub inputs: Vec<String>,
+    /// What the node is assigned, e.g. torch.ops.aten.mul.Tensor(x, 2), empty for inputs
+    pub code: String,
+}
+
+/// The nodes of the top-level `forward` of an FX graph dump, e.g. dynamo_output_graph or
+/// inductor_post_grad_graph, in order
+pub struct FxGraph {
+    pub nodes: Vec<FxNode>,
+    /// Nodes returned by `forward`
+    pub outputs: Vec<String>,
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Splits on commas outside of quotes and brackets, e.g. the parameters of `forward`
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quote, mut start) = (0, None, 0);
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Where `code` refers to names outside of string literals, i.e. the heads of dotted names such
+/// as `l_x_` of `l_x_.size()`, so that `torch.ops.aten.add` doesn't refer to a node named `add`
+fn name_spans(code: &str) -> Vec<Range<usize>> {
+    let mut in_quote = None;
+    let mut unquoted = String::with_capacity(code.len());
+    for c in code.chars() {
+        let quoted = in_quote.is_some() || matches!(c, '"' | '\'');
+        match (in_quote, c) {
+            (Some(q), c) if c == q => in_quote = None,
+            (None, '"' | '\'') => in_quote = Some(c),
+            _ => {}
+        }
+        if quoted {
+            unquoted.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        } else {
+            unquoted.push(c);
+        }
+    }
+    IDENT
+        .find_iter(&unquoted)
+        .map(|m| {
+            let head = m.as_str().find('.').unwrap_or(m.len());
+            m.start()..m.start() + head
+        })
+        .collect()
+}
+
+fn referenced_names(code: &str) -> Vec<&str> {
+    name_spans(code)
+        .into_iter()
+        .map(|span| &code[span])
+        .collect()
+}
+
+/// Hash of a graph dump that ignores comments, e.g. the source locations of nodes, and
+/// trailing whitespace, so that recompiling the same code gives the same hash
+pub fn dump_hash(graph: &str) -> u64 {
+    let normalized: Vec<&str> = graph
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty() && !l.trim_start().starts_with('#'))
+        .collect();
+    fxhash::hash64(&normalized)
+}
+
+impl FxGraph {
+    /// None if the dump has no `forward`
+    pub fn parse(graph: &str) -> Option<Self> {
+        let mut lines = graph.lines();
+        let def = lines.find(|l| l.trim_start().starts_with("def forward("))?;
+        let params = def.trim().strip_prefix("def forward(")?;
+        let params = params.rfind(')').map_or(params, |end| &params[..end]);
+
+        let mut nodes: Vec<FxNode> = Vec::new();
+        let mut names: FxHashSet<String> = FxHashSet::default();
+        for param in split_top_level(params) {
+            let name = param.split([':', '=']).next().unwrap_or_default().trim();
+            if name == "self" || name.starts_with('*') {
+                continue;
+            }
+            let ty = param
+                .split_once(':')
+                .map(|(_, ty)| ty.trim().trim_matches('"').to_string());
+            names.insert(name.to_string());
+            nodes.push(FxNode {
+                name: name.to_string(),
+                target: None,
+                ty,
+                inputs: Vec::new(),
+                code: String::new(),
+            });
+        }
+
+        let mut outputs: Vec<String> = Vec::new();
+        let body_indentation = indentation(def);
+        for line in lines {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            // The end of forward, e.g. the next submodule
+            if indentation(line) <= body_indentation {
+                break;
+            }
+            // Drop the `;  x = None` that frees inputs
+            let statement = trimmed.split(";  ").next().unwrap_or_default();
+            let inputs = |code: &str| -> Vec<String> {
+                let mut inputs: Vec<String> = Vec::new();
+                for name in referenced_names(code) {
+                    if names.contains(name) && !inputs.iter().any(|i| i == name) {
+                        inputs.push(name.to_string());
+                    }
+                }
+                inputs
+            };
+            if let Some(returned) = statement.strip_prefix("return ") {
+                outputs = inputs(returned);
+                continue;
+            }
+            let Some(captures) = NODE.captures(statement) else {
+                continue;
+            };
+            let rhs = &captures[3];
+            let target = rhs.split('(').next().unwrap_or(rhs);
+            let node = FxNode {
+                name: captures[1].to_string(),
+                target: Some(target.trim_start_matches("torch.ops.").to_string()),
+                ty: captures.get(2).map(|ty| ty.as_str().to_string()),
+                inputs: inputs(rhs),
+                code: rhs.to_string(),
+            };
+            names.insert(node.name.clone());
+            nodes.push(node);
+        }
+        Some(FxGraph { nodes, outputs })
+    }
+
+    /// Hash of the graph with its nodes renamed by position, so that graphs that only differ in
+    /// the names of their nodes hash the same
+    pub fn structure_hash(&self) -> u64 {
+        let positions: FxHashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.as_str(), i))
+            .collect();
+        let rename = |code: &str| {
+            let mut renamed = String::with_capacity(code.len());
+            let mut end = 0;
+            for span in name_spans(code) {
+                if let Some(i) = positions.get(&code[span.clone()]) {
+                    renamed.push_str(&code[end..span.start]);
+                    renamed.push_str(&format!("%{i}"));
+                    end = span.end;
+                }
+            }
+            renamed.push_str(&code[end..]);
+            renamed
+        };
+        let nodes: Vec<(&Option<String>, String)> = self
+            .nodes
+            .iter()
+            .map(|n| (&n.ty, rename(&n.code)))
+            .collect();
+        let outputs: Vec<Option<&usize>> = self
+            .outputs
+            .iter()
+            .map(|o| positions.get(o.as_str()))
+            .collect();
+        fxhash::hash64(&(nodes, outputs))
+    }
+
+    pub fn stats(&self) -> GraphStats {
+        let mut op_counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut num_users: FxHashMap<&str, usize> = FxHashMap::default();
+        let names: FxHashSet<&str> = self.nodes.iter().map(|n| n.name.as_str()).collect();
+        for node in &self.nodes {
+            match &node.target {
+                // Renames, e.g. l_x_ = L_x_
+                Some(target) if names.contains(target.as_str()) => {}
+                Some(target) if target.starts_with("self.") => {
+                    *op_counts.entry("get_attr".to_string()).or_default() += 1;
+                }
+                Some(target) => *op_counts.entry(target.clone()).or_default() += 1,
+                None => {}
+            }
+            for input in &node.inputs {
+                *num_users.entry(input.as_str()).or_default() += 1;
+            }
+        }
+        for output in &self.outputs {
+            *num_users.entry(output.as_str()).or_default() += 1;
+        }
+        let num_inputs = self.nodes.iter().filter(|n| n.target.is_none()).count();
+        GraphStats {
+            num_nodes: self.nodes.len() - num_inputs,
+            num_inputs,
+            num_outputs: self.outputs.len(),
+            max_fan_out: num_users.values().copied().max().unwrap_or_default(),
+            op_counts,
+            hash: String::new(),
+            structure_hash: format!("{:016x}", self.structure_hash()),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::Datelike;
+use regex::{Captures, Regex};
+
+/// The glog prefix of a log line, e.g. `V0211 16:42:20.420000 2313073 torch/_logging/structured.py:27] `
+#[derive(Debug)]
+pub struct GlogPrefix<'a> {
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub microsecond: u32,
+    pub thread: u64,
+    // Includes the space separating it from the thread id, like the regex always did
+    pub pathname: &'a str,
+    pub line: u64,
+    /// Byte offset of the JSON envelope that follows the prefix
+    pub payload_start: usize,
+}
+
+/// Microseconds since the start of the year.  glog doesn't log the year, so logs spanning New
+/// Year's Eve come out wrong.
+pub(crate) fn timestamp_us(prefix: &GlogPrefix) -> i64 {
+    // A leap year, so that every date exists
+    let day =
+        chrono::NaiveDate::from_ymd_opt(2000, prefix.month, prefix.day).map_or(0, |d| d.ordinal0());
+    let seconds = ((day as i64 * 24 + prefix.hour as i64) * 60 + prefix.minute as i64) * 60
+        + prefix.second as i64;
+    seconds * 1_000_000 + prefix.microsecond as i64
+}
+
+pub fn glog_regex() -> Regex {
+    Regex::new(concat!(
+        r"(?<level>[VIWEC])(?<month>\d{2})(?<day>\d{2}) ",
+        r"(?<hour>\d{2}):(?<minute>\d{2}):(?<second>\d{2}).(?<millisecond>\d{6}) ",
+        r"(?<thread>\d+)",
+        r"(?<pathname>[^:]+):(?<line>\d+)\] ",
+        r"(?<payload>.)"
+    ))
+    .unwrap()
+}
+
+/// Match the glog prefix at the start of `line` without allocating.  Lines this doesn't handle
+/// (e.g. with leading garbage) fall back to `re_glog`, which may match anywhere in the line.
+pub fn parse_glog_prefix<'a>(line: &'a str, re_glog: &Regex) -> Option<GlogPrefix<'a>> {
+    fast_glog_prefix(line).or_else(|| {
+        re_glog
+            .captures(line)
+            .and_then(|caps| prefix_from_captures(&caps))
+    })
+}
+
+fn digits(bytes: &[u8], start: usize, len: usize) -> Option<u32> {
+    let field = bytes.get(start..start + len)?;
+    field.iter().try_fold(0u32, |acc, b| {
+        b.is_ascii_digit().then(|| acc * 10 + (b - b'0') as u32)
+    })
+}
+
+fn fast_glog_prefix(line: &str) -> Option<GlogPrefix<'_>> {
+    let bytes = line.as_bytes();
+    // "V0211 16:42:20.420000 " is fixed width
+    if !matches!(bytes.first()?, b'V' | b'I' | b'W' | b'E' | b'C')
+        || bytes.get(5) != Some(&b' ')
+        || bytes.get(8) != Some(&b':')
+        || bytes.get(11) != Some(&b':')
+        || bytes.get(21) != Some(&b' ')
+    {
+        return None;
+    }
+    let month = digits(bytes, 1, 2)?;
+    let day = digits(bytes, 3, 2)?;
+    let hour = digits(bytes, 6, 2)?;
+    let minute = digits(bytes, 9, 2)?;
+    let second = digits(bytes, 12, 2)?;
+    let microsecond = digits(bytes, 15, 6)?;
+
+    let thread_start = 22;
+    let thread_end = thread_start
+        + bytes[thread_start..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+    if thread_end == thread_start || bytes.get(thread_end) != Some(&b' ') {
+        return None;
+    }
+    let thread = line[thread_start..thread_end].parse().ok()?;
+
+    let colon = thread_end + bytes[thread_end..].iter().position(|b| *b == b':')?;
+    let line_start = colon + 1;
+    let line_end = line_start
+        + bytes[line_start..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+    if line_end == line_start || bytes.get(line_end..line_end + 2) != Some(b"] ") {
+        return None;
+    }
+    let payload_start = line_end + 2;
+    if payload_start >= bytes.len() {
+        return None;
+    }
+    Some(GlogPrefix {
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        microsecond,
+        thread,
+        pathname: &line[thread_end..colon],
+        line: line[line_start..line_end].parse().ok()?,
+        payload_start,
+    })
+}
+
+fn prefix_from_captures<'a>(caps: &Captures<'a>) -> Option<GlogPrefix<'a>> {
+    let number = |name: &str| caps.name(name)?.as_str().parse::<u64>().ok();
+    Some(GlogPrefix {
+        month: number("month")? as u32,
+        day: number("day")? as u32,
+        hour: number("hour")? as u32,
+        minute: number("minute")? as u32,
+        second: number("second")? as u32,
+        microsecond: number("millisecond")? as u32,
+        thread: number("thread")?,
+        pathname: caps.name("pathname")?.as_str(),
+        line: number("line")?,
+        payload_start: caps.name("payload")?.start(),
+    })
+}
@@ -0,0 +1,73 @@
+use fxhash::FxHashMap;
+use serde::Serialize;
+
+use crate::types::{CompileId, FxIndexMap};
+
+/// The code of the guards that each compile id installed, in order
+pub type GuardsIndex = FxIndexMap<Option<CompileId>, Vec<String>>;
+
+/// How the guards of a compile changed from the previous compile of the same frame, written to
+/// guard_diff.html of the later compile's directory
+#[derive(Debug, Serialize)]
+pub struct GuardDiff {
+    pub compile_id: String,
+    pub previous_compile_id: String,
+    /// Guards of the later compile that the previous one didn't have
+    pub added: Vec<String>,
+    /// Guards of the previous compile that the later one doesn't have
+    pub removed: Vec<String>,
+    pub num_unchanged: usize,
+}
+
+// Guards of `guards` that aren't in `other`, counting duplicates
+fn difference(guards: &[String], other: &[String]) -> Vec<String> {
+    let mut remaining: FxHashMap<&str, usize> = FxHashMap::default();
+    for guard in other {
+        *remaining.entry(guard.as_str()).or_default() += 1;
+    }
+    guards
+        .iter()
+        .filter(|guard| match remaining.get_mut(guard.as_str()) {
+            Some(n) if *n > 0 => {
+                *n -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Diffs the guards of every compile id against those of the previous compile of the same frame,
+/// i.e. the latest earlier attempt or recompile that installed guards
+pub fn guard_diffs(guards_index: &GuardsIndex) -> Vec<(CompileId, GuardDiff)> {
+    let frame = |c: &CompileId| (c.compiled_autograd_id, c.frame_id);
+    let order = |c: &CompileId| (c.frame_compile_id, c.attempt);
+    let compile_ids: Vec<(&CompileId, &Vec<String>)> = guards_index
+        .iter()
+        .filter_map(|(cid, guards)| Some((cid.as_ref()?, guards)))
+        .collect();
+    let mut diffs = Vec::new();
+    for (cid, guards) in &compile_ids {
+        let previous = compile_ids
+            .iter()
+            .filter(|(other, _)| frame(other) == frame(cid) && order(other) < order(cid))
+            .max_by_key(|(other, _)| order(other));
+        let Some((previous_cid, previous_guards)) = previous else {
+            continue;
+        };
+        let added = difference(guards, previous_guards);
+        let removed = difference(previous_guards, guards);
+        diffs.push((
+            (*cid).clone(),
+            GuardDiff {
+                compile_id: cid.to_string(),
+                previous_compile_id: previous_cid.to_string(),
+                num_unchanged: guards.len() - added.len(),
+                added,
+                removed,
+            },
+        ));
+    }
+    diffs
+}
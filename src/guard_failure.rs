@@ -0,0 +1,172 @@
+use std::fmt::Write;
+
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::types::{CompilationMetricsIndex, CompileId, FrameLocals};
+
+// e.g. `0/0: tensor 'L['x']' size mismatch at index 0. expected 3, actual 4`, how Dynamo
+// prefixes the guards that failed with the compile id that installed them
+static FAILED_GUARD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:- )?((?:!\d+/)?\d+/\d+(?:_\d+)?): (.*)$").unwrap());
+
+// A local or global of the frame, e.g. L['x']
+static SOURCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[LG]\['[^']*'\]").unwrap());
+
+/// Locals and symbols captured with the guards of each compile id, by name
+pub type FrameLocalsIndex = FxHashMap<Option<CompileId>, FxHashMap<String, String>>;
+
+/// Adds the locals of a guard_added or propagate_real_tensors event to `index`
+pub fn index_frame_locals(
+    index: &mut FrameLocalsIndex,
+    compile_id: &Option<CompileId>,
+    locals: &FrameLocals,
+) {
+    let entry = index.entry(compile_id.clone()).or_default();
+    let symbols = locals.symbols.iter().flatten();
+    for (name, value) in locals.locals.iter().flatten().chain(symbols) {
+        if let Some(value) = value {
+            entry.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// A guard of an earlier compile of the frame that failed
+#[derive(Debug, Serialize)]
+pub struct FailedGuard {
+    /// e.g. [0/0], or (unknown) if Dynamo didn't say
+    pub compile_id: String,
+    pub guard: String,
+}
+
+/// A recompile caused by guards failing, written to guard_failure_<n>.md
+#[derive(Debug, Serialize)]
+pub struct GuardFailure {
+    /// The compile id of the recompile
+    pub compile_id: String,
+    pub co_name: String,
+    /// e.g. /home/me/model.py:12
+    pub location: Option<String>,
+    pub failed_guards: Vec<FailedGuard>,
+    /// Frame locals of the failed compile ids that the failed guards refer to
+    pub locals: Vec<(String, String)>,
+    /// guard_failure_<n>.md
+    pub url: String,
+}
+
+fn failed_guards(recompile_reason: &str) -> Vec<FailedGuard> {
+    let mut failed_guards: Vec<FailedGuard> = Vec::new();
+    for line in recompile_reason.lines().filter(|l| !l.trim().is_empty()) {
+        if let Some(c) = FAILED_GUARD.captures(line) {
+            failed_guards.push(FailedGuard {
+                compile_id: format!("[{}]", &c[1]),
+                guard: c[2].to_string(),
+            });
+        } else if let Some(last) = failed_guards.last_mut() {
+            // Reasons for several guards of the same compile id
+            write!(last.guard, "\n{line}").unwrap();
+        } else {
+            failed_guards.push(FailedGuard {
+                compile_id: "(unknown)".to_string(),
+                guard: line.to_string(),
+            });
+        }
+    }
+    failed_guards
+}
+
+// Whether the failed guards mention a local, e.g. `x` as L['x'], or a symbol whose source is
+// one, e.g. s0 for L['x'].size()[0]
+fn is_relevant(guards: &[FailedGuard], name: &str, value: &str) -> bool {
+    guards.iter().any(|g| {
+        SOURCE.find_iter(&g.guard).any(|source| {
+            let source = source.as_str();
+            source[3..source.len() - 2] == *name || value.starts_with(source)
+        })
+    })
+}
+
+/// The recompiles of `metrics_index` that Dynamo logged a `recompile_reason` for, in order
+pub fn guard_failures(
+    metrics_index: &CompilationMetricsIndex,
+    frame_locals_index: &FrameLocalsIndex,
+) -> Vec<GuardFailure> {
+    let frame_locals: FxHashMap<String, &FxHashMap<String, String>> = frame_locals_index
+        .iter()
+        .filter_map(|(cid, locals)| Some((cid.as_ref()?.to_string(), locals)))
+        .collect();
+    let mut failures = Vec::new();
+    for (cid, metrics) in metrics_index {
+        for m in metrics {
+            let Some(reason) = m.recompile_reason.as_deref().filter(|r| !r.is_empty()) else {
+                continue;
+            };
+            let failed_guards = failed_guards(reason);
+            let mut locals: Vec<(String, String)> = failed_guards
+                .iter()
+                .filter_map(|g| frame_locals.get(&g.compile_id))
+                .flat_map(|locals| locals.iter())
+                .filter(|(name, value)| is_relevant(&failed_guards, name, value))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            locals.sort();
+            locals.dedup();
+            failures.push(GuardFailure {
+                compile_id: cid
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                co_name: m.co_name.clone().unwrap_or("(unknown)".to_string()),
+                location: m
+                    .co_filename
+                    .as_ref()
+                    .map(|f| format!("{f}:{}", m.co_firstlineno.unwrap_or_default())),
+                failed_guards,
+                locals,
+                url: format!("guard_failure_{}.md", failures.len()),
+            });
+        }
+    }
+    failures
+}
+
+impl GuardFailure {
+    /// Markdown to paste into an issue
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        writeln!(
+            md,
+            "### Recompile of `{}` {}",
+            self.co_name, self.compile_id
+        )
+        .unwrap();
+        writeln!(md).unwrap();
+        match &self.location {
+            Some(location) => write!(md, "`{}` (`{location}`)", self.co_name),
+            None => write!(md, "`{}`", self.co_name),
+        }
+        .unwrap();
+        writeln!(
+            md,
+            " was recompiled as {} because guards of earlier compiles failed:",
+            self.compile_id
+        )
+        .unwrap();
+        for g in &self.failed_guards {
+            writeln!(md).unwrap();
+            writeln!(md, "Guard of {} that failed:", g.compile_id).unwrap();
+            writeln!(md, "```\n{}\n```", g.guard).unwrap();
+        }
+        if !self.locals.is_empty() {
+            writeln!(md).unwrap();
+            writeln!(md, "Frame locals the guards refer to:").unwrap();
+            writeln!(md, "```").unwrap();
+            for (name, value) in &self.locals {
+                writeln!(md, "{name}: {value}").unwrap();
+            }
+            writeln!(md, "```").unwrap();
+        }
+        md
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::payload::LazyPayload;
+use crate::types::{CompileId, FxIndexMap};
+
+// A config option among the components of a cache key, e.g.
+// `[esstihe2nyydk4mhzpvox3qkajyu5y5t23hk3fi2me7jn75xi3o] inductor_config[debug]: False`
+static CONFIG_COMPONENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[\w+\] (\w+_config\[[^\]]+\]): (.*)$").unwrap());
+
+/// The config options of each compile id, e.g. inductor_config[max_autotune] to True, from the
+/// inductor_config and dynamo_config artifacts and the components of cache keys
+pub type ConfigIndex = FxIndexMap<Option<CompileId>, BTreeMap<String, String>>;
+
+fn config_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Adds the config options in the payload of the artifact `name` to `index`
+pub fn record_config(
+    index: &mut ConfigIndex,
+    compile_id: &Option<CompileId>,
+    name: &str,
+    payload: &LazyPayload,
+) {
+    let is_dump = name == "inductor_config" || name == "dynamo_config";
+    if !is_dump && !name.contains("cache") {
+        return;
+    }
+    let Ok(json) = serde_json::from_str::<Value>(payload.get()) else {
+        return;
+    };
+    let config = index.entry(compile_id.clone()).or_default();
+    if is_dump {
+        for (key, value) in json.as_object().into_iter().flatten() {
+            config.insert(format!("{name}[{key}]"), config_value(value));
+        }
+        return;
+    }
+    for component in json["components"].as_array().into_iter().flatten() {
+        if let Some(c) = component
+            .as_str()
+            .and_then(|c| CONFIG_COMPONENT.captures(c))
+        {
+            config.insert(c[1].to_string(), c[2].to_string());
+        }
+    }
+}
+
+/// The config of the whole run: every option, with the value of the first compile id that has it
+pub fn run_config(index: &ConfigIndex) -> BTreeMap<String, String> {
+    let mut config = BTreeMap::new();
+    for options in index.values() {
+        for (key, value) in options {
+            config.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    config
+}
+
+/// A config option that differs between two runs
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub key: String,
+    pub baseline: String,
+    pub current: String,
+}
+
+/// The options of `current` that have another value in `baseline`.  Options only one of the runs
+/// logged are left out, since they mostly come from different versions of PyTorch.
+pub fn config_changes(
+    baseline: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<ConfigChange> {
+    current
+        .iter()
+        .filter_map(|(key, value)| {
+            let baseline = baseline.get(key)?;
+            (baseline != value).then(|| ConfigChange {
+                key: key.clone(),
+                baseline: baseline.clone(),
+                current: value.clone(),
+            })
+        })
+        .collect()
+}
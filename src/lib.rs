@@ -1,33 +1,141 @@
-use anyhow::{anyhow, bail};
+use anyhow::{anyhow, bail, Context};
 use chrono::Datelike;
 use fxhash::{FxHashMap, FxHashSet};
 use md5::{Digest, Md5};
+use once_cell::sync::Lazy;
 use std::ffi::{OsStr, OsString};
 
+use console::Term;
 use html_escape::encode_text;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use regex::Regex;
 use serde_json::Value;
 use std::cell::RefCell;
-use std::fs::{self, File};
-use std::io::{self, BufRead};
-use std::path::PathBuf;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::Instant;
 use tinytemplate::TinyTemplate;
 
+use crate::code_size::{format_bytes, treemap_tiles, CodeSizeIndex};
+use crate::compile_id_summary::compile_id_summary;
+use crate::glog::GlogPrefix;
+use crate::guard_diff::{guard_diffs, GuardsIndex};
+use crate::guard_failure::{guard_failures, index_frame_locals, FrameLocalsIndex};
+use crate::inductor_config::{config_changes, record_config, run_config, ConfigIndex};
+use crate::log_fragments::order_log_fragments;
+use crate::log_source::{log_lines, log_text, LogBytes};
+use crate::module_map::ModuleMap;
+use crate::parser_profile::ParserProfile;
 use crate::parsers::default_parsers;
-use crate::parsers::ParserOutput;
 use crate::parsers::StructuredLogParser;
+use crate::parsers::{FileWriter, ParserOutput};
+use crate::raw_jsonl::{inline_filenames, RawJsonl};
+use crate::records::{LogItem, PayloadCheck, Record, RecordReader};
+use crate::search::SearchIndexBuilder;
+use crate::shape_churn::{shape_churn, SpecializationIndex};
+use crate::source_snippets::SourceSnippets;
 use crate::templates::*;
 use crate::types::*;
+#[cfg(feature = "async-io")]
+mod async_writer;
+mod chromium;
+mod code_size;
+mod compare_runs;
+mod compile_id_summary;
+mod completeness;
+mod constraint_suggestions;
+mod counters;
+mod deep_link;
+mod directory_layout;
+mod dot;
+mod entry_points;
+mod findings;
+mod flamegraph;
+mod frame_locals;
+mod fx_graph;
+mod glog;
+mod guard_diff;
+mod guard_failure;
+mod inductor_config;
+mod log_fragments;
+mod log_source;
+mod memory_report;
+mod module_map;
+mod otel;
+mod output_schema;
+mod parser_profile;
 pub mod parsers;
+mod payload;
+mod profile;
+#[cfg(feature = "python")]
+mod python;
+mod raw_jsonl;
+mod records;
+mod runtime_estimation;
+pub mod search;
+mod shape_churn;
+mod source_snippets;
+mod steps;
+mod summary;
 mod templates;
+mod theme;
+mod threads;
 mod types;
-
+#[cfg(feature = "upload")]
+mod upload;
+mod url_prefix;
+
+pub use directory_layout::DirectoryLayout;
+pub use log_fragments::expand_log_paths;
+#[cfg(feature = "otlp")]
+pub use otel::export_spans;
+pub use output_schema::validate_output;
+pub use payload::LazyPayload;
+pub use profile::MeasuredDurations;
+pub use source_snippets::SourceRoot;
+pub use steps::TrainingSteps;
+pub use summary::{
+    metrics_csv, prometheus_metrics, CompileSummary, CompileThresholds, SlowCompile, SummaryDelta,
+    TrendRecord,
+};
+pub use theme::{theme_css, Theme};
 pub use types::{
-    ArtifactFlags, Diagnostics, DivergenceFlags, DivergenceGroup, GraphAnalysis, GraphRuntime,
-    RankMetaData, RuntimeAnalysis, RuntimeRankDetail,
+    ArtifactFlags, CompileId, CompileTimeSkew, Diagnostics, DistributedInfo, DivergenceFlags,
+    DivergenceGroup, GraphAnalysis, GraphContentDivergence, GraphMemory, GraphRuntime,
+    MemoryAnalysis, RankParseSummary, RankStats, RankSummary, RuntimeAnalysis, RuntimeRankDetail,
+    Stats,
 };
+#[cfg(feature = "upload")]
+pub use upload::upload_report;
+
+/// Writes the output of `parse_path` under `output_dir`, in parallel if
+/// `config.max_parallel_writes` is set
+pub fn write_output(
+    output: ParseOutput,
+    output_dir: &Path,
+    config: &ParseConfig,
+) -> anyhow::Result<()> {
+    if let Some(max_in_flight) = config.max_parallel_writes {
+        #[cfg(feature = "async-io")]
+        return async_writer::write_output(output, output_dir, max_in_flight);
+        #[cfg(not(feature = "async-io"))]
+        bail!("Writing {max_in_flight} files in parallel needs tlparse built with the async-io feature");
+    }
+    for (filename, content) in output {
+        let out_path = output_dir.join(&filename);
+        if let Some(dir) = out_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(out_path, content)?;
+    }
+    Ok(())
+}
+
+// Update the progress bars every this many lines
+const PROGRESS_INTERVAL: usize = 1024;
 
 #[derive(Debug)]
 enum ParserResult {
@@ -35,6 +143,72 @@ enum ParserResult {
     PayloadFilename(String),
 }
 
+/// Which payloads are checked against the md5 hash recorded in their envelope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadVerification {
+    #[default]
+    All,
+    /// Only check every Nth payload
+    Sample(u32),
+    None,
+}
+
+// Year of glog timestamps in --deterministic output
+const DETERMINISTIC_YEAR: i32 = 2000;
+
+/// When to draw progress bars on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Progress {
+    /// Only if stderr is a terminal that can draw them, so CI logs don't fill up with control
+    /// sequences
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Progress {
+    fn draw_target(&self) -> ProgressDrawTarget {
+        let stderr = Term::stderr();
+        match self {
+            Progress::Auto
+                if stderr.is_term() && std::env::var("TERM").as_deref() != Ok("dumb") =>
+            {
+                ProgressDrawTarget::stderr()
+            }
+            // The stderr target skips drawing when stderr isn't a terminal
+            Progress::Always => ProgressDrawTarget::term_like(Box::new(stderr)),
+            Progress::Auto | Progress::Never => ProgressDrawTarget::hidden(),
+        }
+    }
+}
+
+impl FromStr for Progress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Progress::Auto),
+            "always" => Ok(Progress::Always),
+            "never" => Ok(Progress::Never),
+            _ => Err(format!(
+                "unknown progress mode {s}; expected one of: auto, always, never"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Progress::Auto => "auto",
+            Progress::Always => "always",
+            Progress::Never => "never",
+        };
+        write!(f, "{name}")
+    }
+}
+
 pub struct ParseConfig {
     pub strict: bool,
     pub strict_compile_id: bool,
@@ -43,7 +217,96 @@ pub struct ParseConfig {
     pub verbose: bool,
     pub plain_text: bool,
     pub export: bool,
+    /// Provenance tracking is expected, so index.html hints at how to log node mappings if the
+    /// log has none
     pub inductor_provenance: bool,
+    /// Don't generate the provenance tracking pages, which are otherwise generated for logs with
+    /// provenance node mappings
+    pub no_inductor_provenance: bool,
+    /// Template name => template text, replacing the built-in template of that name.
+    /// See `load_template_overrides`.
+    pub template_overrides: FxHashMap<String, String>,
+    pub theme: Theme,
+    /// Extra css appended to theme.css, e.g. to tweak a built-in theme
+    pub custom_css: String,
+    /// Hashing every payload is a noticeable part of parsing multi-GB logs
+    pub verify_payloads: PayloadVerification,
+    /// Number output files by their position in the log, as older versions did, instead of by
+    /// how many files of the same name came before them
+    pub legacy_artifact_numbering: bool,
+    /// Write each distinct payload once, under blobs/<hash>, and point the compile directory at it
+    pub dedup_payloads: bool,
+    /// Only keep the artifacts of this many compile ids, see `cap_compile_ids`
+    pub max_compile_ids: Option<usize>,
+    /// List at most this many compile ids on index.html, continuing on index_1.html etc.
+    pub index_page_size: Option<usize>,
+    /// Names of parsers (see `StructuredLogParser::name`) not to run, e.g. inductor_output_code
+    pub disabled_parsers: Vec<String>,
+    /// If set, run only the parsers of these names
+    pub enabled_parsers: Option<Vec<String>>,
+    /// Summary of an earlier run to compare this one against, see baseline_comparison.html
+    pub baseline: Option<CompileSummary>,
+    /// Kernel durations from a profile of the compiled model, to check the runtime estimations
+    /// against, see runtime_profile_correlation.html
+    pub profile: Option<MeasuredDurations>,
+    /// Have up to this many files in flight when writing the output with `write_output`, which
+    /// needs the async-io feature
+    pub max_parallel_writes: Option<usize>,
+    /// Reattach payload lines interleaved with other threads' log lines, e.g. when several
+    /// threads log at once, and write payloads that still don't match their hash (e.g. truncated
+    /// by a crash) to corrupt_payloads/ instead of parsing them
+    pub recover_payloads: bool,
+    /// When the training steps started, to tell at which step compilations happened
+    pub steps: Option<TrainingSteps>,
+    pub progress: Progress,
+    /// Leave out everything that differs between runs on the same log, e.g. for snapshot tests
+    pub deterministic: bool,
+    /// URL the report is hosted under, ending with a slash, e.g.
+    /// https://bucket.s3.amazonaws.com/reports/1234/.  Relative links of the pages are made
+    /// absolute under it, since they break behind some gateways.
+    pub url_prefix: Option<String>,
+    /// Also write small FX graphs as Graphviz .dot files, see `FxGraphParser`
+    pub graph_dot: bool,
+    /// How the directories of compile ids are named, e.g. frame_0/compile_0/attempt_0/ rather
+    /// than -_0_0_0/
+    pub directory_layout: DirectoryLayout,
+    /// Fail if a JSON file of the output doesn't match its schema, see `validate_output`
+    pub validate_output: bool,
+    /// Also write the compilations as OpenTelemetry spans to otel_spans.json
+    pub otel_spans: bool,
+    /// Also write the compilation metrics as CSV to compilation_metrics.csv, see `metrics_csv`
+    pub metrics_csv: bool,
+    /// Stop parsing at the first compilation that failed and only keep its artifacts, with
+    /// first_failure.html explaining the failure
+    pub first_failure: bool,
+    /// Write the filenames of stack frames into raw.jsonl, instead of their index in its string
+    /// table
+    pub raw_jsonl_filenames: bool,
+    /// Cut renderings of more bytes than this short, with a banner linking to the full payload,
+    /// which is written next to the rendering.  Parsers still see the whole payload.
+    pub max_artifact_bytes: Option<usize>,
+    /// Identify the run in trend_record.json, e.g. the commit and CI job it ran at
+    pub git_sha: Option<String>,
+    pub job_id: Option<String>,
+    /// Rank the log is of, when parsing the logs of all ranks of a distributed job
+    pub rank: Option<u32>,
+    /// Also collect the compilations of the log for `--compare-runs`, see compare_runs.rs
+    pub compare_runs: bool,
+    /// Local checkouts of the logged code, to show the source around the frames of user stacks
+    pub source_roots: Vec<SourceRoot>,
+    /// Stop once the log is parsed, returning only the summary of the run and no output, e.g.
+    /// for `tlparse summarize`.  None of the pages, nor raw.log, are rendered, and strict mode
+    /// doesn't apply.
+    pub summary_only: bool,
+}
+
+impl ParseConfig {
+    pub fn parser_enabled(&self, name: &str) -> bool {
+        self.enabled_parsers
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|n| n == name))
+            && !self.disabled_parsers.iter().any(|n| n == name)
+    }
 }
 
 impl Default for ParseConfig {
@@ -57,11 +320,128 @@ impl Default for ParseConfig {
             plain_text: false,
             export: false,
             inductor_provenance: false,
+            no_inductor_provenance: false,
+            template_overrides: FxHashMap::default(),
+            theme: Theme::default(),
+            custom_css: String::default(),
+            verify_payloads: PayloadVerification::default(),
+            legacy_artifact_numbering: false,
+            dedup_payloads: false,
+            max_compile_ids: None,
+            index_page_size: None,
+            disabled_parsers: Vec::new(),
+            enabled_parsers: None,
+            baseline: None,
+            profile: None,
+            max_parallel_writes: None,
+            recover_payloads: false,
+            steps: None,
+            progress: Progress::default(),
+            deterministic: false,
+            url_prefix: None,
+            graph_dot: false,
+            directory_layout: DirectoryLayout::default(),
+            validate_output: false,
+            otel_spans: false,
+            metrics_csv: false,
+            first_failure: false,
+            raw_jsonl_filenames: false,
+            max_artifact_bytes: None,
+            git_sha: None,
+            job_id: None,
+            rank: None,
+            compare_runs: false,
+            source_roots: Vec::new(),
+            summary_only: false,
+        }
+    }
+}
+
+/// Names of the built-in templates that can be replaced with `--template-dir`.
+/// The export mode landing page is registered as index.html but overridden as export_index.html.
+pub const OVERRIDABLE_TEMPLATES: &[&str] = &[
+    "index.html",
+    "export_index.html",
+    "failures_and_restarts.html",
+    "dynamo_guards.html",
+    "compilation_metrics.html",
+    "bwd_compilation_metrics.html",
+    "aot_autograd_backward_compilation_metrics.html",
+    "symbolic_guard_information.html",
+    "exported_program.html",
+    "provenance_tracking.html",
+    "multi_rank_index.html",
+    "cross_rank_search.html",
+    "compare_runs.html",
+    "search.html",
+    "pass_timings.html",
+    "counters.html",
+    "memory_report.html",
+    "duplicate_graphs.html",
+    "module_map.html",
+    "code_size.html",
+    "trace_size.html",
+    "threads.html",
+    "guard_latency.html",
+    "overflow_compile_ids.html",
+    "first_failure.html",
+    "summary.html",
+    "index_page.html",
+    "baseline_comparison.html",
+    "runtime_profile_correlation.html",
+    "runtime_estimation.html",
+    "guard_diff.html",
+    "inductor_config.html",
+    "cudagraphs.html",
+    "ddp_splits.html",
+    "graph_partitions.html",
+    "compiled_autograd.html",
+    "aot_partition.html",
+    "symbolic_expressions.html",
+];
+
+/// Load template overrides from a directory.  Every file must be named after one of
+/// `OVERRIDABLE_TEMPLATES` and must compile, so mistakes are reported before we spend
+/// minutes parsing a log rather than when the page is rendered.
+pub fn load_template_overrides(dir: &Path) -> anyhow::Result<FxHashMap<String, String>> {
+    let mut overrides = FxHashMap::default();
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Couldn't access template directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !OVERRIDABLE_TEMPLATES.contains(&name.as_str()) {
+            bail!(
+                "Unknown template {} in {}; expected one of: {}",
+                name,
+                dir.display(),
+                OVERRIDABLE_TEMPLATES.join(", ")
+            );
         }
+        let text = fs::read_to_string(&path)?;
+        TinyTemplate::new()
+            .add_template(&name, &text)
+            .with_context(|| format!("Invalid template {}", path.display()))?;
+        overrides.insert(name, text);
     }
+    Ok(overrides)
 }
 
-fn maybe_remove_convert_frame_suffixes(frames: &mut Vec<FrameSummary>) {
+// Use the user's override for a template if there is one
+fn template_source<'a>(config: &'a ParseConfig, name: &str, builtin: &'a str) -> &'a str {
+    config
+        .template_overrides
+        .get(name)
+        .map_or(builtin, |s| s.as_str())
+}
+
+fn maybe_remove_convert_frame_suffixes(frames: &mut Vec<FrameSummary>, intern_table: &InternTable) {
     let all_target_frames = [
         [
             ("torch/_dynamo/convert_frame.py", "catch_errors"),
@@ -83,7 +463,7 @@ fn maybe_remove_convert_frame_suffixes(frames: &mut Vec<FrameSummary>) {
                 .iter()
                 .zip(target_frames.iter())
                 .all(|(frame, target)| {
-                    simplify_filename(unintern_str(frame.filename).as_ref()) == target.0
+                    simplify_filename(frame.filename(intern_table)) == target.0
                         && frame.name == target.1
                 })
             {
@@ -109,12 +489,73 @@ fn add_unique_suffix(raw_filename: PathBuf, output_count: i32) -> PathBuf {
     }
 }
 
+/// Numbers the files written for a log.  By default a file's number counts the earlier files with
+/// the same name, so that e.g. a new parser doesn't renumber every file after its first output.
+/// `ParseConfig::legacy_artifact_numbering` numbers every file by its position in the log instead.
+#[derive(Default)]
+struct OutputCounter {
+    /// Every output so far, in log order.  This is the number shown in the index
+    count: i32,
+    per_artifact: FxHashMap<PathBuf, i32>,
+    legacy: bool,
+    /// Paths of the payloads written to blobs/, when `ParseConfig::dedup_payloads` is set
+    blobs: Option<FxHashSet<PathBuf>>,
+    /// See `ParseConfig::max_artifact_bytes`
+    max_artifact_bytes: Option<usize>,
+    /// Where `ParserOutput::FileWriter` outputs are streamed to, when parsing straight into a
+    /// directory.  Otherwise they are kept in memory like the rest of the output.
+    stream_dir: Option<PathBuf>,
+}
+
+impl OutputCounter {
+    fn unique_filename(&mut self, raw_filename: PathBuf) -> PathBuf {
+        let number = if self.legacy {
+            self.count
+        } else {
+            let seen = self.per_artifact.entry(raw_filename.clone()).or_default();
+            *seen += 1;
+            *seen - 1
+        };
+        add_unique_suffix(raw_filename, number)
+    }
+
+    /// Where to write `payload` in the content-addressed store, and whether nothing was written
+    /// there yet.  The same content is written again for another extension.  None if payloads
+    /// aren't deduplicated.
+    fn blob_filename(
+        &mut self,
+        raw_filename: &Path,
+        payload: &LazyPayload,
+    ) -> Option<(PathBuf, bool)> {
+        let blobs = self.blobs.as_mut()?;
+        let mut blob = PathBuf::from("blobs").join(payload.md5_hex());
+        if let Some(e) = raw_filename.extension() {
+            blob.set_extension(e);
+        }
+        let is_new = blobs.insert(blob.clone());
+        Some((blob, is_new))
+    }
+}
+
+fn cache_status_suffix(filename: &str) -> String {
+    if filename.contains("cache_miss") {
+        "❌".to_string()
+    } else if filename.contains("cache_hit") {
+        "✅".to_string()
+    } else if filename.contains("cache_bypass") {
+        "❓".to_string()
+    } else {
+        "".to_string()
+    }
+}
+
 fn add_file_output(
     filename: PathBuf,
     content: String,
     output: &mut ParseOutput,
     compile_directory: &mut Vec<OutputFile>,
-    output_count: &mut i32,
+    output_count: &mut OutputCounter,
+    log_line: Option<usize>,
 ) {
     let is_stack_traces = is_stack_traces_file(&filename);
     let maybe_content = if is_stack_traces {
@@ -124,15 +565,7 @@ fn add_file_output(
     };
     output.push((filename.clone(), content));
     let filename_str = filename.to_string_lossy().to_string();
-    let suffix = if filename_str.contains("cache_miss") {
-        "❌".to_string()
-    } else if filename_str.contains("cache_hit") {
-        "✅".to_string()
-    } else if filename_str.contains("cache_bypass") {
-        "❓".to_string()
-    } else {
-        "".to_string()
-    };
+    let suffix = cache_status_suffix(&filename_str);
     let readable_url = if let Some(c) = maybe_content {
         Some(add_stack_traces_html(&filename, &c, output, output_count))
     } else {
@@ -141,11 +574,12 @@ fn add_file_output(
     compile_directory.push(OutputFile {
         url: filename_str.clone(),
         name: filename_str,
-        number: *output_count,
+        number: output_count.count,
         suffix: suffix,
         readable_url,
+        log_line,
     });
-    *output_count += 1;
+    output_count.count += 1;
 }
 
 fn is_stack_traces_file(path: &PathBuf) -> bool {
@@ -161,13 +595,15 @@ fn add_stack_traces_html(
     json_path: &PathBuf,
     json_content: &str,
     output: &mut ParseOutput,
-    output_count: &mut i32,
+    output_count: &mut OutputCounter,
 ) -> String {
     let parsed: Value = match serde_json::from_str(json_content) {
         Ok(v) => v,
         Err(_) => return String::new(),
     };
-    let mut html = String::from("<html><body>\n");
+    let root = "../".repeat(json_path.components().count().saturating_sub(1));
+    let mut html =
+        format!("<html><head><link rel=\"stylesheet\" href=\"{root}theme.css\"></head><body>\n");
     if let Some(map) = parsed.as_object() {
         for (kernel, traces) in map {
             html.push_str(&format!("<h3>{}</h3>\n", encode_text(kernel)));
@@ -194,51 +630,247 @@ fn add_stack_traces_html(
     }
     let html_path_str = html_path.to_string_lossy().to_string();
     output.push((html_path.clone(), html));
-    *output_count += 1;
+    output_count.count += 1;
     html_path_str
 }
 
+/// The start of `text` that fits in `max_bytes`, cut at the end of a line if there is one
+fn truncate_text(text: &str, max_bytes: usize) -> &str {
+    let cut = text.floor_char_boundary(max_bytes);
+    let shown = &text[..cut];
+    shown.rfind('\n').map_or(shown, |i| &text[..i + 1])
+}
+
+/// Writes the full payload of an artifact whose rendering `filename` was cut short next to it, and
+/// returns a banner linking to it for the rendering
+fn add_full_payload(
+    ctx: &mut ParserContext,
+    filename: &Path,
+    shown_bytes: usize,
+    rendered_bytes: usize,
+    payload: &LazyPayload,
+    log_line: Option<usize>,
+) -> String {
+    let stem = filename
+        .file_stem()
+        .map_or(String::new(), |s| s.to_string_lossy().to_string());
+    let full_filename = filename.with_file_name(format!("{stem}_full.txt"));
+    let banner = format!(
+        "<div style=\"background: #fff3cd; color: #664d03; border: 1px solid #ffe69c; padding: 8px; margin: 8px 0\">\
+         Truncated: only the first {shown_bytes} of {rendered_bytes} bytes of this page are shown.  \
+         <a href=\"{}\">The full payload</a></div>\n",
+        full_filename
+            .file_name()
+            .map_or(String::new(), |f| f.to_string_lossy().to_string()),
+    );
+    add_file_output(
+        full_filename,
+        payload.get().to_string(),
+        ctx.output,
+        ctx.compile_directory,
+        ctx.output_count,
+        log_line,
+    );
+    banner
+}
+
+/// Passes what is written on to `inner` up to `max_bytes`, and counts everything that was written
+struct CappedWriter<W> {
+    inner: W,
+    max_bytes: Option<usize>,
+    len: usize,
+}
+
+impl<W: Write> Write for CappedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let kept = self.max_bytes.map_or(buf.len(), |max_bytes| {
+            max_bytes.saturating_sub(self.len).min(buf.len())
+        });
+        self.inner.write_all(&buf[..kept])?;
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Streams a `ParserOutput::FileWriter` to `filename` in `stream_dir`, up to `max_bytes`, and
+/// returns how many bytes it wrote in all
+fn stream_file_output(
+    stream_dir: &Path,
+    filename: &Path,
+    write: FileWriter,
+    max_bytes: Option<usize>,
+) -> std::io::Result<usize> {
+    let path = stream_dir.join(filename);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut writer = CappedWriter {
+        inner: BufWriter::new(File::create(path)?),
+        max_bytes,
+        len: 0,
+    };
+    write(&mut writer)?;
+    writer.flush()?;
+    Ok(writer.len)
+}
+
+/// Puts `banner` at the top of the html page `content`
+fn insert_banner(content: &mut String, banner: &str) {
+    let at = content.find("<body>").map_or(0, |i| i + "<body>".len());
+    content.insert_str(at, banner);
+}
+
+/// Where the outputs of the parsers of a record go, and what is counted about them
+struct ParserContext<'c> {
+    output: &'c mut ParseOutput,
+    output_count: &'c mut OutputCounter,
+    /// The outputs of the record's compile id
+    compile_directory: &'c mut Vec<OutputFile>,
+    multi: &'c MultiProgress,
+    stats: &'c mut Stats,
+    profile: &'c mut ParserProfile,
+    /// Where the directory of the record's compile id is
+    layout: DirectoryLayout,
+}
+
 fn run_parser<'t>(
     lineno: usize,
     parser: &Box<dyn StructuredLogParser + 't>,
     e: &Envelope,
-    payload: &str,
-    output_count: &mut i32,
-    output: &mut ParseOutput,
-    compile_directory: &mut Vec<OutputFile>,
-    multi: &MultiProgress,
-    stats: &mut Stats,
+    payload: &LazyPayload,
+    ctx: &mut ParserContext,
 ) -> ParserResult {
     let mut payload_filename = ParserResult::NoPayload;
+    let log_line = Some(lineno);
     if let Some(md) = parser.get_metadata(&e) {
-        let results = parser.parse(lineno, md, e.rank, &e.compile_id, &payload);
+        let start = Instant::now();
+        let outputs_before = ctx.output.len();
+        let results = parser.parse(lineno, md, e.rank, &e.compile_id, payload.get());
         match results {
             Ok(results) => {
                 for parser_result in results {
+                    // Without a directory to stream to, written files are kept in memory like
+                    // any other
+                    let parser_result = match parser_result {
+                        ParserOutput::FileWriter(filename, write)
+                            if ctx.output_count.stream_dir.is_none() =>
+                        {
+                            let mut out = Vec::new();
+                            if let Err(err) = write(&mut out) {
+                                ctx.multi.suspend(|| {
+                                    eprintln!(
+                                        "Failed to write {}: {}",
+                                        filename.to_string_lossy(),
+                                        err
+                                    )
+                                });
+                                ctx.stats.fail_parser += 1;
+                                continue;
+                            }
+                            let out = String::from_utf8(out).unwrap_or_else(|err| {
+                                String::from_utf8_lossy(err.as_bytes()).into_owned()
+                            });
+                            ParserOutput::File(filename, out)
+                        }
+                        parser_result => parser_result,
+                    };
                     match parser_result {
-                        ParserOutput::File(raw_filename, out) => {
-                            let filename = add_unique_suffix(raw_filename, *output_count);
-                            add_file_output(filename, out, output, compile_directory, output_count);
+                        ParserOutput::File(raw_filename, mut out) => {
+                            let raw_filename =
+                                ctx.layout.relocate(raw_filename, e.compile_id.as_ref());
+                            let filename = ctx.output_count.unique_filename(raw_filename);
+                            // Renderings over `ParseConfig::max_artifact_bytes` are cut short;
+                            // the parser still saw the whole payload
+                            if let Some(max_bytes) = ctx
+                                .output_count
+                                .max_artifact_bytes
+                                .filter(|max_bytes| out.len() > *max_bytes)
+                            {
+                                ctx.stats.truncated_artifacts += 1;
+                                let rendered_len = out.len();
+                                out.truncate(truncate_text(&out, max_bytes).len());
+                                let banner = add_full_payload(
+                                    ctx,
+                                    &filename,
+                                    out.len(),
+                                    rendered_len,
+                                    payload,
+                                    log_line,
+                                );
+                                if filename.extension() == Some(OsStr::new("html")) {
+                                    insert_banner(&mut out, &banner);
+                                }
+                            }
+                            add_file_output(
+                                filename,
+                                out,
+                                ctx.output,
+                                ctx.compile_directory,
+                                ctx.output_count,
+                                log_line,
+                            );
                         }
                         ParserOutput::GlobalFile(filename, out) => {
-                            add_file_output(filename, out, output, compile_directory, output_count);
+                            add_file_output(
+                                filename,
+                                out,
+                                ctx.output,
+                                ctx.compile_directory,
+                                ctx.output_count,
+                                log_line,
+                            );
                         }
                         ParserOutput::PayloadFile(raw_filename) => {
-                            let filename = add_unique_suffix(raw_filename, *output_count);
+                            let raw_filename =
+                                ctx.layout.relocate(raw_filename, e.compile_id.as_ref());
+                            if let Some((blob, is_new)) =
+                                ctx.output_count.blob_filename(&raw_filename, payload)
+                            {
+                                let url = blob.to_string_lossy().to_string();
+                                let name = ctx
+                                    .output_count
+                                    .unique_filename(raw_filename)
+                                    .to_string_lossy()
+                                    .to_string();
+                                payload_filename = ParserResult::PayloadFilename(url.clone());
+                                if is_new {
+                                    ctx.output.push((blob, payload.get().to_string()));
+                                } else {
+                                    ctx.stats.deduplicated_payload_bytes += payload.len() as u64;
+                                }
+                                ctx.compile_directory.push(OutputFile {
+                                    url,
+                                    suffix: cache_status_suffix(&name),
+                                    name,
+                                    number: ctx.output_count.count,
+                                    readable_url: None,
+                                    log_line,
+                                });
+                                ctx.output_count.count += 1;
+                                continue;
+                            }
+                            let filename = ctx.output_count.unique_filename(raw_filename);
                             payload_filename = ParserResult::PayloadFilename(
                                 filename.to_string_lossy().to_string(),
                             );
                             add_file_output(
                                 filename,
-                                payload.to_string(),
-                                output,
-                                compile_directory,
-                                output_count,
+                                payload.get().to_string(),
+                                ctx.output,
+                                ctx.compile_directory,
+                                ctx.output_count,
+                                log_line,
                             );
                         }
                         ParserOutput::PayloadReformatFile(raw_filename, formatter) => {
-                            let filename = add_unique_suffix(raw_filename, *output_count);
-                            match formatter(payload) {
+                            let raw_filename =
+                                ctx.layout.relocate(raw_filename, e.compile_id.as_ref());
+                            let filename = ctx.output_count.unique_filename(raw_filename);
+                            match formatter(payload.get()) {
                                 Ok(formatted_content) => {
                                     payload_filename = ParserResult::PayloadFilename(
                                         filename.to_string_lossy().to_string(),
@@ -246,203 +878,1193 @@ fn run_parser<'t>(
                                     add_file_output(
                                         filename,
                                         formatted_content,
-                                        output,
-                                        compile_directory,
-                                        output_count,
+                                        ctx.output,
+                                        ctx.compile_directory,
+                                        ctx.output_count,
+                                        log_line,
                                     );
                                 }
                                 Err(err) => {
-                                    multi.suspend(|| {
+                                    ctx.multi.suspend(|| {
                                         eprintln!(
                                             "Failed to format payload for {}: {}",
                                             filename.to_string_lossy(),
                                             err
                                         )
                                     });
-                                    stats.fail_parser += 1;
+                                    ctx.stats.fail_parser += 1;
                                 }
                             }
                         }
+                        ParserOutput::FileWriter(raw_filename, write) => {
+                            let raw_filename =
+                                ctx.layout.relocate(raw_filename, e.compile_id.as_ref());
+                            let filename = ctx.output_count.unique_filename(raw_filename);
+                            let stream_dir =
+                                ctx.output_count.stream_dir.clone().unwrap_or_default();
+                            let max_bytes = ctx.output_count.max_artifact_bytes;
+                            let written =
+                                stream_file_output(&stream_dir, &filename, write, max_bytes)
+                                    .and_then(|rendered_bytes| {
+                                        let Some(max_bytes) = max_bytes
+                                            .filter(|max_bytes| rendered_bytes > *max_bytes)
+                                        else {
+                                            return Ok(());
+                                        };
+                                        ctx.stats.truncated_artifacts += 1;
+                                        let banner = add_full_payload(
+                                            ctx,
+                                            &filename,
+                                            max_bytes,
+                                            rendered_bytes,
+                                            payload,
+                                            log_line,
+                                        );
+                                        if filename.extension() != Some(OsStr::new("html")) {
+                                            return Ok(());
+                                        }
+                                        // The start of the page is already written
+                                        OpenOptions::new()
+                                            .append(true)
+                                            .open(stream_dir.join(&filename))?
+                                            .write_all(banner.as_bytes())
+                                    });
+                            if let Err(err) = written {
+                                ctx.multi.suspend(|| {
+                                    eprintln!(
+                                        "Failed to write {}: {}",
+                                        filename.to_string_lossy(),
+                                        err
+                                    )
+                                });
+                                ctx.stats.fail_parser += 1;
+                                continue;
+                            }
+                            let filename = filename.to_string_lossy().to_string();
+                            ctx.compile_directory.push(OutputFile {
+                                url: filename.clone(),
+                                suffix: cache_status_suffix(&filename),
+                                name: filename,
+                                number: ctx.output_count.count,
+                                readable_url: None,
+                                log_line,
+                            });
+                            ctx.output_count.count += 1;
+                        }
                         ParserOutput::Link(name, url) => {
-                            compile_directory.push(OutputFile {
+                            ctx.compile_directory.push(OutputFile {
                                 url: url,
                                 name: name,
-                                number: *output_count,
+                                number: ctx.output_count.count,
                                 suffix: "".to_string(),
                                 readable_url: None,
+                                log_line,
                             });
-                            *output_count += 1;
+                            ctx.output_count.count += 1;
                         }
                     }
                 }
             }
             Err(err) => match parser.name() {
                 "dynamo_guards" => {
-                    multi.suspend(|| eprintln!("Failed to parse guards json: {}", err));
-                    stats.fail_dynamo_guards_json += 1;
+                    ctx.multi
+                        .suspend(|| eprintln!("Failed to parse guards json: {}", err));
+                    ctx.stats.fail_dynamo_guards_json += 1;
                 }
                 name => {
-                    multi.suspend(|| eprintln!("Parser {name} failed: {err}"));
-                    stats.fail_parser += 1;
+                    ctx.multi
+                        .suspend(|| eprintln!("Parser {name} failed: {err}"));
+                    ctx.stats.fail_parser += 1;
                 }
             },
         }
+        ctx.profile.record(
+            parser.name(),
+            start.elapsed(),
+            &ctx.output[outputs_before..],
+        );
     }
     payload_filename
 }
 
-fn directory_to_json(
-    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
-) -> serde_json::Value {
-    let mut json_map = serde_json::Map::new();
+/// Drops the artifacts of all but the `max_compile_ids` most expensive compile ids, preferring the
+/// most recent ones on ties, so that runaway recompilation doesn't make the index unloadable.
+/// Returns a summary of the compilation metrics of every dropped compile id.
+fn cap_compile_ids(
+    directory: &mut FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    output: &mut ParseOutput,
+    stream_dir: Option<&Path>,
+    metrics_index: &CompilationMetricsIndex,
+    max_compile_ids: usize,
+) -> Vec<OverflowCompileIdRow> {
+    let compile_time_s = |cid: &Option<CompileId>| -> Option<f64> {
+        metrics_index
+            .get(cid)?
+            .iter()
+            .filter_map(|m| m.entire_frame_compile_time_s)
+            .reduce(|a, b| a + b)
+    };
+    // Artifacts without a compile id are always kept, as are compile ids without artifacts
+    let mut ranked: Vec<(usize, f64)> = directory
+        .iter()
+        .enumerate()
+        .filter(|(_, (cid, files))| cid.is_some() && !files.is_empty())
+        .map(|(i, (cid, _))| (i, compile_time_s(cid).unwrap_or(0.0)))
+        .collect();
+    if ranked.len() <= max_compile_ids {
+        return Vec::new();
+    }
+    ranked.sort_by(|(i, a), (j, b)| b.total_cmp(a).then(j.cmp(i)));
+    let dropped: FxHashSet<usize> = ranked[max_compile_ids..].iter().map(|(i, _)| *i).collect();
+
+    let mut rows = Vec::new();
+    let mut dropped_files: FxHashSet<String> = FxHashSet::default();
+    let mut i = 0;
+    directory.retain(|cid, files| {
+        let keep = !dropped.contains(&i);
+        i += 1;
+        if keep {
+            return true;
+        }
+        let metrics = metrics_index.get(cid).and_then(|m| m.last());
+        let metric = |f: fn(&CompilationMetricsMetadata) -> Option<u64>| {
+            metrics.and_then(f).map_or(String::new(), |v| v.to_string())
+        };
+        rows.push(OverflowCompileIdRow {
+            compile_id: cid
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            compile_time_s: compile_time_s(cid).map_or(String::new(), |t| t.to_string()),
+            graph_op_count: metric(|m| m.graph_op_count),
+            guard_count: metric(|m| m.guard_count),
+            fail_type: metrics
+                .and_then(|m| m.fail_type.clone())
+                .unwrap_or_default(),
+            num_artifacts: files.len(),
+        });
+        dropped_files.extend(dropped_urls(files));
+        false
+    });
+    drop_output_files(output, stream_dir, &dropped_files);
+    rows
+}
 
-    for (compile_id, output_files) in directory {
-        let key = compile_id
-            .as_ref()
-            .map_or_else(|| "unknown".to_string(), |cid| cid.to_string());
+/// The files of `files` to drop from the output along with their compile id
+fn dropped_urls(files: &[OutputFile]) -> impl Iterator<Item = String> + '_ {
+    files.iter().flat_map(|file| {
+        // Deduplicated payloads may be shared with compile ids we keep
+        std::iter::once(&file.url)
+            .chain(file.readable_url.as_ref())
+            .filter(|url| !url.starts_with("blobs/"))
+            .cloned()
+    })
+}
 
-        let artifacts: Vec<serde_json::Value> = output_files
-            .iter()
-            .map(|file| {
-                serde_json::json!({
-                    "url": file.url,
-                    // Strip away any leading directory names, that will just be in the url path anyway
-                    "name": file.name.split('/').last().unwrap_or(&file.name),
-                    "number": file.number,
-                    "suffix": file.suffix,
-                    "readable_url": file.readable_url,
-                })
-            })
-            .collect();
+/// Drops the artifacts of every compile id but `compile_id`, for `first_failure`
+fn keep_compile_id(
+    directory: &mut FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    output: &mut ParseOutput,
+    stream_dir: Option<&Path>,
+    compile_id: &Option<CompileId>,
+) {
+    let mut dropped_files: FxHashSet<String> = FxHashSet::default();
+    directory.retain(|cid, files| {
+        if cid == compile_id {
+            return true;
+        }
+        dropped_files.extend(dropped_urls(files));
+        false
+    });
+    drop_output_files(output, stream_dir, &dropped_files);
+}
 
-        json_map.insert(key, serde_json::json!({"artifacts": artifacts}));
+/// Drops `dropped_files` from the output, including those already streamed to `stream_dir`
+fn drop_output_files(
+    output: &mut ParseOutput,
+    stream_dir: Option<&Path>,
+    dropped_files: &FxHashSet<String>,
+) {
+    output.retain(|(path, _)| !dropped_files.contains(path.to_string_lossy().as_ref()));
+    if let Some(stream_dir) = stream_dir {
+        for file in dropped_files {
+            // Most of them were never streamed
+            let _ = fs::remove_file(stream_dir.join(file));
+        }
     }
-    serde_json::Value::Object(json_map)
 }
 
-fn handle_guard(
-    failure_type: &str,
-    reason: &str,
-    lineno: usize,
-    e: &Envelope,
-    payload: &str,
-    output_count: &mut i32,
-    output: &mut Vec<(PathBuf, String)>,
-    compile_directory: &mut Vec<OutputFile>,
+/// Writes a malformed payload block to corrupt_payloads/ and reports it
+fn quarantine_payload(
+    mut corrupt: CorruptPayload,
+    payload: &LazyPayload,
+    output: &mut ParseOutput,
+    corrupt_payloads: &mut Vec<CorruptPayload>,
     multi: &MultiProgress,
     stats: &mut Stats,
-    tt: &TinyTemplate,
-    sym_expr_info_index: &RefCell<SymExprInfoIndex>,
-    export_failures: &mut Vec<ExportFailure>,
 ) {
-    let sym_expr_info_index_borrowed = sym_expr_info_index.borrow();
-    let parser: Box<dyn StructuredLogParser> =
-        Box::new(crate::parsers::PropagateRealTensorsParser {
-            tt,
-            sym_expr_info_index: &sym_expr_info_index_borrowed,
-        });
-    let _ = run_parser(
-        lineno,
-        &parser,
-        e,
-        payload,
-        output_count,
-        output,
-        compile_directory,
-        multi,
-        stats,
-    );
-
-    let filename = format!(
-        "symbolic_guard_information_{}.html",
-        (*output_count - 1).to_string()
-    );
-    let compile_id_dir: PathBuf = e
-        .compile_id
-        .as_ref()
-        .map_or(format!("unknown_{lineno}"), |cid| cid.as_directory_name())
-        .into();
-    let additional_info = format!(
-        "Please click <a href='{}/{}'>here</a> for more information.",
-        compile_id_dir.display(),
-        filename,
-    );
-
-    export_failures.push(ExportFailure {
-        failure_type: failure_type.to_string(),
-        reason: reason.to_string(),
-        additional_info,
+    corrupt.url = format!("corrupt_payloads/line_{}.txt", corrupt.line);
+    multi.suspend(|| {
+        eprintln!(
+            "Quarantined malformed payload on line {} in {}: {}",
+            corrupt.line, corrupt.url, corrupt.reason
+        )
     });
+    output.push((PathBuf::from(&corrupt.url), payload.get().to_string()));
+    corrupt_payloads.push(corrupt);
+    stats.quarantined_payloads += 1;
 }
 
-pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseOutput> {
-    let strict = config.strict;
-    if !path.is_file() {
-        bail!("{} is not a file", path.display())
+// How many of the biggest record types of a compile id to name on trace_size.html
+const TRACE_SIZE_BREAKDOWN: usize = 3;
+
+fn percent_of(bytes: u64, total: u64) -> String {
+    if total == 0 {
+        return "0.0%".to_string();
     }
-    let file = File::open(path)?;
-    let metadata = file.metadata()?;
-    let file_size = metadata.len();
+    format!("{:.1}%", bytes as f64 / total as f64 * 100.0)
+}
 
-    // TODO: abstract out this spinner to not be part of the library
-    // Instead, add a callback trait for CLIs to implement
-    let multi = MultiProgress::new();
-    let pb = multi.add(ProgressBar::new(file_size));
-    pb.set_style(ProgressStyle::default_bar()
-        .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} [{bytes_per_sec}] ({eta})")?
-        .progress_chars("#>-"));
-    let spinner = multi.add(ProgressBar::new_spinner());
+/// Where the bytes of the log go: record types from `envelope_stats`, which must be sorted, and
+/// compile ids, biggest first
+fn trace_size_context(
+    envelope_stats: &[EnvelopeTypeStats],
+    by_compile_id: &FxIndexMap<Option<CompileId>, FxIndexMap<String, u64>>,
+) -> TraceSizeContext {
+    let total: u64 = envelope_stats.iter().map(|s| s.log_bytes).sum();
+    let mut types: Vec<&EnvelopeTypeStats> = envelope_stats.iter().collect();
+    types.sort_by_key(|s| std::cmp::Reverse(s.log_bytes));
+    let mut compile_ids: Vec<(u64, TraceSizeRow)> = by_compile_id
+        .iter()
+        .map(|(compile_id, bytes_by_type)| {
+            let bytes: u64 = bytes_by_type.values().sum();
+            let mut biggest: Vec<(&String, &u64)> = bytes_by_type.iter().collect();
+            biggest.sort_by_key(|(_, bytes)| std::cmp::Reverse(**bytes));
+            let row = TraceSizeRow {
+                name: compile_id
+                    .as_ref()
+                    .map_or("(no compile id)".to_string(), |c| c.to_string()),
+                size: format_bytes(bytes),
+                percent: percent_of(bytes, total),
+                breakdown: biggest
+                    .iter()
+                    .take(TRACE_SIZE_BREAKDOWN)
+                    .map(|(t, b)| format!("{t}: {}", format_bytes(**b)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            (bytes, row)
+        })
+        .collect();
+    compile_ids.sort_by_key(|(bytes, _)| std::cmp::Reverse(*bytes));
+    TraceSizeContext {
+        css: TEMPLATE_FAILURES_CSS,
+        total_size: format_bytes(total),
+        types: types
+            .into_iter()
+            .map(|s| TraceSizeRow {
+                name: s.envelope_type.clone(),
+                size: format_bytes(s.log_bytes),
+                percent: percent_of(s.log_bytes, total),
+                breakdown: s.count.to_string(),
+            })
+            .collect(),
+        compile_ids: compile_ids.into_iter().map(|(_, row)| row).collect(),
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    }
+}
 
-    let reader = io::BufReader::new(file);
+/// The type of a log record, e.g. dynamo_start, or artifact:<name> for artifacts.  That is the
+/// field of the envelope naming its type, the first by name if there are several, including
+/// fields tlparse doesn't know.
+fn envelope_type(e: &Envelope) -> String {
+    if let Some(ref artifact) = e.artifact {
+        return format!("artifact:{}", artifact.name);
+    }
+    let known = [
+        ("dynamo_start", e.dynamo_start.is_some()),
+        ("str", e.str.is_some()),
+        ("dynamo_output_graph", e.dynamo_output_graph.is_some()),
+        (
+            "optimize_ddp_split_graph",
+            e.optimize_ddp_split_graph.is_some(),
+        ),
+        (
+            "optimize_ddp_split_child",
+            e.optimize_ddp_split_child.is_some(),
+        ),
+        (
+            "compiled_autograd_graph",
+            e.compiled_autograd_graph.is_some(),
+        ),
+        ("dynamo_guards", e.dynamo_guards.is_some()),
+        ("aot_forward_graph", e.aot_forward_graph.is_some()),
+        ("aot_backward_graph", e.aot_backward_graph.is_some()),
+        ("aot_inference_graph", e.aot_inference_graph.is_some()),
+        ("aot_joint_graph", e.aot_joint_graph.is_some()),
+        (
+            "inductor_pre_grad_graph",
+            e.inductor_pre_grad_graph.is_some(),
+        ),
+        (
+            "inductor_post_grad_graph",
+            e.inductor_post_grad_graph.is_some(),
+        ),
+        ("dynamo_cpp_guards_str", e.dynamo_cpp_guards_str.is_some()),
+        ("inductor_output_code", e.inductor_output_code.is_some()),
+        ("compilation_metrics", e.compilation_metrics.is_some()),
+        (
+            "bwd_compilation_metrics",
+            e.bwd_compilation_metrics.is_some(),
+        ),
+        (
+            "aot_autograd_backward_compilation_metrics",
+            e.aot_autograd_backward_compilation_metrics.is_some(),
+        ),
+        ("graph_dump", e.graph_dump.is_some()),
+        ("link", e.link.is_some()),
+        (
+            "symbolic_shape_specialization",
+            e.symbolic_shape_specialization.is_some(),
+        ),
+        (
+            "propagate_real_tensors_provenance",
+            e.propagate_real_tensors_provenance.is_some(),
+        ),
+        ("guard_added", e.guard_added.is_some()),
+        ("create_unbacked_symbol", e.create_unbacked_symbol.is_some()),
+        ("expression_created", e.expression_created.is_some()),
+        ("missing_fake_kernel", e.missing_fake_kernel.is_some()),
+        ("mismatched_fake_kernel", e.mismatched_fake_kernel.is_some()),
+        ("describe_storage", e.describe_storage.is_some()),
+        ("describe_tensor", e.describe_tensor.is_some()),
+        ("describe_source", e.describe_source.is_some()),
+        ("dump_file", e.dump_file.is_some()),
+        ("chromium_event", e.chromium_event.is_some()),
+        ("guard_added_fast", e.guard_added_fast.is_some()),
+        ("exported_program", e.exported_program.is_some()),
+    ];
+    known
+        .iter()
+        .filter(|(_, present)| *present)
+        .map(|(name, _)| *name)
+        .chain(e._other.keys().map(|k| k.as_str()))
+        .min()
+        .unwrap_or("(none)")
+        .to_string()
+}
 
-    let re_glog = Regex::new(concat!(
-        r"(?<level>[VIWEC])(?<month>\d{2})(?<day>\d{2}) ",
-        r"(?<hour>\d{2}):(?<minute>\d{2}):(?<second>\d{2}).(?<millisecond>\d{6}) ",
-        r"(?<thread>\d+)",
-        r"(?<pathname>[^:]+):(?<line>\d+)\] ",
-        r"(?<payload>.)"
-    ))?;
+fn compile_directory(
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    compile_steps: &FxHashMap<Option<CompileId>, u64>,
+    layout: DirectoryLayout,
+) -> CompileDirectory {
+    let compile_ids = directory
+        .iter()
+        .map(|(compile_id, output_files)| {
+            let key = compile_id
+                .as_ref()
+                .map_or_else(|| "unknown".to_string(), |cid| cid.to_string());
+            let artifacts = output_files
+                .iter()
+                .map(|file| CompileDirectoryArtifact {
+                    url: file.url.clone(),
+                    // Strip away any leading directory names, that will just be in the url path anyway
+                    name: file
+                        .name
+                        .split('/')
+                        .next_back()
+                        .unwrap_or(&file.name)
+                        .to_string(),
+                    number: file.number,
+                    suffix: file.suffix.clone(),
+                    readable_url: file.readable_url.clone(),
+                    log_line: file.log_line,
+                })
+                .collect();
+            let entry = CompileDirectoryEntry {
+                artifacts,
+                directory: compile_id.as_ref().map(|cid| layout.directory_name(cid)),
+                layout: layout.to_string(),
+                step: compile_steps.get(compile_id).copied(),
+            };
+            (key, entry)
+        })
+        .collect();
+    CompileDirectory {
+        schema_version: COMPILE_DIRECTORY_SCHEMA_VERSION,
+        compile_ids,
+    }
+}
 
-    // Helper functions to reduce repetitive serde_json::Value creation
-    let make_string_value = |caps: &regex::Captures, name: &str| -> serde_json::Value {
-        serde_json::Value::String(caps.name(name).unwrap().as_str().to_string())
-    };
+// Graphs that should be identical on every rank that compiles the same compile id
+const GRAPH_ARTIFACT_PREFIXES: &[&str] = &[
+    "dynamo_output_graph",
+    "inductor_post_grad_graph",
+    "after_post_grad_graph",
+];
 
-    let make_number_value = |caps: &regex::Captures, name: &str| -> serde_json::Value {
-        let parsed: u64 = caps.name(name).unwrap().as_str().parse().unwrap();
-        serde_json::Value::Number(serde_json::Number::from(parsed))
-    };
+static DEVICE_INDEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(cuda|xpu|hpu|mtia):\d+").unwrap());
 
-    // Helper function to format timestamp as ISO-8601
-    let format_timestamp = |caps: &regex::Captures| -> String {
-        let month: u32 = caps.name("month").unwrap().as_str().parse().unwrap();
-        let day: u32 = caps.name("day").unwrap().as_str().parse().unwrap();
-        let hour: u32 = caps.name("hour").unwrap().as_str().parse().unwrap();
-        let minute: u32 = caps.name("minute").unwrap().as_str().parse().unwrap();
-        let second: u32 = caps.name("second").unwrap().as_str().parse().unwrap();
-        let microsecond: u32 = caps.name("millisecond").unwrap().as_str().parse().unwrap();
+// md5 of a graph with device indices normalized, since each rank runs on its own device
+fn graph_hash(graph: &str) -> String {
+    let graph = DEVICE_INDEX.replace_all(graph, "$1");
+    Md5::digest(graph.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
 
-        // Assume current year since glog doesn't include year
-        let year = chrono::Utc::now().year();
+fn rank_parse_summary(
+    directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    output: &ParseOutput,
+    stats: Stats,
+    compile_summary: CompileSummary,
+    num_chromium_events: usize,
+    compiles: Vec<RunCompile>,
+) -> RankParseSummary {
+    let contents: FxHashMap<&Path, &str> = output
+        .iter()
+        .map(|(path, content)| (path.as_path(), content.as_str()))
+        .collect();
+    let mut compile_ids = FxHashSet::default();
+    let mut cache_statuses: Vec<(i32, &str)> = Vec::new();
+    let mut graph_hashes = FxHashMap::default();
+    for (compile_id, files) in directory {
+        let key = compile_id
+            .as_ref()
+            .map_or_else(|| "unknown".to_string(), |cid| cid.to_string());
+        if compile_id.is_some() {
+            compile_ids.insert(key.clone());
+        }
+        for file in files {
+            if !file.suffix.is_empty() {
+                cache_statuses.push((file.number, &file.suffix));
+            }
+            let name = file.name.rsplit('/').next().unwrap_or(&file.name);
+            if GRAPH_ARTIFACT_PREFIXES.iter().any(|p| name.starts_with(p)) {
+                if let Some(graph) = contents.get(Path::new(&file.url)) {
+                    graph_hashes.insert((key.clone(), name.to_string()), graph_hash(graph));
+                }
+            }
+        }
+    }
+    cache_statuses.sort_by_key(|(number, _)| *number);
+    RankParseSummary {
+        stats,
+        compile_summary,
+        compile_ids,
+        cache_sequence: cache_statuses.into_iter().map(|(_, s)| s).collect(),
+        graph_hashes,
+        num_chromium_events,
+        compiles,
+    }
+}
 
-        // Format as ISO-8601 with microsecond precision
-        format!(
-            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
-            year, month, day, hour, minute, second, microsecond
-        )
+/// Link each compile id traced by compiled autograd back to the forward compile ids whose backward
+/// it runs.  Compiled autograd graphs get compile ids of their own, so we match them by stack: the
+/// forwards are the earlier compile ids (in `order`) whose stack shares the longest common prefix
+/// with the stack that triggered the compiled autograd compile, e.g. the training step calling
+/// both `model(x)` and `loss.backward()`.
+fn correlate_compiled_autograd<'a>(
+    stack_index: &StackIndex,
+    order: impl Iterator<Item = &'a Option<CompileId>>,
+) -> Vec<(CompileId, Vec<CompileId>)> {
+    let same_frame = |a: &FrameSummary, b: &FrameSummary| {
+        a.filename == b.filename && a.line == b.line && a.name == b.name
     };
+    let stacks: FxHashMap<CompileId, &StackSummary> = stack_index
+        .iter()
+        .filter_map(|(cid, stack)| {
+            let mut cid = cid.clone()?;
+            if cid.frame_compile_id.is_some() && cid.attempt.is_none() {
+                cid.attempt = Some(0);
+            }
+            Some((cid, stack))
+        })
+        .collect();
+    let mut forwards: Vec<(&CompileId, &StackSummary)> = Vec::new();
+    let mut result = Vec::new();
+    for cid in order.flatten() {
+        let Some(stack) = stacks.get(cid) else {
+            continue;
+        };
+        if cid.compiled_autograd_id.is_none() {
+            forwards.push((cid, stack));
+            continue;
+        }
+        let common_prefix = |other: &StackSummary| {
+            stack
+                .iter()
+                .zip(other.iter())
+                .take_while(|(a, b)| same_frame(a, b))
+                .count()
+        };
+        let best = forwards
+            .iter()
+            .map(|(_, s)| common_prefix(s))
+            .max()
+            .unwrap_or(0);
+        if best == 0 {
+            continue;
+        }
+        let matched: Vec<CompileId> = forwards
+            .iter()
+            .filter(|(_, s)| common_prefix(s) == best)
+            .map(|(c, _)| (*c).clone())
+            .collect();
+        result.push((cid.clone(), matched));
+    }
+    result
+}
 
-    let mut stack_trie = StackTrieNode::default();
-    let mut unknown_stack_trie = StackTrieNode::default();
-
-    let mut stats = Stats::default();
-    let _mod_count: FxHashMap<String, i32> = FxHashMap::default();
-
-    let mut bytes_read: u64 = 0;
+fn group_export_failures(failures: &[ExportFailure]) -> Vec<ExportFailureGroup> {
+    let mut groups: FxIndexMap<&str, ExportFailureGroup> = FxIndexMap::default();
+    for failure in failures {
+        let group = groups
+            .entry(&failure.failure_type)
+            .or_insert_with(|| ExportFailureGroup {
+                failure_type: failure.failure_type.clone(),
+                count: 0,
+                ops: Vec::new(),
+                failures: Vec::new(),
+            });
+        group.count += 1;
+        if let Some(op) = &failure.op {
+            match group.ops.iter_mut().find(|o| &o.op == op) {
+                Some(o) => o.count += 1,
+                None => group.ops.push(ExportFailureOp {
+                    op: op.clone(),
+                    count: 1,
+                }),
+            }
+        }
+        group.failures.push(failure.clone());
+    }
+    let mut groups: Vec<ExportFailureGroup> = groups.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+    for group in &mut groups {
+        group.ops.sort_by_key(|o| std::cmp::Reverse(o.count));
+    }
+    groups
+}
 
-    // Some stuff for profiling
-    let mut fastest_time = std::time::Duration::MAX;
-    let mut slowest_time = std::time::Duration::ZERO;
+/// Machine readable summary of an export report, e.g. for failing CI when draft export
+/// finds issues.  Reasons are stripped of the HTML used by the report.
+fn export_failures_json(groups: &[ExportFailureGroup]) -> serde_json::Value {
+    let plain = |html: &str| {
+        HTML_TAG
+            .replace_all(html, "")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let num_failures: usize = groups.iter().map(|g| g.count).sum();
+    serde_json::json!({
+        "success": num_failures == 0,
+        "num_failures": num_failures,
+        "failure_types": groups
+            .iter()
+            .map(|g| serde_json::json!({
+                "failure_type": g.failure_type,
+                "count": g.count,
+                "ops": g.ops,
+                "failures": g
+                    .failures
+                    .iter()
+                    .map(|f| serde_json::json!({
+                        "reason": plain(&f.reason),
+                        "op": f.op,
+                        "suggestions": f.suggestions,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn group_failures(failures: &[(String, String)]) -> Vec<FailureGroup> {
+    let mut groups: FxIndexMap<&str, FailureGroup> = FxIndexMap::default();
+    for (compile_id, reason) in failures {
+        let group = groups.entry(reason).or_insert_with(|| FailureGroup {
+            reason: reason.clone(),
+            count: 0,
+            compile_ids: Vec::new(),
+        });
+        group.count += 1;
+        if !group.compile_ids.contains(compile_id) {
+            group.compile_ids.push(compile_id.clone());
+        }
+    }
+    let mut groups: Vec<FailureGroup> = groups.into_values().collect();
+    // Stable, so ties stay in the order they were first seen
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+    groups
+}
+
+fn pass_timings_rows(pass_timings_index: &PassTimingsIndex) -> Vec<PassTimingRow> {
+    let mut rows: Vec<(f64, PassTimingRow)> = pass_timings_index
+        .iter()
+        .map(|((stage, pass), runs)| {
+            let total_s: f64 = runs.iter().map(|(_, d)| d).fold(0.0, |acc, d| acc + d);
+            let (slowest_compile_id, max_s) = runs
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(cid, d)| (cid.clone(), *d))
+                .unwrap_or((None, 0.0));
+            let row = PassTimingRow {
+                stage: stage.clone(),
+                pass: pass.clone(),
+                count: runs.len(),
+                total_s: format!("{total_s:.4}"),
+                mean_s: format!("{:.4}", total_s / runs.len() as f64),
+                max_s: format!("{max_s:.4}"),
+                slowest_compile_id: slowest_compile_id
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+            };
+            (total_s, row)
+        })
+        .collect();
+    rows.sort_by(|a, b| b.0.total_cmp(&a.0));
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+/// Graphs that more than one compile id produced, by their name and structure, most duplicated
+/// first
+fn duplicate_graph_clusters(graph_stats_index: &GraphStatsIndex) -> Vec<DuplicateGraphCluster> {
+    // Compile ids by hash by structure hash, in the order they were logged
+    let mut clusters: FxIndexMap<(&str, &str), FxIndexMap<&str, Vec<String>>> =
+        FxIndexMap::default();
+    for ((compile_id, name), dumps) in graph_stats_index {
+        let compile_id = compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string());
+        for stats in dumps {
+            let compile_ids = clusters
+                .entry((name, &stats.structure_hash))
+                .or_default()
+                .entry(&stats.hash)
+                .or_default();
+            // A compile id may dump the same graph more than once
+            if compile_ids.last() != Some(&compile_id) {
+                compile_ids.push(compile_id.clone());
+            }
+        }
+    }
+    let mut clusters: Vec<DuplicateGraphCluster> = clusters
+        .into_iter()
+        .filter_map(|((graph, _), by_hash)| {
+            let num_compile_ids = by_hash.values().flatten().collect::<FxHashSet<_>>().len();
+            (num_compile_ids > 1).then(|| DuplicateGraphCluster {
+                graph: graph.to_string(),
+                kind: if by_hash.len() == 1 {
+                    "Identical"
+                } else {
+                    "Identical up to node names"
+                },
+                num_compile_ids,
+                identical_compile_ids: by_hash.into_values().map(|ids| ids.join(", ")).collect(),
+            })
+        })
+        .collect();
+    // Stable, so ties stay in the order they were first seen
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.num_compile_ids));
+    clusters
+}
+
+/// Orders the directory so that the attempts of each frame are listed together, each with the
+/// reasons the attempt before it was restarted.  Dynamo logs the compilation metrics of a frame
+/// once, under its last attempt, with the reasons for all of its restarts, so those are only
+/// split between the attempts when there is one per restart.
+fn attempt_tree(
+    directory: FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    metrics_index: &CompilationMetricsIndex,
+    compile_steps: &FxHashMap<Option<CompileId>, u64>,
+    graph_stats_index: &GraphStatsIndex,
+) -> Vec<(String, Vec<OutputFile>, IndexCompileId)> {
+    // Compile ids without an attempt are listed on their own
+    let mut frames: FxIndexMap<Result<CompileId, usize>, Vec<_>> = FxIndexMap::default();
+    for (i, (compile_id, files)) in directory.into_iter().enumerate() {
+        let frame = match &compile_id {
+            Some(c) if c.attempt.is_some() => Ok(CompileId {
+                attempt: None,
+                ..c.clone()
+            }),
+            _ => Err(i),
+        };
+        frames.entry(frame).or_default().push((compile_id, files));
+    }
+    let mut rows = Vec::new();
+    for (frame_index, (frame, mut attempts)) in frames.into_iter().enumerate() {
+        attempts.sort_by_key(|(compile_id, _)| compile_id.as_ref().and_then(|c| c.attempt));
+        let num_attempts = attempts.len();
+        let reasons: Vec<String> = attempts
+            .iter()
+            .flat_map(|(compile_id, _)| metrics_index.get(compile_id).into_iter().flatten())
+            .flat_map(|m| m.restart_reasons.iter().flatten().cloned())
+            .collect();
+        let frame = frame
+            .ok()
+            .filter(|_| num_attempts > 1)
+            .map(|c| c.to_string());
+        for (i, (compile_id, files)) in attempts.into_iter().enumerate() {
+            let step = compile_steps.get(&compile_id).map(|step| step.to_string());
+            let graph_nodes = graph_stats_index
+                .get(&(compile_id.clone(), "dynamo_output_graph"))
+                .and_then(|dumps| dumps.last())
+                .map(|stats| stats.num_nodes.to_string());
+            let restart_reasons = if i == 0 {
+                vec![]
+            } else if reasons.len() == num_attempts - 1 {
+                vec![reasons[i - 1].clone()]
+            } else if i == num_attempts - 1 {
+                reasons.clone()
+            } else {
+                vec![]
+            };
+            rows.push((
+                compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                files,
+                IndexCompileId {
+                    frame: frame.clone(),
+                    num_attempts,
+                    attempt: compile_id.and_then(|c| c.attempt).unwrap_or(0),
+                    restart_reasons,
+                    step,
+                    graph_nodes,
+                    frame_index,
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    nest_attempts(&mut rows);
+    rows
+}
+
+/// Marks where the attempts of each restarted frame start and end in `rows`, which may be one page
+/// of the index that splits a frame's attempts with the next
+fn nest_attempts(rows: &mut [(String, Vec<OutputFile>, IndexCompileId)]) {
+    for i in 0..rows.len() {
+        let frame_index = rows[i].2.frame_index;
+        let nested = rows[i].2.frame.is_some();
+        rows[i].2.first_attempt = nested && (i == 0 || rows[i - 1].2.frame_index != frame_index);
+        rows[i].2.last_attempt = nested
+            && rows
+                .get(i + 1)
+                .is_none_or(|r| r.2.frame_index != frame_index);
+    }
+}
+
+/// Rank compile ids by cumulative guard evaluation overhead.  Looking up a frame in the cache
+/// evaluates the guards of its earlier entries before reaching a later one, so the cumulative
+/// latency of a compile id includes that of every compilation of the same frame before it.
+fn guard_latency_rows(metrics_index: &CompilationMetricsIndex) -> (Vec<GuardLatencyRow>, f64, u64) {
+    let mut per_frame: FxHashMap<(Option<u32>, Option<u32>), f64> = FxHashMap::default();
+    let mut total_latency_us = 0.0;
+    let mut total_guard_count = 0;
+    let mut rows: Vec<(f64, GuardLatencyRow)> = Vec::new();
+    for (compile_id, metrics) in metrics_index {
+        for m in metrics {
+            let Some(latency_us) = m.guard_latency_us else {
+                continue;
+            };
+            let frame = compile_id
+                .as_ref()
+                .map_or((None, None), |c| (c.compiled_autograd_id, c.frame_id));
+            let cumulative_us = per_frame.entry(frame).or_insert(0.0);
+            *cumulative_us += latency_us;
+            total_latency_us += latency_us;
+            total_guard_count += m.guard_count.unwrap_or(0);
+            rows.push((
+                *cumulative_us,
+                GuardLatencyRow {
+                    compile_id: compile_id
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    guard_count: m.guard_count.map_or("".to_string(), |c| c.to_string()),
+                    latency_us: format!("{latency_us:.1}"),
+                    cumulative_latency_us: format!("{cumulative_us:.1}"),
+                },
+            ));
+        }
+    }
+    rows.sort_by(|a, b| b.0.total_cmp(&a.0));
+    (
+        rows.into_iter().map(|(_, row)| row).collect(),
+        total_latency_us,
+        total_guard_count,
+    )
+}
+
+// Link the symbols in each guard expression to the exported program lines that constrain them
+// A symbol of an export failure's expression, e.g. s0 or u1
+static EXPORT_SYMBOL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[a-z]+\d+\b").unwrap());
+
+fn link_export_failures_to_program(failures: &mut [ExportFailure], program: &str, url: &str) {
+    for failure in failures.iter_mut() {
+        let Some(expr) = failure.expr.as_ref() else {
+            continue;
+        };
+        let mut seen = FxHashSet::default();
+        let links: Vec<String> = EXPORT_SYMBOL
+            .find_iter(expr)
+            .map(|m| m.as_str())
+            .filter(|symbol| seen.insert(*symbol))
+            .filter_map(|symbol| {
+                crate::parsers::exported_program_anchor(program, symbol)
+                    .map(|anchor| format!("<a href='{url}#{anchor}'><code>{symbol}</code></a>"))
+            })
+            .collect();
+        if !links.is_empty() {
+            failure.additional_info.push_str(&format!(
+                " In the exported program, see {}.",
+                links.join(", ")
+            ));
+        }
+    }
+}
+
+/// Writes the symbolic_guard_information.html pages of guards
+struct GuardPages<'g> {
+    tt: &'g TinyTemplate<'g>,
+    sym_expr_info_index: &'g RefCell<SymExprInfoIndex>,
+    intern_table: &'g RefCell<InternTable>,
+    sources: Option<&'g SourceSnippets>,
+}
+
+impl GuardPages<'_> {
+    /// Writes the page of the guard of `e` and returns its url
+    fn write(
+        &self,
+        lineno: usize,
+        e: &Envelope,
+        payload: &LazyPayload,
+        ctx: &mut ParserContext,
+    ) -> String {
+        let sym_expr_info_index = self.sym_expr_info_index.borrow();
+        let intern_table = self.intern_table.borrow();
+        let parser: Box<dyn StructuredLogParser> =
+            Box::new(crate::parsers::PropagateRealTensorsParser {
+                tt: self.tt,
+                sym_expr_info_index: &sym_expr_info_index,
+                intern_table: &intern_table,
+                sources: self.sources,
+                layout: ctx.layout,
+            });
+        let _ = run_parser(lineno, &parser, e, payload, ctx);
+
+        // The guard's page is the last output, since it just ran
+        let filename = ctx
+            .compile_directory
+            .last()
+            .and_then(|f| Path::new(&f.url).file_name())
+            .map_or(String::new(), |f| f.to_string_lossy().to_string());
+        let compile_id_dir = ctx.layout.compile_id_dir(e.compile_id.as_ref(), lineno);
+        format!("{}/{}", compile_id_dir.display(), filename)
+    }
+
+    /// The guard of `e` as an export failure, linking to its page at `url`
+    fn export_failure(
+        &self,
+        failure_type: &str,
+        reason: &str,
+        e: &Envelope,
+        url: &str,
+    ) -> ExportFailure {
+        let guard = e
+            .guard_added
+            .as_ref()
+            .or(e.propagate_real_tensors_provenance.as_ref());
+        let additional_info =
+            format!("Please click <a href='{url}'>here</a> for more information.");
+        ExportFailure {
+            failure_type: failure_type.to_string(),
+            reason: reason.to_string(),
+            additional_info,
+            op: None,
+            expr: guard.and_then(|guard| guard.expr.clone()),
+            suggestions: guard.map_or(Vec::new(), |guard| {
+                crate::constraint_suggestions::suggest_constraints(
+                    guard,
+                    &self.sym_expr_info_index.borrow(),
+                )
+            }),
+        }
+    }
+}
+
+/// Registers the templates of the report's pages, with the overrides of `config`
+fn add_templates<'a>(tt: &mut TinyTemplate<'a>, config: &'a ParseConfig) -> anyhow::Result<()> {
+    // Guards with data-dependent asserts get a page in both modes
+    tt.add_template(
+        "symbolic_guard_information.html",
+        template_source(
+            config,
+            "symbolic_guard_information.html",
+            TEMPLATE_SYMBOLIC_GUARD_INFO,
+        ),
+    )?;
+    if config.export {
+        tt.add_template(
+            "index.html",
+            template_source(config, "export_index.html", TEMPLATE_EXPORT_INDEX),
+        )?;
+        tt.add_template(
+            "exported_program.html",
+            template_source(config, "exported_program.html", TEMPLATE_EXPORTED_PROGRAM),
+        )?;
+    } else {
+        for (name, builtin) in [
+            ("index.html", TEMPLATE_INDEX),
+            ("failures_and_restarts.html", TEMPLATE_FAILURES_AND_RESTARTS),
+            ("dynamo_guards.html", TEMPLATE_DYNAMO_GUARDS),
+            ("compilation_metrics.html", TEMPLATE_COMPILATION_METRICS),
+            (
+                "bwd_compilation_metrics.html",
+                TEMPLATE_BWD_COMPILATION_METRICS,
+            ),
+            (
+                "aot_autograd_backward_compilation_metrics.html",
+                TEMPLATE_AOT_AUTOGRAD_BACKWARD_COMPILATION_METRICS,
+            ),
+            ("search.html", TEMPLATE_SEARCH),
+            ("pass_timings.html", TEMPLATE_PASS_TIMINGS),
+            ("counters.html", TEMPLATE_COUNTERS),
+            ("memory_report.html", TEMPLATE_MEMORY_REPORT),
+            ("duplicate_graphs.html", TEMPLATE_DUPLICATE_GRAPHS),
+            ("module_map.html", TEMPLATE_MODULE_MAP),
+            ("code_size.html", TEMPLATE_CODE_SIZE),
+            ("trace_size.html", TEMPLATE_TRACE_SIZE),
+            ("threads.html", TEMPLATE_THREADS),
+            ("guard_latency.html", TEMPLATE_GUARD_LATENCY),
+            ("overflow_compile_ids.html", TEMPLATE_OVERFLOW_COMPILE_IDS),
+            ("first_failure.html", TEMPLATE_FIRST_FAILURE),
+            ("index_page.html", TEMPLATE_INDEX_PAGE),
+            ("baseline_comparison.html", TEMPLATE_BASELINE_COMPARISON),
+            (
+                "runtime_profile_correlation.html",
+                TEMPLATE_RUNTIME_PROFILE_CORRELATION,
+            ),
+            ("runtime_estimation.html", TEMPLATE_RUNTIME_ESTIMATION),
+            ("guard_diff.html", TEMPLATE_GUARD_DIFF),
+            ("inductor_config.html", TEMPLATE_INDUCTOR_CONFIG),
+            ("summary.html", TEMPLATE_COMPILE_ID_SUMMARY),
+            ("cudagraphs.html", TEMPLATE_CUDAGRAPHS),
+            ("ddp_splits.html", TEMPLATE_DDP_SPLITS),
+            ("graph_partitions.html", TEMPLATE_GRAPH_PARTITIONS),
+            ("compiled_autograd.html", TEMPLATE_COMPILED_AUTOGRAD),
+            ("aot_partition.html", TEMPLATE_AOT_PARTITION),
+            ("symbolic_expressions.html", TEMPLATE_SYMBOLIC_EXPRESSIONS),
+        ] {
+            tt.add_template(name, template_source(config, name, builtin))?;
+        }
+    }
+    tt.add_template(
+        "provenance_tracking.html",
+        template_source(
+            config,
+            "provenance_tracking.html",
+            TEMPLATE_PROVENANCE_TRACKING,
+        ),
+    )?;
+    Ok(())
+}
+
+pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseOutput> {
+    parse_path_with_summary(path, config).map(|(output, _)| output)
+}
+
+/// Like `parse_path`, also returning what the multi-rank report compares across ranks, so that it
+/// doesn't have to read it back from the output
+pub fn parse_path_with_summary(
+    path: &PathBuf,
+    config: &ParseConfig,
+) -> anyhow::Result<(ParseOutput, RankParseSummary)> {
+    parse_path_impl(
+        std::slice::from_ref(path),
+        config,
+        RawJsonl::Memory(String::new()),
+        None,
+    )
+    .and_then(|(output, summary)| Ok((finish_output(output, config)?, summary)))
+}
+
+/// Like `parse_path_with_summary`, but writes raw.jsonl to `raw_jsonl_path` as the log is
+/// parsed, rather than returning it with the rest of the output.  raw.jsonl is the biggest output
+/// of huge logs besides raw.log, so this saves a lot of memory on them.
+///
+/// `paths` are the fragments of one log, e.g. rotated files, which are parsed as one log in the
+/// order they were written.
+pub fn parse_path_with_raw_jsonl_file(
+    paths: &[PathBuf],
+    config: &ParseConfig,
+    raw_jsonl_path: &Path,
+) -> anyhow::Result<(ParseOutput, RankParseSummary)> {
+    parse_path_impl(paths, config, RawJsonl::spill(raw_jsonl_path)?, None)
+        .and_then(|(output, summary)| Ok((finish_output(output, config)?, summary)))
+}
+
+/// Like `parse_path_with_raw_jsonl_file`, writing raw.jsonl into `output_dir`, and also streaming
+/// the `ParserOutput::FileWriter` outputs of parsers into `output_dir` rather than returning
+/// them.  They are written as is, without the links `finish_output` adds to the rest of the
+/// output.  The rest of the output is still returned, for `write_output` into `output_dir`.
+pub fn parse_path_to_directory(
+    paths: &[PathBuf],
+    config: &ParseConfig,
+    output_dir: &Path,
+) -> anyhow::Result<(ParseOutput, RankParseSummary)> {
+    let raw_jsonl = RawJsonl::spill(&output_dir.join("raw.jsonl"))?;
+    parse_path_impl(paths, config, raw_jsonl, Some(output_dir))
+        .and_then(|(output, summary)| Ok((finish_output(output, config)?, summary)))
+}
+
+/// Runs only the parser named `parser_name` over the log at `path`, e.g. to pull the
+/// dynamo_output_graph of one compile id out of a log without generating the rest of the
+/// report.  Without an attempt, `compile_id` matches any attempt of the compile id; without a
+/// compile id, every record is parsed.  Files are named as in the report.
+pub fn extract_artifact(
+    path: &Path,
+    parser_name: &str,
+    compile_id: Option<&CompileId>,
+) -> anyhow::Result<ParseOutput> {
+    let config = ParseConfig::default();
+    let mut tt = TinyTemplate::new();
+    tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
+    add_templates(&mut tt, &config)?;
+    let parsers = default_parsers(&tt, &config);
+    let Some(parser) = parsers.iter().find(|p| p.name() == parser_name) else {
+        let names: Vec<&str> = parsers.iter().map(|p| p.name()).collect();
+        bail!(
+            "Unknown parser {parser_name}, expected one of: {}",
+            names.join(", ")
+        );
+    };
+    let wanted = |c: &Option<CompileId>| {
+        let (Some(wanted), Some(c)) = (compile_id, c) else {
+            return compile_id.is_none();
+        };
+        c.compiled_autograd_id == wanted.compiled_autograd_id
+            && c.frame_id == wanted.frame_id
+            && c.frame_compile_id == wanted.frame_compile_id
+            && wanted
+                .attempt
+                .is_none_or(|attempt| c.attempt.unwrap_or(0) == attempt)
+    };
+
+    let log = [LogBytes::read(path)?];
+    let mut records = RecordReader::new(log_lines(&log), &config);
+    let multi = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    let mut stats = Stats::default();
+    let mut output_count = OutputCounter::default();
+    let mut output: ParseOutput = Vec::new();
+    let mut directory: Vec<OutputFile> = Vec::new();
+    let mut profile = ParserProfile::default();
+    while let Some(item) = records.next_item(&mut stats) {
+        let LogItem::Record(record) = item else {
+            continue;
+        };
+        if !wanted(&record.envelope.compile_id) {
+            continue;
+        }
+        run_parser(
+            record.lineno,
+            parser,
+            &record.envelope,
+            &record.payload,
+            &mut ParserContext {
+                output: &mut output,
+                output_count: &mut output_count,
+                compile_directory: &mut directory,
+                multi: &multi,
+                stats: &mut stats,
+                profile: &mut profile,
+                layout: config.directory_layout,
+            },
+        );
+    }
+    if stats.fail_parser > 0 {
+        bail!("{parser_name} failed on {} record(s)", stats.fail_parser);
+    }
+    Ok(output)
+}
+
+// Everything is written without the URL prefix, and then relinked
+fn finish_output(output: ParseOutput, config: &ParseConfig) -> anyhow::Result<ParseOutput> {
+    let output = deep_link::add_line_views(output);
+    let output = deep_link::add_deep_link_script(output);
+    let output = match &config.url_prefix {
+        Some(url_prefix) => url_prefix::root_output_links(output, url_prefix),
+        None => output,
+    };
+    if config.validate_output {
+        validate_output(&output)?;
+    }
+    Ok(output)
+}
+
+fn parse_path_impl(
+    paths: &[PathBuf],
+    config: &ParseConfig,
+    mut raw_jsonl: RawJsonl,
+    stream_dir: Option<&Path>,
+) -> anyhow::Result<(ParseOutput, RankParseSummary)> {
+    let strict = config.strict;
+    let mut log = Vec::new();
+    for path in order_log_fragments(paths)? {
+        if path.is_dir() {
+            bail!("{} is not a file", path.display())
+        }
+        log.push(LogBytes::read(&path)?);
+    }
+    let file_size: u64 = log.iter().map(|fragment| fragment.len() as u64).sum();
+
+    // TODO: abstract out this spinner to not be part of the library
+    // Instead, add a callback trait for CLIs to implement
+    let multi = MultiProgress::with_draw_target(config.progress.draw_target());
+    let pb = multi.add(ProgressBar::new(file_size));
+    // console already leaves out colors for NO_COLOR, but only on unix
+    let template = if std::env::var_os("NO_COLOR").is_some() {
+        "{spinner} [{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} [{bytes_per_sec}] ({eta})"
+    } else {
+        "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} [{bytes_per_sec}] ({eta})"
+    };
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(template)?
+            .progress_chars("#>-"),
+    );
+    let spinner = multi.add(ProgressBar::new_spinner());
+
+    // Helper function to format timestamp as ISO-8601
+    let format_timestamp = |prefix: &GlogPrefix| -> String {
+        // Assume current year since glog doesn't include year.  Deterministic output assumes a
+        // leap year instead, so that Feb 29 is still a date
+        let year = if config.deterministic {
+            DETERMINISTIC_YEAR
+        } else {
+            chrono::Utc::now().year()
+        };
+
+        // Format as ISO-8601 with microsecond precision
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+            year,
+            prefix.month,
+            prefix.day,
+            prefix.hour,
+            prefix.minute,
+            prefix.second,
+            prefix.microsecond
+        )
+    };
+
+    let mut stack_trie = StackTrieNode::default();
+    let mut unknown_stack_trie = StackTrieNode::default();
+
+    let mut stats = Stats::default();
+    let _mod_count: FxHashMap<String, i32> = FxHashMap::default();
 
     let mut expected_rank: Option<Option<u32>> = None;
 
@@ -452,216 +2074,364 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
     let mut directory: FxIndexMap<Option<CompileId>, Vec<OutputFile>> = FxIndexMap::default();
 
     let mut metrics_index: CompilationMetricsIndex = FxIndexMap::default();
+    let mut frame_locals_index: FrameLocalsIndex = FxHashMap::default();
     let stack_index: RefCell<StackIndex> = RefCell::new(FxHashMap::default());
+    let intern_table: RefCell<InternTable> = RefCell::new(InternTable::default());
+    let sources =
+        (!config.source_roots.is_empty()).then(|| SourceSnippets::new(config.source_roots.clone()));
 
     let symbolic_shape_specialization_index: RefCell<SymbolicShapeSpecializationIndex> =
         RefCell::new(FxHashMap::default());
+    let mut specialization_index = SpecializationIndex::default();
+    let mut config_index = ConfigIndex::default();
     let guard_added_fast_index: RefCell<GuardAddedFastIndex> = RefCell::new(FxHashMap::default());
     let sym_expr_info_index: RefCell<SymExprInfoIndex> = RefCell::new(FxHashMap::default());
+    let pass_timings_index: RefCell<PassTimingsIndex> = RefCell::new(FxIndexMap::default());
+    let counters_index: RefCell<CountersIndex> = RefCell::new(Vec::new());
+    let memory_index: RefCell<MemoryIndex> = RefCell::new(FxIndexMap::default());
+    let aot_graphs_index: RefCell<AOTGraphsIndex> = RefCell::new(FxIndexMap::default());
+    let ddp_splits_index: RefCell<DDPSplitsIndex> = RefCell::new(FxIndexMap::default());
+    let graph_partitions_index: RefCell<GraphPartitionsIndex> = RefCell::new(FxIndexMap::default());
+    let graph_stats_index: RefCell<GraphStatsIndex> = RefCell::new(FxIndexMap::default());
+    let mut symbolic_expressions_index: SymbolicExpressionsIndex = FxIndexMap::default();
 
     // Store results in an output ParseOutput
     let mut output: ParseOutput = Vec::new();
 
-    // Store raw.jsonl content (without payloads)
-    let mut shortraw_content = String::new();
-
     let mut tt: TinyTemplate = TinyTemplate::new();
     tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
-    if config.export {
-        tt.add_template("index.html", TEMPLATE_EXPORT_INDEX)?;
-        tt.add_template(
-            "symbolic_guard_information.html",
-            TEMPLATE_SYMBOLIC_GUARD_INFO,
-        )?;
-    } else {
-        tt.add_template("index.html", TEMPLATE_INDEX)?;
-        tt.add_template("failures_and_restarts.html", TEMPLATE_FAILURES_AND_RESTARTS)?;
-        tt.add_template("dynamo_guards.html", TEMPLATE_DYNAMO_GUARDS)?;
-        tt.add_template("compilation_metrics.html", TEMPLATE_COMPILATION_METRICS)?;
-        tt.add_template(
-            "bwd_compilation_metrics.html",
-            TEMPLATE_BWD_COMPILATION_METRICS,
-        )?;
-        tt.add_template(
-            "aot_autograd_backward_compilation_metrics.html",
-            TEMPLATE_AOT_AUTOGRAD_BACKWARD_COMPILATION_METRICS,
-        )?;
-    }
-    tt.add_template("provenance_tracking.html", TEMPLATE_PROVENANCE_TRACKING)?;
+    add_templates(&mut tt, config)?;
 
     let mut unknown_fields: FxHashSet<String> = FxHashSet::default();
 
-    let mut output_count = 0;
+    let mut output_count = OutputCounter {
+        legacy: config.legacy_artifact_numbering,
+        blobs: config.dedup_payloads.then(FxHashSet::default),
+        max_artifact_bytes: config.max_artifact_bytes,
+        stream_dir: stream_dir.map(Path::to_path_buf),
+        ..Default::default()
+    };
 
     let mut breaks = RestartsAndFailuresContext {
         css: TEMPLATE_FAILURES_CSS,
         failures: Vec::new(),
+        groups: Vec::new(),
         qps: TEMPLATE_QUERY_PARAM_SCRIPT,
     };
 
     let mut export_failures: Vec<ExportFailure> = Vec::new();
-
-    // NB: Sometimes, the log output we get from Logarithm stutters with a blank line.
-    // Filter them out, they're never valid (a blank line in payload will still be \t)
-    let mut iter = reader
-        .lines()
-        .enumerate()
-        .filter_map(|(i, l)| match l {
-            // 1-indexed line numbers please
-            Ok(l) if !l.is_empty() => Some((i + 1, l)),
-            _ => None,
-        })
-        .peekable();
+    let mut data_dependent_specializations: Vec<DataDependentSpecialization> = Vec::new();
+    let mut exported_program: Option<String> = None;
+
+    let mut search_index = SearchIndexBuilder::default();
+    let mut envelope_stats: FxIndexMap<String, EnvelopeTypeStats> = FxIndexMap::default();
+    let mut parser_profile = ParserProfile::default();
+    // Bytes of the log by record type, for each compile id
+    let mut trace_size_by_compile_id: FxIndexMap<Option<CompileId>, FxIndexMap<String, u64>> =
+        FxIndexMap::default();
+    let mut code_size_index = CodeSizeIndex::default();
+    let mut distributed_info: Option<DistributedInfo> = None;
+    // Inductor's estimated runtime of each op of the graphs it compiled
+    let mut runtime_estimations: FxIndexMap<Option<CompileId>, Vec<OpRuntime>> =
+        FxIndexMap::default();
+    // The first output code of each compile id, as bytes of the log that are only decoded for
+    // compile ids with runtime estimations, and the kernels named in its provenance mappings, to
+    // link the estimated ops to
+    let mut output_codes: FxHashMap<Option<CompileId>, LazyPayload> = FxHashMap::default();
+    let mut provenance_kernels: FxHashMap<Option<CompileId>, Vec<String>> = FxHashMap::default();
 
     let default_parsers = default_parsers(&tt, config);
     let mut all_parsers: Vec<&Box<dyn StructuredLogParser>> = default_parsers.iter().collect();
     let mut chromium_events: Vec<serde_json::Value> = Vec::new();
+    let pass_timings_parser: Box<dyn StructuredLogParser> =
+        Box::new(crate::parsers::InductorPassTimingsParser {
+            pass_timings_index: &pass_timings_index,
+        });
+    let counters_parser: Box<dyn StructuredLogParser> = Box::new(crate::parsers::CountersParser {
+        counters_index: &counters_index,
+    });
+    let memory_planning_parser: Box<dyn StructuredLogParser> =
+        Box::new(crate::parsers::MemoryPlanningParser {
+            tt: &tt,
+            memory_index: &memory_index,
+            layout: config.directory_layout,
+        });
+    let aot_partition_parser: Box<dyn StructuredLogParser> =
+        Box::new(crate::parsers::AOTPartitionParser {
+            aot_graphs_index: &aot_graphs_index,
+        });
+    let ddp_splits_parser: Box<dyn StructuredLogParser> =
+        Box::new(crate::parsers::DDPSplitsParser {
+            ddp_splits_index: &ddp_splits_index,
+        });
+    let graph_partition_parser: Box<dyn StructuredLogParser> =
+        Box::new(crate::parsers::GraphPartitionParser {
+            graph_partitions_index: &graph_partitions_index,
+        });
+    let fx_graph_parser: Box<dyn StructuredLogParser> = Box::new(crate::parsers::FxGraphParser {
+        graph_stats_index: &graph_stats_index,
+        graph_dot: config.graph_dot,
+    });
+    if !config.export {
+        all_parsers.push(&pass_timings_parser);
+        all_parsers.push(&counters_parser);
+        all_parsers.push(&memory_planning_parser);
+        all_parsers.push(&aot_partition_parser);
+        all_parsers.push(&ddp_splits_parser);
+        all_parsers.push(&graph_partition_parser);
+        all_parsers.push(&fx_graph_parser);
+    }
     all_parsers.extend(config.custom_parsers.iter());
-
-    while let Some((lineno, line)) = iter.next() {
-        bytes_read += line.len() as u64;
-        pb.set_position(bytes_read);
-        spinner.set_message(format!("{}", stats));
-        //spinner.set_message(format!("{:?} {:?}", slowest_time, fastest_time));
-        let start = Instant::now();
-
-        let Some(caps) = re_glog.captures(&line) else {
-            multi.suspend(|| eprintln!("Failed to parse glog prefix on line {}", lineno));
-            stats.fail_glog += 1;
-            continue;
-        };
-
-        let end = start.elapsed();
-        if end < fastest_time {
-            fastest_time = end;
+    // Compilation metrics are parsed separately, once the other artifacts of the compile id exist
+    let mut parser_names: Vec<&str> = all_parsers.iter().map(|p| p.name()).collect();
+    parser_names.push("compilation_metrics");
+    // Export mode runs different parsers, so leave names it doesn't know alone
+    if !config.export {
+        for name in config
+            .disabled_parsers
+            .iter()
+            .chain(config.enabled_parsers.iter().flatten())
+        {
+            if !parser_names.contains(&name.as_str()) {
+                bail!(
+                    "Unknown parser {name}, expected one of: {}",
+                    parser_names.join(", ")
+                );
+            }
         }
-        if end > slowest_time {
-            slowest_time = end;
+    }
+    all_parsers.retain(|p| config.parser_enabled(p.name()));
+    let guards_enabled = config.parser_enabled("dynamo_guards");
+    let output_code_enabled = config.parser_enabled("inductor_output_code");
+    let artifacts_enabled = config.parser_enabled("artifact");
+
+    let mut corrupt_payloads: Vec<CorruptPayload> = Vec::new();
+    let mut thread_index = threads::ThreadIndex::default();
+    let mut span_index = otel::SpanIndex::default();
+    let mut guards_index: GuardsIndex = GuardsIndex::default();
+    // With `first_failure`, the compile id and metrics of the first compilation that failed
+    let mut first_failure: Option<(Option<CompileId>, CompilationMetricsMetadata)> = None;
+    // Line of the envelope that was cut off
+    let mut truncated_line: Option<usize> = None;
+    let mut report_truncation = |lineno: usize, stats: &mut Stats| {
+        multi.suspend(|| {
+            eprintln!("Log truncated, last envelope incomplete (line {lineno})");
+        });
+        stats.truncated_at_eof += 1;
+        truncated_line = Some(lineno);
+    };
+
+    // Helper function to safely insert keys and detect conflicts
+    let try_insert = |obj: &mut serde_json::Map<String, serde_json::Value>,
+                      key: &str,
+                      value: serde_json::Value,
+                      multi: &MultiProgress,
+                      stats: &mut Stats|
+     -> bool {
+        if obj.contains_key(key) {
+            multi.suspend(|| {
+                eprintln!("Key conflict: '{}' already exists in JSON payload, skipping raw.jsonl JSONL conversion", key);
+            });
+            stats.fail_key_conflict += 1;
+            false
+        } else {
+            obj.insert(key.to_string(), value);
+            true
         }
-        let payload = &line[caps.name("payload").unwrap().start()..];
-        let original_json_envelope = payload; // Store the original JSON envelope
+    };
 
-        // Helper function to safely insert keys and detect conflicts
-        let try_insert = |obj: &mut serde_json::Map<String, serde_json::Value>,
-                          key: &str,
-                          value: serde_json::Value,
-                          multi: &MultiProgress,
-                          stats: &mut Stats|
-         -> bool {
-            if obj.contains_key(key) {
-                multi.suspend(|| {
-                    eprintln!("Key conflict: '{}' already exists in JSON payload, skipping raw.jsonl JSONL conversion", key);
-                });
-                stats.fail_key_conflict += 1;
-                false
-            } else {
-                obj.insert(key.to_string(), value);
-                true
-            }
-        };
+    // Create cleanup lambda to handle raw.jsonl writing as JSONL
+    let write_to_shortraw = |raw_jsonl: &mut RawJsonl,
+                             prefix: &GlogPrefix,
+                             original_json_envelope: &str,
+                             payload_filename: Option<String>,
+                             multi: &MultiProgress,
+                             stats: &mut Stats| {
+        match serde_json::from_str::<serde_json::Value>(original_json_envelope) {
+            Ok(mut json_value) => {
+                if let Some(obj) = json_value.as_object_mut() {
+                    // Try to add all log fields, abort on any conflict
+                    let success = try_insert(
+                        obj,
+                        "timestamp",
+                        serde_json::Value::String(format_timestamp(prefix)),
+                        multi,
+                        stats,
+                    ) && try_insert(
+                        obj,
+                        "thread",
+                        serde_json::Value::from(prefix.thread),
+                        multi,
+                        stats,
+                    ) && try_insert(
+                        obj,
+                        "pathname",
+                        serde_json::Value::String(prefix.pathname.to_string()),
+                        multi,
+                        stats,
+                    ) && try_insert(
+                        obj,
+                        "lineno",
+                        serde_json::Value::from(prefix.line),
+                        multi,
+                        stats,
+                    );
+
+                    // Try to add payload filename if provided
+                    let success = if let Some(payload_file) = payload_filename {
+                        success
+                            && try_insert(
+                                obj,
+                                "payload_filename",
+                                serde_json::Value::String(payload_file),
+                                multi,
+                                stats,
+                            )
+                    } else {
+                        success
+                    };
 
-        // Create cleanup lambda to handle raw.jsonl writing as JSONL
-        let write_to_shortraw = |shortraw_content: &mut String,
-                                 payload_filename: Option<String>,
-                                 multi: &MultiProgress,
-                                 stats: &mut Stats| {
-            match serde_json::from_str::<serde_json::Value>(original_json_envelope) {
-                Ok(mut json_value) => {
-                    if let Some(obj) = json_value.as_object_mut() {
-                        // Try to add all log fields, abort on any conflict
-                        let success = try_insert(
-                            obj,
-                            "timestamp",
-                            serde_json::Value::String(format_timestamp(&caps)),
-                            multi,
-                            stats,
-                        ) && try_insert(
-                            obj,
-                            "thread",
-                            make_number_value(&caps, "thread"),
-                            multi,
-                            stats,
-                        ) && try_insert(
-                            obj,
-                            "pathname",
-                            make_string_value(&caps, "pathname"),
-                            multi,
-                            stats,
-                        ) && try_insert(
-                            obj,
-                            "lineno",
-                            make_number_value(&caps, "line"),
-                            multi,
-                            stats,
-                        );
-
-                        // Try to add payload filename if provided
-                        let success = if let Some(payload_file) = payload_filename {
-                            success
-                                && try_insert(
-                                    obj,
-                                    "payload_filename",
-                                    serde_json::Value::String(payload_file),
-                                    multi,
-                                    stats,
-                                )
-                        } else {
-                            success
-                        };
+                    if !success {
+                        // Drop line due to key conflict - don't write anything to maintain JSONL format
+                        return;
+                    }
 
-                        if !success {
-                            // Drop line due to key conflict - don't write anything to maintain JSONL format
-                            return;
-                        }
+                    if config.raw_jsonl_filenames {
+                        inline_filenames(&mut json_value, &intern_table.borrow());
+                    }
 
-                        // Output as JSONL
-                        match serde_json::to_string(&json_value) {
-                            Ok(jsonl_line) => {
-                                shortraw_content.push_str(&jsonl_line);
-                                shortraw_content.push('\n');
-                            }
-                            Err(e) => {
-                                multi.suspend(|| {
-                                    eprintln!("Failed to serialize JSON for raw.jsonl: {}", e);
-                                });
-                                stats.fail_json_serialization += 1;
-                                // Drop line to maintain JSONL format - don't write anything
-                            }
+                    // Output as JSONL
+                    match serde_json::to_string(&json_value) {
+                        Ok(jsonl_line) => {
+                            raw_jsonl.push_line(&jsonl_line);
+                        }
+                        Err(e) => {
+                            multi.suspend(|| {
+                                eprintln!("Failed to serialize JSON for raw.jsonl: {}", e);
+                            });
+                            stats.fail_json_serialization += 1;
+                            // Drop line to maintain JSONL format - don't write anything
                         }
-                    } else {
-                        // Not a JSON object, drop line to maintain JSONL format
-                        multi.suspend(|| {
-                            eprintln!(
-                                "JSON payload is not an object, dropping line from raw.jsonl"
-                            );
-                        });
-                        stats.fail_json += 1;
                     }
-                }
-                Err(e) => {
-                    // JSON parsing failed, drop line to maintain JSONL format
+                } else {
+                    // Not a JSON object, drop line to maintain JSONL format
                     multi.suspend(|| {
-                        eprintln!("Failed to parse JSON envelope for raw.jsonl: {}", e);
+                        eprintln!("JSON payload is not an object, dropping line from raw.jsonl");
                     });
                     stats.fail_json += 1;
                 }
             }
-        };
+            Err(e) => {
+                // JSON parsing failed, drop line to maintain JSONL format
+                multi.suspend(|| {
+                    eprintln!("Failed to parse JSON envelope for raw.jsonl: {}", e);
+                });
+                stats.fail_json += 1;
+            }
+        }
+    };
+
+    let guard_pages = GuardPages {
+        tt: &tt,
+        sym_expr_info_index: &sym_expr_info_index,
+        intern_table: &intern_table,
+        sources: sources.as_ref(),
+    };
 
-        let e = match serde_json::from_str::<Envelope>(payload) {
-            Ok(r) => r,
-            Err(err) => {
+    // Lines are scanned straight out of the mapped file, without copying them
+    let mut records = RecordReader::new(log_lines(&log), config);
+    while let Some(item) = records.next_item(&mut stats) {
+        if first_failure.is_some() {
+            break;
+        }
+        // Formatting the stats for every line is surprisingly expensive on large logs
+        if item.lineno() % PROGRESS_INTERVAL == 0 {
+            pb.set_position(records.bytes_read);
+            spinner.set_message(format!("{}", stats));
+        }
+
+        let record = match item {
+            LogItem::Record(record) => record,
+            LogItem::OrphanPayload { lineno, payload } => {
+                // The rest of a payload whose envelope was lost, e.g. in a crash
+                let corrupt = CorruptPayload {
+                    line: lineno,
+                    thread: None,
+                    reason: "payload lines without an envelope".to_string(),
+                    expected_md5: None,
+                    actual_md5: payload.md5_hex(),
+                    num_lines: payload.num_lines(),
+                    interleaved_lines: Vec::new(),
+                    url: String::new(),
+                };
+                quarantine_payload(
+                    corrupt,
+                    &payload,
+                    &mut output,
+                    &mut corrupt_payloads,
+                    &multi,
+                    &mut stats,
+                );
+                continue;
+            }
+            LogItem::NotGlog { lineno } => {
+                multi.suspend(|| eprintln!("Failed to parse glog prefix on line {}", lineno));
+                stats.fail_glog += 1;
+                continue;
+            }
+            LogItem::BadEnvelope { lineno, at_eof, .. } if at_eof => {
+                report_truncation(lineno, &mut stats);
+                continue;
+            }
+            LogItem::BadEnvelope {
+                prefix,
+                json,
+                error,
+                ..
+            } => {
                 multi.suspend(|| {
-                    eprintln!("Failed to parse metadata JSON: {}\n{:?}", payload, err);
+                    eprintln!("Failed to parse metadata JSON: {}\n{:?}", json, error);
                 });
                 stats.fail_json += 1;
-                write_to_shortraw(&mut shortraw_content, None, &multi, &mut stats);
+                write_to_shortraw(&mut raw_jsonl, &prefix, &json, None, &multi, &mut stats);
                 continue;
             }
         };
+        let Record {
+            lineno,
+            prefix,
+            json,
+            line_bytes,
+            envelope: e,
+            payload,
+            check,
+            interleaved_lines,
+        } = *record;
 
         stats.unknown += e._other.len() as u64;
 
+        let record_type = envelope_type(&e);
+        let record_bytes = line_bytes;
+        let record_stats = envelope_stats
+            .entry(record_type.clone())
+            .or_insert_with_key(|t| EnvelopeTypeStats {
+                envelope_type: t.clone(),
+                ..Default::default()
+            });
+        record_stats.count += 1;
+        record_stats.log_bytes += record_bytes;
+        // Records outside of compilation, e.g. some chromium events, have an empty compile id
+        let compile_id_bytes = trace_size_by_compile_id
+            .entry(
+                e.compile_id
+                    .clone()
+                    .filter(|c| c.compiled_autograd_id.is_some() || c.frame_id.is_some()),
+            )
+            .or_default()
+            .entry(record_type)
+            .or_default();
+        *compile_id_bytes += record_bytes;
+
         for k in e._other.keys() {
             unknown_fields.insert(k.clone());
             if config.verbose {
@@ -670,35 +2440,60 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         }
 
         if let Some((s, i)) = e.str {
-            let mut intern_table = INTERN_TABLE.lock().unwrap();
-            intern_table.insert(i, s);
+            intern_table.borrow_mut().insert(i, s);
             continue;
         };
 
-        let mut payload = String::new();
         if let Some(ref expect) = e.has_payload {
-            let mut first = true;
-            while let Some((_payload_lineno, payload_line)) =
-                iter.next_if(|(_, l)| l.starts_with('\t'))
+            record_stats.payload_bytes += payload.len() as u64;
+            record_stats.log_bytes += payload.raw_len() as u64;
+            *compile_id_bytes += payload.raw_len() as u64;
+            let mut valid = true;
+            // A payload that was cut off is still parsed like other payloads that don't match, as
+            // far as it goes
+            if let PayloadCheck::Mismatch {
+                expected_md5,
+                at_eof,
+            } = check
             {
-                // Careful! Distinguish between missing EOL and not
-                if !first {
-                    payload.push('\n');
-                }
-                first = false;
-                payload.push_str(&payload_line[1..]);
-            }
-            let mut hasher = Md5::new();
-            hasher.update(&payload);
-            let hash = hasher.finalize();
-            let mut expect_buf = [0u8; 16];
-            if base16ct::lower::decode(expect, &mut expect_buf).is_ok() {
-                if expect_buf != hash[..] {
-                    // TODO: error log
+                // TODO: error log
+                if at_eof {
+                    report_truncation(lineno, &mut stats);
+                } else {
                     stats.fail_payload_md5 += 1;
                 }
-            } else {
-                stats.fail_payload_md5 += 1;
+                valid = false;
+                if config.recover_payloads {
+                    let corrupt = CorruptPayload {
+                        line: lineno,
+                        thread: Some(prefix.thread),
+                        reason: if expected_md5.is_some() {
+                            "payload doesn't match its md5, it may be truncated".to_string()
+                        } else {
+                            format!("envelope has an invalid md5 {expect}")
+                        },
+                        expected_md5: expected_md5.map(|_| expect.clone()),
+                        actual_md5: payload.md5_hex(),
+                        num_lines: payload.num_lines(),
+                        interleaved_lines: interleaved_lines.clone(),
+                        url: String::new(),
+                    };
+                    quarantine_payload(
+                        corrupt,
+                        &payload,
+                        &mut output,
+                        &mut corrupt_payloads,
+                        &multi,
+                        &mut stats,
+                    );
+                }
+            }
+            if valid && !interleaved_lines.is_empty() {
+                stats.recovered_payloads += 1;
+            }
+            if !valid && config.recover_payloads {
+                write_to_shortraw(&mut raw_jsonl, &prefix, &json, None, &multi, &mut stats);
+                continue;
             }
         }
 
@@ -706,7 +2501,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             Some(rank) => {
                 if rank != e.rank {
                     stats.other_rank += 1;
-                    write_to_shortraw(&mut shortraw_content, None, &multi, &mut stats);
+                    write_to_shortraw(&mut raw_jsonl, &prefix, &json, None, &multi, &mut stats);
                     continue;
                 }
             }
@@ -733,8 +2528,14 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             }
         }
 
+        let known_compile_id = compile_id_entry
+            .clone()
+            .filter(|c| c.compiled_autograd_id.is_some() || c.frame_id.is_some());
+        thread_index.record(&prefix, &known_compile_id);
+        span_index.record(&prefix, &known_compile_id);
+
         // TODO: output should be able to generate this without explicitly creating
-        let compile_directory = directory.entry(compile_id_entry).or_default();
+        let compile_directory = directory.entry(compile_id_entry.clone()).or_default();
 
         let mut parser_payload_filename = ParserResult::NoPayload;
         for parser in &all_parsers {
@@ -743,11 +2544,15 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 parser,
                 &e,
                 &payload,
-                &mut output_count,
-                &mut output,
-                compile_directory,
-                &multi,
-                &mut stats,
+                &mut ParserContext {
+                    output: &mut output,
+                    output_count: &mut output_count,
+                    compile_directory,
+                    multi: &multi,
+                    stats: &mut stats,
+                    profile: &mut parser_profile,
+                    layout: config.directory_layout,
+                },
             );
             // Take the last PayloadFilename entry as per the requirement
             if matches!(result, ParserResult::PayloadFilename(_)) {
@@ -757,49 +2562,73 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
         if let Some(ref m) = e.compilation_metrics {
             let copied_directory = compile_directory.clone();
-            let compile_id_dir: PathBuf = e
-                .compile_id
-                .as_ref()
-                .map_or(format!("unknown_{lineno}"), |cid| cid.as_directory_name())
-                .into();
+            let compile_id_dir = config
+                .directory_layout
+                .compile_id_dir(e.compile_id.as_ref(), lineno);
             let parser: Box<dyn StructuredLogParser> =
                 Box::new(crate::parsers::CompilationMetricsParser {
                     tt: &tt,
+                    layout: config.directory_layout,
                     stack_index: &stack_index,
                     symbolic_shape_specialization_index: &symbolic_shape_specialization_index,
                     guard_added_fast_index: &guard_added_fast_index,
+                    intern_table: &intern_table,
                     output_files: &copied_directory,
                     compile_id_dir: &compile_id_dir,
+                    sources: sources.as_ref(),
+                    frame_locals: frame_locals_index.get(&e.compile_id),
                 });
-            let result = run_parser(
-                lineno,
-                &parser,
-                &e,
-                &payload,
-                &mut output_count,
-                &mut output,
-                compile_directory,
-                &multi,
-                &mut stats,
-            );
-            // Take the last PayloadFilename entry as per the requirement
-            if matches!(result, ParserResult::PayloadFilename(_)) {
-                parser_payload_filename = result;
-            }
+            let metrics_filename = if config.parser_enabled(parser.name()) {
+                let result = run_parser(
+                    lineno,
+                    &parser,
+                    &e,
+                    &payload,
+                    &mut ParserContext {
+                        output: &mut output,
+                        output_count: &mut output_count,
+                        compile_directory,
+                        multi: &multi,
+                        stats: &mut stats,
+                        profile: &mut parser_profile,
+                        layout: config.directory_layout,
+                    },
+                );
+                // Take the last PayloadFilename entry as per the requirement
+                if matches!(result, ParserResult::PayloadFilename(_)) {
+                    parser_payload_filename = result;
+                }
 
-            // compilation metrics is always the last output, since it just ran
-            let metrics_filename = format!(
-                "compilation_metrics_{}.html",
-                (output_count - 1).to_string(),
-            );
-            let id = e.compile_id.clone().map_or("(unknown) ".to_string(), |c| {
-                format!(
-                    "<a href='{}/{}'>{cid}</a> ",
-                    compile_id_dir.display(),
-                    metrics_filename,
-                    cid = c,
-                )
-            });
+                // compilation metrics is always the last output, since it just ran
+                compile_directory
+                    .last()
+                    .and_then(|f| Path::new(&f.url).file_name())
+                    .map(|f| f.to_string_lossy().to_string())
+            } else {
+                None
+            };
+            let id =
+                e.compile_id.clone().map_or(
+                    "(unknown) ".to_string(),
+                    |c| match &metrics_filename {
+                        Some(metrics_filename) => format!(
+                            "<a href='{}/{}'>{cid}</a> ",
+                            compile_id_dir.display(),
+                            metrics_filename,
+                            cid = c,
+                        ),
+                        None => format!("{c} "),
+                    },
+                );
+            let step = config
+                .steps
+                .as_ref()
+                .zip(m.start_time)
+                .and_then(|(steps, t)| steps.step_at(t));
+            let id = match step {
+                Some(step) => format!("{id}<span class='step'>step {step}</span> "),
+                None => id,
+            };
             if let Some(rr) = m.restart_reasons.as_ref() {
                 for restart in rr {
                     breaks.failures.push((
@@ -835,13 +2664,127 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     c.attempt = Some(0);
                 }
             }
-            metrics_index.entry(cid).or_default().push(m.clone());
+            if config.first_failure && m.fail_type.is_some() {
+                first_failure = Some((cid.clone(), m.clone()));
+            }
+            metrics_index.entry(cid).or_default().push(m.clone());
+        }
+
+        // Shared by the search index and the guard diffs
+        let guards = e
+            .dynamo_guards
+            .as_ref()
+            .and_then(|_| serde_json::from_str::<Vec<DynamoGuard>>(payload.get()).ok());
+        // The passes over the payloads of a parser are skipped along with it
+        search_index.record(
+            &e,
+            guards.as_deref().filter(|_| guards_enabled),
+            &compile_id_entry,
+        );
+        if e.inductor_output_code.is_some() && output_code_enabled {
+            search_index.record_kernels(&payload, &compile_id_entry);
+        }
+        if let Some(artifact) = e.artifact.as_ref().filter(|_| artifacts_enabled) {
+            record_config(
+                &mut config_index,
+                &compile_id_entry,
+                &artifact.name,
+                &payload,
+            );
+        }
+        let code_size_enabled = if e.inductor_output_code.is_some() {
+            output_code_enabled
+        } else {
+            artifacts_enabled
+        };
+        if code_size_enabled {
+            code_size_index.record(&e, &payload, &compile_id_entry);
+        }
+
+        if e.artifact
+            .as_ref()
+            .is_some_and(|a| a.name == "inductor_runtime_and_tensor_meta")
+        {
+            #[derive(serde::Deserialize)]
+            struct RuntimeJson {
+                ops: Vec<OpRuntime>,
+            }
+            if let Ok(json) = serde_json::from_str::<RuntimeJson>(payload.get()) {
+                runtime_estimations
+                    .entry(e.compile_id.clone())
+                    .or_default()
+                    .extend(json.ops);
+            }
+        }
+        if e.inductor_output_code.is_some() {
+            output_codes
+                .entry(e.compile_id.clone())
+                .or_insert_with(|| payload.without_decoded());
+        }
+        if e.artifact
+            .as_ref()
+            .is_some_and(|a| a.name == "inductor_provenance_tracking_node_mappings")
+        {
+            if let Ok(mappings) = serde_json::from_str::<Value>(payload.get()) {
+                if let Some(kernels) = mappings["cppCodeToPost"].as_object() {
+                    // Drop debug handles, e.g. triton_poi_fused_mul_1:2
+                    provenance_kernels.insert(
+                        e.compile_id.clone(),
+                        kernels
+                            .keys()
+                            .map(|k| k.split(':').next().unwrap_or(k).to_string())
+                            .collect(),
+                    );
+                }
+            }
+        }
+
+        if distributed_info.is_none()
+            && e.artifact
+                .as_ref()
+                .is_some_and(|a| a.name == "distributed_info" && a.encoding == "json")
+        {
+            if let Ok(mut info) = serde_json::from_str::<DistributedInfo>(payload.get()) {
+                info.rank = info.rank.or(e.rank);
+                distributed_info = Some(info);
+            }
+        }
+
+        // Symbolic expressions are indexed in every mode: export uses them to explain guard
+        // failures, and otherwise they get a symbolic_expressions.html page per compile id
+        let sym_expr_info = e.expression_created.clone().or_else(|| {
+            e.create_unbacked_symbol
+                .as_ref()
+                .map(|unbacked_symbol| SymExprInfoMetadata {
+                    result: unbacked_symbol.symbol.clone(),
+                    result_id: unbacked_symbol.node_id,
+                    user_stack: unbacked_symbol.user_stack.clone(),
+                    stack: unbacked_symbol.stack.clone(),
+                    ..Default::default()
+                })
+        });
+        if let Some(id) = sym_expr_info.as_ref().and_then(|info| info.result_id) {
+            sym_expr_info_index
+                .borrow_mut()
+                .insert(id, sym_expr_info.unwrap());
+            symbolic_expressions_index
+                .entry(compile_id_entry.clone())
+                .or_insert_with(|| SymbolicExpressions {
+                    lineno,
+                    node_ids: Vec::new(),
+                })
+                .node_ids
+                .push(id);
         }
 
         if config.export {
+            if e.exported_program.is_some() {
+                exported_program = Some(payload.get().to_string());
+            }
+
             if let Some(ref guard) = e.guard_added {
                 if guard.prefix.as_deref() != Some("eval") {
-                    write_to_shortraw(&mut shortraw_content, None, &multi, &mut stats);
+                    write_to_shortraw(&mut raw_jsonl, &prefix, &json, None, &multi, &mut stats);
                     continue;
                 }
                 let failure_type = "Guard Evaluated";
@@ -852,21 +2795,21 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     guard.expr.clone().unwrap(),
                 );
 
-                handle_guard(
-                    failure_type,
-                    &reason,
+                let url = guard_pages.write(
                     lineno,
                     &e,
                     &payload,
-                    &mut output_count,
-                    &mut output,
-                    compile_directory,
-                    &multi,
-                    &mut stats,
-                    &tt,
-                    &sym_expr_info_index,
-                    &mut export_failures,
+                    &mut ParserContext {
+                        output: &mut output,
+                        output_count: &mut output_count,
+                        compile_directory,
+                        multi: &multi,
+                        stats: &mut stats,
+                        profile: &mut parser_profile,
+                        layout: config.directory_layout,
+                    },
                 );
+                export_failures.push(guard_pages.export_failure(failure_type, &reason, &e, &url));
             }
 
             if let Some(ref guard) = e.propagate_real_tensors_provenance {
@@ -881,29 +2824,30 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     guard.result.clone().unwrap()
                 );
 
-                handle_guard(
-                    failure_type,
-                    &reason,
+                let url = guard_pages.write(
                     lineno,
                     &e,
                     &payload,
-                    &mut output_count,
-                    &mut output,
-                    compile_directory,
-                    &multi,
-                    &mut stats,
-                    &tt,
-                    &sym_expr_info_index,
-                    &mut export_failures,
+                    &mut ParserContext {
+                        output: &mut output,
+                        output_count: &mut output_count,
+                        compile_directory,
+                        multi: &multi,
+                        stats: &mut stats,
+                        profile: &mut parser_profile,
+                        layout: config.directory_layout,
+                    },
                 );
+                export_failures.push(guard_pages.export_failure(failure_type, &reason, &e, &url));
             }
 
             if let Some(fake_kernel) = e.missing_fake_kernel {
                 let failure_type = "Missing Fake Kernel";
 
+                let op = fake_kernel.op.unwrap();
                 let reason = format!(
                     "<code>torch.ops.{}</code> is missing a fake kernel implementation",
-                    fake_kernel.op.unwrap()
+                    op
                 );
 
                 let additional_info = "Please refer to <a href='https://docs.google.com/document/d/1_W62p8WJOQQUzPsJYa7s701JXt0qf2OfLub2sbkHOaU/edit#heading=h.ahugy69p2jmz'>this doc</a> for more detailed instructions on how to write a fake kernel.";
@@ -912,17 +2856,21 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     failure_type: failure_type.to_string(),
                     reason: reason,
                     additional_info: additional_info.to_string(),
+                    op: Some(op),
+                    expr: None,
+                    suggestions: Vec::new(),
                 });
             }
 
             if let Some(fake_kernel) = e.mismatched_fake_kernel {
                 let failure_type = "Mismatched Fake Kernel";
+                let op = fake_kernel.op.unwrap();
 
                 let reason = format!(
                     "<code>torch.ops.{}</code> has a fake kernel implementation,
                     but it has incorrect behavior, based on the real kernel.<br>
                     The reason for the mismatch is: {}",
-                    fake_kernel.op.unwrap(),
+                    op,
                     fake_kernel.reason.unwrap(),
                 );
 
@@ -932,27 +2880,37 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     failure_type: failure_type.to_string(),
                     reason: reason,
                     additional_info: additional_info.to_string(),
+                    op: Some(op),
+                    expr: None,
+                    suggestions: Vec::new(),
                 });
             }
-
-            if let Some(sym_expr_info) = e.expression_created {
-                sym_expr_info_index
-                    .borrow_mut()
-                    .insert(sym_expr_info.result_id.unwrap(), sym_expr_info);
-            }
-
-            if let Some(unbacked_symbol) = e.create_unbacked_symbol {
-                sym_expr_info_index.borrow_mut().insert(
-                    unbacked_symbol.node_id.unwrap(),
-                    SymExprInfoMetadata {
-                        result: unbacked_symbol.symbol.clone(),
-                        result_id: unbacked_symbol.node_id.clone(),
-                        user_stack: unbacked_symbol.user_stack.clone(),
-                        stack: unbacked_symbol.stack.clone(),
-                        ..Default::default()
-                    },
-                );
-            }
+        } else if let Some(ref guard) = e.propagate_real_tensors_provenance {
+            // Draft export's asserts also slip into eager compiles, e.g. with
+            // propagate_real_tensors set, where they would otherwise go unnoticed
+            let url = guard_pages.write(
+                lineno,
+                &e,
+                &payload,
+                &mut ParserContext {
+                    output: &mut output,
+                    output_count: &mut output_count,
+                    compile_directory,
+                    multi: &multi,
+                    stats: &mut stats,
+                    profile: &mut parser_profile,
+                    layout: config.directory_layout,
+                },
+            );
+            data_dependent_specializations.push(DataDependentSpecialization {
+                compile_id: e
+                    .compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                url,
+                expr: guard.expr.clone().unwrap_or_default(),
+                result: guard.result.clone().unwrap_or_default(),
+            });
         }
 
         if let Some(stack) = e.stack {
@@ -960,10 +2918,35 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         }
 
         if let Some(_) = e.chromium_event {
-            chromium_events.push(serde_json::from_str(&payload)?);
+            match serde_json::from_str(payload.get()) {
+                Ok(event) => {
+                    span_index.calibrate(&prefix, &event);
+                    chromium_events.push(event);
+                }
+                Err(err) => {
+                    multi.suspend(|| eprintln!("Malformed chromium event: {err}"));
+                    stats.chromium_events_dropped += 1;
+                }
+            }
+        }
+
+        // For diffing the guards of recompiles against the compile before
+        if let Some(guards) = guards {
+            guards_index
+                .entry(compile_id_entry.clone())
+                .or_default()
+                .extend(guards.into_iter().map(|g| g.code));
+        }
+
+        // For the frame locals of guards that fail later
+        for guard in [&e.guard_added, &e.propagate_real_tensors_provenance] {
+            if let Some(locals) = guard.as_ref().and_then(|g| g.frame_locals.as_ref()) {
+                index_frame_locals(&mut frame_locals_index, &e.compile_id, locals);
+            }
         }
 
         if let Some(specialization) = e.symbolic_shape_specialization {
+            specialization_index.record(&e.compile_id, &specialization);
             symbolic_shape_specialization_index
                 .borrow_mut()
                 .entry(e.compile_id.clone())
@@ -980,7 +2963,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
         if let Some(m) = e.dynamo_start {
             if let Some(mut stack) = m.stack {
-                maybe_remove_convert_frame_suffixes(&mut stack);
+                maybe_remove_convert_frame_suffixes(&mut stack, &intern_table.borrow());
                 stack_index
                     .borrow_mut()
                     .insert(e.compile_id.clone(), stack.clone());
@@ -997,7 +2980,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     if !payload.is_empty() && e.chromium_event.is_none() {
                         let hash_str = expect;
                         let payload_path = PathBuf::from(format!("payloads/{}.txt", hash_str));
-                        output.push((payload_path, payload.clone()));
+                        output.push((payload_path, payload.get().to_string()));
                         Some(format!("payloads/{}.txt", hash_str))
                     } else {
                         None
@@ -1011,7 +2994,9 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         // Write to raw.jsonl with optional payload filename, but skip chromium events
         if e.chromium_event.is_none() {
             write_to_shortraw(
-                &mut shortraw_content,
+                &mut raw_jsonl,
+                &prefix,
+                &json,
                 final_payload_filename,
                 &multi,
                 &mut stats,
@@ -1019,107 +3004,1045 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         }
     }
 
-    if config.export {
-        let num_failures = export_failures.len();
+    if config.summary_only {
+        let mut compile_summary = CompileSummary::new(&metrics_index, &directory);
+        compile_summary.config = run_config(&config_index);
+        let compiles = if config.compare_runs {
+            compare_runs::run_compiles(
+                &metrics_index,
+                &stack_index.borrow(),
+                &intern_table.borrow(),
+                &directory,
+            )
+        } else {
+            Vec::new()
+        };
+        let parse_summary = rank_parse_summary(
+            &directory,
+            &output,
+            stats,
+            compile_summary,
+            chromium_events.len(),
+            compiles,
+        );
+        return Ok((Vec::new(), parse_summary));
+    }
+
+    output.push((
+        PathBuf::from("theme.css"),
+        theme_css(config.theme, &config.custom_css),
+    ));
+    let mut envelope_stats: Vec<EnvelopeTypeStats> = envelope_stats.into_values().collect();
+    envelope_stats.sort_by(|a, b| {
+        (b.payload_bytes, b.count)
+            .cmp(&(a.payload_bytes, a.count))
+            .then_with(|| a.envelope_type.cmp(&b.envelope_type))
+    });
+    if !corrupt_payloads.is_empty() {
+        output.push((
+            PathBuf::from("corrupt_payloads/index.json"),
+            serde_json::to_string_pretty(&corrupt_payloads)?,
+        ));
+    }
+    output.push((
+        PathBuf::from("envelope_stats.json"),
+        serde_json::to_string_pretty(&envelope_stats)?,
+    ));
+    let completeness_hints = completeness::completeness_hints(&envelope_stats, config);
+    // Timings differ between runs
+    let parser_timings = if config.deterministic {
+        Vec::new()
+    } else {
+        parser_profile.timings()
+    };
+    if !config.deterministic {
+        output.push((
+            PathBuf::from("parser_profile.json"),
+            serde_json::to_string_pretty(&parser_timings)?,
+        ));
+    }
+
+    if config.export {
+        let exported_program_url = directory
+            .values()
+            .flatten()
+            .filter(|output_file| output_file.url.contains("exported_program"))
+            .max_by_key(|output_file| output_file.url.ends_with(".html"))
+            .map(|output_file| output_file.url.clone());
+        if let (Some(program), Some(url)) = (&exported_program, &exported_program_url) {
+            link_export_failures_to_program(&mut export_failures, program, url);
+        }
+
+        let num_failures = export_failures.len();
+        let export_failure_groups = group_export_failures(&export_failures);
+        output.push((
+            PathBuf::from("export_failures.json"),
+            serde_json::to_string_pretty(&export_failures_json(&export_failure_groups))?,
+        ));
+
+        let parse_summary = rank_parse_summary(
+            &directory,
+            &output,
+            stats.clone(),
+            CompileSummary::new(&metrics_index, &directory),
+            chromium_events.len(),
+            if config.compare_runs {
+                compare_runs::run_compiles(
+                    &metrics_index,
+                    &stack_index.borrow(),
+                    &intern_table.borrow(),
+                    &directory,
+                )
+            } else {
+                Vec::new()
+            },
+        );
+        let index_context = ExportIndexContext {
+            css: EXPORT_CSS,
+            javascript: JAVASCRIPT,
+            custom_header_html: config.custom_header_html.clone(),
+            directory: directory
+                .drain(..)
+                .map(|(x, y)| (x.map_or("(unknown)".to_string(), |e| e.to_string()), y))
+                .collect(),
+            groups: export_failure_groups,
+            failures: export_failures,
+            num_failures: num_failures,
+            success: num_failures == 0,
+            exported_program_url: exported_program_url.unwrap_or("".to_string()),
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        };
+
+        output.push((
+            PathBuf::from("index.html"),
+            tt.render("index.html", &index_context)?,
+        ));
+
+        raw_jsonl.discard();
+        return Ok((output, parse_summary));
+    }
+
+    output.push((
+        PathBuf::from("trace_size.html"),
+        tt.render(
+            "trace_size.html",
+            &trace_size_context(&envelope_stats, &trace_size_by_compile_id),
+        )?,
+    ));
+
+    let layout = config.directory_layout;
+    // Rendered after parsing, since these pages need graphs from several log entries
+    let mut compile_id_pages: Vec<(Option<CompileId>, (PathBuf, String))> = Vec::new();
+    for (compile_id, graphs) in aot_graphs_index.borrow().iter() {
+        if let Some(page) = crate::parsers::render_aot_partition(&tt, layout, compile_id, graphs)? {
+            compile_id_pages.push((compile_id.clone(), page));
+        }
+    }
+    for (compile_id, splits) in ddp_splits_index.borrow().iter() {
+        if let Some(page) = crate::parsers::render_ddp_splits(&tt, layout, compile_id, splits)? {
+            compile_id_pages.push((compile_id.clone(), page));
+        }
+    }
+    for (compile_id, expressions) in &symbolic_expressions_index {
+        compile_id_pages.push((
+            compile_id.clone(),
+            crate::parsers::render_symbolic_expressions(
+                &tt,
+                layout,
+                compile_id,
+                expressions,
+                &sym_expr_info_index.borrow(),
+                &intern_table.borrow(),
+            )?,
+        ));
+    }
+    let compiled_autograd_links =
+        correlate_compiled_autograd(&stack_index.borrow(), directory.keys());
+    // The link to `cid` from a page of `from`
+    let compile_id_url = |from: &CompileId, cid: &CompileId| -> String {
+        let root = layout.root_link(Some(from));
+        directory
+            .get(&Some(cid.clone()))
+            .and_then(|files| {
+                files
+                    .iter()
+                    .find(|f| f.name.contains("compilation_metrics"))
+                    .or(files.first())
+            })
+            .map_or(format!("{root}index.html#{cid}"), |f| {
+                format!("{root}{}", f.url)
+            })
+    };
+    // Both sides of a link get a page pointing at the other
+    let mut related: FxIndexMap<CompileId, (bool, Vec<RelatedCompileId>)> = FxIndexMap::default();
+    for (ca_cid, forward_cids) in &compiled_autograd_links {
+        for forward_cid in forward_cids {
+            related
+                .entry(ca_cid.clone())
+                .or_insert((true, Vec::new()))
+                .1
+                .push(RelatedCompileId {
+                    compile_id: forward_cid.to_string(),
+                    url: compile_id_url(ca_cid, forward_cid),
+                });
+            related
+                .entry(forward_cid.clone())
+                .or_insert((false, Vec::new()))
+                .1
+                .push(RelatedCompileId {
+                    compile_id: ca_cid.to_string(),
+                    url: compile_id_url(forward_cid, ca_cid),
+                });
+        }
+    }
+    for (cid, (is_compiled_autograd, related)) in related {
+        let html = tt.render(
+            "compiled_autograd.html",
+            &CompiledAutogradContext {
+                root: layout.root_link(Some(&cid)),
+                css: TEMPLATE_FAILURES_CSS,
+                compile_id: cid.to_string(),
+                is_compiled_autograd,
+                related,
+                qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            },
+        )?;
+        let path = PathBuf::from(layout.directory_name(&cid)).join("compiled_autograd.html");
+        compile_id_pages.push((Some(cid), (path, html)));
+    }
+    let compile_steps = config
+        .steps
+        .as_ref()
+        .map(|steps| steps.compile_steps(&metrics_index))
+        .unwrap_or_default();
+    chromium::repair_chromium_events(&mut chromium_events, &mut stats);
+    let mut chromium_events_by_compile_id =
+        chromium::chromium_events_by_compile_id(&chromium_events);
+    for (compile_id, compile_directory) in directory.iter_mut() {
+        let Some(compile_id) = compile_id else {
+            continue;
+        };
+        // The events refer to compile ids as PyTorch prints them, i.e. without brackets
+        let name = compile_id.to_string();
+        let name = name.trim_start_matches('[').trim_end_matches(']');
+        if let Some(events) = chromium_events_by_compile_id.swap_remove(name) {
+            add_file_output(
+                PathBuf::from(layout.directory_name(compile_id)).join("chromium_events.json"),
+                serde_json::to_string_pretty(&events)?,
+                &mut output,
+                compile_directory,
+                &mut output_count,
+                None,
+            );
+        }
+    }
+    for (compile_id, ops) in &runtime_estimations {
+        let Some(cid) = compile_id else {
+            continue;
+        };
+        let compile_directory = directory.entry(compile_id.clone()).or_default();
+        let output_code_url = compile_directory
+            .iter()
+            .find(|f| {
+                Path::new(&f.name)
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with("inductor_output_code"))
+            })
+            .map(|f| format!("{}{}", layout.root_link(Some(cid)), f.url));
+        let output_code = output_codes
+            .get(compile_id)
+            .map(|code| code.get())
+            .zip(output_code_url.as_deref());
+        let html = tt.render(
+            "runtime_estimation.html",
+            &RuntimeEstimationContext {
+                root: layout.root_link(Some(cid)),
+                css: TEMPLATE_FAILURES_CSS,
+                compile_id: cid.to_string(),
+                rows: runtime_estimation::runtime_estimation_rows(
+                    ops,
+                    output_code,
+                    provenance_kernels
+                        .get(compile_id)
+                        .map_or(&[], |k| k.as_slice()),
+                ),
+                qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            },
+        )?;
+        add_file_output(
+            output_count.unique_filename(
+                PathBuf::from(layout.directory_name(cid)).join("runtime_estimation.html"),
+            ),
+            html,
+            &mut output,
+            compile_directory,
+            &mut output_count,
+            None,
+        );
+    }
+    for (cid, diff) in guard_diffs(&guards_index) {
+        // Where all the guards of the previous compile are
+        let previous_guards_url = directory
+            .iter()
+            .find(|(c, _)| {
+                c.as_ref()
+                    .is_some_and(|c| c.to_string() == diff.previous_compile_id)
+            })
+            .and_then(|(_, files)| {
+                files.iter().find(|f| {
+                    Path::new(&f.url)
+                        .file_name()
+                        .is_some_and(|n| n.to_string_lossy().starts_with("dynamo_guards"))
+                })
+            })
+            .map(|f| format!("{}{}", layout.root_link(Some(&cid)), f.url));
+        let html = tt.render(
+            "guard_diff.html",
+            &GuardDiffContext {
+                root: layout.root_link(Some(&cid)),
+                css: TEMPLATE_FAILURES_CSS,
+                diff,
+                previous_guards_url,
+                qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            },
+        )?;
+        add_file_output(
+            PathBuf::from(layout.directory_name(&cid)).join("guard_diff.html"),
+            html,
+            &mut output,
+            directory.entry(Some(cid)).or_default(),
+            &mut output_count,
+            None,
+        );
+    }
+    let mut previous_config: Option<&std::collections::BTreeMap<String, String>> = None;
+    for (cid, config) in &config_index {
+        let (Some(cid), false) = (cid, config.is_empty()) else {
+            continue;
+        };
+        let options = config
+            .iter()
+            .map(|(key, value)| ConfigOption {
+                key: key.clone(),
+                value: value.clone(),
+                changed: previous_config.is_some_and(|p| p.get(key) != Some(value)),
+            })
+            .collect();
+        previous_config = Some(config);
+        let html = tt.render(
+            "inductor_config.html",
+            &InductorConfigContext {
+                root: layout.root_link(Some(cid)),
+                css: TEMPLATE_FAILURES_CSS,
+                compile_id: cid.to_string(),
+                options,
+                qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            },
+        )?;
+        add_file_output(
+            PathBuf::from(layout.directory_name(cid)).join("inductor_config.html"),
+            html,
+            &mut output,
+            directory.entry(Some(cid.clone())).or_default(),
+            &mut output_count,
+            None,
+        );
+    }
+    for (mut compile_id_entry, (path, html)) in compile_id_pages {
+        if let Some(ref mut entry) = compile_id_entry {
+            if entry.frame_compile_id.is_some() && entry.attempt.is_none() {
+                entry.attempt = Some(0);
+            }
+        }
+        add_file_output(
+            output_count.unique_filename(path),
+            html,
+            &mut output,
+            directory.entry(compile_id_entry).or_default(),
+            &mut output_count,
+            None,
+        );
+    }
+
+    // Last, so that it links to every other artifact of the compile id
+    for (cid, files) in directory.iter_mut() {
+        let (Some(cid), false) = (cid, files.is_empty()) else {
+            continue;
+        };
+        let metrics = metrics_index
+            .get(&Some(cid.clone()))
+            .and_then(|metrics| metrics.last());
+        let graph_nodes = graph_stats_index
+            .borrow()
+            .get(&(Some(cid.clone()), "dynamo_output_graph"))
+            .and_then(|dumps| dumps.last())
+            .map(|stats| stats.num_nodes);
+        let html = tt.render(
+            "summary.html",
+            &compile_id_summary(cid, layout, metrics, graph_nodes, files),
+        )?;
+        add_file_output(
+            PathBuf::from(layout.directory_name(cid)).join("summary.html"),
+            html,
+            &mut output,
+            files,
+            &mut output_count,
+            None,
+        );
+    }
+
+    if let Some((compile_id, metrics)) = &first_failure {
+        keep_compile_id(&mut directory, &mut output, stream_dir, compile_id);
+        output.push((
+            PathBuf::from("first_failure.html"),
+            tt.render(
+                "first_failure.html",
+                &FirstFailureContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    compile_id: compile_id
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    co_name: metrics.co_name.clone().unwrap_or_default(),
+                    location: metrics
+                        .co_filename
+                        .as_ref()
+                        .map(|f| format!("{f}:{}", metrics.co_firstlineno.unwrap_or_default())),
+                    fail_type: metrics.fail_type.clone().unwrap_or_default(),
+                    fail_reason: metrics.fail_reason.clone().unwrap_or_default(),
+                    user_frame: metrics.fail_user_frame_filename.as_ref().map(|f| {
+                        format!("{f}:{}", metrics.fail_user_frame_lineno.unwrap_or_default())
+                    }),
+                    artifacts: directory.get(compile_id).cloned().unwrap_or_default(),
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+    }
+
+    // Before the compile ids over --max-compile-ids are dropped, which still count
+    let mut summary = CompileSummary::new(&metrics_index, &directory);
+    summary.config = run_config(&config_index);
+    let overflow_rows = match config.max_compile_ids {
+        Some(max_compile_ids) => cap_compile_ids(
+            &mut directory,
+            &mut output,
+            stream_dir,
+            &metrics_index,
+            max_compile_ids,
+        ),
+        None => Vec::new(),
+    };
+    let num_overflow_compile_ids = overflow_rows.len();
+    if !overflow_rows.is_empty() {
+        output.push((
+            PathBuf::from("overflow_compile_ids.html"),
+            tt.render(
+                "overflow_compile_ids.html",
+                &OverflowCompileIdsContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    max_compile_ids: config.max_compile_ids.unwrap_or_default(),
+                    rows: overflow_rows,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+    }
+
+    breaks.groups = group_failures(&breaks.failures);
+    output.push((
+        PathBuf::from("failures_and_restarts.html"),
+        tt.render("failures_and_restarts.html", &breaks)?,
+    ));
+    pb.finish_with_message("done");
+    spinner.finish();
+
+    if let Some(steps) = &config.steps {
+        let step_events = steps.chromium_events(&chromium_events);
+        chromium_events.extend(step_events);
+    }
+    output.push((
+        PathBuf::from("chromium_events.json"),
+        serde_json::to_string_pretty(&chromium_events).unwrap(),
+    ));
+    if config.otel_spans && !span_index.is_empty() {
+        let year = if config.deterministic {
+            DETERMINISTIC_YEAR
+        } else {
+            chrono::Utc::now().year()
+        };
+        output.push((
+            PathBuf::from("otel_spans.json"),
+            serde_json::to_string_pretty(&otel::otlp_spans(
+                &span_index,
+                &chromium_events,
+                year,
+                config.rank,
+            ))?,
+        ));
+    }
+
+    eprintln!("{}", stats);
+    if unknown_fields.len() > 0 {
+        eprintln!(
+            "Unknown fields: {:?} (consider updating tlparse to render these)",
+            unknown_fields
+        );
+    }
+
+    let has_unknown_compile_id = directory.contains_key(&None);
+
+    // Rather than making users rerun a long parse with --inductor-provenance.  Without node
+    // mappings the pages would be empty, and index.html hints at the config that logs them.
+    let inductor_provenance = !config.no_inductor_provenance
+        && output.iter().any(|(path, _)| {
+            path.file_name().is_some_and(|name| {
+                name.to_string_lossy()
+                    .starts_with("inductor_provenance_tracking_node_mappings")
+            })
+        });
+
+    let directory_names: Vec<String> = directory
+        .iter()
+        .map(|(x, _)| {
+            x.as_ref()
+                .map_or("(unknown)".to_string(), |e| e.as_directory_name())
+        })
+        .collect();
+    if let Some(baseline) = &config.baseline {
+        output.push((
+            PathBuf::from("baseline_comparison.html"),
+            tt.render(
+                "baseline_comparison.html",
+                &BaselineComparisonContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    rows: summary.compare(baseline),
+                    config_changes: config_changes(&baseline.config, &summary.config),
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+    }
+    let parse_summary = rank_parse_summary(
+        &directory,
+        &output,
+        stats.clone(),
+        summary.clone(),
+        chromium_events.len(),
+        if config.compare_runs {
+            compare_runs::run_compiles(
+                &metrics_index,
+                &stack_index.borrow(),
+                &intern_table.borrow(),
+                &directory,
+            )
+        } else {
+            Vec::new()
+        },
+    );
+    let mut has_runtime_profile_correlation = false;
+    if let Some(profile) = &config.profile {
+        let graphs: Vec<profile::GraphCorrelation> = runtime_estimations
+            .iter()
+            .map(|(compile_id, ops)| {
+                let compile_id = compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string());
+                profile::correlate_graph(compile_id, ops, profile)
+            })
+            .collect();
+        has_runtime_profile_correlation = !graphs.is_empty();
+        if has_runtime_profile_correlation {
+            output.push((
+                PathBuf::from("runtime_profile_correlation.json"),
+                serde_json::to_string_pretty(&graphs)?,
+            ));
+            output.push((
+                PathBuf::from("runtime_profile_correlation.html"),
+                tt.render(
+                    "runtime_profile_correlation.html",
+                    &RuntimeProfileCorrelationContext {
+                        css: TEMPLATE_FAILURES_CSS,
+                        graphs,
+                        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                    },
+                )?,
+            ));
+        }
+    }
+    output.push((
+        PathBuf::from("compile_summary.json"),
+        serde_json::to_string_pretty(&summary)?,
+    ));
+    let recorded_at = (!config.deterministic).then(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    });
+    output.push((
+        PathBuf::from("trend_record.json"),
+        serde_json::to_string(&TrendRecord::new(
+            &summary,
+            config.git_sha.clone(),
+            config.job_id.clone(),
+            config.rank,
+            recorded_at,
+        ))?,
+    ));
+    output.push((
+        PathBuf::from("metrics.prom"),
+        prometheus_metrics(&[(None, &summary)]),
+    ));
+    if config.metrics_csv {
+        output.push((
+            PathBuf::from("compilation_metrics.csv"),
+            metrics_csv(&metrics_index),
+        ));
+    }
+    output.push((
+        PathBuf::from("compile_directory.json"),
+        serde_json::to_string_pretty(&compile_directory(
+            &directory,
+            &compile_steps,
+            config.directory_layout,
+        ))?,
+    ));
+    let pass_timings = pass_timings_rows(&pass_timings_index.borrow());
+    let has_pass_timings = !pass_timings.is_empty();
+    if has_pass_timings {
+        output.push((
+            PathBuf::from("pass_timings.html"),
+            tt.render(
+                "pass_timings.html",
+                &PassTimingsContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    rows: pass_timings,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+    }
+
+    let counters_index = counters_index.take();
+    let has_counters = !counters_index.is_empty();
+    if has_counters {
+        output.push((
+            PathBuf::from("counters.html"),
+            tt.render(
+                "counters.html",
+                &crate::counters::counters_context(&counters_index),
+            )?,
+        ));
+    }
 
-        let exported_program_url = directory
-            .values()
-            .flatten()
-            .find(|output_file| output_file.url.contains("exported_program"))
-            .map(|output_file| output_file.url.clone());
+    let mut memory_peaks: Vec<(u64, MemoryPeak)> = memory_index
+        .take()
+        .into_iter()
+        .map(|(cid, peak)| {
+            let url = directory
+                .get(&cid)
+                .and_then(|files| files.iter().find(|f| f.name.contains("memory_report")))
+                .map_or("index.html".to_string(), |f| f.url.clone());
+            let memory_peak = MemoryPeak {
+                compile_id: cid.map_or("(unknown)".to_string(), |c| c.to_string()),
+                url,
+                peak_memory: format_bytes(peak),
+            };
+            (peak, memory_peak)
+        })
+        .collect();
+    // Stable, so ties stay in the order they were compiled
+    memory_peaks.sort_by_key(|(peak, _)| std::cmp::Reverse(*peak));
 
-        let index_context = ExportIndexContext {
-            css: EXPORT_CSS,
-            javascript: JAVASCRIPT,
-            custom_header_html: config.custom_header_html.clone(),
-            directory: directory
-                .drain(..)
-                .map(|(x, y)| (x.map_or("(unknown)".to_string(), |e| e.to_string()), y))
-                .collect(),
-            failures: export_failures,
-            num_failures: num_failures,
-            success: num_failures == 0,
-            exported_program_url: exported_program_url.unwrap_or("".to_string()),
-            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
-        };
+    let graph_stats_index = graph_stats_index.take();
+    let has_graph_stats = !graph_stats_index.is_empty();
+    if has_graph_stats {
+        let graph_stats: Vec<serde_json::Value> = graph_stats_index
+            .iter()
+            .flat_map(|((compile_id, name), dumps)| {
+                let compile_id = compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string());
+                dumps.iter().map(move |stats| {
+                    serde_json::json!({
+                        "compile_id": compile_id,
+                        "graph": name,
+                        "stats": stats,
+                    })
+                })
+            })
+            .collect();
+        output.push((
+            PathBuf::from("graph_stats.json"),
+            serde_json::to_string_pretty(&graph_stats)?,
+        ));
+    }
+    let duplicate_graphs = duplicate_graph_clusters(&graph_stats_index);
+    let num_duplicate_graphs = duplicate_graphs.len();
+    if !duplicate_graphs.is_empty() {
+        output.push((
+            PathBuf::from("duplicate_graphs.html"),
+            tt.render(
+                "duplicate_graphs.html",
+                &DuplicateGraphsContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    clusters: duplicate_graphs,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+    }
 
+    let module_map_graphs: Vec<ModuleMapGraph> = directory_names
+        .iter()
+        .filter_map(|directory_name| {
+            let module_map = ModuleMap::new(&get_file_content(
+                &output,
+                &["before_pre_grad_graph", "inductor_pre_grad_graph"],
+                directory_name,
+            ));
+            (!module_map.is_empty()).then(|| ModuleMapGraph {
+                directory_name: directory_name.clone(),
+                tree_html: module_map.tree_html(),
+            })
+        })
+        .collect();
+    let has_module_map = !module_map_graphs.is_empty();
+    if has_module_map {
         output.push((
-            PathBuf::from("index.html"),
-            tt.render("index.html", &index_context)?,
+            PathBuf::from("module_map.html"),
+            tt.render(
+                "module_map.html",
+                &ModuleMapContext {
+                    css: MODULE_MAP_CSS,
+                    graphs: module_map_graphs,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
         ));
+    }
 
-        return Ok(output);
+    let has_graph_partitions = !graph_partitions_index.borrow().is_empty();
+    if has_graph_partitions {
+        let compile_ids = graph_partitions_index
+            .take()
+            .into_iter()
+            .map(|(compile_id, artifacts)| GraphPartitionsCompileId {
+                compile_id: compile_id.map_or("(unknown)".to_string(), |c| c.to_string()),
+                artifacts,
+            })
+            .collect();
+        output.push((
+            PathBuf::from("graph_partitions.html"),
+            tt.render(
+                "graph_partitions.html",
+                &GraphPartitionsContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    compile_ids,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
     }
 
-    output.push((
-        PathBuf::from("failures_and_restarts.html"),
-        tt.render("failures_and_restarts.html", &breaks)?,
-    ));
-    pb.finish_with_message("done");
-    spinner.finish();
+    let (guard_latency, total_guard_latency_us, total_guard_count) =
+        guard_latency_rows(&metrics_index);
+    let guard_latency_summary = if guard_latency.is_empty() {
+        None
+    } else {
+        output.push((
+            PathBuf::from("guard_latency.html"),
+            tt.render(
+                "guard_latency.html",
+                &GuardLatencyContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    rows: guard_latency,
+                    total_latency_us: format!("{total_guard_latency_us:.1}"),
+                    total_guard_count,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+        Some(GuardLatencySummary {
+            total_latency_us: format!("{total_guard_latency_us:.1}"),
+            total_guard_count,
+        })
+    };
 
-    output.push((
-        PathBuf::from("chromium_events.json"),
-        serde_json::to_string_pretty(&chromium_events).unwrap(),
-    ));
+    let cudagraph_skips: Vec<CudagraphSkip> = metrics_index
+        .iter()
+        .flat_map(|(cid, metrics)| {
+            let url = directory
+                .get(cid)
+                .and_then(|files| files.iter().find(|f| f.name.contains("cudagraphs")))
+                .map_or("index.html".to_string(), |f| f.url.clone());
+            metrics.iter().filter_map(move |m| {
+                Some(CudagraphSkip {
+                    compile_id: cid
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    url: url.clone(),
+                    // The rest of the reason is usually a stack, shown on the compile id's page
+                    reason: m
+                        .cudagraph_skip_reason
+                        .as_deref()?
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+        })
+        .collect();
 
-    eprintln!("{}", stats);
-    if unknown_fields.len() > 0 {
-        eprintln!(
-            "Unknown fields: {:?} (consider updating tlparse to render these)",
-            unknown_fields
-        );
+    let findings = findings::findings(&metrics_index, &directory, &cudagraph_skips);
+    let guard_failures = guard_failures(&metrics_index, &frame_locals_index);
+    let shape_churn = shape_churn(&specialization_index);
+    for failure in &guard_failures {
+        output.push((PathBuf::from(&failure.url), failure.to_markdown()));
     }
 
-    let has_unknown_compile_id = directory.contains_key(&None);
+    // Only worth a page if compiles could have overlapped
+    let num_threads = thread_index.num_threads();
+    let mut num_overlapping_compiles = 0;
+    if num_threads > 1 {
+        let summary = thread_index.summary();
+        num_overlapping_compiles = summary
+            .iter()
+            .flat_map(|t| &t.compiles)
+            .filter(|c| c.overlapping)
+            .count();
+        output.push((
+            PathBuf::from("threads.html"),
+            tt.render(
+                "threads.html",
+                &ThreadsContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    rows: threads::timeline_rows(&summary),
+                    num_overlapping: num_overlapping_compiles,
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+        output.push((
+            PathBuf::from("threads.json"),
+            serde_json::to_string_pretty(&summary)?,
+        ));
+    }
 
-    let directory_names: Vec<String> = directory
+    let has_code_size = !code_size_index.is_empty();
+    if has_code_size {
+        let summary = code_size_index.summary();
+        output.push((
+            PathBuf::from("code_size.html"),
+            tt.render(
+                "code_size.html",
+                &CodeSizeContext {
+                    css: TEMPLATE_FAILURES_CSS,
+                    treemap_css: CODE_SIZE_CSS,
+                    total_size: format_bytes(summary.total_bytes),
+                    tiles: treemap_tiles(&summary),
+                    rows: summary
+                        .compile_ids
+                        .iter()
+                        .map(|c| CodeSizeRow {
+                            compile_id: c.compile_id.clone(),
+                            total: format_bytes(c.total_bytes),
+                            breakdown: c
+                                .artifacts
+                                .iter()
+                                .map(|(kind, bytes)| format!("{kind}: {}", format_bytes(*bytes)))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        })
+                        .collect(),
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+        output.push((
+            PathBuf::from("code_size.json"),
+            serde_json::to_string_pretty(&summary)?,
+        ));
+    }
+
+    let hot_entry_points = entry_points::hot_entry_points(
+        &stack_index.borrow(),
+        &metrics_index,
+        &intern_table.borrow(),
+    );
+
+    let compile_stacks = flamegraph::folded_stacks(
+        &stack_index.borrow(),
+        &metrics_index,
+        &intern_table.borrow(),
+    );
+    if !compile_stacks.is_empty() {
+        output.push((
+            PathBuf::from("compile_flamegraph.folded"),
+            flamegraph::to_folded(&compile_stacks),
+        ));
+        output.push((
+            PathBuf::from("compile_flamegraph.svg"),
+            flamegraph::render_svg(&compile_stacks, "Compile time by user stack"),
+        ));
+    }
+
+    let search_index_json = serde_json::to_string(&search_index.finish(&directory))?;
+    output.push((
+        PathBuf::from("search.html"),
+        tt.render(
+            "search.html",
+            &SearchContext {
+                css: TEMPLATE_FAILURES_CSS,
+                lookup_javascript: SEARCH_LOOKUP_JAVASCRIPT,
+                javascript: SEARCH_JAVASCRIPT,
+                // Keep a "</script>" inside e.g. a guard expression from ending the script early
+                search_index_json: search_index_json.replace("</", "<\\/"),
+                qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            },
+        )?,
+    ));
+    output.push((PathBuf::from("search_index.json"), search_index_json));
+    let mut directory = attempt_tree(
+        directory,
+        &metrics_index,
+        &compile_steps,
+        &graph_stats_index,
+    );
+    // The first page stays index.html, with the summary and stack trie; later pages only list
+    // build products
+    let mut index_pages: Vec<Vec<(String, Vec<OutputFile>, IndexCompileId)>> = Vec::new();
+    if let Some(page_size) = config.index_page_size.map(|n| n.max(1)) {
+        while directory.len() > page_size {
+            let last_page_start = (directory.len() - 1) / page_size * page_size;
+            index_pages.push(directory.split_off(last_page_start));
+        }
+        index_pages.reverse();
+        nest_attempts(&mut directory);
+        for page in &mut index_pages {
+            nest_attempts(page);
+        }
+    }
+    let page_url = |page: usize| format!("index_{page}.html");
+    let page_links = |current: usize| -> Vec<IndexPageLink> {
+        (0..=index_pages.len())
+            .map(|page| IndexPageLink {
+                number: page + 1,
+                url: if page == 0 {
+                    "index.html".to_string()
+                } else {
+                    page_url(page)
+                },
+                current: page == current,
+            })
+            .collect()
+    };
+    let index_page_of: std::collections::BTreeMap<&str, String> = index_pages
         .iter()
-        .map(|(x, _)| {
-            x.as_ref()
-                .map_or("(unknown)".to_string(), |e| e.as_directory_name())
+        .enumerate()
+        .flat_map(|(i, page)| {
+            page.iter()
+                .map(move |(compile_id, _, _)| (compile_id.as_str(), page_url(i + 1)))
         })
         .collect();
-    output.push((
-        PathBuf::from("compile_directory.json"),
-        serde_json::to_string_pretty(&directory_to_json(&directory))?,
-    ));
+    let index_page_of_json = serde_json::to_string(&index_page_of)?.replace("</", "<\\/");
+    for (i, page) in index_pages.iter().enumerate() {
+        output.push((
+            PathBuf::from(page_url(i + 1)),
+            tt.render(
+                "index_page.html",
+                &IndexPageContext {
+                    css: CSS,
+                    number: i + 2,
+                    num_pages: index_pages.len() + 1,
+                    index_pages: page_links(i + 1),
+                    directory: page.clone(),
+                    qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+                },
+            )?,
+        ));
+    }
     let index_context = IndexContext {
         css: CSS,
         javascript: JAVASCRIPT,
+        findings,
+        completeness_hints,
+        has_first_failure: first_failure.is_some(),
+        truncated_line,
         custom_header_html: config.custom_header_html.clone(),
-        directory: directory
-            .drain(..)
-            .map(|(x, y)| (x.map_or("(unknown)".to_string(), |e| e.to_string()), y))
-            .collect(),
+        directory,
         stack_trie_html: stack_trie
-            .fmt(Some(&metrics_index), "Stack", false)
+            .fmt(
+                &intern_table.borrow(),
+                Some(&metrics_index),
+                None,
+                "Stack",
+                false,
+            )
             .unwrap(),
         unknown_stack_trie_html: unknown_stack_trie
-            .fmt(Some(&metrics_index), "Stack", false)
+            .fmt(
+                &intern_table.borrow(),
+                Some(&metrics_index),
+                None,
+                "Stack",
+                false,
+            )
             .unwrap(),
         has_unknown_stack_trie: !unknown_stack_trie.is_empty(),
         num_breaks: breaks.failures.len(),
         has_chromium_events: !chromium_events.is_empty(),
+        has_compile_flamegraph: !compile_stacks.is_empty(),
+        has_pass_timings,
+        has_counters,
+        memory_peaks: memory_peaks.into_iter().map(|(_, p)| p).collect(),
+        has_code_size,
+        has_graph_partitions,
+        has_graph_stats,
+        num_duplicate_graphs,
+        has_module_map,
+        hot_entry_points,
+        guard_latency: guard_latency_summary,
+        num_cudagraph_skips: cudagraph_skips.len(),
+        cudagraph_skips,
+        num_data_dependent_specializations: data_dependent_specializations.len(),
+        data_dependent_specializations,
+        guard_failures,
+        shape_churn,
+        distributed_info,
         qps: TEMPLATE_QUERY_PARAM_SCRIPT,
-        has_inductor_provenance: config.inductor_provenance,
+        has_inductor_provenance: inductor_provenance,
         directory_names: directory_names.clone(),
+        has_overflow_compile_ids: num_overflow_compile_ids > 0,
+        num_overflow_compile_ids,
+        has_cache_stats: summary.cache_hit_rate.is_some(),
+        summary,
+        has_baseline: config.baseline.is_some(),
+        has_runtime_profile_correlation,
+        has_index_pages: !index_pages.is_empty(),
+        index_pages: page_links(0).split_off(1),
+        index_page_of_json,
+        envelope_stats,
+        parser_timings,
+        num_threads: if num_threads > 1 { num_threads } else { 0 },
+        num_overlapping_compiles,
     };
     output.push((
         PathBuf::from("index.html"),
         tt.render("index.html", &index_context)?,
     ));
 
-    output.push((PathBuf::from("raw.log"), fs::read_to_string(path)?));
-
-    // Create string table from INTERN_TABLE as an array with nulls for missing indices
-    let intern_table = INTERN_TABLE.lock().unwrap();
-    let max_index = intern_table.keys().max().copied().unwrap_or(0) as usize;
-    let mut string_table: Vec<Option<String>> = vec![None; max_index + 1];
-    for (&index, value) in intern_table.iter() {
-        string_table[index as usize] = Some(value.clone());
+    if stats.invalid_utf8 > 0 {
+        multi.suspend(|| {
+            eprintln!(
+                "Replaced {} invalid UTF-8 sequence(s) in the log",
+                stats.invalid_utf8
+            );
+        });
     }
-    drop(intern_table); // Release the lock early
+    output.push((PathBuf::from("raw.log"), log_text(&log)));
+
+    let string_table = intern_table.borrow().string_table();
 
     // Serialize string table as JSON object
     let string_table_json = serde_json::json!({
@@ -1128,13 +4051,9 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
     let string_table_line = serde_json::to_string(&string_table_json)?;
 
     // Prepend string table to raw.jsonl content
-    let mut final_shortraw_content =
-        String::with_capacity(string_table_line.len() + 1 + shortraw_content.len());
-    final_shortraw_content.push_str(&string_table_line);
-    final_shortraw_content.push('\n');
-    final_shortraw_content.push_str(&shortraw_content);
-
-    output.push((PathBuf::from("raw.jsonl"), final_shortraw_content));
+    if let Some(raw_jsonl) = raw_jsonl.finish(&string_table_line)? {
+        output.push((PathBuf::from("raw.jsonl"), raw_jsonl));
+    }
 
     // other_rank is included here because you should only have logs from one rank when
     // configured properly
@@ -1142,6 +4061,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         && (stats.fail_glog
             + stats.fail_json
             + stats.fail_payload_md5
+            + stats.truncated_at_eof
             + stats.other_rank
             + stats.fail_dynamo_guards_json
             + stats.fail_parser
@@ -1155,25 +4075,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         return Err(anyhow!("Some log entries did not have compile id"));
     }
 
-    if config.inductor_provenance {
-        // Helper function to get file content for a specific directory name
-        fn get_file_content(
-            output: &[(PathBuf, String)],
-            filename_patterns: &[&str],
-            directory_name: &str,
-        ) -> String {
-            // Try each pattern in order and return the first match found
-            for pattern in filename_patterns {
-                if let Some((_, content)) = output.iter().rev().find(|(path, _)| {
-                    path.to_string_lossy()
-                        .contains(&format!("{}/{}", directory_name, pattern))
-                }) {
-                    return content.clone();
-                }
-            }
-            String::default()
-        }
-
+    if inductor_provenance {
         // Generate HTML for each directory name
         for directory_name in &directory_names {
             let pre_grad_graph_content = get_file_content(
@@ -1186,8 +4088,26 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 &["after_post_grad_graph", "inductor_post_grad_graph"],
                 directory_name,
             );
-            let output_code_content =
-                get_file_content(&output, &["inductor_output_code"], directory_name);
+            // Graph partitions and subgraphs have their own output code
+            let mut code_files: Vec<ProvenanceCodeFile> = output
+                .iter()
+                .filter(|(path, _)| {
+                    path.to_string_lossy()
+                        .contains(&format!("{}/inductor_output_code", directory_name))
+                })
+                .map(|(path, content)| ProvenanceCodeFile {
+                    name: path
+                        .file_name()
+                        .map_or(String::new(), |n| n.to_string_lossy().to_string()),
+                    content: content.clone(),
+                })
+                .collect();
+            if code_files.is_empty() {
+                code_files.push(ProvenanceCodeFile {
+                    name: String::new(),
+                    content: String::new(),
+                });
+            }
             let aot_code_content =
                 get_file_content(&output, &["inductor_aot_wrapper_code"], directory_name);
             let node_mappings_content = get_file_content(
@@ -1196,16 +4116,58 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 directory_name,
             );
 
-            // Convert node mappings to line number mappings
-            let line_mappings_content = convert_node_mappings_to_line_numbers(
-                &node_mappings_content,
-                &pre_grad_graph_content,
-                &post_grad_graph_content,
-                &output_code_content,
-                &aot_code_content,
-            );
-            let line_mappings_content_str = serde_json::to_string_pretty(&line_mappings_content)
-                .unwrap_or_else(|_| "{}".to_string());
+            // Convert node mappings to line number mappings, for each code file.  The AOT wrapper
+            // code is shown after the first.
+            let mut code_file_line_mappings: Vec<Value> = Vec::new();
+            let mut diagnostics: Option<ProvenanceDiagnostics> = None;
+            for (i, code_file) in code_files.iter().enumerate() {
+                let (line_mappings, file_diagnostics) = convert_node_mappings_to_line_numbers(
+                    &node_mappings_content,
+                    &pre_grad_graph_content,
+                    &post_grad_graph_content,
+                    &code_file.content,
+                    if i == 0 { &aot_code_content } else { "" },
+                );
+                code_file_line_mappings.push(line_mappings);
+                // Kernels only need to be found in one of the files
+                match &mut diagnostics {
+                    Some(d) => d
+                        .unmapped_kernels
+                        .retain(|k| file_diagnostics.unmapped_kernels.contains(k)),
+                    None => diagnostics = Some(file_diagnostics),
+                }
+            }
+            let diagnostics = diagnostics.unwrap_or_default();
+            let line_mappings_content_str =
+                serde_json::to_string_pretty(&code_file_line_mappings[0])
+                    .unwrap_or_else(|_| "{}".to_string());
+            let code_file_line_mappings_str = serde_json::to_string(&code_file_line_mappings)
+                .unwrap_or_else(|_| "[]".to_string());
+            // Embedded in a <script>, so a "</script>" in a stack trace mustn't end it
+            let kernel_stack_traces_content = output
+                .iter()
+                .rev()
+                .find(|(path, _)| {
+                    path.starts_with(directory_name.as_str()) && is_stack_traces_file(path)
+                })
+                .and_then(|(_, content)| serde_json::from_str::<Value>(content).ok())
+                .map_or("{}".to_string(), |traces| {
+                    traces.to_string().replace("</", "<\\/")
+                });
+            let kernel_sources: std::collections::BTreeMap<String, String> = code_files
+                .iter()
+                .flat_map(|f| extract_kernel_sources(&f.content))
+                .collect();
+            let kernel_to_post =
+                kernel_to_post_grad_lines(&node_mappings_content, &post_grad_graph_content);
+            let kernel_sources_content = serde_json::json!({
+                "sources": kernel_sources,
+                "kernelToPost": kernel_to_post,
+            })
+            .to_string()
+            .replace("</", "<\\/");
+
+            let module_map = ModuleMap::new(&pre_grad_graph_content);
 
             output.push((
                 PathBuf::from(format!("provenance_tracking_{}.html", directory_name)),
@@ -1216,16 +4178,26 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                         js: PROVENANCE_JS,
                         pre_grad_graph_content,
                         post_grad_graph_content,
-                        output_code_content,
+                        output_code_content: code_files[0].content.clone(),
+                        has_multiple_code_files: code_files.len() > 1,
+                        code_files,
                         aot_code_content,
                         line_mappings_content: line_mappings_content_str,
+                        code_file_line_mappings_content: code_file_line_mappings_str,
+                        kernel_stack_traces_content,
+                        has_kernel_sources: !kernel_sources.is_empty(),
+                        kernel_sources_content,
+                        num_unmapped: diagnostics.num_unmapped(),
+                        diagnostics,
+                        module_tree_html: module_map.tree_html(),
+                        has_module_tree: !module_map.is_empty(),
                     },
                 )?,
             ));
         }
     }
 
-    Ok(output)
+    Ok((output, parse_summary))
 }
 
 pub fn read_chromium_events_with_pid(
@@ -1255,7 +4227,7 @@ pub fn read_chromium_events_with_pid(
 
 pub fn generate_multi_rank_html(
     out_path: &PathBuf,
-    sorted_ranks: Vec<String>,
+    rank_summaries: Vec<RankSummary>,
     cfg: &ParseConfig,
     has_chromium_events: bool,
     show_desync_warning: bool,
@@ -1265,25 +4237,96 @@ pub fn generate_multi_rank_html(
     // Create the TinyTemplate instance for rendering the landing page.
     let mut tt = TinyTemplate::new();
     tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
-    tt.add_template("multi_rank_index.html", TEMPLATE_MULTI_RANK_INDEX)?;
+    tt.add_template(
+        "multi_rank_index.html",
+        template_source(cfg, "multi_rank_index.html", TEMPLATE_MULTI_RANK_INDEX),
+    )?;
 
+    let findings = diagnostics
+        .compile_time_skew
+        .as_ref()
+        .map(findings::straggler_findings)
+        .unwrap_or_default();
     let ctx = MultiRankContext {
         css: CSS,
         custom_header_html: &cfg.custom_header_html,
-        num_ranks: sorted_ranks.len(),
-        ranks: sorted_ranks,
+        num_ranks: rank_summaries.len(),
+        has_rank_stats: rank_summaries.iter().any(|r| r.stats.is_some()),
+        ranks: rank_summaries.iter().map(|r| r.rank.to_string()).collect(),
+        has_distributed_info: rank_summaries.iter().any(|r| r.info.is_some()),
+        rank_summaries,
         qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        sortable_table_script: TEMPLATE_SORTABLE_TABLE_SCRIPT,
         has_chromium_events,
         show_desync_warning,
         compile_id_divergence,
         diagnostics,
+        findings,
     };
-    let html = tt.render("multi_rank_index.html", &ctx)?;
+    let mut html = tt.render("multi_rank_index.html", &ctx)?;
+    if let Some(url_prefix) = &cfg.url_prefix {
+        html = url_prefix::root_links(&html, Path::new("index.html"), url_prefix);
+    }
     let landing_page_path = out_path.join("index.html");
 
     Ok((landing_page_path, html))
 }
 
+/// cross_rank_search.html of a multi-rank report, over the search indices of the ranks under
+/// `out_path`
+pub fn generate_cross_rank_search_html(
+    out_path: &Path,
+    rank_nums: &[u32],
+    cfg: &ParseConfig,
+) -> anyhow::Result<(PathBuf, String)> {
+    let mut tt = TinyTemplate::new();
+    tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
+    tt.add_template(
+        "cross_rank_search.html",
+        template_source(cfg, "cross_rank_search.html", TEMPLATE_CROSS_RANK_SEARCH),
+    )?;
+    let index = search::cross_rank_search_index(out_path, rank_nums)?;
+    let mut html = tt.render(
+        "cross_rank_search.html",
+        &CrossRankSearchContext {
+            css: TEMPLATE_FAILURES_CSS,
+            num_ranks: rank_nums.len(),
+            lookup_javascript: SEARCH_LOOKUP_JAVASCRIPT,
+            javascript: CROSS_RANK_SEARCH_JAVASCRIPT,
+            // Keep a "</script>" inside e.g. a guard expression from ending the script early
+            search_index_json: serde_json::to_string(&index)?.replace("</", "<\\/"),
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        },
+    )?;
+    if let Some(url_prefix) = &cfg.url_prefix {
+        html = url_prefix::root_links(&html, Path::new("cross_rank_search.html"), url_prefix);
+    }
+    Ok((out_path.join("cross_rank_search.html"), html))
+}
+
+/// The landing page of a report comparing `runs`, each a name and the summary of its log, whose
+/// reports are in run_0/, run_1/, ... of `out_path`
+pub fn generate_compare_runs_html(
+    out_path: &Path,
+    runs: &[(String, RankParseSummary)],
+    cfg: &ParseConfig,
+) -> anyhow::Result<(PathBuf, String)> {
+    let mut tt = TinyTemplate::new();
+    tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
+    tt.add_template(
+        "compare_runs.html",
+        template_source(cfg, "compare_runs.html", TEMPLATE_COMPARE_RUNS),
+    )?;
+    let mut html = tt.render(
+        "compare_runs.html",
+        &compare_runs::compare_runs(runs, &cfg.custom_header_html),
+    )?;
+    if let Some(url_prefix) = &cfg.url_prefix {
+        html = url_prefix::root_links(&html, Path::new("index.html"), url_prefix);
+    }
+    Ok((out_path.join("index.html"), html))
+}
+
 fn prepare_and_validate_graphs(
     runtime_estimations: &[GraphRuntime],
 ) -> Option<(
@@ -1395,22 +4438,228 @@ pub fn analyze_graph_runtime_deltas(
     })
 }
 
+/// Compares the estimated peak memory of each graph across the ranks that compiled it, flagging
+/// ranks whose estimate differs from that of most ranks.  None without any memory plans.
+pub fn analyze_graph_memory_deltas(memory_estimations: &[GraphMemory]) -> Option<MemoryAnalysis> {
+    if memory_estimations.is_empty() {
+        return None;
+    }
+    use std::collections::BTreeMap;
+
+    let mut by_graph: BTreeMap<&str, Vec<(u32, u64)>> = BTreeMap::new();
+    for gm in memory_estimations {
+        by_graph
+            .entry(&gm.graph)
+            .or_default()
+            .push((gm.rank, gm.peak_bytes));
+    }
+    let graphs: Vec<GraphMemoryComparison> = by_graph
+        .into_iter()
+        .map(|(graph, mut ranks)| {
+            ranks.sort_unstable();
+            let mut counts: BTreeMap<u64, usize> = BTreeMap::new();
+            for (_, peak) in &ranks {
+                *counts.entry(*peak).or_default() += 1;
+            }
+            // The most common estimate, the smallest if there are several
+            let majority = counts
+                .iter()
+                .max_by_key(|(peak, count)| (**count, std::cmp::Reverse(**peak)))
+                .map_or(0, |(peak, _)| *peak);
+            let min = counts.keys().next().copied().unwrap_or_default();
+            let max = counts.keys().next_back().copied().unwrap_or_default();
+            GraphMemoryComparison {
+                graph_id: graph.to_string(),
+                peak_memory: format_bytes(majority),
+                delta: format_bytes(max - min),
+                diverging_ranks: ranks
+                    .into_iter()
+                    .filter(|(_, peak)| *peak != majority)
+                    .map(|(rank, peak)| RankMemory {
+                        rank,
+                        peak_memory: format_bytes(peak),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    Some(MemoryAnalysis {
+        num_diverging_graphs: graphs
+            .iter()
+            .filter(|g| !g.diverging_ranks.is_empty())
+            .count(),
+        graphs,
+    })
+}
+
+/// Compares each rank's total compile time to the median, flagging ranks that deviate by more
+/// than `threshold` (e.g. 0.2 for 20%).  None with fewer than 2 ranks or no compile time.
+pub fn analyze_compile_time_skew(
+    compile_times: &[(u32, f64)],
+    threshold: f64,
+) -> Option<CompileTimeSkew> {
+    if compile_times.len() < 2 {
+        return None;
+    }
+    let mut sorted: Vec<f64> = compile_times.iter().map(|(_, t)| *t).collect();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    };
+    let max = sorted[sorted.len() - 1];
+    if median <= 0.0 {
+        return None;
+    }
+    let ranks: Vec<RankCompileTime> = compile_times
+        .iter()
+        .map(|&(rank, compile_time_s)| {
+            let deviation = compile_time_s / median - 1.0;
+            RankCompileTime {
+                rank,
+                compile_time_s,
+                deviation: format!("{:+.1}%", deviation * 100.0),
+                bar_pct: (compile_time_s / max * 1000.0).round() / 10.0,
+                straggler: deviation.abs() > threshold,
+            }
+        })
+        .collect();
+    Some(CompileTimeSkew {
+        median_s: (median * 1000.0).round() / 1000.0,
+        threshold_pct: threshold * 100.0,
+        num_stragglers: ranks.iter().filter(|r| r.straggler).count(),
+        ranks,
+    })
+}
+
+/// Content of the last file of the compile directory `directory_name` whose name starts with
+/// one of `filename_patterns`, trying them in order
+fn get_file_content(
+    output: &[(PathBuf, String)],
+    filename_patterns: &[&str],
+    directory_name: &str,
+) -> String {
+    // Try each pattern in order and return the first match found
+    for pattern in filename_patterns {
+        if let Some((_, content)) = output.iter().rev().find(|(path, _)| {
+            path.to_string_lossy()
+                .contains(&format!("{}/{}", directory_name, pattern))
+        }) {
+            return content.clone();
+        }
+    }
+    String::default()
+}
+
+// Helper function to check if a line is valid (not empty and doesn't start with comment)
+fn valid_line(line: &str, symbol: &str) -> bool {
+    let stripped = line.trim();
+    !stripped.is_empty() && !stripped.starts_with(symbol)
+}
+
+// Helper function to extract node name from a line
+fn extract_node_name(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    if valid_line(trimmed, "#") {
+        // Split on '=' and take everything before it
+        let before_equals = trimmed.split('=').next()?;
+        // Split on ':' and take everything before it
+        let node_name = before_equals.split(':').next()?.trim();
+        if !node_name.is_empty() {
+            return Some(node_name.to_string());
+        }
+    }
+    None
+}
+
+// Helper function to build node-to-line lookup map from graph content
+fn build_node_to_lines_map(content: &str) -> std::collections::HashMap<String, usize> {
+    let mut node_to_lines = std::collections::HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        if let Some(node_name) = extract_node_name(line) {
+            node_to_lines.insert(node_name, i + 1); // 1-based line numbers
+        }
+    }
+    node_to_lines
+}
+
+static HTML_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+static KERNEL_DEFINITION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(\w+) = async_compile\.\w+\(.*'''$").unwrap());
+
+/// The source of each kernel that `code` compiles with async_compile, e.g. Triton or C++
+/// kernels, by kernel name.  `code` may be the HTML rendering of inductor_output_code.
+fn extract_kernel_sources(code: &str) -> std::collections::BTreeMap<String, String> {
+    let text = html_escape::decode_html_entities(&HTML_TAG.replace_all(code, "")).to_string();
+    let mut sources = std::collections::BTreeMap::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let Some(captures) = KERNEL_DEFINITION.captures(line.trim_end()) else {
+            continue;
+        };
+        let body: Vec<&str> = lines
+            .by_ref()
+            .take_while(|l| !l.trim_start().starts_with("'''"))
+            .collect();
+        sources.insert(captures[1].to_string(), body.join("\n").trim().to_string());
+    }
+    sources
+}
+
+/// The post-grad graph lines of each kernel, by kernel name without its debug handle
+fn kernel_to_post_grad_lines(
+    node_mappings_content: &str,
+    post_grad_graph_content: &str,
+) -> std::collections::BTreeMap<String, Vec<usize>> {
+    let node_mappings: serde_json::Value =
+        serde_json::from_str(node_mappings_content).unwrap_or_default();
+    let post_grad_node_to_lines = build_node_to_lines_map(post_grad_graph_content);
+    let mut kernel_to_lines = std::collections::BTreeMap::new();
+    let Some(kernels) = node_mappings
+        .get("cppCodeToPost")
+        .and_then(|v| v.as_object())
+    else {
+        return kernel_to_lines;
+    };
+    for (kernel_name, post_nodes) in kernels {
+        let pure_kernel_name = kernel_name.split(':').next().unwrap_or(kernel_name);
+        let lines: &mut Vec<usize> = kernel_to_lines
+            .entry(pure_kernel_name.to_string())
+            .or_default();
+        for post_node in post_nodes.as_array().into_iter().flatten() {
+            if let Some(line) = post_node
+                .as_str()
+                .and_then(|n| post_grad_node_to_lines.get(n))
+            {
+                lines.push(*line);
+            }
+        }
+        lines.sort_unstable();
+        lines.dedup();
+    }
+    kernel_to_lines
+}
+
 /// Converts node-based mappings to line number-based mappings for visualization.
 ///
 /// This function processes node mappings and converts them to line number mappings
 /// that can be used to highlight corresponding lines across different views.
 /// It handles pre-grad graph, post-grad graph, and generated code files.
+/// Nodes and kernels that couldn't be found in their graph or code are returned alongside, as
+/// their mappings are dropped.
 fn convert_node_mappings_to_line_numbers(
     node_mappings_content: &str,
     pre_grad_graph_content: &str,
     post_grad_graph_content: &str,
     output_code_content: &str,
     aot_code_content: &str,
-) -> serde_json::Value {
+) -> (serde_json::Value, ProvenanceDiagnostics) {
     // Parse the node mappings JSON
     let node_mappings: serde_json::Value = match serde_json::from_str(node_mappings_content) {
         Ok(mappings) => mappings,
-        Err(_) => return serde_json::json!({}),
+        Err(_) => return (serde_json::json!({}), ProvenanceDiagnostics::default()),
     };
 
     let version = node_mappings
@@ -1418,38 +4667,6 @@ fn convert_node_mappings_to_line_numbers(
         .and_then(|v| v.as_f64())
         .unwrap_or(1.0) as i64;
 
-    // Helper function to check if a line is valid (not empty and doesn't start with comment)
-    fn valid_line(line: &str, symbol: &str) -> bool {
-        let stripped = line.trim();
-        !stripped.is_empty() && !stripped.starts_with(symbol)
-    }
-
-    // Helper function to extract node name from a line
-    fn extract_node_name(line: &str) -> Option<String> {
-        let trimmed = line.trim();
-        if valid_line(trimmed, "#") {
-            // Split on '=' and take everything before it
-            let before_equals = trimmed.split('=').next()?;
-            // Split on ':' and take everything before it
-            let node_name = before_equals.split(':').next()?.trim();
-            if !node_name.is_empty() {
-                return Some(node_name.to_string());
-            }
-        }
-        None
-    }
-
-    // Helper function to build node-to-line lookup map from graph content
-    fn build_node_to_lines_map(content: &str) -> std::collections::HashMap<String, usize> {
-        let mut node_to_lines = std::collections::HashMap::new();
-        for (i, line) in content.lines().enumerate() {
-            if let Some(node_name) = extract_node_name(line) {
-                node_to_lines.insert(node_name, i + 1); // 1-based line numbers
-            }
-        }
-        node_to_lines
-    }
-
     // Helper function to build Python kernel-to-lines lookup map
     fn build_python_kernel_to_lines_map(
         content: &str,
@@ -1785,13 +5002,54 @@ fn convert_node_mappings_to_line_numbers(
         std::collections::HashMap::new()
     };
 
+    // Names in the mappings that none of the lookups above found
+    fn unmapped(
+        node_mappings: &serde_json::Value,
+        keys_of: &[&str],
+        values_of: &[&str],
+        is_mapped: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let keys = keys_of
+            .iter()
+            .filter_map(|m| node_mappings.get(m).and_then(|v| v.as_object()))
+            .flat_map(|obj| obj.keys().map(|k| k.as_str()));
+        let values = values_of
+            .iter()
+            .filter_map(|m| node_mappings.get(m).and_then(|v| v.as_object()))
+            .flat_map(|obj| obj.values())
+            .filter_map(|v| v.as_array())
+            .flatten()
+            .filter_map(|v| v.as_str());
+        let names: std::collections::BTreeSet<&str> =
+            keys.chain(values).filter(|name| !is_mapped(name)).collect();
+        names.into_iter().map(str::to_string).collect()
+    }
+    let diagnostics = ProvenanceDiagnostics {
+        unmapped_pre_grad_nodes: unmapped(&node_mappings, &["preToPost"], &["postToPre"], |n| {
+            pre_grad_node_to_lines.contains_key(n)
+        }),
+        unmapped_post_grad_nodes: unmapped(
+            &node_mappings,
+            &["postToPre", "postToCppCode"],
+            &["preToPost", "cppCodeToPost"],
+            |n| post_grad_node_to_lines.contains_key(n),
+        ),
+        unmapped_kernels: unmapped(
+            &node_mappings,
+            &["cppCodeToPost"],
+            &["postToCppCode"],
+            |n| py_kernel_to_lines.contains_key(n) || cpp_code_to_lines.contains_key(n),
+        ),
+    };
+
     // Convert all HashMaps to JSON objects
-    serde_json::json!({
+    let line_mappings = serde_json::json!({
         "preToPost": hashmap_to_json_map(line_pre_to_post),
         "postToPre": hashmap_to_json_map(line_post_to_pre),
         "pyCodeToPost": hashmap_to_json_map(line_py_code_to_post),
         "postToPyCode": hashmap_to_json_map(line_post_to_py_code),
         "cppCodeToPost": hashmap_to_json_map(line_cpp_code_to_post),
         "postToCppCode": hashmap_to_json_map(line_post_to_cpp_code)
-    })
+    });
+    (line_mappings, diagnostics)
 }
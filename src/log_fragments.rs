@@ -0,0 +1,96 @@
+// Logs of one rank split over several files, e.g. by log rotation, which are parsed as one log
+// by concatenating them in the order they were written.
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use regex::Regex;
+
+use crate::glog::{glog_regex, parse_glog_prefix, timestamp_us};
+
+fn has_wildcard(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?'])
+}
+
+/// The files matching `pattern`, whose file name may have `*` and `?` wildcards, e.g.
+/// logs/trace.*.log, sorted by name
+fn expand_wildcards(pattern: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let file_name = pattern
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if has_wildcard(dir) {
+        bail!(
+            "Wildcards are only supported in file names, not in {}",
+            dir.display()
+        );
+    }
+    let re = Regex::new(&format!(
+        "^{}$",
+        regex::escape(&file_name)
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".")
+    ))?;
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Couldn't list {}", dir.display()))?
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|e| re.is_match(&e.file_name().to_string_lossy()))
+        .map(|e| e.path())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// The log files of `paths`, with patterns like logs/trace.*.log expanded to the files they match
+pub fn expand_log_paths(paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if path.exists() || !has_wildcard(path) {
+            expanded.push(path.clone());
+            continue;
+        }
+        let matches = expand_wildcards(path)?;
+        if matches.is_empty() {
+            bail!("No files match {}", path.display());
+        }
+        expanded.extend(matches);
+    }
+    Ok(expanded)
+}
+
+// Timestamp of the first log line of `path`, in microseconds since the start of the year
+fn first_timestamp(path: &Path) -> anyhow::Result<Option<i64>> {
+    let re_glog = glog_regex();
+    let file = File::open(path).with_context(|| format!("Couldn't open {}", path.display()))?;
+    // Read as bytes, since a log may have some that aren't UTF-8
+    for line in BufReader::new(file).split(b'\n') {
+        if let Some(prefix) = parse_glog_prefix(&String::from_utf8_lossy(&line?), &re_glog) {
+            return Ok(Some(timestamp_us(&prefix)));
+        }
+    }
+    Ok(None)
+}
+
+/// The fragments of a log in `paths`, ordered by the timestamp of their first line.  Fragments of
+/// one log don't overlap in time, so parsing them in this order keeps their lines in order.
+pub fn order_log_fragments(paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    if paths.len() <= 1 {
+        return Ok(paths.to_vec());
+    }
+    let mut fragments: Vec<(Option<i64>, &PathBuf)> = Vec::new();
+    for path in paths {
+        fragments.push((first_timestamp(path)?, path));
+    }
+    // Fragments without log lines go last, by name otherwise
+    fragments.sort_by_key(|(t, path)| (t.is_none(), *t, *path));
+    Ok(fragments
+        .into_iter()
+        .map(|(_, path)| path.clone())
+        .collect())
+}
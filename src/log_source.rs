@@ -0,0 +1,123 @@
+// The bytes of a log: memory-mapped when it is a regular file, and read into memory otherwise,
+// e.g. when it is a pipe like /dev/stdin.  A log may be split over several files, see
+// log_fragments.rs.
+use std::fs::File;
+use std::io::Read;
+use std::ops::Deref;
+use std::path::Path;
+
+use anyhow::Context;
+use memmap2::Mmap;
+
+pub enum LogBytes {
+    Mapped(Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl LogBytes {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let mut file =
+            File::open(path).with_context(|| format!("Couldn't open {}", path.display()))?;
+        // A pipe has no length, so mapping it "succeeds" with nothing in it
+        if file.metadata()?.is_file() {
+            // SAFETY: logs are not modified while we parse them; if one is truncated underneath
+            // us we may crash, just as a reader would see a torn log
+            if let Ok(mmap) = unsafe { Mmap::map(&file) } {
+                return Ok(LogBytes::Mapped(mmap));
+            }
+        }
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .with_context(|| format!("Couldn't read {}", path.display()))?;
+        Ok(LogBytes::Buffered(buf))
+    }
+}
+
+impl Deref for LogBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            LogBytes::Mapped(mmap) => mmap,
+            LogBytes::Buffered(buf) => buf,
+        }
+    }
+}
+
+/// A line of a log, without its line ending
+#[derive(Debug, Clone, Copy)]
+pub struct LogLine<'a> {
+    /// 1-indexed
+    pub lineno: usize,
+    pub log: &'a [u8],
+    /// Byte range of the line in `log`
+    pub start: usize,
+    pub end: usize,
+}
+
+impl<'a> LogLine<'a> {
+    pub fn bytes(&self) -> &'a [u8] {
+        &self.log[self.start..self.end]
+    }
+
+    /// Whether this is a line of the payload of an envelope
+    pub fn is_payload(&self) -> bool {
+        self.bytes().starts_with(b"\t")
+    }
+}
+
+/// The lines of the fragments of a log, numbered as if the fragments were one file.  Lines are
+/// bytes, since a log may have some that aren't UTF-8; each is decoded on its own when it is
+/// parsed.
+pub fn log_lines(fragments: &[LogBytes]) -> impl Iterator<Item = LogLine<'_>> {
+    let mut first_lineno = 0;
+    fragments.iter().flat_map(move |log| {
+        let lines = fragment_lines(log, first_lineno);
+        first_lineno += num_lines(log);
+        lines
+    })
+}
+
+fn fragment_lines(log: &[u8], first_lineno: usize) -> impl Iterator<Item = LogLine<'_>> {
+    let mut start = 0;
+    log.split(|b| *b == b'\n')
+        .enumerate()
+        .filter_map(move |(i, l)| {
+            let line_start = start;
+            start += l.len() + 1;
+            let len = l.strip_suffix(b"\r").unwrap_or(l).len();
+            // NB: Sometimes, the log output we get from Logarithm stutters with a blank line.
+            // Filter them out, they're never valid (a blank line in payload will still be \t)
+            (len > 0).then_some(LogLine {
+                lineno: first_lineno + i + 1,
+                log,
+                start: line_start,
+                end: line_start + len,
+            })
+        })
+}
+
+fn num_lines(log: &[u8]) -> usize {
+    let newlines = log.iter().filter(|b| **b == b'\n').count();
+    newlines + usize::from(!log.is_empty() && !log.ends_with(b"\n"))
+}
+
+/// The text of the fragments of a log as one file, e.g. for raw.log
+pub fn log_text(fragments: &[LogBytes]) -> String {
+    let mut text = String::with_capacity(fragments.iter().map(|log| log.len() + 1).sum());
+    for log in fragments {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&String::from_utf8_lossy(log));
+    }
+    text
+}
+
+/// Number of byte sequences of `bytes` that aren't UTF-8, which decoding replaces with U+FFFD
+pub fn invalid_utf8_sequences(bytes: &[u8]) -> u64 {
+    bytes
+        .utf8_chunks()
+        .filter(|c| !c.invalid().is_empty())
+        .count() as u64
+}
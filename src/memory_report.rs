@@ -0,0 +1,137 @@
+// Inductor's memory planning, logged as an inductor_memory_planning artifact: the buffers of a
+// compiled graph with their sizes and the steps (scheduler nodes) they are live for, and the peak
+// memory Inductor estimated from them.  These are the `BufferInfo`s of `compute_memory_timeline`
+// and the peak of `estimate_peak_memory` in torch/_inductor/memory.py, whose field names
+// (`size_alloc`, `start_step`, `end_step`) are accepted as well.
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use crate::code_size::format_bytes;
+use crate::directory_layout::DirectoryLayout;
+use crate::templates::{TEMPLATE_FAILURES_CSS, TEMPLATE_QUERY_PARAM_SCRIPT};
+use crate::types::CompileId;
+use crate::types::{MemoryBuffer, MemoryReportContext};
+
+// Allocations listed as the largest of a compile id
+const NUM_LARGEST: usize = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct MemoryPlan {
+    /// In bytes; computed from the buffers if it wasn't logged
+    pub peak_memory: Option<u64>,
+    #[serde(default)]
+    pub buffers: Vec<PlannedBuffer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlannedBuffer {
+    pub name: String,
+    /// In bytes
+    #[serde(alias = "size_alloc")]
+    pub size: u64,
+    /// The steps the buffer is allocated at and freed after
+    #[serde(alias = "start_step")]
+    pub start: usize,
+    #[serde(alias = "end_step")]
+    pub end: usize,
+}
+
+impl PlannedBuffer {
+    /// The steps the buffer is live for, None if they don't fit in a usize
+    fn steps(&self) -> Option<Range<usize>> {
+        Some(self.start.min(self.end)..self.end.checked_add(1)?)
+    }
+}
+
+/// The number of steps of the plan, and the step with the most bytes live with how many, the
+/// first if there are several.  Buffers are added where they start and subtracted where they
+/// end, and the steps in between summed up, so this doesn't depend on how long buffers live.
+fn peak(buffers: &[PlannedBuffer]) -> (usize, Option<(usize, u64)>) {
+    let mut deltas: Vec<(usize, i128)> = buffers
+        .iter()
+        .filter_map(|b| b.steps().map(|steps| (b, steps)))
+        .flat_map(|(b, steps)| {
+            [
+                (steps.start, b.size as i128),
+                (steps.end, -(b.size as i128)),
+            ]
+        })
+        .collect();
+    deltas.sort_unstable_by_key(|(step, _)| *step);
+    let num_steps = deltas.last().map_or(0, |(step, _)| *step);
+    let mut live: i128 = 0;
+    let mut peak: Option<(usize, u64)> = None;
+    for (i, (step, delta)) in deltas.iter().enumerate() {
+        live += delta;
+        // Only once all the changes at the step are in
+        if deltas.get(i + 1).is_some_and(|(next, _)| next == step) || *step == num_steps {
+            continue;
+        }
+        let bytes = u64::try_from(live).unwrap_or(u64::MAX);
+        if peak.is_none_or(|(_, most)| bytes > most) {
+            peak = Some((*step, bytes));
+        }
+    }
+    (num_steps, peak)
+}
+
+impl MemoryPlan {
+    /// The estimated peak memory in bytes
+    pub fn peak_memory(&self) -> u64 {
+        self.peak_memory
+            .unwrap_or_else(|| peak(&self.buffers).1.map_or(0, |(_, bytes)| bytes))
+    }
+}
+
+/// The estimated peak memory of `plan` in bytes, and the context of its memory_report.html.
+/// Buffers whose steps don't fit in a usize are left out.
+pub fn memory_report(
+    plan: &MemoryPlan,
+    compile_id: Option<&CompileId>,
+    layout: DirectoryLayout,
+) -> (u64, MemoryReportContext) {
+    let (num_steps, peak_at) = peak(&plan.buffers);
+    let peak_step = peak_at.map(|(step, _)| step);
+    let peak = plan.peak_memory();
+    let num_steps = num_steps.max(1) as f64;
+    let to_buffer = |(b, steps): (&PlannedBuffer, Range<usize>)| MemoryBuffer {
+        name: b.name.clone(),
+        size: format_bytes(b.size),
+        start: b.start,
+        end: b.end,
+        left: format!("{:.1}", steps.start as f64 / num_steps * 100.0),
+        width: format!("{:.1}", steps.len() as f64 / num_steps * 100.0),
+        live_at_peak: peak_step.is_some_and(|s| steps.contains(&s)),
+    };
+    let planned: Vec<(&PlannedBuffer, Range<usize>)> = plan
+        .buffers
+        .iter()
+        .filter_map(|b| b.steps().map(|steps| (b, steps)))
+        .collect();
+    let mut largest = planned.clone();
+    // Stable, so ties stay in the order they were logged
+    largest.sort_by_key(|(b, _)| std::cmp::Reverse(b.size));
+    let largest = largest
+        .into_iter()
+        .take(NUM_LARGEST)
+        .map(to_buffer)
+        .collect();
+    let total_allocated = planned.iter().map(|(b, _)| b.size).sum();
+    let num_buffers = planned.len();
+    let mut buffers: Vec<MemoryBuffer> = planned.into_iter().map(to_buffer).collect();
+    buffers.sort_by_key(|b| (b.start, b.end));
+    let context = MemoryReportContext {
+        css: TEMPLATE_FAILURES_CSS,
+        root: layout.root_link(compile_id),
+        compile_id: compile_id.map_or("(unknown)".to_string(), |c| c.to_string()),
+        peak_memory: format_bytes(peak),
+        peak_step: peak_step.map_or(String::new(), |s| s.to_string()),
+        num_buffers,
+        total_allocated: format_bytes(total_allocated),
+        largest,
+        buffers,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    };
+    (peak, context)
+}
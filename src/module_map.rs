@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use html_escape::{encode_double_quoted_attribute, encode_text};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// e.g. `# nn_module_stack: {'L__self___layers_3_attn': ('layers.3.attn', <class 'Attention'>)}`,
+// which newer graph dumps print above the nodes of a module
+static MODULE_STACK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^#\s*(?:nn_module_stack|Module stack):\s*(.*)$").unwrap());
+static MODULE_STACK_ENTRY: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(\s*'([^']*)'").unwrap());
+// Parameters and buffers that Dynamo lifted to inputs, e.g.
+// l_self_modules_layers_modules_3_modules_attn_parameters_weight_
+static MODULE_INPUT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b[lL]_self_modules_(\w+?)_(?:parameters|buffers)_").unwrap());
+static NODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([A-Za-z_]\w*)\s*(?::[^=]*)?=").unwrap());
+
+/// Nodes of a pre-grad graph by the nn.Module that they belong to, e.g. `layers.3.attn`
+#[derive(Debug, Default)]
+pub struct ModuleMap {
+    /// Module path => (node, line) with 1-based lines of the graph dump
+    modules: BTreeMap<String, Vec<(String, usize)>>,
+}
+
+// The innermost module of an nn_module_stack comment
+fn innermost_module(stack: &str) -> Option<String> {
+    MODULE_STACK_ENTRY
+        .captures_iter(stack)
+        .last()
+        .map(|c| c[1].to_string())
+        .filter(|m| !m.is_empty())
+}
+
+// The module of the first parameter or buffer that `code` uses
+fn module_of_inputs(code: &str) -> Option<String> {
+    MODULE_INPUT
+        .captures(code)
+        .map(|c| c[1].split("_modules_").collect::<Vec<_>>().join("."))
+}
+
+impl ModuleMap {
+    /// Assigns each node of the graph to the module of its nn_module_stack, or failing that to
+    /// the module whose parameters or buffers it uses
+    pub fn new(pre_grad_graph: &str) -> Self {
+        let mut modules: BTreeMap<String, Vec<(String, usize)>> = BTreeMap::new();
+        let mut module_stack: Option<String> = None;
+        for (i, line) in pre_grad_graph.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                // Blank lines separate nodes with different metadata
+                module_stack = None;
+                continue;
+            }
+            if let Some(c) = MODULE_STACK.captures(line) {
+                module_stack = innermost_module(&c[1]);
+                continue;
+            }
+            let Some(c) = NODE.captures(line) else {
+                continue;
+            };
+            let node = &c[1];
+            // Parameters and buffers themselves, e.g. l_self_modules_fc1_parameters_weight_ =
+            // L_self_modules_fc1_parameters_weight_, belong to their module
+            let (_, code) = line.split_once('=').unwrap_or_default();
+            let module = module_stack
+                .clone()
+                .or_else(|| module_of_inputs(node))
+                .or_else(|| module_of_inputs(code));
+            if let Some(module) = module {
+                modules
+                    .entry(module)
+                    .or_default()
+                    .push((node.to_string(), i + 1));
+            }
+        }
+        ModuleMap { modules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// The modules as nested lists, each with the graph lines of its nodes and its submodules'
+    /// in `data-lines`, for highlighting them in the provenance page
+    pub fn tree_html(&self) -> String {
+        // Module path => lines of it and its submodules
+        let mut lines: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (module, nodes) in &self.modules {
+            let mut prefix_end = Some(module.len());
+            while let Some(end) = prefix_end {
+                let prefix = &module[..end];
+                lines
+                    .entry(prefix)
+                    .or_default()
+                    .extend(nodes.iter().map(|(_, line)| *line));
+                prefix_end = prefix.rfind('.');
+            }
+        }
+        let mut html = String::from("<ul class=\"module-tree\">\n");
+        let mut open: Vec<&str> = Vec::new();
+        for (module, module_lines) in &mut lines {
+            module_lines.sort_unstable();
+            while open
+                .last()
+                .is_some_and(|parent| !module.starts_with(&format!("{parent}.")))
+            {
+                open.pop();
+                html.push_str("</ul></li>\n");
+            }
+            let name = open
+                .last()
+                .map_or(*module, |parent| &module[parent.len() + 1..]);
+            let data_lines: Vec<String> = module_lines.iter().map(|l| l.to_string()).collect();
+            let own_nodes: Vec<&str> = self
+                .modules
+                .get(*module)
+                .into_iter()
+                .flatten()
+                .map(|(node, _)| node.as_str())
+                .collect();
+            write!(
+                html,
+                "<li><span class=\"module\" data-module=\"{}\" data-lines=\"{}\">{}</span> \
+                 <span class=\"module-size\">{} node(s)</span>",
+                encode_double_quoted_attribute(module),
+                data_lines.join(","),
+                encode_text(name),
+                module_lines.len(),
+            )
+            .unwrap();
+            if !own_nodes.is_empty() {
+                write!(
+                    html,
+                    " <code class=\"module-nodes\">{}</code>",
+                    encode_text(&own_nodes.join(", "))
+                )
+                .unwrap();
+            }
+            html.push_str("\n<ul>\n");
+            open.push(module);
+        }
+        for _ in open {
+            html.push_str("</ul></li>\n");
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+}
@@ -0,0 +1,223 @@
+// OpenTelemetry spans of the compilations in a log, written to otel_spans.json in the OTLP JSON
+// encoding and optionally sent to an OTLP/HTTP collector.
+//
+// Each compile id becomes a span from its first to its last envelope, with the dynamo, AOT and
+// inductor phases of its chromium events nested under it.  Sending needs the otlp feature and
+// shells out to curl, like uploads shell out to the AWS CLI, rather than linking an HTTP client.
+#[cfg(feature = "otlp")]
+use std::path::Path;
+#[cfg(feature = "otlp")]
+use std::process::Command;
+
+#[cfg(feature = "otlp")]
+use anyhow::{bail, Context};
+use fxhash::FxHashMap;
+use serde_json::{json, Value};
+
+use crate::glog::{timestamp_us, GlogPrefix};
+use crate::types::{CompileId, FxIndexMap};
+
+/// When each compile id was logged, and how the glog clock relates to the chromium events'
+#[derive(Debug, Default)]
+pub struct SpanIndex {
+    // First and last envelope of each compile id, in microseconds of `timestamp_us`
+    compile_ids: FxIndexMap<String, (i64, i64)>,
+    // Chromium event timestamps minus the glog timestamps of their envelopes, in microseconds.
+    // glog doesn't log the year or the time zone, chromium events are in Unix time.
+    clock_offset_us: Option<f64>,
+}
+
+impl SpanIndex {
+    pub fn record(&mut self, prefix: &GlogPrefix, compile_id: &Option<CompileId>) {
+        let Some(compile_id) = compile_id else {
+            return;
+        };
+        // The compile id as PyTorch prints it in the chromium events
+        let name = compile_id.to_string();
+        let name = name.trim_start_matches('[').trim_end_matches(']');
+        let t = timestamp_us(prefix);
+        self.compile_ids
+            .entry(name.to_string())
+            .and_modify(|(first, last)| {
+                *first = (*first).min(t);
+                *last = (*last).max(t);
+            })
+            .or_insert((t, t));
+    }
+
+    /// Lines the glog clock up with the chromium events' clock, using an event and the prefix of
+    /// its envelope
+    pub fn calibrate(&mut self, prefix: &GlogPrefix, event: &Value) {
+        if self.clock_offset_us.is_some() {
+            return;
+        }
+        if let Some(ts) = event["ts"].as_f64() {
+            self.clock_offset_us = Some(ts - timestamp_us(prefix) as f64);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.compile_ids.is_empty()
+    }
+}
+
+fn hex_id(parts: impl std::hash::Hash) -> String {
+    format!("{:016x}", fxhash::hash64(&parts))
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({"key": key, "value": {"stringValue": value}})
+}
+
+fn span(
+    trace_id: &str,
+    span_id: String,
+    parent_span_id: Option<&str>,
+    name: &str,
+    (start_ns, end_ns): (i64, i64),
+    compile_id: &str,
+) -> Value {
+    let mut span = json!({
+        "traceId": trace_id,
+        "spanId": span_id,
+        "name": name,
+        // SPAN_KIND_INTERNAL
+        "kind": 1,
+        // 64-bit integers are strings in OTLP JSON
+        "startTimeUnixNano": start_ns.to_string(),
+        "endTimeUnixNano": end_ns.max(start_ns).to_string(),
+        "attributes": [attribute("torch.compile_id", compile_id)],
+    });
+    if let Some(parent) = parent_span_id {
+        span["parentSpanId"] = json!(parent);
+    }
+    span
+}
+
+fn ns(ts_us: f64) -> i64 {
+    (ts_us * 1000.0).round() as i64
+}
+
+/// The spans of `index` and `chromium_events` as an OTLP ExportTraceServiceRequest.  Without
+/// chromium events to line the clocks up, glog timestamps are taken to be UTC in `year`.  The
+/// ids of the spans of a rank's log are its own, since other ranks compile the same compile ids
+/// at about the same time.
+pub fn otlp_spans(
+    index: &SpanIndex,
+    chromium_events: &[Value],
+    year: i32,
+    rank: Option<u32>,
+) -> Value {
+    let offset_us = index.clock_offset_us.unwrap_or_else(|| {
+        chrono::NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map_or(0.0, |t| t.and_utc().timestamp_micros() as f64)
+    });
+    let first_us = index.compile_ids.values().map(|(first, _)| *first).min();
+    let trace_id = format!(
+        "{}{}",
+        hex_id(("tlparse", rank, first_us)),
+        hex_id(("trace", rank, first_us))
+    );
+
+    let mut spans: Vec<Value> = Vec::new();
+    let mut compile_span_ids: FxHashMap<&str, String> = FxHashMap::default();
+    for (compile_id, (first, last)) in &index.compile_ids {
+        let span_id = hex_id(("compile", rank, compile_id));
+        spans.push(span(
+            &trace_id,
+            span_id.clone(),
+            None,
+            &format!("torch.compile [{compile_id}]"),
+            (ns(*first as f64 + offset_us), ns(*last as f64 + offset_us)),
+            compile_id,
+        ));
+        compile_span_ids.insert(compile_id, span_id);
+    }
+
+    // Phases that began but didn't end yet on each (pid, tid): name, compile id, start and id
+    let mut open: FxHashMap<String, Vec<(String, String, f64, String)>> = FxHashMap::default();
+    for (i, event) in chromium_events.iter().enumerate() {
+        let track = format!("{}/{}", event["pid"], event["tid"]);
+        let compile_id = event["args"]["compile_id"].as_str();
+        let ts = event["ts"].as_f64().unwrap_or_default();
+        let name = event["name"].as_str().unwrap_or_default();
+        let stack = open.entry(track).or_default();
+        let parent = |stack: &[(String, String, f64, String)], compile_id: &str| {
+            stack
+                .iter()
+                .rev()
+                .find(|(_, c, _, _)| c == compile_id)
+                .map(|(_, _, _, id)| id.clone())
+                .or_else(|| compile_span_ids.get(compile_id).cloned())
+        };
+        match event["ph"].as_str() {
+            Some("B") => {
+                let Some(compile_id) = compile_id.filter(|c| compile_span_ids.contains_key(c))
+                else {
+                    continue;
+                };
+                let span_id = hex_id(("phase", rank, i));
+                stack.push((name.to_string(), compile_id.to_string(), ts, span_id));
+            }
+            Some("E") => {
+                let Some(pos) = stack.iter().rposition(|(n, _, _, _)| n == name) else {
+                    continue;
+                };
+                let (name, compile_id, start, span_id) = stack.remove(pos);
+                let parent = parent(&stack[..pos], &compile_id);
+                spans.push(span(
+                    &trace_id,
+                    span_id,
+                    parent.as_deref(),
+                    &name,
+                    (ns(start), ns(ts)),
+                    &compile_id,
+                ));
+            }
+            Some("X") => {
+                let Some(compile_id) = compile_id.filter(|c| compile_span_ids.contains_key(c))
+                else {
+                    continue;
+                };
+                let dur = event["dur"].as_f64().unwrap_or_default();
+                spans.push(span(
+                    &trace_id,
+                    hex_id(("phase", rank, i)),
+                    parent(stack, compile_id).as_deref(),
+                    name,
+                    (ns(ts), ns(ts + dur)),
+                    compile_id,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    json!({
+        "resourceSpans": [{
+            "resource": {"attributes": [attribute("service.name", "torch.compile")]},
+            "scopeSpans": [{
+                "scope": {"name": "tlparse", "version": env!("CARGO_PKG_VERSION")},
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+/// Sends otel_spans.json to the OTLP/HTTP collector at `endpoint`, e.g. http://localhost:4318
+#[cfg(feature = "otlp")]
+pub fn export_spans(spans: &Path, endpoint: &str) -> anyhow::Result<()> {
+    let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    let status = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "-X", "POST"])
+        .args(["-H", "Content-Type: application/json", "--data-binary"])
+        .arg(format!("@{}", spans.display()))
+        .arg(&url)
+        .status()
+        .context("Couldn't run curl to send the spans")?;
+    if !status.success() {
+        bail!("Sending the spans to {url} failed ({status})");
+    }
+    Ok(())
+}
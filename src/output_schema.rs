@@ -0,0 +1,62 @@
+use anyhow::bail;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::types::{CompileDirectory, ParseOutput, COMPILE_DIRECTORY_SCHEMA_VERSION};
+use crate::{CompileSummary, TrendRecord};
+
+/// `content` as a `T`, if it is one: it has the fields `T` declares with their types, and no
+/// others, i.e. writing it back out as a `T` gives the same JSON
+fn check<T: DeserializeOwned + Serialize>(content: &str) -> Result<T, String> {
+    let json: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let parsed: T = serde_json::from_value(json.clone()).map_err(|e| e.to_string())?;
+    let written = serde_json::to_value(&parsed).map_err(|e| e.to_string())?;
+    if written != json {
+        return Err("has fields that its schema doesn't declare".to_string());
+    }
+    Ok(parsed)
+}
+
+fn check_compile_directory(content: &str) -> Result<(), String> {
+    let directory = check::<CompileDirectory>(content)?;
+    if directory.schema_version != COMPILE_DIRECTORY_SCHEMA_VERSION {
+        return Err(format!(
+            "schema_version is {}, expected {COMPILE_DIRECTORY_SCHEMA_VERSION}",
+            directory.schema_version
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the JSON files of `output` against their schemas: compile_directory.json,
+/// compile_summary.json and trend_record.json against the structs they're written from, the
+/// chromium events against the trace event format, and every other JSON file for being JSON at
+/// all
+pub fn validate_output(output: &ParseOutput) -> anyhow::Result<()> {
+    let mut problems: Vec<String> = Vec::new();
+    for (path, content) in output {
+        if path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let result = match file_name.as_ref() {
+            "compile_directory.json" => check_compile_directory(content),
+            "compile_summary.json" => check::<CompileSummary>(content).map(drop),
+            "trend_record.json" => check::<TrendRecord>(content).map(drop),
+            "chromium_events.json" => check::<Vec<Map<String, Value>>>(content).map(drop),
+            _ => check::<Value>(content).map(drop),
+        };
+        if let Err(e) = result {
+            problems.push(format!("{}: {e}", path.display()));
+        }
+    }
+    if !problems.is_empty() {
+        bail!(
+            "{} output file(s) don't match their schema:\n{}",
+            problems.len(),
+            problems.join("\n")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+// Where the time of a parse goes by parser, e.g. to tell whether syntax highlighting the output
+// code is what makes a large log slow.  Timings cover running the parser and adding its outputs.
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::types::{FxIndexMap, ParserTiming};
+
+#[derive(Default)]
+pub struct ParserProfile {
+    // Time, calls, outputs and output bytes of each parser, in the order they first ran
+    parsers: FxIndexMap<&'static str, (Duration, u64, u64, u64)>,
+}
+
+impl ParserProfile {
+    /// Records one run of `parser`, which took `elapsed` and wrote `outputs`
+    pub fn record(
+        &mut self,
+        parser: &'static str,
+        elapsed: Duration,
+        outputs: &[(PathBuf, String)],
+    ) {
+        let (time, calls, num_outputs, output_bytes) = self.parsers.entry(parser).or_default();
+        *time += elapsed;
+        *calls += 1;
+        *num_outputs += outputs.len() as u64;
+        *output_bytes += outputs.iter().map(|(_, c)| c.len() as u64).sum::<u64>();
+    }
+
+    /// Time spent in each parser, slowest first, to the microsecond
+    pub fn timings(&self) -> Vec<ParserTiming> {
+        let mut timings: Vec<ParserTiming> = self
+            .parsers
+            .iter()
+            .map(
+                |(parser, &(time, calls, outputs, output_bytes))| ParserTiming {
+                    parser: parser.to_string(),
+                    calls,
+                    outputs,
+                    output_bytes,
+                    total_s: (time.as_secs_f64() * 1e6).round() / 1e6,
+                    mean_ms: (time.as_secs_f64() / calls as f64 * 1e6).round() / 1e3,
+                },
+            )
+            .collect();
+        timings.sort_by(|a, b| b.total_s.total_cmp(&a.total_s));
+        timings
+    }
+}
@@ -1,9 +1,20 @@
-use crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT;
+use crate::directory_layout::DirectoryLayout;
+use crate::frame_locals::{render_frame_locals, render_locals};
+use crate::fx_graph::{dump_hash, FxGraph};
+use crate::source_snippets::SourceSnippets;
+use crate::templates::{
+    EXPORTED_PROGRAM_CSS, SYMBOLIC_EXPRESSIONS_CSS, TEMPLATE_FAILURES_CSS,
+    TEMPLATE_QUERY_PARAM_SCRIPT,
+};
 use crate::{types::*, ParseConfig};
+use fxhash::{FxHashMap, FxHashSet};
 use html_escape::encode_text;
+use md5::{Digest, Md5};
+use regex::Regex;
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::{OsStr, OsString};
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use tinytemplate::TinyTemplate;
@@ -20,11 +31,15 @@ fn format_json_pretty(payload: &str) -> Result<String, anyhow::Error> {
     }
 }
 
+use once_cell::sync::Lazy;
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
 
 // Re-export types from types.rs for external use
-pub use crate::types::{CompileId, EmptyMetadata, Envelope, GraphRuntime, Metadata, OpRuntime};
+pub use crate::types::{
+    CompileId, EmptyMetadata, Envelope, GraphMemory, GraphRuntime, Metadata, OpRuntime,
+};
 
 pub enum ParserOutput {
     File(PathBuf, String),       // File to be saved on disk
@@ -32,8 +47,15 @@ pub enum ParserOutput {
     PayloadFile(PathBuf),        // File using payload directly from log entry
     PayloadReformatFile(PathBuf, fn(&str) -> Result<String, anyhow::Error>), // File using reformatted payload from log entry
     Link(String, String), // External href to (name, url) (linked in compile_directory, not returned)
+    // Like File, but the content is written by the callback, so that very large outputs (e.g.
+    // output code) are streamed to disk rather than built up as a String, when the report is
+    // parsed straight into a directory (see parse_path_to_directory)
+    FileWriter(PathBuf, FileWriter),
 }
 
+// Writes the content of a ParserOutput::FileWriter
+pub type FileWriter = Box<dyn FnOnce(&mut dyn Write) -> std::io::Result<()>>;
+
 // Each parser returns a list of files to save and links to render in compile directory
 pub type ParserResults = Vec<ParserOutput>;
 
@@ -63,7 +85,8 @@ pub trait StructuredLogParser {
     fn name(&self) -> &'static str;
 }
 
-// Helper function to build file path with compile ID directory
+// Helper function to build file path with compile ID directory.  Files under it are moved to
+// the directory that ParseConfig::directory_layout gives the compile id when they are written.
 fn build_file_path(filename: &str, lineno: usize, compile_id: &Option<CompileId>) -> PathBuf {
     let compile_id_dir: PathBuf = compile_id
         .as_ref()
@@ -199,6 +222,7 @@ impl StructuredLogParser for DynamoOutputGraphParser {
 
 pub struct DynamoGuardParser<'t> {
     tt: &'t TinyTemplate<'t>,
+    layout: DirectoryLayout,
 }
 impl StructuredLogParser for DynamoGuardParser<'_> {
     fn name(&self) -> &'static str {
@@ -218,6 +242,7 @@ impl StructuredLogParser for DynamoGuardParser<'_> {
         let filename = format!("{}.html", self.name());
         let guards = serde_json::from_str::<Vec<DynamoGuard>>(payload)?;
         let guards_context = DynamoGuardsContext {
+            root: self.layout.root_link(compile_id.as_ref()),
             guards,
             qps: TEMPLATE_QUERY_PARAM_SCRIPT,
         };
@@ -226,15 +251,33 @@ impl StructuredLogParser for DynamoGuardParser<'_> {
     }
 }
 
+/// Syntect's default syntaxes and themes.  Loading them takes far longer than highlighting a
+/// typical artifact, so they're loaded once, when the first artifact is highlighted, and shared by
+/// all parsers.
+struct Syntect {
+    syntax_set: SyntaxSet,
+    theme_set: ThemeSet,
+}
+
+static SYNTECT: Lazy<Syntect> = Lazy::new(|| Syntect {
+    syntax_set: SyntaxSet::load_defaults_newlines(),
+    theme_set: ThemeSet::load_defaults(),
+});
+
 pub struct InductorOutputCodeParser {
     // If true we output the code as plain text, otherwise we output it as rendered html
     plain_text: bool,
+    // Syntect theme used for the rendered html
+    syntect_theme: &'static str,
+    syntect: &'static Lazy<Syntect>,
 }
 
 impl InductorOutputCodeParser {
     pub fn new(config: &ParseConfig) -> Self {
         InductorOutputCodeParser {
             plain_text: config.plain_text,
+            syntect_theme: config.theme.syntect_theme(),
+            syntect: &SYNTECT,
         }
     }
 }
@@ -285,12 +328,13 @@ impl StructuredLogParser for InductorOutputCodeParser {
             if self.plain_text {
                 payload_file_output(&filename.to_string_lossy(), lineno, compile_id)
             } else {
-                let output_content = match generate_html_output(payload) {
-                    Ok(html) => html,
-                    Err(_e) => {
-                        return Err(anyhow::anyhow!("Failed to parse inductor code to html"))
-                    }
-                };
+                let output_content =
+                    match generate_html_output(payload, self.syntect, self.syntect_theme) {
+                        Ok(html) => html,
+                        Err(_e) => {
+                            return Err(anyhow::anyhow!("Failed to parse inductor code to html"))
+                        }
+                    };
                 simple_file_output(
                     &filename.to_string_lossy(),
                     lineno,
@@ -304,17 +348,270 @@ impl StructuredLogParser for InductorOutputCodeParser {
     }
 }
 
-fn generate_html_output(payload: &str) -> Result<String, anyhow::Error> {
-    let syntax_set = SyntaxSet::load_defaults_newlines();
-    let theme_set = ThemeSet::load_defaults();
+// Like syntect::html::highlighted_html_for_string, with an anchor per line (L1, L2, ...) for
+// linking to kernels
+fn generate_html_output(
+    payload: &str,
+    syntect: &Syntect,
+    theme: &str,
+) -> Result<String, anyhow::Error> {
+    let syntax_set = &syntect.syntax_set;
     let syntax = syntax_set.find_syntax_by_extension("py").unwrap();
-    let html = syntect::html::highlighted_html_for_string(
-        &payload,
-        &syntax_set,
-        &syntax,
-        &theme_set.themes["InspiredGitHub"],
-    );
-    Ok(html?)
+    let theme = &syntect.theme_set.themes[theme];
+    let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
+    let (mut html, bg) = syntect::html::start_highlighted_html_snippet(theme);
+    for (i, line) in LinesWithEndings::from(payload).enumerate() {
+        let regions = highlighter.highlight_line(line, syntax_set)?;
+        html.push_str(&format!("<span id=\"L{}\">", i + 1));
+        html.push_str(&syntect::html::styled_line_to_highlighted_html(
+            &regions,
+            syntect::html::IncludeBackground::IfDifferent(bg),
+        )?);
+        html.push_str("</span>");
+    }
+    html.push_str("</pre>\n");
+    Ok(html)
+}
+
+/// Renders the ExportedProgram printed by export: the graph module is highlighted with an anchor
+/// per line, the graph signature is indented one spec per line, and range constraints get a row
+/// (and anchor) per symbol.
+pub struct ExportedProgramParser<'t> {
+    tt: &'t TinyTemplate<'t>,
+    syntect_theme: &'static str,
+    syntect: &'static Lazy<Syntect>,
+    layout: DirectoryLayout,
+    legacy_artifact_numbering: bool,
+    dedup_payloads: bool,
+    /// Exported programs so far of each compile id, which number their exported_program.txt
+    programs: RefCell<FxHashMap<Option<CompileId>, i32>>,
+}
+
+impl<'t> ExportedProgramParser<'t> {
+    pub fn new(tt: &'t TinyTemplate<'t>, config: &ParseConfig) -> Self {
+        ExportedProgramParser {
+            tt,
+            layout: config.directory_layout,
+            syntect_theme: config.theme.syntect_theme(),
+            syntect: &SYNTECT,
+            legacy_artifact_numbering: config.legacy_artifact_numbering,
+            dedup_payloads: config.dedup_payloads,
+            programs: RefCell::new(FxHashMap::default()),
+        }
+    }
+
+    /// The name `run_parser` gives to the exported_program.txt of `payload`, as linked from the
+    /// exported_program.html next to it.  Unknown with legacy numbering, which numbers files by
+    /// their position among all outputs.
+    fn text_url(&self, compile_id: &Option<CompileId>, payload: &str) -> Option<String> {
+        let mut programs = self.programs.borrow_mut();
+        let number = programs.entry(compile_id.clone()).or_default();
+        *number += 1;
+        if self.dedup_payloads {
+            let md5: String = Md5::digest(payload.as_bytes())
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect();
+            return Some(format!(
+                "{}blobs/{md5}.txt",
+                self.layout.root_link(compile_id.as_ref())
+            ));
+        }
+        (!self.legacy_artifact_numbering).then(|| format!("exported_program_{}.txt", *number - 1))
+    }
+}
+
+impl StructuredLogParser for ExportedProgramParser<'_> {
+    fn name(&self) -> &'static str {
+        "exported_program"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        e.exported_program.as_ref().map(Metadata::Empty)
+    }
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        _metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let program = split_exported_program(payload);
+
+        let syntax_set = &self.syntect.syntax_set;
+        let syntax = syntax_set.find_syntax_by_extension("py").unwrap();
+        let theme = &self.syntect.theme_set.themes[self.syntect_theme];
+        let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
+        let mut graph_lines = Vec::new();
+        for (i, line) in LinesWithEndings::from(program.graph).enumerate() {
+            let regions = highlighter.highlight_line(line, syntax_set)?;
+            graph_lines.push(ExportedProgramLine {
+                lineno: i + 1,
+                html: syntect::html::styled_line_to_highlighted_html(
+                    &regions,
+                    syntect::html::IncludeBackground::No,
+                )?,
+            });
+        }
+        let (pre_open, _) = syntect::html::start_highlighted_html_snippet(theme);
+
+        let context = ExportedProgramContext {
+            root: self.layout.root_link(compile_id.as_ref()),
+            text_url: self.text_url(compile_id, payload),
+            css: EXPORTED_PROGRAM_CSS,
+            pre_open,
+            graph_lines,
+            graph_signature: program.graph_signature.map(pretty_print_signature),
+            range_constraints: program
+                .range_constraints
+                .map(parse_range_constraints)
+                .unwrap_or_default(),
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        };
+        let html = self.tt.render("exported_program.html", &context)?;
+        let mut results = payload_file_output("exported_program.txt", lineno, compile_id)?;
+        results.push(ParserOutput::File(
+            build_file_path("exported_program.html", lineno, compile_id),
+            html,
+        ));
+        Ok(results)
+    }
+}
+
+struct ExportedProgramSections<'p> {
+    graph: &'p str,
+    graph_signature: Option<&'p str>,
+    range_constraints: Option<&'p str>,
+}
+
+// The printed ExportedProgram is the graph module code, followed by one line each for the graph
+// signature and the range constraints.
+fn split_exported_program(payload: &str) -> ExportedProgramSections<'_> {
+    let mut graph_end = payload.len();
+    let mut graph_signature = None;
+    let mut range_constraints = None;
+    let mut offset = 0;
+    for line in payload.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("Graph signature:") {
+            graph_end = graph_end.min(offset);
+            graph_signature = Some(rest.trim());
+        } else if let Some(rest) = trimmed.strip_prefix("Range constraints:") {
+            graph_end = graph_end.min(offset);
+            range_constraints = Some(rest.trim());
+        }
+        offset += line.len();
+    }
+    ExportedProgramSections {
+        graph: payload[..graph_end].trim_end(),
+        graph_signature,
+        range_constraints,
+    }
+}
+
+// Break the one-line ExportGraphSignature(input_specs=[...], output_specs=[...]) repr so that every
+// spec is on its own line.  Only the outer two levels of brackets are broken up.
+fn pretty_print_signature(signature: &str) -> String {
+    const INDENT: &str = "    ";
+    let mut out = String::new();
+    let mut depth = 0usize;
+    let mut in_quote = false;
+    let mut chars = signature.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quote {
+            in_quote = c != '\'';
+            out.push(c);
+            continue;
+        }
+        match c {
+            '\'' => {
+                in_quote = true;
+                out.push(c);
+            }
+            '(' | '[' | '{' => {
+                depth += 1;
+                out.push(c);
+                if depth <= 2 && !matches!(chars.peek(), Some(')' | ']' | '}')) {
+                    out.push('\n');
+                    out.push_str(&INDENT.repeat(depth));
+                }
+            }
+            ')' | ']' | '}' => {
+                if depth <= 2 && !matches!(out.chars().last(), Some('(' | '[' | '{')) {
+                    out.push('\n');
+                    out.push_str(&INDENT.repeat(depth.saturating_sub(1)));
+                }
+                depth = depth.saturating_sub(1);
+                out.push(c);
+            }
+            ',' if depth <= 2 => {
+                out.push(c);
+                out.push('\n');
+                out.push_str(&INDENT.repeat(depth));
+                while chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Parse "{u0: VR[-int_oo, 15], s0: VR[2, int_oo]}" into one entry per symbol
+fn parse_range_constraints(constraints: &str) -> Vec<RangeConstraint> {
+    let inner = constraints
+        .trim()
+        .trim_start_matches('{')
+        .trim_end_matches('}');
+    let mut result = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                result.extend(parse_range_constraint(&inner[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    result.extend(parse_range_constraint(&inner[start..]));
+    result
+}
+
+fn parse_range_constraint(entry: &str) -> Option<RangeConstraint> {
+    let (symbol, range) = entry.split_once(':')?;
+    Some(RangeConstraint {
+        symbol: symbol.trim().to_string(),
+        range: range.trim().to_string(),
+    })
+}
+
+/// Anchor in exported_program.html that best explains `symbol`: the graph line that defines it
+/// (e.g. `_local_scalar_dense: "Sym(u1)" = ...`), else the first graph line mentioning it, else
+/// its range constraint.
+pub fn exported_program_anchor(payload: &str, symbol: &str) -> Option<String> {
+    let program = split_exported_program(payload);
+    let mentions = Regex::new(&format!(r"\b{}\b", regex::escape(symbol))).unwrap();
+    let definition = format!("\"Sym({symbol})\"");
+    let lines: Vec<&str> = program.graph.lines().collect();
+    let graph_line = lines
+        .iter()
+        .position(|l| l.contains(&definition))
+        .or_else(|| lines.iter().position(|l| mentions.is_match(l)));
+    if let Some(i) = graph_line {
+        return Some(format!("L{}", i + 1));
+    }
+    program
+        .range_constraints
+        .map(parse_range_constraints)
+        .unwrap_or_default()
+        .iter()
+        .find(|c| c.symbol == symbol)
+        .map(|c| format!("sym-{}", c.symbol))
 }
 
 pub struct OptimizeDdpSplitChildParser;
@@ -373,19 +670,32 @@ impl StructuredLogParser for LinkParser {
     }
 }
 
-fn format_stack(stack: &StackSummary, caption: &str, open: bool) -> String {
+// `sources` adds the source around each frame, for user stacks
+fn format_stack(
+    stack: &StackSummary,
+    intern_table: &InternTable,
+    sources: Option<&SourceSnippets>,
+    caption: &str,
+    open: bool,
+) -> String {
     let mut trie = StackTrieNode::default();
     trie.insert_no_terminal(stack.to_vec());
-    trie.fmt(None, caption, open).unwrap()
+    trie.fmt(intern_table, None, sources, caption, open)
+        .unwrap()
 }
 
 pub struct CompilationMetricsParser<'t> {
     pub tt: &'t TinyTemplate<'t>,
+    pub layout: DirectoryLayout,
     pub stack_index: &'t RefCell<StackIndex>,
     pub symbolic_shape_specialization_index: &'t RefCell<SymbolicShapeSpecializationIndex>,
     pub guard_added_fast_index: &'t RefCell<GuardAddedFastIndex>,
+    pub intern_table: &'t RefCell<InternTable>,
     pub output_files: &'t Vec<OutputFile>,
     pub compile_id_dir: &'t PathBuf,
+    pub sources: Option<&'t SourceSnippets>,
+    /// Frame locals captured with the guards of the compile, see `index_frame_locals`
+    pub frame_locals: Option<&'t FxHashMap<String, String>>,
 }
 impl StructuredLogParser for CompilationMetricsParser<'_> {
     fn name(&self) -> &'static str {
@@ -406,6 +716,7 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
     ) -> anyhow::Result<ParserResults> {
         let filename = format!("{}.html", self.name());
         if let Metadata::CompilationMetrics(m) = metrics {
+            let intern_table = self.intern_table.borrow();
             let id = compile_id
                 .clone()
                 .map_or("(unknown) ".to_string(), |c| format!("{cid} ", cid = c));
@@ -420,7 +731,9 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                 .stack_index
                 .borrow()
                 .get(&cid)
-                .map_or("".to_string(), |stack| format_stack(stack, "Stack", false));
+                .map_or("".to_string(), |stack| {
+                    format_stack(stack, &intern_table, self.sources, "Stack", false)
+                });
             let mini_stack_html = if let (Some(name), Some(filename), Some(line)) =
                 (&m.co_name, &m.co_filename, m.co_firstlineno)
             {
@@ -432,6 +745,8 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                         name: name.clone(),
                         loc: None,
                     }]),
+                    &intern_table,
+                    self.sources,
                     "Stack",
                     false,
                 )
@@ -450,11 +765,15 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                     value: spec.value.unwrap_or("".to_string()),
                     user_stack_html: format_stack(
                         &spec.user_stack.unwrap_or(Vec::new()),
+                        &intern_table,
+                        self.sources,
                         "User Stack",
                         false,
                     ),
                     stack_html: format_stack(
                         &spec.stack.unwrap_or(Vec::new()),
+                        &intern_table,
+                        None,
                         "Framework Stack",
                         false,
                     ),
@@ -470,22 +789,40 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                     expr: guard.expr.unwrap_or("".to_string()),
                     user_stack_html: format_stack(
                         &guard.user_stack.unwrap_or(Vec::new()),
+                        &intern_table,
+                        self.sources,
                         "User Stack",
                         false,
                     ),
                     stack_html: format_stack(
                         &guard.stack.unwrap_or(Vec::new()),
+                        &intern_table,
+                        None,
                         "Framework Stack",
                         false,
                     ),
                 })
                 .collect();
+            let extra_metrics = m
+                .extra
+                .iter()
+                .map(|(key, value)| ExtraMetric {
+                    key: key.clone(),
+                    value: match value {
+                        serde_json::Value::String(s) => s.clone(),
+                        v => v.to_string(),
+                    },
+                })
+                .collect();
+            let compile_id_dir = format!("{}/", self.compile_id_dir.display());
             let remove_prefix = |x: &String| -> String {
-                // url is X_Y_Z/<rest>. Get the rest of the string for the link
+                // Deduplicated payloads live outside of the compile directory
+                if x.starts_with("blobs/") {
+                    return format!("{}{x}", self.layout.root_link(compile_id.as_ref()));
+                }
+                // url is <compile_id_dir>/<rest>. Get the rest of the string for the link
                 // on compilation metrics page
-                let parts: Vec<_> = x.split("/").collect();
-                let new_str: String = parts[1..].join("");
-                new_str
+                x.strip_prefix(&compile_id_dir).unwrap_or(x).to_string()
             };
             let output_files: Vec<OutputFile> = self
                 .output_files
@@ -496,14 +833,20 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                     number: o.number.clone(),
                     suffix: o.suffix.clone(),
                     readable_url: o.readable_url.as_ref().map(|u| remove_prefix(u)),
+                    log_line: o.log_line,
                 })
                 .collect();
             let context = CompilationMetricsContext {
+                root: self.layout.root_link(compile_id.as_ref()),
                 css: crate::CSS,
                 m: &m,
                 compile_id: id,
                 stack_html: stack_html,
                 mini_stack_html: mini_stack_html,
+                extra_metrics,
+                locals_html: self.frame_locals.map_or(String::new(), |locals| {
+                    render_locals(locals.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+                }),
                 symbolic_shape_specializations: specializations,
                 guards_added_fast: guards_added_fast,
                 output_files: &output_files,
@@ -520,6 +863,7 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
 
 pub struct AOTAutogradBackwardCompilationMetricsParser<'t> {
     tt: &'t TinyTemplate<'t>,
+    layout: DirectoryLayout,
 }
 impl StructuredLogParser for AOTAutogradBackwardCompilationMetricsParser<'_> {
     fn name(&self) -> &'static str {
@@ -544,6 +888,7 @@ impl StructuredLogParser for AOTAutogradBackwardCompilationMetricsParser<'_> {
                 .clone()
                 .map_or("(unknown) ".to_string(), |c| format!("{cid} ", cid = c));
             let context = AOTAutogradBackwardCompilationMetricsContext {
+                root: self.layout.root_link(compile_id.as_ref()),
                 css: crate::CSS,
                 m: &m,
                 compile_id: id,
@@ -561,6 +906,7 @@ impl StructuredLogParser for AOTAutogradBackwardCompilationMetricsParser<'_> {
 
 pub struct BwdCompilationMetricsParser<'t> {
     tt: &'t TinyTemplate<'t>,
+    layout: DirectoryLayout,
 }
 impl StructuredLogParser for BwdCompilationMetricsParser<'_> {
     fn name(&self) -> &'static str {
@@ -585,6 +931,7 @@ impl StructuredLogParser for BwdCompilationMetricsParser<'_> {
                 .clone()
                 .map_or("(unknown) ".to_string(), |c| format!("{cid} ", cid = c));
             let context = BwdCompilationMetricsContext {
+                root: self.layout.root_link(compile_id.as_ref()),
                 css: crate::CSS,
                 m: &m,
                 compile_id: id,
@@ -598,6 +945,49 @@ impl StructuredLogParser for BwdCompilationMetricsParser<'_> {
     }
 }
 
+/// Explains why Inductor didn't use CUDA graphs for a compile id, which otherwise only shows up
+/// as a perf hint in the console
+pub struct CudagraphsParser<'t> {
+    tt: &'t TinyTemplate<'t>,
+    layout: DirectoryLayout,
+}
+impl StructuredLogParser for CudagraphsParser<'_> {
+    fn name(&self) -> &'static str {
+        "cudagraphs"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        e.compilation_metrics
+            .as_ref()
+            .filter(|m| m.cudagraph_skip_reason.is_some())
+            .map(Metadata::CompilationMetrics)
+    }
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        metrics: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        _payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let filename = format!("{}.html", self.name());
+        if let Metadata::CompilationMetrics(m) = metrics {
+            let context = CudagraphsContext {
+                root: self.layout.root_link(compile_id.as_ref()),
+                css: TEMPLATE_FAILURES_CSS,
+                compile_id: compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                skip_reasons: m.cudagraph_skip_reason.iter().cloned().collect(),
+                qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            };
+            let output = self.tt.render(&filename, &context)?;
+            simple_file_output(&filename, lineno, compile_id, &output)
+        } else {
+            Err(anyhow::anyhow!("Expected CompilationMetrics metadata"))
+        }
+    }
+}
+
 pub struct DumpFileParser;
 impl StructuredLogParser for DumpFileParser {
     fn name(&self) -> &'static str {
@@ -661,6 +1051,7 @@ pub fn anchor_source(text: &str) -> String {
             background-color: #ffff00;
         }
     </style>
+    <link rel="stylesheet" href="../theme.css">
 </head>
 <body>
     <pre>"#,
@@ -705,6 +1096,25 @@ pub fn read_runtime_estimations(
     )
 }
 
+pub fn read_memory_estimations(
+    out_path: &PathBuf,
+    rank_nums: &[u32],
+) -> anyhow::Result<Vec<GraphMemory>> {
+    read_artifacts(
+        out_path,
+        rank_nums,
+        "inductor_memory_planning",
+        |content, rank, graph| {
+            let plan: crate::memory_report::MemoryPlan = serde_json::from_str(content)?;
+            Ok(Some(GraphMemory {
+                rank,
+                graph,
+                peak_bytes: plan.peak_memory(),
+            }))
+        },
+    )
+}
+
 /// Reads inductor_tlparse_tensor_meta*.json from each rank/graph, canonicalizes the JSON,
 /// computes a fingerprint per graph, and returns entries for each graph
 pub fn read_tensor_meta_fingerprints(
@@ -745,6 +1155,28 @@ pub fn read_collective_schedules(
     )
 }
 
+/// Reads the distributed info each rank logged about itself, keyed by the rank of its directory
+pub fn read_distributed_info(
+    out_path: &PathBuf,
+    rank_nums: &[u32],
+) -> anyhow::Result<FxHashMap<u32, DistributedInfo>> {
+    let infos = read_artifacts(
+        out_path,
+        rank_nums,
+        "distributed_info",
+        |content, rank, _| {
+            Ok(serde_json::from_str::<DistributedInfo>(content)
+                .ok()
+                .map(|info| (rank, info)))
+        },
+    )?;
+    let mut by_rank = FxHashMap::default();
+    for (rank, info) in infos {
+        by_rank.entry(rank).or_insert(info);
+    }
+    Ok(by_rank)
+}
+
 /// Parses a prefixed JSON file from each multi-rank output directory.
 /// It finds the first matching file, calls `parse_fn` on its contents,
 /// and collects the `Some(T)` results into a vector.
@@ -839,9 +1271,663 @@ impl StructuredLogParser for ArtifactParser {
     }
 }
 
+// Matches an assignment to a node in a printed FX graph, capturing the node name
+const FX_NODE_PATTERN: &str = r#"^\s+(\w+)(?:: "[^"]*")? = "#;
+
+// A submodule is flagged as unbalanced when it has more than this many times the average
+// number of nodes of the split
+const DDP_UNBALANCED_FACTOR: f64 = 2.0;
+
+/// Collects optimize_ddp_split_graph and the optimize_ddp_split_child graphs of each compile id,
+/// so that ddp_splits.html can be rendered with `render_ddp_splits` once parsing is done.  The
+/// graphs themselves are still dumped by their own parsers.
+pub struct DDPSplitsParser<'t> {
+    pub ddp_splits_index: &'t RefCell<DDPSplitsIndex>,
+}
+impl StructuredLogParser for DDPSplitsParser<'_> {
+    fn name(&self) -> &'static str {
+        "ddp_splits"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        if e.optimize_ddp_split_graph.is_some() {
+            Some(Metadata::DDPSplit(None))
+        } else {
+            e.optimize_ddp_split_child
+                .as_ref()
+                .map(|m| Metadata::DDPSplit(Some(m)))
+        }
+    }
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let Metadata::DDPSplit(child) = metadata else {
+            return Err(anyhow::anyhow!("Expected DDPSplit metadata"));
+        };
+        let mut index = self.ddp_splits_index.borrow_mut();
+        let splits = index.entry(compile_id.clone()).or_default();
+        match child {
+            Some(child) => splits
+                .children
+                .push((child.name.clone(), payload.to_string())),
+            None => splits.split_graph = Some(payload.to_string()),
+        }
+        splits.lineno = lineno;
+        Ok(Vec::new())
+    }
+}
+
+// Artifacts describing how regional compilation or Inductor's graph partitioning split a graph,
+// by name prefix
+const GRAPH_PARTITION_ARTIFACTS: [(&str, &str); 2] = [
+    (
+        "graph_region",
+        "Regions of the Dynamo graph, where identical regions are compiled once and reused",
+    ),
+    (
+        "graph_partition",
+        "Partitions of the Inductor graph, e.g. split around ops that can't be cudagraphed",
+    ),
+];
+
+fn graph_partition_description(name: &str) -> Option<&'static str> {
+    GRAPH_PARTITION_ARTIFACTS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, description)| *description)
+}
+
+/// The regions in a graph_region* or graph_partition* artifact.  Versions of PyTorch disagree on
+/// the layout, so this accepts a list of regions, optionally under a "regions" or "partitions" key,
+/// where a region is a list of node names, an object with a "nodes" list, or a group of identical
+/// regions that are lists of node names themselves.
+fn parse_graph_regions(payload: &str) -> anyhow::Result<Vec<GraphRegion>> {
+    let value: Value = serde_json::from_str(payload)?;
+    let list = match &value {
+        Value::Array(list) => list,
+        Value::Object(obj) => ["regions", "partitions", "region_groups"]
+            .iter()
+            .find_map(|key| obj.get(*key)?.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Expected a list of regions"))?,
+        _ => anyhow::bail!("Expected a list of regions"),
+    };
+    let node_names = |v: &Value| -> Option<Vec<String>> {
+        v.as_array()?
+            .iter()
+            .map(|n| n.as_str().map(|n| n.to_string()))
+            .collect()
+    };
+    let region = |name: String, nodes: Vec<String>, reason: Option<String>| GraphRegion {
+        name,
+        num_nodes: nodes.len(),
+        nodes: nodes.join(", "),
+        reason,
+    };
+    let mut regions = Vec::new();
+    for (i, entry) in list.iter().enumerate() {
+        if let Some(nodes) = node_names(entry) {
+            regions.push(region(format!("{i}"), nodes, None));
+        } else if let Some(obj) = entry.as_object() {
+            let nodes = obj
+                .get("nodes")
+                .and_then(node_names)
+                .ok_or_else(|| anyhow::anyhow!("Region {i} has no list of nodes"))?;
+            let name = obj
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map_or(format!("{i}"), |n| n.to_string());
+            let reason = ["reason", "skip_reason", "skip_cudagraph_reason"]
+                .iter()
+                .find_map(|key| obj.get(*key)?.as_str())
+                .map(|r| r.to_string());
+            regions.push(region(name, nodes, reason));
+        } else {
+            let group = entry
+                .as_array()
+                .and_then(|group| group.iter().map(node_names).collect::<Option<Vec<_>>>())
+                .ok_or_else(|| anyhow::anyhow!("Region {i} is not a list of nodes"))?;
+            for (j, nodes) in group.into_iter().enumerate() {
+                regions.push(region(format!("{i}.{j}"), nodes, None));
+            }
+        }
+    }
+    Ok(regions)
+}
+
+/// Collects graph_region* and graph_partition* artifacts, so that graph_partitions.html can
+/// show how each frame was split once parsing is done.  The artifacts themselves are still dumped
+/// by ArtifactParser.
+pub struct GraphPartitionParser<'t> {
+    pub graph_partitions_index: &'t RefCell<GraphPartitionsIndex>,
+}
+impl StructuredLogParser for GraphPartitionParser<'_> {
+    fn name(&self) -> &'static str {
+        "graph_partition"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        e.artifact
+            .as_ref()
+            .filter(|m| m.encoding == "json" && graph_partition_description(&m.name).is_some())
+            .map(Metadata::Artifact)
+    }
+    fn parse<'e>(
+        &self,
+        _lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let Metadata::Artifact(metadata) = metadata else {
+            return Err(anyhow::anyhow!("Expected Artifact metadata"));
+        };
+        let regions = parse_graph_regions(payload)?;
+        self.graph_partitions_index
+            .borrow_mut()
+            .entry(compile_id.clone())
+            .or_default()
+            .push(GraphPartitionArtifact {
+                name: metadata.name.clone(),
+                description: graph_partition_description(&metadata.name).unwrap_or_default(),
+                num_nodes: regions.iter().map(|r| r.num_nodes).sum(),
+                regions,
+            });
+        Ok(Vec::new())
+    }
+}
+
+/// Render ddp_splits.html, which shows how DDPOptimizer split a graph into submodules and how
+/// many nodes ended up in each of them.  Returns None if no submodule was logged.
+pub fn render_ddp_splits(
+    tt: &TinyTemplate,
+    layout: DirectoryLayout,
+    compile_id: &Option<CompileId>,
+    splits: &DDPSplits,
+) -> anyhow::Result<Option<(PathBuf, String)>> {
+    if splits.children.is_empty() {
+        return Ok(None);
+    }
+    let node_re = Regex::new(FX_NODE_PATTERN)?;
+    let sizes: Vec<usize> = splits
+        .children
+        .iter()
+        .map(|(_, graph)| graph.lines().filter(|l| node_re.is_match(l)).count())
+        .collect();
+    let total: usize = sizes.iter().sum();
+    let mean = total as f64 / sizes.len() as f64;
+    let children: Vec<DDPSplitChild> = splits
+        .children
+        .iter()
+        .zip(sizes)
+        .map(|((name, graph), num_nodes)| DDPSplitChild {
+            name: name.clone(),
+            num_nodes,
+            percent: format!("{:.1}", 100.0 * num_nodes as f64 / total.max(1) as f64),
+            unbalanced: splits.children.len() > 1
+                && num_nodes as f64 > DDP_UNBALANCED_FACTOR * mean,
+            graph: graph.clone(),
+        })
+        .collect();
+    let context = DDPSplitsContext {
+        root: layout.root_link(compile_id.as_ref()),
+        css: TEMPLATE_FAILURES_CSS,
+        compile_id: compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string()),
+        split_graph: splits.split_graph.clone().unwrap_or_default(),
+        unbalanced: children.iter().any(|c| c.unbalanced),
+        children,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    };
+    Ok(Some((
+        layout
+            .compile_id_dir(compile_id.as_ref(), splits.lineno)
+            .join("ddp_splits.html"),
+        tt.render("ddp_splits.html", &context)?,
+    )))
+}
+
+/// Renders the DAG of symbolic expressions created by a compile id.  Every expression links to the
+/// expressions it was computed from and to the expressions computed from it, so that the
+/// provenance of an unbacked symbol can be followed in both directions.
+pub fn render_symbolic_expressions(
+    tt: &TinyTemplate,
+    layout: DirectoryLayout,
+    compile_id: &Option<CompileId>,
+    expressions: &SymbolicExpressions,
+    sym_expr_info_index: &SymExprInfoIndex,
+    intern_table: &InternTable,
+) -> anyhow::Result<(PathBuf, String)> {
+    // Ids are Python object ids, which may be logged more than once
+    let mut seen = FxHashSet::default();
+    let node_ids: Vec<u64> = expressions
+        .node_ids
+        .iter()
+        .copied()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    let link = |id: &u64| {
+        sym_expr_info_index.get(id).map(|node| SymExprLink {
+            id: *id,
+            result: node.result.clone().unwrap_or_default(),
+        })
+    };
+    let mut users: FxHashMap<u64, Vec<u64>> = FxHashMap::default();
+    for id in &node_ids {
+        let Some(node) = sym_expr_info_index.get(id) else {
+            continue;
+        };
+        for arg in node.argument_ids.iter().flatten() {
+            users.entry(*arg).or_default().push(*id);
+        }
+    }
+
+    let mut unbacked_symbols = Vec::new();
+    let mut roots = Vec::new();
+    let mut nodes = Vec::new();
+    for id in &node_ids {
+        let Some(node) = sym_expr_info_index.get(id) else {
+            continue;
+        };
+        let node_users: Vec<SymExprLink> = users
+            .get(id)
+            .map_or(Vec::new(), |u| u.iter().filter_map(link).collect());
+        if node.method.is_none() {
+            unbacked_symbols.extend(link(id));
+        } else if node_users.is_empty() {
+            roots.extend(link(id));
+        }
+        nodes.push(SymExprNode {
+            id: *id,
+            result: node.result.clone().unwrap_or_default(),
+            method: node.method.clone().unwrap_or_default(),
+            arguments: node
+                .arguments
+                .as_ref()
+                .map_or(String::new(), |a| a.join(", ")),
+            inputs: node
+                .argument_ids
+                .iter()
+                .flatten()
+                .filter_map(link)
+                .collect(),
+            users: node_users,
+            user_stack_html: format_stack(
+                node.user_stack.as_ref().unwrap_or(&Vec::new()),
+                intern_table,
+                None,
+                "User Stack",
+                false,
+            ),
+        });
+    }
+
+    let context = SymbolicExpressionsContext {
+        root: layout.root_link(compile_id.as_ref()),
+        css: SYMBOLIC_EXPRESSIONS_CSS,
+        compile_id: compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string()),
+        unbacked_symbols,
+        roots,
+        nodes,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    };
+    Ok((
+        layout
+            .compile_id_dir(compile_id.as_ref(), expressions.lineno)
+            .join("symbolic_expressions.html"),
+        tt.render("symbolic_expressions.html", &context)?,
+    ))
+}
+
+// The name of the FX graph dump `e` carries, if any
+fn fx_graph_metadata(e: &Envelope) -> Option<Metadata<'_>> {
+    let graphs = [
+        ("dynamo_output_graph", e.dynamo_output_graph.is_some()),
+        ("aot_joint_graph", e.aot_joint_graph.is_some()),
+        ("aot_forward_graph", e.aot_forward_graph.is_some()),
+        ("aot_backward_graph", e.aot_backward_graph.is_some()),
+        ("aot_inference_graph", e.aot_inference_graph.is_some()),
+        (
+            "inductor_pre_grad_graph",
+            e.inductor_pre_grad_graph.is_some(),
+        ),
+        (
+            "inductor_post_grad_graph",
+            e.inductor_post_grad_graph.is_some(),
+        ),
+    ];
+    graphs
+        .into_iter()
+        .find(|(_, logged)| *logged)
+        .map(|(name, _)| Metadata::FxGraph(name))
+}
+
+/// Parses each FX graph dump once, collecting its node counts, fan-out etc. for graph_stats.json
+/// and the index and, with `ParseConfig::graph_dot`, writing it as a Graphviz graph next to its
+/// text if it has up to `MAX_DOT_NODES` nodes
+pub struct FxGraphParser<'t> {
+    pub graph_stats_index: &'t RefCell<GraphStatsIndex>,
+    pub graph_dot: bool,
+}
+impl StructuredLogParser for FxGraphParser<'_> {
+    fn name(&self) -> &'static str {
+        "fx_graph"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        fx_graph_metadata(e)
+    }
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let Metadata::FxGraph(name) = metadata else {
+            return Err(anyhow::anyhow!("Expected FxGraph metadata"));
+        };
+        let Some(graph) = FxGraph::parse(payload) else {
+            return Ok(Vec::new());
+        };
+        let mut stats = graph.stats();
+        stats.hash = format!("{:016x}", dump_hash(payload));
+        // Repeated dumps of a graph, e.g. of a recompiled frame, are all kept
+        self.graph_stats_index
+            .borrow_mut()
+            .entry((compile_id.clone(), name))
+            .or_default()
+            .push(stats);
+        match self
+            .graph_dot
+            .then(|| crate::dot::fx_graph_to_dot(&graph))
+            .flatten()
+        {
+            Some(dot) => simple_file_output(&format!("{name}.dot"), lineno, compile_id, &dot),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Collects aot_joint_graph, aot_forward_graph and aot_backward_graph of each compile id, so that
+/// aot_partition.html can be rendered with `render_aot_partition` once parsing is done.  The
+/// graphs themselves are still dumped by their SentinelFileParser.
+pub struct AOTPartitionParser<'t> {
+    pub aot_graphs_index: &'t RefCell<AOTGraphsIndex>,
+}
+impl StructuredLogParser for AOTPartitionParser<'_> {
+    fn name(&self) -> &'static str {
+        "aot_partition"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        if e.aot_joint_graph.is_some() {
+            Some(Metadata::AOTGraph(AOTGraphKind::Joint))
+        } else if e.aot_forward_graph.is_some() {
+            Some(Metadata::AOTGraph(AOTGraphKind::Forward))
+        } else if e.aot_backward_graph.is_some() {
+            Some(Metadata::AOTGraph(AOTGraphKind::Backward))
+        } else {
+            None
+        }
+    }
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let Metadata::AOTGraph(kind) = metadata else {
+            return Err(anyhow::anyhow!("Expected AOTGraph metadata"));
+        };
+        let mut index = self.aot_graphs_index.borrow_mut();
+        let graphs = index.entry(compile_id.clone()).or_default();
+        let graph = match kind {
+            AOTGraphKind::Joint => &mut graphs.joint,
+            AOTGraphKind::Forward => &mut graphs.forward,
+            AOTGraphKind::Backward => &mut graphs.backward,
+        };
+        *graph = Some(payload.to_string());
+        graphs.lineno = lineno;
+        Ok(Vec::new())
+    }
+}
+
+/// Render aot_partition.html, which shows for every node of the joint graph whether the
+/// partitioner put it in the forward graph, the backward graph or both, matching nodes by name.
+/// Returns None unless both the joint and the backward graph were logged.
+pub fn render_aot_partition(
+    tt: &TinyTemplate,
+    layout: DirectoryLayout,
+    compile_id: &Option<CompileId>,
+    graphs: &AOTGraphs,
+) -> anyhow::Result<Option<(PathBuf, String)>> {
+    let (Some(joint), Some(backward)) = (&graphs.joint, &graphs.backward) else {
+        return Ok(None);
+    };
+    let forward = graphs.forward.clone().unwrap_or_default();
+    let node_re = Regex::new(FX_NODE_PATTERN)?;
+    let node_name = |line: &str| node_re.captures(line).map(|caps| caps[1].to_string());
+    let forward_nodes: FxHashSet<String> = forward.lines().filter_map(node_name).collect();
+    let backward_nodes: FxHashSet<String> = backward.lines().filter_map(node_name).collect();
+
+    let (mut num_forward, mut num_backward, mut num_recomputed, mut num_unused) = (0, 0, 0, 0);
+    let mut joint_graph_html = String::new();
+    for line in joint.lines() {
+        let class = node_name(line).map(|name| {
+            match (
+                forward_nodes.contains(&name),
+                backward_nodes.contains(&name),
+            ) {
+                (true, false) => {
+                    num_forward += 1;
+                    "partition-forward"
+                }
+                (false, true) => {
+                    num_backward += 1;
+                    "partition-backward"
+                }
+                (true, true) => {
+                    num_recomputed += 1;
+                    "partition-recomputed"
+                }
+                (false, false) => {
+                    num_unused += 1;
+                    "partition-unused"
+                }
+            }
+        });
+        match class {
+            Some(class) => joint_graph_html.push_str(&format!(
+                "<span class=\"{class}\">{}</span>\n",
+                encode_text(line)
+            )),
+            None => {
+                joint_graph_html.push_str(&encode_text(line));
+                joint_graph_html.push('\n');
+            }
+        }
+    }
+
+    let context = AOTPartitionContext {
+        root: layout.root_link(compile_id.as_ref()),
+        css: crate::templates::AOT_PARTITION_CSS,
+        compile_id: compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string()),
+        num_forward,
+        num_backward,
+        num_recomputed,
+        num_unused,
+        joint_graph_html,
+        forward_graph: forward,
+        backward_graph: backward.clone(),
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    };
+    Ok(Some((
+        layout
+            .compile_id_dir(compile_id.as_ref(), graphs.lineno)
+            .join("aot_partition.html"),
+        tt.render("aot_partition.html", &context)?,
+    )))
+}
+
+/// Records the pass-level timings Inductor logs as json artifacts named `<stage>_pass_timings`
+/// (e.g. joint_graph_pass_timings, post_grad_pass_timings), whose payload maps each pass name
+/// to its duration in seconds.  The artifact itself is still dumped by ArtifactParser; the
+/// collected timings are aggregated across compile ids into pass_timings.html.
+pub struct InductorPassTimingsParser<'t> {
+    pub pass_timings_index: &'t RefCell<PassTimingsIndex>,
+}
+impl StructuredLogParser for InductorPassTimingsParser<'_> {
+    fn name(&self) -> &'static str {
+        "inductor_pass_timings"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        e.artifact
+            .as_ref()
+            .filter(|m| m.name.ends_with("pass_timings") && m.encoding == "json")
+            .map(Metadata::Artifact)
+    }
+    fn parse<'e>(
+        &self,
+        _lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        if let Metadata::Artifact(metadata) = metadata {
+            let timings = serde_json::from_str::<BTreeMap<String, f64>>(payload)?;
+            let stage = metadata
+                .name
+                .trim_end_matches("pass_timings")
+                .trim_end_matches('_');
+            let stage = if stage.is_empty() { "inductor" } else { stage };
+            let mut index = self.pass_timings_index.borrow_mut();
+            for (pass, duration_s) in timings {
+                index
+                    .entry((stage.to_string(), pass))
+                    .or_default()
+                    .push((compile_id.clone(), duration_s));
+            }
+            Ok(Vec::new())
+        } else {
+            Err(anyhow::anyhow!("Expected Artifact metadata"))
+        }
+    }
+}
+
+/// Records the counters PyTorch logs as json artifacts named like `dynamo_counters` or
+/// `waitcounters`, whose payload maps counter names (possibly nested by category) to their
+/// values so far.  Each artifact is a snapshot; counters.html shows what changed between them.
+pub struct CountersParser<'t> {
+    pub counters_index: &'t RefCell<CountersIndex>,
+}
+impl StructuredLogParser for CountersParser<'_> {
+    fn name(&self) -> &'static str {
+        "counters"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        e.artifact
+            .as_ref()
+            .filter(|m| m.name.trim_end_matches('s').ends_with("counter") && m.encoding == "json")
+            .map(Metadata::Artifact)
+    }
+    fn parse<'e>(
+        &self,
+        _lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        if let Metadata::Artifact(metadata) = metadata {
+            let mut values = BTreeMap::new();
+            flatten_counters("", &serde_json::from_str(payload)?, &mut values);
+            self.counters_index.borrow_mut().push(CounterSnapshot {
+                compile_id: compile_id.clone(),
+                source: metadata.name.clone(),
+                values,
+            });
+            Ok(Vec::new())
+        } else {
+            Err(anyhow::anyhow!("Expected Artifact metadata"))
+        }
+    }
+}
+
+/// Renders memory_report.html for the inductor_memory_planning artifact of a compile id, and
+/// records its estimated peak memory for the summary on index.html
+pub struct MemoryPlanningParser<'t> {
+    pub tt: &'t TinyTemplate<'t>,
+    pub memory_index: &'t RefCell<MemoryIndex>,
+    pub layout: DirectoryLayout,
+}
+impl StructuredLogParser for MemoryPlanningParser<'_> {
+    fn name(&self) -> &'static str {
+        "memory_report"
+    }
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        e.artifact
+            .as_ref()
+            .filter(|m| m.name == "inductor_memory_planning" && m.encoding == "json")
+            .map(Metadata::Artifact)
+    }
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        _metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let plan: crate::memory_report::MemoryPlan = serde_json::from_str(payload)?;
+        let (peak, context) =
+            crate::memory_report::memory_report(&plan, compile_id.as_ref(), self.layout);
+        self.memory_index
+            .borrow_mut()
+            .insert(compile_id.clone(), peak);
+        let filename = format!("{}.html", self.name());
+        let output = self.tt.render(&filename, &context)?;
+        simple_file_output(&filename, lineno, compile_id, &output)
+    }
+}
+
+// Nested categories become dotted names, e.g. {"stats": {"calls_captured": 3}} is
+// stats.calls_captured; anything that isn't a number is skipped
+fn flatten_counters(prefix: &str, value: &Value, values: &mut BTreeMap<String, f64>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_counters(&name, value, values);
+            }
+        }
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64() {
+                values.insert(prefix.to_string(), n);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn render_sym_expr_trie(
     expr: u64,
     sym_expr_info_index: &SymExprInfoIndex,
+    intern_table: &InternTable,
     depth: usize,
     visited: &mut HashSet<u64>,
 ) -> Option<String> {
@@ -856,9 +1942,13 @@ fn render_sym_expr_trie(
 
     let mut children_elements = Vec::new();
     for arg_id in sym_expr_args_id {
-        if let Some(child_element) =
-            render_sym_expr_trie(*arg_id, sym_expr_info_index, depth + 1, visited)
-        {
+        if let Some(child_element) = render_sym_expr_trie(
+            *arg_id,
+            sym_expr_info_index,
+            intern_table,
+            depth + 1,
+            visited,
+        ) {
             children_elements.push(child_element);
         }
     }
@@ -889,11 +1979,15 @@ fn render_sym_expr_trie(
             .join(", "),
         format_stack(
             &sym_expr_info.user_stack.as_ref().unwrap_or(&Vec::new()),
+            intern_table,
+            None,
             "User Stack",
             true
         ),
         format_stack(
             &sym_expr_info.stack.as_ref().unwrap_or(&Vec::new()),
+            intern_table,
+            None,
             "Stack",
             false
         ),
@@ -909,6 +2003,9 @@ fn render_sym_expr_trie(
 pub struct PropagateRealTensorsParser<'t> {
     pub tt: &'t TinyTemplate<'t>,
     pub sym_expr_info_index: &'t SymExprInfoIndex,
+    pub intern_table: &'t InternTable,
+    pub sources: Option<&'t SourceSnippets>,
+    pub layout: DirectoryLayout,
 }
 impl StructuredLogParser for PropagateRealTensorsParser<'_> {
     fn name(&self) -> &'static str {
@@ -935,29 +2032,33 @@ impl StructuredLogParser for PropagateRealTensorsParser<'_> {
             let filename = "symbolic_guard_information.html";
             let framework_stack_html = format_stack(
                 &m.stack.as_ref().unwrap_or(&Vec::new()),
+                self.intern_table,
+                None,
                 "Framework Stack",
                 false,
             );
             let user_stack_html = format_stack(
                 &m.user_stack.as_ref().unwrap_or(&Vec::new()),
+                self.intern_table,
+                self.sources,
                 "User Stack",
                 true,
             );
-            let locals_html = format!(
-                "{}",
-                m.frame_locals.as_ref().unwrap_or(&FrameLocals::default())
-            );
+            let locals_html =
+                render_frame_locals(m.frame_locals.as_ref().unwrap_or(&FrameLocals::default()));
 
             let mut visited = HashSet::new();
             let sym_expr_trie_html = render_sym_expr_trie(
                 m.expr_node_id.unwrap(),
                 self.sym_expr_info_index,
+                self.intern_table,
                 0,
                 &mut visited,
             )
             .unwrap_or("".to_string());
 
             let context = SymbolicGuardContext {
+                root: self.layout.root_link(compile_id.as_ref()),
                 css: crate::CSS,
                 expr: m.expr.clone().unwrap(),
                 user_stack_html: user_stack_html,
@@ -982,9 +2083,7 @@ pub fn default_parsers<'t>(
 ) -> Vec<Box<dyn StructuredLogParser + 't>> {
     // We need to use Box wrappers here because vecs in Rust need to have known size
     if parser_config.export {
-        return vec![Box::new(SentinelFileParser::new("exported_program", |e| {
-            e.exported_program.as_ref()
-        }))];
+        return vec![Box::new(ExportedProgramParser::new(tt, parser_config))];
     }
 
     let result: Vec<Box<dyn StructuredLogParser>> = vec![
@@ -1017,11 +2116,24 @@ pub fn default_parsers<'t>(
         })),
         Box::new(GraphDumpParser),
         Box::new(DynamoOutputGraphParser),
-        Box::new(DynamoGuardParser { tt }),
+        Box::new(DynamoGuardParser {
+            tt,
+            layout: parser_config.directory_layout,
+        }),
         Box::new(InductorOutputCodeParser::new(parser_config)),
         Box::new(OptimizeDdpSplitChildParser),
-        Box::new(AOTAutogradBackwardCompilationMetricsParser { tt }), // TODO: use own tt instances
-        Box::new(BwdCompilationMetricsParser { tt }),                 // TODO: use own tt instances
+        Box::new(AOTAutogradBackwardCompilationMetricsParser {
+            tt,
+            layout: parser_config.directory_layout,
+        }), // TODO: use own tt instances
+        Box::new(BwdCompilationMetricsParser {
+            tt,
+            layout: parser_config.directory_layout,
+        }), // TODO: use own tt instances
+        Box::new(CudagraphsParser {
+            tt,
+            layout: parser_config.directory_layout,
+        }),
         Box::new(LinkParser),
         Box::new(ArtifactParser),
         Box::new(DumpFileParser),
@@ -0,0 +1,117 @@
+use std::cell::OnceCell;
+use std::ops::Range;
+
+use md5::{Digest, Md5};
+
+use crate::log_source::{invalid_utf8_sequences, LogLine};
+
+/// The payload of a log entry, i.e. the tab-prefixed lines following its envelope.  The payload
+/// is kept as byte ranges of the (memory-mapped) log, one for each run of consecutive lines, and
+/// only decoded into a `String` the first time something reads it, so payloads nobody looks at
+/// are never copied.  Bytes that aren't UTF-8, e.g. of binary data dumped into the payload, are
+/// replaced when it is decoded.
+#[derive(Debug, Default)]
+pub struct LazyPayload<'a> {
+    // Lines of a range are separated by a single \n, and each still has its leading tab
+    ranges: Vec<(&'a [u8], Range<usize>)>,
+    len: usize,
+    raw_len: usize,
+    num_lines: usize,
+    decoded: OnceCell<String>,
+}
+
+impl<'a> LazyPayload<'a> {
+    pub(crate) fn push_line(&mut self, line: LogLine<'a>) {
+        debug_assert!(self.decoded.get().is_none());
+        let line_len = line.end - line.start;
+        if self.num_lines > 0 {
+            self.len += 1;
+        }
+        self.len += line_len - 1;
+        self.raw_len += line_len + 1;
+        self.num_lines += 1;
+        match self.ranges.last_mut() {
+            Some((log, range))
+                if log.as_ptr() == line.log.as_ptr() && range.end + 1 == line.start =>
+            {
+                range.end = line.end
+            }
+            _ => self.ranges.push((line.log, line.start..line.end)),
+        }
+    }
+
+    /// The same bytes of the log as another payload, without what this one decoded, e.g. to read
+    /// them once the record is gone
+    pub(crate) fn without_decoded(&self) -> Self {
+        LazyPayload {
+            ranges: self.ranges.clone(),
+            len: self.len,
+            raw_len: self.raw_len,
+            num_lines: self.num_lines,
+            decoded: OnceCell::new(),
+        }
+    }
+
+    // Each line still has its leading tab
+    fn lines(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.ranges
+            .iter()
+            .flat_map(|(log, range)| log[range.clone()].split(|b| *b == b'\n'))
+    }
+
+    /// Length in bytes of the decoded payload
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Length in bytes of the payload's lines in the log, i.e. with their tabs and newlines
+    pub fn raw_len(&self) -> usize {
+        self.raw_len
+    }
+
+    pub fn num_lines(&self) -> usize {
+        self.num_lines
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// md5 of the decoded payload, computed without decoding it
+    pub fn md5(&self) -> [u8; 16] {
+        let mut hasher = Md5::new();
+        for (i, line) in self.lines().enumerate() {
+            // Careful! Distinguish between missing EOL and not
+            if i > 0 {
+                hasher.update(b"\n");
+            }
+            hasher.update(&line[1..]);
+        }
+        hasher.finalize().into()
+    }
+
+    pub fn md5_hex(&self) -> String {
+        self.md5().iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Number of byte sequences of the payload that aren't UTF-8
+    pub fn invalid_utf8(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|(log, range)| invalid_utf8_sequences(&log[range.clone()]))
+            .sum()
+    }
+
+    pub fn get(&self) -> &str {
+        self.decoded.get_or_init(|| {
+            let mut payload = String::with_capacity(self.len());
+            for (i, line) in self.lines().enumerate() {
+                if i > 0 {
+                    payload.push('\n');
+                }
+                payload.push_str(&String::from_utf8_lossy(&line[1..]));
+            }
+            payload
+        })
+    }
+}
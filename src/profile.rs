@@ -0,0 +1,131 @@
+use anyhow::Context;
+use fxhash::FxHashMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::types::OpRuntime;
+
+/// Measured durations from a profiler trace (e.g. Kineto's chrome trace export), by event name
+#[derive(Debug, Default)]
+pub struct MeasuredDurations {
+    // Total duration in us and number of occurrences
+    by_name: FxHashMap<String, (f64, u64)>,
+}
+
+impl MeasuredDurations {
+    pub fn from_trace(trace: &str) -> anyhow::Result<Self> {
+        let trace: Value = serde_json::from_str(trace).context("Profiler trace is not JSON")?;
+        // Either a bare list of events or an object with a traceEvents list
+        let events = trace
+            .as_array()
+            .or_else(|| trace["traceEvents"].as_array())
+            .context("Profiler trace has no traceEvents")?;
+        let mut measured = MeasuredDurations::default();
+        for event in events {
+            if event["ph"] != "X" {
+                continue;
+            }
+            let (Some(name), Some(dur)) = (event["name"].as_str(), event["dur"].as_f64()) else {
+                continue;
+            };
+            let entry = measured.by_name.entry(name.to_string()).or_default();
+            entry.0 += dur;
+            entry.1 += 1;
+        }
+        Ok(measured)
+    }
+
+    /// Mean duration in us of the profiler events that ran `op`, if any
+    pub fn mean_us(&self, op: &str) -> Option<f64> {
+        candidate_names(op).iter().find_map(|name| {
+            self.by_name
+                .get(name)
+                .map(|(total, count)| total / *count as f64)
+        })
+    }
+}
+
+/// Names a profiler may record an op from the runtime estimations as, e.g. aten::mm for
+/// extern_kernels.mm and _c10d_functional::all_reduce_ for
+/// torch.ops._c10d_functional.all_reduce_.default
+fn candidate_names(op: &str) -> Vec<String> {
+    let mut names = vec![op.to_string()];
+    if let Some(kernel) = op.strip_prefix("extern_kernels.") {
+        names.push(format!("aten::{kernel}"));
+    }
+    if let Some(qualified) = op.strip_prefix("torch.ops.") {
+        let parts: Vec<&str> = qualified.split('.').collect();
+        if parts.len() >= 2 {
+            names.push(format!("{}::{}", parts[0], parts[1]));
+        }
+    }
+    names
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpCorrelation {
+    pub name: String,
+    pub estimated_us: f64,
+    /// None if no profiler event matches the op
+    pub measured_us: Option<f64>,
+    /// measured / estimated
+    pub ratio: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphCorrelation {
+    pub compile_id: String,
+    pub num_matched: usize,
+    pub num_unmatched: usize,
+    /// Totals over the matched ops only
+    pub estimated_total_us: f64,
+    pub measured_total_us: f64,
+    /// Mean absolute percentage error of the matched ops' estimates
+    pub mean_abs_pct_error: Option<f64>,
+    pub ops: Vec<OpCorrelation>,
+}
+
+// Round to 0.1 us, which is plenty for a report
+fn round_us(t: f64) -> f64 {
+    (t * 10.0).round() / 10.0
+}
+
+pub fn correlate_graph(
+    compile_id: String,
+    ops: &[OpRuntime],
+    measured: &MeasuredDurations,
+) -> GraphCorrelation {
+    let ops: Vec<OpCorrelation> = ops
+        .iter()
+        .map(|op| {
+            let estimated_us = op.estimated_runtime_ns / 1000.0;
+            let measured_us = measured.mean_us(&op.name);
+            OpCorrelation {
+                name: op.name.clone(),
+                estimated_us: round_us(estimated_us),
+                measured_us: measured_us.map(round_us),
+                ratio: measured_us
+                    .filter(|_| estimated_us > 0.0)
+                    .map(|m| (m / estimated_us * 100.0).round() / 100.0),
+            }
+        })
+        .collect();
+    let matched: Vec<&OpCorrelation> = ops.iter().filter(|op| op.measured_us.is_some()).collect();
+    let errors: Vec<f64> = matched
+        .iter()
+        .filter_map(|op| {
+            let measured = op.measured_us?;
+            (measured > 0.0).then(|| ((op.estimated_us - measured) / measured).abs() * 100.0)
+        })
+        .collect();
+    GraphCorrelation {
+        compile_id,
+        num_matched: matched.len(),
+        num_unmatched: ops.len() - matched.len(),
+        estimated_total_us: round_us(matched.iter().map(|op| op.estimated_us).sum()),
+        measured_total_us: round_us(matched.iter().filter_map(|op| op.measured_us).sum()),
+        mean_abs_pct_error: (!errors.is_empty())
+            .then(|| round_us(errors.iter().sum::<f64>() / errors.len() as f64)),
+        ops,
+    }
+}
@@ -12,6 +12,19 @@ let cppCodeToPost = {};
 
 let lineMappings = null;
 
+// Text and line mappings of each generated code file, when there are several
+let codeFiles = [];
+let codeFileLineMappings = [];
+
+// Kernel name -> its recorded stack traces
+let kernelStackTraces = {};
+
+// Kernel name -> its source, and the post-grad graph lines it computes
+let kernelSources = {};
+let kernelToPost = {};
+let postToKernels = {};
+let currentKernel = null;
+
 /**
  * Initializes the line number mappings from the pre-processed data.
  * 
@@ -53,11 +66,202 @@ function initializeLineMappings() {
         } else {
             console.warn('No line mappings element found');
         }
+        const codeFileLineMappingsElement = document.getElementById('codeFileLineMappings');
+        if (codeFileLineMappingsElement) {
+            codeFileLineMappings = JSON.parse(codeFileLineMappingsElement.textContent);
+        }
     } catch (error) {
         console.error('Error initializing line mappings:', error);
     }
 }
 
+// Use the line mappings between the graphs and the code file at index
+function useCodeFileLineMappings(index) {
+    const mappings = codeFileLineMappings[index];
+    if (!mappings) return;
+    pyCodeToPost = mappings.pyCodeToPost || {};
+    postToPyCode = mappings.postToPyCode || {};
+    cppCodeToPost = mappings.cppCodeToPost || {};
+    postToCppCode = mappings.postToCppCode || {};
+}
+
+// Set the generated code to content, which is either Python or C++ code
+function setCodeData(content) {
+    if (content.includes('AOTInductorModel::run_impl')) {
+        // This is C++ code
+        cppCodeData = content.split('\n');
+        codeData = null;
+    } else {
+        // This is Python code
+        codeData = content.split('\n');
+        cppCodeData = null;
+    }
+}
+
+// Show the code file at index in the generated code pane
+function selectCodeFile(index) {
+    if (index >= codeFiles.length) return;
+    setCodeData(codeFiles[index]);
+    useCodeFileLineMappings(index);
+    setupEditorContent('preGradGraph', preGradGraphData);
+    setupEditorContent('postGradGraph', postGradGraphData);
+    setupEditorContent('generatedCode', codeData || cppCodeData);
+    document.querySelectorAll('.code-tab').forEach(tab => {
+        tab.classList.toggle('active', Number(tab.dataset.index) === index);
+    });
+}
+
+function setupCodeTabs() {
+    document.querySelectorAll('.code-tab').forEach(tab => {
+        tab.addEventListener('click', () => selectCodeFile(Number(tab.dataset.index)));
+    });
+}
+
+
+/**
+ * Initializes the kernel stack traces from the embedded
+ * inductor_provenance_tracking_kernel_stack_traces JSON, which maps
+ * "kernel_name:debug_handle" to a list of stack traces.
+ */
+function initializeKernelStackTraces() {
+    try {
+        const element = document.getElementById('kernelStackTraces');
+        if (!element) return;
+        const traces = JSON.parse(element.textContent);
+        for (const [key, kernelTraces] of Object.entries(traces)) {
+            const kernel = key.split(':')[0];
+            if (!kernelStackTraces[kernel]) {
+                kernelStackTraces[kernel] = [];
+            }
+            // Newlines are encoded as "\\n" sequences
+            kernelStackTraces[kernel].push(...kernelTraces.map(t => t.replaceAll('\\n', '\n')));
+        }
+    } catch (error) {
+        console.error('Error initializing kernel stack traces:', error);
+    }
+}
+
+// Kernels with stack traces that a line of generated code mentions
+function kernelsInLine(line) {
+    return namesInLine(line, Object.keys(kernelStackTraces));
+}
+
+// The kernel names that a line mentions
+function namesInLine(line, kernels) {
+    return kernels.filter(kernel => {
+        const index = line.indexOf(kernel);
+        if (index < 0) return false;
+        // Whole names only, so that kernel_1 doesn't match kernel_10
+        const next = line[index + kernel.length];
+        return next === undefined || !/\w/.test(next);
+    });
+}
+
+// Show the stack traces of the kernels a line of generated code mentions, if any
+function showKernelStackTraces(lineNumber) {
+    const lines = codeData || cppCodeData;
+    if (!lines) return;
+    const kernels = kernelsInLine(lines[lineNumber - 1] || '');
+    if (kernels.length === 0) return;
+
+    const panel = document.getElementById('stackTracePanel');
+    const traces = document.getElementById('stackTraces');
+    document.getElementById('stackTraceKernel').textContent = kernels.join(', ');
+    traces.innerHTML = '';
+    kernels.forEach(kernel => {
+        kernelStackTraces[kernel].forEach(trace => {
+            const pre = document.createElement('pre');
+            pre.textContent = trace.trimEnd();
+            traces.appendChild(pre);
+        });
+    });
+    panel.hidden = false;
+}
+
+/**
+ * Initializes the kernel source pane from the embedded JSON, which has the
+ * source of each kernel of the generated code and the post-grad graph lines
+ * that each kernel computes.
+ */
+function initializeKernelSources() {
+    try {
+        const element = document.getElementById('kernelSources');
+        if (!element) return;
+        const data = JSON.parse(element.textContent);
+        kernelSources = data.sources || {};
+        kernelToPost = data.kernelToPost || {};
+        for (const [kernel, lines] of Object.entries(kernelToPost)) {
+            if (!kernelSources[kernel]) continue;
+            lines.forEach(line => {
+                if (!postToKernels[line]) {
+                    postToKernels[line] = [];
+                }
+                postToKernels[line].push(kernel);
+            });
+        }
+    } catch (error) {
+        console.error('Error initializing kernel sources:', error);
+    }
+}
+
+// The kernel to show in the kernel source pane after clicking a line of editorId
+function kernelForLine(editorId, lineNumber) {
+    switch (editorId) {
+        case 'preGradGraph':
+            for (const postLine of preToPost[lineNumber] || []) {
+                if (postToKernels[postLine]) return postToKernels[postLine][0];
+            }
+            return null;
+        case 'postGradGraph':
+            return (postToKernels[lineNumber] || [])[0];
+        case 'generatedCode': {
+            const lines = codeData || cppCodeData || [];
+            return namesInLine(lines[lineNumber - 1] || '', Object.keys(kernelSources))[0];
+        }
+    }
+    return null;
+}
+
+function showKernelSource(kernel) {
+    if (!kernel || !kernelSources[kernel] || kernel === currentKernel) return;
+    currentKernel = kernel;
+    document.getElementById('kernelSourceName').textContent = kernel;
+    setupEditorContent('kernelSource', kernelSources[kernel].split('\n'));
+}
+
+// Highlight the pre-grad graph nodes of a module of the module tree, and what they became
+function selectModule(moduleElement) {
+    clearHighlights();
+    document.querySelectorAll('.module-tree .module.selected').forEach(m => m.classList.remove('selected'));
+    moduleElement.classList.add('selected');
+    const lines = moduleElement.dataset.lines.split(',').filter(l => l).map(Number);
+    lines.forEach(line => {
+        const lineElement = document.querySelector(`#preGradGraph .line:nth-child(${line})`);
+        if (lineElement) {
+            lineElement.classList.add('highlight');
+        }
+        highlightCorrespondingLines('preGradGraph', line);
+    });
+    const firstLine = document.querySelector(`#preGradGraph .line:nth-child(${lines[0]})`);
+    if (firstLine) {
+        firstLine.scrollIntoView({ behavior: 'smooth', block: 'center', inline: 'nearest' });
+    }
+}
+
+function setupModuleTree() {
+    document.querySelectorAll('.module-tree .module').forEach(moduleElement => {
+        moduleElement.addEventListener('click', () => selectModule(moduleElement));
+    });
+}
+
+function setupStackTracePanel() {
+    const close = document.getElementById('stackTraceClose');
+    if (close) {
+        close.addEventListener('click', () => {
+            document.getElementById('stackTracePanel').hidden = true;
+        });
+    }
+}
 
 // Setup editor content
 function setupEditorContent(editorId, lines) {
@@ -71,6 +275,8 @@ function setupEditorContent(editorId, lines) {
     lines.forEach((line, index) => {
         const lineDiv = document.createElement('div');
         lineDiv.className = 'line';
+        // For deep links, e.g. ?row=postGradGraph-L12
+        lineDiv.id = `${editorId}-L${index + 1}`;
         
         // Create text nodes instead of using innerHTML
         const lineNumber = document.createElement('span');
@@ -97,11 +303,17 @@ function setupEditorContent(editorId, lines) {
                 hasMatch = (pyCodeToPost[lineNum] && pyCodeToPost[lineNum].length > 0) || 
                 (cppCodeToPost[lineNum] && cppCodeToPost[lineNum].length > 0);
                 break;
+            case 'kernelSource':
+                hasMatch = kernelToPost[currentKernel] && kernelToPost[currentKernel].length > 0;
+                break;
         }
         
         if (hasMatch) {
             lineContent.classList.add('has-match');
         }
+        if (editorId === 'generatedCode' && kernelsInLine(line).length > 0) {
+            lineContent.classList.add('has-stack-trace');
+        }
         
         lineDiv.appendChild(lineNumber);
         lineDiv.appendChild(lineContent);
@@ -129,6 +341,10 @@ function handleLineHover(editorId, lineNumber) {
 
     // Highlight and scroll corresponding lines
     highlightCorrespondingLines(editorId, lineNumber);
+
+    if (editorId === 'generatedCode') {
+        showKernelStackTraces(lineNumber);
+    }
 }
 
 // Clear all highlights
@@ -140,6 +356,9 @@ function clearHighlights() {
 
 // Update handleLineClick to use the same pattern
 function handleLineClick(editorId, lineNumber) {
+    if (editorId !== 'kernelSource') {
+        showKernelSource(kernelForLine(editorId, lineNumber));
+    }
     clearHighlights();
     
     // Add highlight to clicked line
@@ -155,6 +374,10 @@ function handleLineClick(editorId, lineNumber) {
 
     // Highlight corresponding lines
     highlightCorrespondingLines(editorId, lineNumber);
+
+    if (editorId === 'generatedCode') {
+        showKernelStackTraces(lineNumber);
+    }
 }
 
 // Initialize data from pre-embedded content
@@ -168,20 +391,18 @@ function initializeData() {
         if (preGradGraph) preGradGraphData = preGradGraph.textContent.split('\n');
         if (postGradGraph) postGradGraphData = postGradGraph.textContent.split('\n');
         if (generatedCode) {
-            const content = generatedCode.textContent;
-            if (content.includes('AOTInductorModel::run_impl')) {
-                // This is C++ code
-                cppCodeData = content.split('\n');
-                codeData = null;
-            } else {
-                // This is Python code
-                codeData = content.split('\n');
-                cppCodeData = null;
-            }
+            codeFiles = [generatedCode.textContent];
+            setCodeData(codeFiles[0]);
         }
+        document.querySelectorAll('.code-file pre').forEach(pre => codeFiles.push(pre.textContent));
 
         // Initialize line mappings from pre-processed data
         initializeLineMappings();
+        initializeKernelStackTraces();
+        initializeKernelSources();
+        setupStackTracePanel();
+        setupCodeTabs();
+        setupModuleTree();
 
         // Setup highlighting
         setupEditorContent('preGradGraph', preGradGraphData);
@@ -275,6 +496,17 @@ function findCorrespondingLines(sourceEditorId, lineNumber) {
             }
             break;
             
+        case 'kernelSource':
+            // Every line of a kernel computes the same nodes
+            result.postGradGraph = kernelToPost[currentKernel] || [];
+            result.preGradGraph = [];
+            for (const postLine of result.postGradGraph) {
+                if (postToPre[postLine]) {
+                    result.preGradGraph.push(...postToPre[postLine]);
+                }
+            }
+            break;
+
         case 'generatedCode':
             if (codeData) {
                 // Python code
@@ -0,0 +1,223 @@
+// Python bindings for calling tlparse in-process, enabled with the `python` feature.
+//
+// Build with `maturin develop -b pyo3 --features python`, which builds the library as a cdylib
+// (plain cargo builds only build the rlib), then:
+//
+//     import tlparse
+//     report = tlparse.parse_path("/tmp/trace/dedicated_log_torch_trace_rank_0.log")
+//     report["summary"]["total_compiles"], report["stats"]["fail_json"]
+//     compile_directory = json.loads(report["files"]["compile_directory.json"])
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::{
+    CompileId, CompileSummary, MeasuredDurations, ParseConfig, ParseOutput, PayloadVerification,
+    SourceRoot, TrainingSteps,
+};
+
+/// Parse a TORCH_TRACE log and return a dict with:
+///
+/// - `files`: each output path (relative to the output directory) mapped to its contents
+/// - `summary`: the compile summary, as in compile_summary.json
+/// - `stats`: counts of the records parsed and of those that failed, as printed with --verbose
+///
+/// Keyword arguments set the `ParseConfig` field of the same name, e.g. `inductor_provenance=True`
+/// or `theme="dark"`.  `verify_payloads` is a bool, or N to check every Nth payload; `baseline`,
+/// `profile` and `steps` are paths to the files of --baseline, --profile-trace and --steps.
+/// `custom_parsers` only exist in Rust, and `max_parallel_writes` is moot as nothing is written
+/// to disk.
+#[pyfunction]
+#[pyo3(signature = (path, **config))]
+fn parse_path(
+    py: Python<'_>,
+    path: PathBuf,
+    config: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Py<PyDict>> {
+    let setters = config
+        .into_iter()
+        .flatten()
+        .map(|(key, value)| config_setter(&key.extract::<String>()?, &value))
+        .collect::<PyResult<Vec<Setter>>>()?;
+    // Parsing can take minutes on large logs; don't hold the GIL while we do it
+    let (output, summary) = py
+        .allow_threads(|| {
+            let mut config = ParseConfig::default();
+            for set in setters {
+                set(&mut config);
+            }
+            crate::parse_path_with_summary(&path, &config)
+        })
+        .map_err(to_py_err)?;
+    let json = py.import("json")?;
+    let to_python = |value: String| json.call_method1("loads", (value,));
+    let report = PyDict::new(py);
+    report.set_item("files", files_dict(output))?;
+    report.set_item(
+        "summary",
+        to_python(serde_json::to_string(&summary.compile_summary).map_err(to_py_err)?)?,
+    )?;
+    report.set_item(
+        "stats",
+        to_python(serde_json::to_string(&summary.stats).map_err(to_py_err)?)?,
+    )?;
+    Ok(report.unbind())
+}
+
+// Sets a field of a ParseConfig.  ParseConfig itself can't be built before the GIL is released,
+// since custom parsers aren't Send.
+type Setter = Box<dyn FnOnce(&mut ParseConfig) + Send>;
+
+/// Sets the field with `value` as is
+fn set<T>(value: &Bound<'_, PyAny>, set: fn(&mut ParseConfig, T)) -> PyResult<Setter>
+where
+    T: for<'py> FromPyObject<'py> + Send + 'static,
+{
+    let value: T = value.extract()?;
+    Ok(Box::new(move |config| set(config, value)))
+}
+
+/// Sets the field with `value` parsed like the command line flag
+fn set_parsed<T>(value: &Bound<'_, PyAny>, set: fn(&mut ParseConfig, T)) -> PyResult<Setter>
+where
+    T: FromStr + Send + 'static,
+    T::Err: Display,
+{
+    let value = parse(&value.extract::<String>()?)?;
+    Ok(Box::new(move |config| set(config, value)))
+}
+
+/// Sets the field with the file at the path `value`, as loaded by `load`
+fn set_loaded<T: Send + 'static>(
+    value: &Bound<'_, PyAny>,
+    load: fn(&str) -> anyhow::Result<T>,
+    set: fn(&mut ParseConfig, T),
+) -> PyResult<Setter> {
+    let path: PathBuf = value.extract()?;
+    let value = fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| load(&contents))
+        .map_err(|e| PyValueError::new_err(format!("Couldn't load {}: {e:#}", path.display())))?;
+    Ok(Box::new(move |config| set(config, value)))
+}
+
+fn parse<T>(value: &str) -> PyResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|e: T::Err| PyValueError::new_err(e.to_string()))
+}
+
+fn config_setter(key: &str, value: &Bound<'_, PyAny>) -> PyResult<Setter> {
+    match key {
+        "strict" => set(value, |c, v| c.strict = v),
+        "strict_compile_id" => set(value, |c, v| c.strict_compile_id = v),
+        "custom_header_html" => set(value, |c, v| c.custom_header_html = v),
+        "verbose" => set(value, |c, v| c.verbose = v),
+        "plain_text" => set(value, |c, v| c.plain_text = v),
+        "export" => set(value, |c, v| c.export = v),
+        "inductor_provenance" => set(value, |c, v| c.inductor_provenance = v),
+        "no_inductor_provenance" => set(value, |c, v| c.no_inductor_provenance = v),
+        "template_overrides" => set(value, |c, v| c.template_overrides = v),
+        "theme" => set_parsed(value, |c, v| c.theme = v),
+        "custom_css" => set(value, |c, v| c.custom_css = v),
+        "verify_payloads" => {
+            let verification = match value.extract::<bool>() {
+                Ok(true) => PayloadVerification::All,
+                Ok(false) => PayloadVerification::None,
+                Err(_) => PayloadVerification::Sample(value.extract()?),
+            };
+            Ok(Box::new(move |c| c.verify_payloads = verification))
+        }
+        "legacy_artifact_numbering" => set(value, |c, v| c.legacy_artifact_numbering = v),
+        "dedup_payloads" => set(value, |c, v| c.dedup_payloads = v),
+        "max_compile_ids" => set(value, |c, v| c.max_compile_ids = v),
+        "index_page_size" => set(value, |c, v| c.index_page_size = v),
+        "disabled_parsers" => set(value, |c, v| c.disabled_parsers = v),
+        "enabled_parsers" => set(value, |c, v| c.enabled_parsers = v),
+        "baseline" => set_loaded(
+            value,
+            |json| Ok(serde_json::from_str::<CompileSummary>(json)?),
+            |c, v| c.baseline = Some(v),
+        ),
+        "profile" => set_loaded(value, MeasuredDurations::from_trace, |c, v| {
+            c.profile = Some(v)
+        }),
+        "recover_payloads" => set(value, |c, v| c.recover_payloads = v),
+        "steps" => set_loaded(value, TrainingSteps::from_json, |c, v| c.steps = Some(v)),
+        "progress" => set_parsed(value, |c, v| c.progress = v),
+        "deterministic" => set(value, |c, v| c.deterministic = v),
+        "url_prefix" => set(value, |c, v| c.url_prefix = v),
+        "graph_dot" => set(value, |c, v| c.graph_dot = v),
+        "directory_layout" => set_parsed(value, |c, v| c.directory_layout = v),
+        "validate_output" => set(value, |c, v| c.validate_output = v),
+        "otel_spans" => set(value, |c, v| c.otel_spans = v),
+        "metrics_csv" => set(value, |c, v| c.metrics_csv = v),
+        "first_failure" => set(value, |c, v| c.first_failure = v),
+        "raw_jsonl_filenames" => set(value, |c, v| c.raw_jsonl_filenames = v),
+        "max_artifact_bytes" => set(value, |c, v| c.max_artifact_bytes = v),
+        "git_sha" => set(value, |c, v| c.git_sha = v),
+        "job_id" => set(value, |c, v| c.job_id = v),
+        "rank" => set(value, |c, v| c.rank = v),
+        "compare_runs" => set(value, |c, v| c.compare_runs = v),
+        "source_roots" => {
+            let source_roots = value
+                .extract::<Vec<String>>()?
+                .iter()
+                .map(|root| parse::<SourceRoot>(root))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(Box::new(move |c| c.source_roots = source_roots))
+        }
+        "summary_only" => set(value, |c, v| c.summary_only = v),
+        _ => Err(PyTypeError::new_err(format!(
+            "parse_path() got an unexpected keyword argument '{key}'"
+        ))),
+    }
+}
+
+/// Run only the parser named `parser_name` over a TORCH_TRACE log, e.g. to pull out the
+/// dynamo_output_graph of compile id "3/1", and return a dict of its outputs like `parse_path`.
+#[pyfunction]
+#[pyo3(signature = (path, parser_name, compile_id = None))]
+fn extract_artifact(
+    py: Python<'_>,
+    path: PathBuf,
+    parser_name: String,
+    compile_id: Option<String>,
+) -> PyResult<HashMap<String, String>> {
+    let compile_id = compile_id
+        .map(|id| id.parse::<CompileId>())
+        .transpose()
+        .map_err(PyValueError::new_err)?;
+    let output = py
+        .allow_threads(|| crate::extract_artifact(&path, &parser_name, compile_id.as_ref()))
+        .map_err(to_py_err)?;
+    Ok(files_dict(output))
+}
+
+fn files_dict(output: ParseOutput) -> HashMap<String, String> {
+    output
+        .into_iter()
+        .map(|(filename, content)| (filename.to_string_lossy().to_string(), content))
+        .collect()
+}
+
+fn to_py_err(e: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(format!("{e:#}"))
+}
+
+#[pymodule]
+fn tlparse(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(parse_path, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_artifact, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::types::InternTable;
+
+/// raw.jsonl as it's built up during parsing.  It needs the string table on its first line, which
+/// is only complete at the end, so when it's written straight to disk the entries are spilled to
+/// a file next to it and spliced in after the string table at the end.  On huge logs this saves
+/// holding all of raw.jsonl in memory.
+pub enum RawJsonl {
+    Memory(String),
+    Spill {
+        path: PathBuf,
+        // Before `spill_path`, so that the file is closed before it's removed
+        writer: BufWriter<File>,
+        spill_path: SpillFile,
+        // The first write error, reported by `finish`
+        error: Option<io::Error>,
+    },
+}
+
+/// The file entries are spilled to, removed when it's dropped, e.g. when parsing fails before
+/// raw.jsonl is finished
+pub struct SpillFile(PathBuf);
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl RawJsonl {
+    /// Spill the entries to `<path>.partial` and write raw.jsonl to `path` when done
+    pub fn spill(path: &Path) -> anyhow::Result<Self> {
+        let mut spill_path = path.as_os_str().to_owned();
+        spill_path.push(".partial");
+        let spill_path = PathBuf::from(spill_path);
+        let writer = BufWriter::new(File::create(&spill_path)?);
+        Ok(RawJsonl::Spill {
+            path: path.to_path_buf(),
+            writer,
+            spill_path: SpillFile(spill_path),
+            error: None,
+        })
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        match self {
+            RawJsonl::Memory(content) => {
+                content.push_str(line);
+                content.push('\n');
+            }
+            RawJsonl::Spill { writer, error, .. } => {
+                if error.is_none() {
+                    if let Err(e) = writeln!(writer, "{line}") {
+                        *error = Some(e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Puts `string_table_line` in front of the entries.  Returns the content of raw.jsonl if it's
+    /// kept in memory.
+    pub fn finish(self, string_table_line: &str) -> anyhow::Result<Option<String>> {
+        match self {
+            RawJsonl::Memory(content) => {
+                let mut raw_jsonl =
+                    String::with_capacity(string_table_line.len() + 1 + content.len());
+                raw_jsonl.push_str(string_table_line);
+                raw_jsonl.push('\n');
+                raw_jsonl.push_str(&content);
+                Ok(Some(raw_jsonl))
+            }
+            RawJsonl::Spill {
+                path,
+                writer,
+                spill_path,
+                error,
+            } => {
+                if let Some(e) = error {
+                    return Err(e.into());
+                }
+                writer.into_inner().map_err(|e| e.into_error())?;
+                let mut out = BufWriter::new(File::create(&path)?);
+                writeln!(out, "{string_table_line}")?;
+                io::copy(&mut File::open(&spill_path.0)?, &mut out)?;
+                out.flush()?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Drops the entries, e.g. because there's no raw.jsonl in export mode
+    pub fn discard(self) {}
+}
+
+/// Replaces the filenames of stack frames in `value`, which are indices into the string table,
+/// with the filenames themselves, so that raw.jsonl can be read without joining against it.
+/// Indices not in `intern_table` yet are kept.
+pub fn inline_filenames(value: &mut Value, intern_table: &InternTable) {
+    match value {
+        Value::Object(obj) => {
+            // Frames are {"line": 12, "name": "forward", "filename": 3, "loc": "..."}
+            if obj.contains_key("line") {
+                if let Some(filename) = obj.get_mut("filename") {
+                    if let Some(s) = filename
+                        .as_u64()
+                        .and_then(|i| intern_table.lookup(u32::try_from(i).ok()?))
+                    {
+                        *filename = Value::String(s.to_string());
+                    }
+                }
+            }
+            for v in obj.values_mut() {
+                inline_filenames(v, intern_table);
+            }
+        }
+        Value::Array(values) => {
+            for v in values {
+                inline_filenames(v, intern_table);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,254 @@
+// Reading a log one record at a time: the envelope of each log line with the payload that follows
+// it.  Both the report and `extract_artifact` read logs this way.
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::iter::Peekable;
+
+use regex::Regex;
+
+use crate::glog::{glog_regex, parse_glog_prefix, GlogPrefix};
+use crate::log_source::{invalid_utf8_sequences, LogLine};
+use crate::payload::LazyPayload;
+use crate::types::{Envelope, Stats};
+use crate::{ParseConfig, PayloadVerification};
+
+// How many log lines of other threads may be interleaved with a payload before we give up on it
+const MAX_INTERLEAVED_LINES: usize = 16;
+
+/// Whether the payload of a record matches the md5 of its envelope
+#[derive(Debug)]
+pub enum PayloadCheck {
+    NoPayload,
+    /// Not checked, see `ParseConfig::verify_payloads`
+    Unverified,
+    Valid,
+    /// `expected_md5` is None if the envelope's isn't an md5.  A payload that runs to the end of
+    /// the log and doesn't match was cut off, e.g. because the job crashed.
+    Mismatch {
+        expected_md5: Option<[u8; 16]>,
+        at_eof: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct Record<'a> {
+    pub lineno: usize,
+    pub prefix: GlogPrefix<'a>,
+    /// The envelope as logged
+    pub json: Cow<'a, str>,
+    /// Bytes of the envelope's line in the log
+    pub line_bytes: u64,
+    pub envelope: Envelope,
+    pub payload: LazyPayload<'a>,
+    pub check: PayloadCheck,
+    /// Lines of other threads found in the middle of the payload, which are read after it
+    pub interleaved_lines: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub enum LogItem<'a> {
+    // Envelopes are big
+    Record(Box<Record<'a>>),
+    /// A line without a glog prefix
+    NotGlog {
+        lineno: usize,
+    },
+    /// Payload lines without an envelope, e.g. because it was lost in a crash.  Only with
+    /// `ParseConfig::recover_payloads`, they are lines without a glog prefix otherwise.
+    OrphanPayload {
+        lineno: usize,
+        payload: LazyPayload<'a>,
+    },
+    /// An envelope that isn't JSON.  One on the last line of the log was cut off, `at_eof`.
+    BadEnvelope {
+        lineno: usize,
+        prefix: GlogPrefix<'a>,
+        json: Cow<'a, str>,
+        error: serde_json::Error,
+        at_eof: bool,
+    },
+}
+
+impl LogItem<'_> {
+    pub fn lineno(&self) -> usize {
+        match self {
+            LogItem::Record(record) => record.lineno,
+            LogItem::NotGlog { lineno }
+            | LogItem::OrphanPayload { lineno, .. }
+            | LogItem::BadEnvelope { lineno, .. } => *lineno,
+        }
+    }
+}
+
+pub struct RecordReader<'a, I: Iterator<Item = LogLine<'a>>> {
+    lines: Peekable<I>,
+    // Lines of other threads found in the middle of a payload, to read next
+    deferred_lines: VecDeque<LogLine<'a>>,
+    re_glog: Regex,
+    recover_payloads: bool,
+    verify_payloads: PayloadVerification,
+    // Payloads seen so far, for sampling which ones to verify
+    num_payloads: u64,
+    /// Bytes of the log's lines read so far
+    pub bytes_read: u64,
+}
+
+impl<'a, I: Iterator<Item = LogLine<'a>>> RecordReader<'a, I> {
+    pub fn new(lines: I, config: &ParseConfig) -> Self {
+        RecordReader {
+            lines: lines.peekable(),
+            deferred_lines: VecDeque::new(),
+            re_glog: glog_regex(),
+            recover_payloads: config.recover_payloads,
+            verify_payloads: config.verify_payloads,
+            num_payloads: 0,
+            bytes_read: 0,
+        }
+    }
+
+    fn at_eof(&mut self) -> bool {
+        self.lines.peek().is_none() && self.deferred_lines.is_empty()
+    }
+
+    /// The next item of the log.  Bytes that aren't UTF-8 are replaced instead of dropping the
+    /// lines they are on, and counted in `stats`.
+    pub fn next_item(&mut self, stats: &mut Stats) -> Option<LogItem<'a>> {
+        let line = self
+            .deferred_lines
+            .pop_front()
+            .or_else(|| self.lines.next())?;
+        let lineno = line.lineno;
+        let bytes = line.bytes();
+        self.bytes_read += bytes.len() as u64;
+
+        // The glog prefix is ASCII, so bytes that aren't UTF-8 are in the envelope
+        let valid = match std::str::from_utf8(bytes) {
+            Ok(valid) => valid,
+            Err(e) => {
+                stats.invalid_utf8 += invalid_utf8_sequences(bytes);
+                std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default()
+            }
+        };
+        let Some(prefix) = parse_glog_prefix(valid, &self.re_glog) else {
+            if self.recover_payloads && line.is_payload() {
+                let mut payload = LazyPayload::default();
+                payload.push_line(line);
+                while let Some(payload_line) = self.lines.next_if(|l| l.is_payload()) {
+                    payload.push_line(payload_line);
+                }
+                return Some(LogItem::OrphanPayload { lineno, payload });
+            }
+            return Some(LogItem::NotGlog { lineno });
+        };
+
+        let json = String::from_utf8_lossy(&bytes[prefix.payload_start..]);
+        let envelope = match serde_json::from_str::<Envelope>(&json) {
+            Ok(envelope) => envelope,
+            Err(error) => {
+                let at_eof = self.at_eof();
+                return Some(LogItem::BadEnvelope {
+                    lineno,
+                    prefix,
+                    json,
+                    error,
+                    at_eof,
+                });
+            }
+        };
+
+        let mut payload = LazyPayload::default();
+        let mut check = PayloadCheck::NoPayload;
+        let mut interleaved_lines = Vec::new();
+        if let Some(expect) = &envelope.has_payload {
+            while let Some(payload_line) = self.lines.next_if(|l| l.is_payload()) {
+                payload.push_line(payload_line);
+            }
+            let mut expect_buf = [0u8; 16];
+            let expected_md5 = base16ct::lower::decode(expect, &mut expect_buf)
+                .is_ok()
+                .then_some(expect_buf);
+            let interleaved = match expected_md5 {
+                Some(expected_md5) if self.recover_payloads => reattach_interleaved_payload(
+                    &mut self.lines,
+                    &self.re_glog,
+                    prefix.thread,
+                    &mut payload,
+                    expected_md5,
+                ),
+                _ => Vec::new(),
+            };
+            let invalid_utf8 = payload.invalid_utf8();
+            if invalid_utf8 > 0 {
+                stats.invalid_utf8 += invalid_utf8;
+                stats.invalid_utf8_payloads += 1;
+            }
+            // Recovering payloads relies on their hashes
+            let verify = self.recover_payloads
+                || match self.verify_payloads {
+                    PayloadVerification::All => true,
+                    PayloadVerification::Sample(n) => {
+                        self.num_payloads.is_multiple_of(n.max(1) as u64)
+                    }
+                    PayloadVerification::None => false,
+                };
+            self.num_payloads += 1;
+            check = if !verify {
+                PayloadCheck::Unverified
+            } else if expected_md5 == Some(payload.md5()) {
+                PayloadCheck::Valid
+            } else {
+                PayloadCheck::Mismatch {
+                    expected_md5,
+                    at_eof: self.at_eof() && interleaved.is_empty(),
+                }
+            };
+            interleaved_lines = interleaved.iter().map(|l| l.lineno).collect();
+            self.deferred_lines.extend(interleaved);
+        }
+
+        Some(LogItem::Record(Box::new(Record {
+            lineno,
+            prefix,
+            json,
+            line_bytes: bytes.len() as u64 + 1,
+            envelope,
+            payload,
+            check,
+            interleaved_lines,
+        })))
+    }
+}
+
+/// Continues reading a payload of `thread` whose hash doesn't match `expected_md5` past log lines
+/// of other threads that have no payload of their own, on the theory that they were written in
+/// the middle of ours.  Returns the lines skipped, which still need to be read.
+fn reattach_interleaved_payload<'a, I: Iterator<Item = LogLine<'a>>>(
+    iter: &mut Peekable<I>,
+    re_glog: &Regex,
+    thread: u64,
+    payload: &mut LazyPayload<'a>,
+    expected_md5: [u8; 16],
+) -> Vec<LogLine<'a>> {
+    let mut interleaved = Vec::new();
+    while payload.md5() != expected_md5 && interleaved.len() < MAX_INTERLEAVED_LINES {
+        let Some(&line) = iter.peek() else {
+            break;
+        };
+        let decoded = String::from_utf8_lossy(line.bytes());
+        let Some(prefix) = parse_glog_prefix(&decoded, re_glog) else {
+            break;
+        };
+        if prefix.thread == thread || decoded[prefix.payload_start..].contains("\"has_payload\"") {
+            break;
+        }
+        iter.next();
+        interleaved.push(line);
+        if !iter.peek().is_some_and(|l| l.is_payload()) {
+            break;
+        }
+        while let Some(payload_line) = iter.next_if(|l| l.is_payload()) {
+            payload.push_line(payload_line);
+        }
+    }
+    interleaved
+}
@@ -0,0 +1,110 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::types::OpRuntime;
+
+#[derive(Debug, Serialize)]
+pub struct RuntimeEstimationRow {
+    pub name: String,
+    pub estimated_us: f64,
+    /// Share of the graph's total estimated runtime
+    pub percent: f64,
+    /// Where the op runs in the output code, e.g. triton_poi_fused_relu_1 or extern_kernels.mm
+    pub kernel: Option<String>,
+    pub url: Option<String>,
+}
+
+// A kernel launch in the output code, e.g. triton_poi_fused_relu_1.run(
+static LAUNCH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w+)\.run\(").unwrap());
+
+/// Finds the line of the output code's `call` that runs each op.  Ops are estimated in the order
+/// they run, so each is looked for after the previous one's line.  Extern kernels and collectives
+/// are called by name; fused nodes (op1, op2_op3, ...) launch generated kernels, which are the
+/// ones in the provenance mappings when there are any, or else anything `.run(`.
+fn kernel_lines(
+    output_code: &str,
+    ops: &[OpRuntime],
+    kernel_names: &[String],
+) -> Vec<Option<(String, usize)>> {
+    let lines: Vec<&str> = output_code.lines().collect();
+    let mut cursor = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("def call("))
+        .unwrap_or(lines.len());
+    ops.iter()
+        .map(|op| {
+            let is_call =
+                op.name.starts_with("extern_kernels.") || op.name.starts_with("torch.ops.");
+            let found = lines[cursor..].iter().enumerate().find_map(|(i, line)| {
+                let code = line.trim_start();
+                if code.starts_with('#') {
+                    return None;
+                }
+                let kernel = if is_call {
+                    code.contains(&format!("{}(", op.name))
+                        .then(|| op.name.clone())
+                } else if kernel_names.is_empty() {
+                    LAUNCH.captures(code).map(|c| c[1].to_string())
+                } else {
+                    kernel_names
+                        .iter()
+                        .filter(|k| !k.starts_with("extern_kernels."))
+                        .find(|k| code.contains(k.as_str()))
+                        .cloned()
+                };
+                kernel.map(|k| (k, cursor + i))
+            });
+            found.map(|(kernel, i)| {
+                cursor = i + 1;
+                // 1-based, like the anchors of the rendered output code
+                (kernel, i + 1)
+            })
+        })
+        .collect()
+}
+
+/// Ops sorted by estimated runtime, slowest first, linked to where they run in the output code
+/// at `output_code_url` if there is one
+pub fn runtime_estimation_rows(
+    ops: &[OpRuntime],
+    output_code: Option<(&str, &str)>,
+    kernel_names: &[String],
+) -> Vec<RuntimeEstimationRow> {
+    let total_ns: f64 = ops.iter().map(|op| op.estimated_runtime_ns).sum();
+    let kernels = match output_code {
+        Some((code, _)) => kernel_lines(code, ops, kernel_names),
+        None => vec![None; ops.len()],
+    };
+    let mut rows: Vec<(f64, RuntimeEstimationRow)> = ops
+        .iter()
+        .zip(kernels)
+        .map(|(op, kernel)| {
+            let url = kernel
+                .as_ref()
+                .zip(output_code)
+                .map(|((_, line), (_, url))| {
+                    // Plain text output code has no anchors
+                    if url.ends_with(".html") {
+                        format!("{url}#L{line}")
+                    } else {
+                        url.to_string()
+                    }
+                });
+            let row = RuntimeEstimationRow {
+                name: op.name.clone(),
+                estimated_us: (op.estimated_runtime_ns / 100.0).round() / 10.0,
+                percent: if total_ns > 0.0 {
+                    (op.estimated_runtime_ns / total_ns * 1000.0).round() / 10.0
+                } else {
+                    0.0
+                },
+                kernel: kernel.map(|(k, _)| k),
+                url,
+            };
+            (op.estimated_runtime_ns, row)
+        })
+        .collect();
+    rows.sort_by(|a, b| b.0.total_cmp(&a.0));
+    rows.into_iter().map(|(_, row)| row).collect()
+}
@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use fxhash::FxHashSet;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::payload::LazyPayload;
+use crate::types::{CompileId, DynamoGuard, Envelope, FxIndexMap, OutputFile};
+
+/// What a search entry refers to.  Entries which aren't artifacts themselves link to the artifact
+/// of their compile id which best shows them (e.g. a kernel links to the inductor output code).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Artifact,
+    CompileId,
+    Guard,
+    Failure,
+    Kernel,
+}
+
+impl SearchKind {
+    // Name fragment of the artifact an entry of this kind should link to
+    fn target_artifact(&self) -> Option<&'static str> {
+        match self {
+            SearchKind::Artifact => None,
+            SearchKind::CompileId => Some("compilation_metrics"),
+            SearchKind::Guard => Some("dynamo_guards"),
+            SearchKind::Failure => Some("compilation_metrics"),
+            SearchKind::Kernel => Some("inductor_output_code"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchEntry {
+    pub kind: SearchKind,
+    pub text: String,
+    pub compile_id: String,
+    pub url: String,
+}
+
+/// Inverted index over everything a user may want to look up in a report, serialized as
+/// search_index.json and embedded into search.html.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub entries: Vec<SearchEntry>,
+    /// token => indices into `entries`
+    pub index: BTreeMap<String, BTreeSet<usize>>,
+}
+
+/// Collects search entries while the log is being parsed.  Entries that link to another
+/// artifact are resolved in `finish`, once all artifacts of their compile id are known.
+pub struct SearchIndexBuilder {
+    pending: Vec<(SearchKind, String, Option<CompileId>)>,
+    seen: FxHashSet<(String, Option<CompileId>)>,
+    kernel_re: Regex,
+}
+
+impl Default for SearchIndexBuilder {
+    fn default() -> Self {
+        SearchIndexBuilder {
+            pending: Vec::new(),
+            seen: FxHashSet::default(),
+            kernel_re: Regex::new(r"(?m)^(\w+) = async_compile\.\w+\(").unwrap(),
+        }
+    }
+}
+
+impl SearchIndexBuilder {
+    fn push(&mut self, kind: SearchKind, text: String, compile_id: &Option<CompileId>) {
+        // Guards in particular are repeated for every recompile of a frame
+        if self.seen.insert((text.clone(), compile_id.clone())) {
+            self.pending.push((kind, text, compile_id.clone()));
+        }
+    }
+
+    /// Record the guards and failure reasons mentioned by a single log entry.  `guards` are those
+    /// of its dynamo_guards payload, which the caller parses once for everyone.
+    pub fn record(
+        &mut self,
+        e: &Envelope,
+        guards: Option<&[DynamoGuard]>,
+        compile_id: &Option<CompileId>,
+    ) {
+        for guard in guards.into_iter().flatten() {
+            self.push(SearchKind::Guard, guard.code.clone(), compile_id);
+        }
+        for expr in [
+            e.guard_added_fast.as_ref().and_then(|g| g.expr.as_ref()),
+            e.guard_added.as_ref().and_then(|g| g.expr.as_ref()),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.push(SearchKind::Guard, expr.clone(), compile_id);
+        }
+        if let Some(m) = e.compilation_metrics.as_ref() {
+            for restart in m.restart_reasons.iter().flatten() {
+                self.push(SearchKind::Failure, restart.clone(), compile_id);
+            }
+            if let (Some(fail_type), Some(fail_reason)) = (&m.fail_type, &m.fail_reason) {
+                self.push(
+                    SearchKind::Failure,
+                    format!("{fail_type}: {fail_reason}"),
+                    compile_id,
+                );
+            }
+        }
+    }
+
+    /// Record the kernels defined in the payload of an inductor_output_code entry
+    pub fn record_kernels(&mut self, payload: &LazyPayload, compile_id: &Option<CompileId>) {
+        let kernels: Vec<String> = self
+            .kernel_re
+            .captures_iter(payload.get())
+            .map(|caps| caps[1].to_string())
+            .collect();
+        for kernel in kernels {
+            self.push(SearchKind::Kernel, kernel, compile_id);
+        }
+    }
+
+    /// Build the index, adding every artifact and compile id of the report
+    pub fn finish(self, directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>) -> SearchIndex {
+        let mut index = SearchIndex::default();
+        let compile_id_str = |cid: &Option<CompileId>| {
+            cid.as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string())
+        };
+        let resolve_url = |kind: SearchKind, cid: &Option<CompileId>| -> String {
+            let files = directory.get(cid).map(Vec::as_slice).unwrap_or_default();
+            kind.target_artifact()
+                .and_then(|target| files.iter().find(|f| f.name.contains(target)))
+                .or(files.first())
+                .map_or("index.html".to_string(), |f| f.url.clone())
+        };
+
+        for (cid, files) in directory {
+            if cid.is_some() {
+                index.add(SearchEntry {
+                    kind: SearchKind::CompileId,
+                    text: compile_id_str(cid),
+                    compile_id: compile_id_str(cid),
+                    url: resolve_url(SearchKind::CompileId, cid),
+                });
+            }
+            for file in files {
+                index.add(SearchEntry {
+                    kind: SearchKind::Artifact,
+                    text: file.name.clone(),
+                    compile_id: compile_id_str(cid),
+                    url: file.url.clone(),
+                });
+            }
+        }
+        for (kind, text, cid) in self.pending {
+            let url = resolve_url(kind, &cid);
+            index.add(SearchEntry {
+                kind,
+                text,
+                compile_id: compile_id_str(&cid),
+                url,
+            });
+        }
+        index
+    }
+}
+
+impl SearchIndex {
+    fn add(&mut self, entry: SearchEntry) {
+        let id = self.entries.len();
+        for token in tokenize(&entry.text).chain(tokenize(&entry.compile_id)) {
+            self.index.entry(token).or_default().insert(id);
+        }
+        self.entries.push(entry);
+    }
+}
+
+/// Split text into lowercase search tokens.  Underscores and slashes are kept so that kernel
+/// names (triton_poi_fused_mul_1) and compile ids (0/1) stay a single token; search.html
+/// tokenizes queries the same way.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '/'))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+}
+
+/// Where a guard, kernel or failure shows up in a multi-rank report
+#[derive(Debug, Serialize)]
+pub struct CrossRankHit {
+    pub rank: u32,
+    pub compile_id: String,
+    /// Relative to the root of the multi-rank report, e.g. rank_3/-_0_0_0/dynamo_guards_0.html
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrossRankEntry {
+    pub kind: SearchKind,
+    pub text: String,
+    pub hits: Vec<CrossRankHit>,
+}
+
+/// The guards, kernels and failures of every rank, each with the ranks and compile ids it shows
+/// up in, embedded into cross_rank_search.html
+#[derive(Debug, Default, Serialize)]
+pub struct CrossRankSearchIndex {
+    pub entries: Vec<CrossRankEntry>,
+    /// token => indices into `entries`
+    pub index: BTreeMap<String, BTreeSet<usize>>,
+}
+
+/// Merges the search_index.json of each rank under `out_path`, e.g. rank_3/search_index.json.
+/// Ranks without one, e.g. because they were parsed in export mode, are left out.
+pub fn cross_rank_search_index(
+    out_path: &Path,
+    rank_nums: &[u32],
+) -> anyhow::Result<CrossRankSearchIndex> {
+    let mut merged: FxIndexMap<(SearchKind, String), Vec<CrossRankHit>> = FxIndexMap::default();
+    for &rank in rank_nums {
+        let path = out_path
+            .join(format!("rank_{rank}"))
+            .join("search_index.json");
+        if !path.exists() {
+            continue;
+        }
+        let rank_index: SearchIndex = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("Couldn't load {}", path.display()))?;
+        for entry in rank_index.entries {
+            if !matches!(
+                entry.kind,
+                SearchKind::Guard | SearchKind::Kernel | SearchKind::Failure
+            ) {
+                continue;
+            }
+            merged
+                .entry((entry.kind, entry.text))
+                .or_default()
+                .push(CrossRankHit {
+                    rank,
+                    compile_id: entry.compile_id,
+                    url: format!("rank_{rank}/{}", entry.url),
+                });
+        }
+    }
+    let mut index = CrossRankSearchIndex::default();
+    for ((kind, text), hits) in merged {
+        let id = index.entries.len();
+        for token in tokenize(&text) {
+            index.index.entry(token).or_default().insert(id);
+        }
+        index.entries.push(CrossRankEntry { kind, text, hits });
+    }
+    Ok(index)
+}
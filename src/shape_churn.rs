@@ -0,0 +1,114 @@
+use fxhash::FxHashSet;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::types::{CompileId, FxIndexMap, SymbolicShapeSpecializationMetadata};
+
+// The size of a dimension of a local, e.g. L['x'].size()[0], which mark_dynamic can target
+static LOCAL_SIZE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^L\['([A-Za-z_][A-Za-z0-9_]*)'\]\.size\(\)\[(\d+)\]$").unwrap());
+
+/// The compiled autograd id and frame id of a compile.  Sources are locals of a frame, so the same
+/// source of different frames is a different size.
+type Frame = (Option<u32>, Option<u32>);
+
+/// The values each source of each frame was specialized to, and by which compile ids, in log
+/// order
+#[derive(Debug, Default)]
+pub struct SpecializationIndex {
+    sources: FxIndexMap<(Frame, String), FxIndexMap<String, Vec<String>>>,
+}
+
+impl SpecializationIndex {
+    pub fn record(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        specialization: &SymbolicShapeSpecializationMetadata,
+    ) {
+        let (Some(source), Some(value)) = (
+            specialization.sources.iter().flatten().next(),
+            specialization.value.as_ref(),
+        ) else {
+            return;
+        };
+        let frame = compile_id
+            .as_ref()
+            .map_or((None, None), |c| (c.compiled_autograd_id, c.frame_id));
+        let compile_id = compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string());
+        let compile_ids = self
+            .sources
+            .entry((frame, source.clone()))
+            .or_default()
+            .entry(value.clone())
+            .or_default();
+        if !compile_ids.contains(&compile_id) {
+            compile_ids.push(compile_id);
+        }
+    }
+}
+
+/// A value a source was specialized to
+#[derive(Debug, Serialize)]
+pub struct SpecializedValue {
+    pub value: String,
+    pub compile_ids: Vec<String>,
+}
+
+/// A source that was specialized to different values by different compiles, which recompiles
+/// the frame for every new value, e.g. a batch size that varies
+#[derive(Debug, Serialize)]
+pub struct ShapeChurn {
+    /// The frame the source is a local of, e.g. 0 or !1/0 under compiled autograd
+    pub frame: String,
+    /// e.g. L['x'].size()[0]
+    pub source: String,
+    pub values: Vec<SpecializedValue>,
+    pub num_compile_ids: usize,
+    /// How to keep the source dynamic, e.g. torch._dynamo.mark_dynamic(x, 0)
+    pub hint: String,
+}
+
+fn frame_name((compiled_autograd_id, frame_id): Frame) -> String {
+    let frame_id = frame_id.map_or("-".to_string(), |id| id.to_string());
+    match compiled_autograd_id {
+        Some(compiled_autograd_id) => format!("!{compiled_autograd_id}/{frame_id}"),
+        None => frame_id,
+    }
+}
+
+fn mark_dynamic_hint(source: &str) -> String {
+    match LOCAL_SIZE.captures(source) {
+        Some(c) => format!("torch._dynamo.mark_dynamic({}, {})", &c[1], &c[2]),
+        None => format!("torch._dynamo.mark_dynamic on the tensor of {source}"),
+    }
+}
+
+/// The sources of `index` specialized to more than one value, those recompiled the most first
+pub fn shape_churn(index: &SpecializationIndex) -> Vec<ShapeChurn> {
+    let mut churn: Vec<ShapeChurn> = index
+        .sources
+        .iter()
+        .filter(|(_, values)| values.len() > 1)
+        .map(|((frame, source), values)| {
+            let compile_ids: FxHashSet<&String> = values.values().flatten().collect();
+            ShapeChurn {
+                frame: frame_name(*frame),
+                source: source.clone(),
+                values: values
+                    .iter()
+                    .map(|(value, compile_ids)| SpecializedValue {
+                        value: value.clone(),
+                        compile_ids: compile_ids.clone(),
+                    })
+                    .collect(),
+                num_compile_ids: compile_ids.len(),
+                hint: mark_dynamic_hint(source),
+            }
+        })
+        .collect();
+    churn.sort_by_key(|c| std::cmp::Reverse(c.num_compile_ids));
+    churn
+}
@@ -0,0 +1,133 @@
+// Source context of user stack frames, read from local checkouts of the logged code with
+// `--source-root`, so that a stack shows what each frame was doing rather than just file:line.
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use fxhash::FxHashMap;
+use html_escape::encode_text;
+
+// Lines shown before and after the line of a frame
+const CONTEXT_LINES: usize = 2;
+
+/// Where to find the source files of a log: files under `prefix` (the directory of the code on
+/// the machine that logged it) are read from under `dir`.  Without a prefix, a file is read from
+/// the longest tail of its path that exists under `dir`, e.g. test/test_misc.py for
+/// /data/users/me/pytorch/test/test_misc.py.  A tail has at least a directory and the file, so
+/// that an unrelated file of the same name, e.g. __init__.py, isn't shown instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceRoot {
+    pub prefix: Option<String>,
+    pub dir: PathBuf,
+}
+
+impl FromStr for SourceRoot {
+    type Err = String;
+
+    /// `DIR` or `PREFIX=DIR`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, dir) = match s.split_once('=') {
+            Some((prefix, dir)) => (Some(prefix.to_string()), dir),
+            None => (None, s),
+        };
+        if dir.is_empty() {
+            return Err(format!("{s} has no directory to read sources from"));
+        }
+        Ok(SourceRoot {
+            prefix,
+            dir: PathBuf::from(dir),
+        })
+    }
+}
+
+/// Reads the snippets of source around frames, with the lines of each file cached since the
+/// frames of a log keep coming back to the same files
+pub struct SourceSnippets {
+    roots: Vec<SourceRoot>,
+    // None if the file couldn't be found or read
+    files: RefCell<FxHashMap<String, Option<Vec<String>>>>,
+}
+
+impl SourceSnippets {
+    pub fn new(roots: Vec<SourceRoot>) -> Self {
+        SourceSnippets {
+            roots,
+            files: RefCell::new(FxHashMap::default()),
+        }
+    }
+
+    fn resolve(&self, filename: &str) -> Option<PathBuf> {
+        for root in &self.roots {
+            match &root.prefix {
+                Some(prefix) => {
+                    if let Some(rest) = filename.strip_prefix(prefix.as_str()) {
+                        let rest = Path::new(rest.trim_start_matches('/'));
+                        // Only read files under `dir`, whatever the log says
+                        if !rest.components().all(|c| matches!(c, Component::Normal(_))) {
+                            continue;
+                        }
+                        let path = root.dir.join(rest);
+                        if path.is_file() {
+                            return Some(path);
+                        }
+                    }
+                }
+                None => {
+                    let components: Vec<_> = Path::new(filename)
+                        .components()
+                        .filter(|c| matches!(c, Component::Normal(_)))
+                        .collect();
+                    for start in 0..components.len().saturating_sub(1) {
+                        let path = root
+                            .dir
+                            .join(components[start..].iter().collect::<PathBuf>());
+                        if path.is_file() {
+                            return Some(path);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Lines around `line` (1-based) of `filename` as html, with the line itself highlighted, or
+    /// None if the file isn't under any of the roots
+    pub fn snippet_html(&self, filename: &str, line: i32) -> Option<String> {
+        let mut files = self.files.borrow_mut();
+        let lines = files
+            .entry(filename.to_string())
+            .or_insert_with(|| {
+                let content = std::fs::read(self.resolve(filename)?).ok()?;
+                Some(
+                    String::from_utf8_lossy(&content)
+                        .lines()
+                        .map(str::to_string)
+                        .collect(),
+                )
+            })
+            .as_ref()?;
+        let index = usize::try_from(line).ok()?.checked_sub(1)?;
+        if index >= lines.len() {
+            return None;
+        }
+        let mut html = String::from("<pre class='source-snippet'>");
+        let end = (index + CONTEXT_LINES + 1).min(lines.len());
+        for (i, text) in lines
+            .iter()
+            .enumerate()
+            .take(end)
+            .skip(index.saturating_sub(CONTEXT_LINES))
+        {
+            let text = format!("{:>6}  {}", i + 1, encode_text(text));
+            if i == index {
+                writeln!(html, "<span class='current'>{text}</span>").unwrap();
+            } else {
+                writeln!(html, "{text}").unwrap();
+            }
+        }
+        html.push_str("</pre>");
+        Some(html)
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::Context;
+use fxhash::FxHashMap;
+use serde_json::{json, Value};
+
+use crate::types::{CompilationMetricsIndex, CompileId};
+
+// Timestamps above this are in milliseconds, as MLflow logs them, rather than seconds
+const MAX_TIMESTAMP_S: f64 = 1e11;
+
+/// When each training step started, from a sidecar file exported from e.g. wandb or MLflow
+#[derive(Debug, Default)]
+pub struct TrainingSteps {
+    // (unix timestamp in seconds, step), sorted by timestamp
+    steps: Vec<(f64, u64)>,
+}
+
+fn timestamp_s(value: &Value) -> Option<f64> {
+    let t = value.as_f64()?;
+    Some(if t > MAX_TIMESTAMP_S { t / 1000.0 } else { t })
+}
+
+impl TrainingSteps {
+    /// Either a list of records with a step and a timestamp, named like wandb (`_step`,
+    /// `_timestamp`) or MLflow (`step`, `timestamp`) name them, or an object mapping steps to
+    /// timestamps, e.g. {"1200": 1712345678.5}
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(json).context("Steps file is not JSON")?;
+        let mut steps: Vec<(f64, u64)> = match &value {
+            Value::Array(records) => records
+                .iter()
+                .enumerate()
+                .map(|(i, record)| {
+                    let step = ["step", "_step"]
+                        .iter()
+                        .find_map(|key| record[*key].as_u64())
+                        .with_context(|| format!("Record {i} of the steps file has no step"))?;
+                    let t = ["timestamp", "_timestamp", "time"]
+                        .iter()
+                        .find_map(|key| timestamp_s(&record[*key]))
+                        .with_context(|| {
+                            format!("Record {i} of the steps file has no timestamp")
+                        })?;
+                    Ok((t, step))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            Value::Object(map) => map
+                .iter()
+                .map(|(step, t)| {
+                    let step: u64 = step
+                        .parse()
+                        .with_context(|| format!("Step {step} is not a number"))?;
+                    let t =
+                        timestamp_s(t).with_context(|| format!("Step {step} has no timestamp"))?;
+                    Ok((t, step))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            _ => anyhow::bail!("Expected a list of steps or an object mapping steps to timestamps"),
+        };
+        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(TrainingSteps { steps })
+    }
+
+    /// The step running at unix time `t`, i.e. the last one started by then
+    pub fn step_at(&self, t: f64) -> Option<u64> {
+        let started = self.steps.partition_point(|(start, _)| *start <= t);
+        started.checked_sub(1).map(|i| self.steps[i].1)
+    }
+
+    /// The step each compilation started in, by its compilation metrics' start_time
+    pub fn compile_steps(
+        &self,
+        metrics_index: &CompilationMetricsIndex,
+    ) -> FxHashMap<Option<CompileId>, u64> {
+        metrics_index
+            .iter()
+            .filter_map(|(compile_id, metrics)| {
+                let start_time = metrics.iter().find_map(|m| m.start_time)?;
+                Some((compile_id.clone(), self.step_at(start_time)?))
+            })
+            .collect()
+    }
+
+    /// Instant events marking the start of each step between the first and last of
+    /// `chromium_events`, whose timestamps are in microseconds since the epoch
+    pub fn chromium_events(&self, chromium_events: &[Value]) -> Vec<Value> {
+        let timestamps = chromium_events.iter().filter_map(|e| e["ts"].as_f64());
+        let (first, last) = timestamps.fold((f64::MAX, f64::MIN), |(first, last), ts| {
+            (first.min(ts), last.max(ts))
+        });
+        self.steps
+            .iter()
+            .map(|(t, step)| (t * 1e6, step))
+            .filter(|(ts, _)| *ts >= first && *ts <= last)
+            .map(|(ts, step)| {
+                json!({
+                    "name": format!("step {step}"),
+                    "cat": "training_step",
+                    "ph": "i",
+                    "s": "g",
+                    "ts": ts,
+                    "pid": 0,
+                    "tid": 0,
+                    "args": {"step": step},
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::inductor_config::config_changes;
+use crate::types::{CompilationMetricsIndex, CompileId, FxIndexMap, OutputFile};
+
+// How many of the slowest compiles to list
+const NUM_SLOWEST: usize = 5;
+
+// Compile times are reported to the millisecond
+fn round_s(t: f64) -> f64 {
+    (t * 1000.0).round() / 1000.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowCompile {
+    pub compile_id: String,
+    pub compile_time_s: f64,
+    /// Name of the compiled function
+    #[serde(default)]
+    pub co_name: Option<String>,
+}
+
+/// Headline numbers about the compilations in a log, shown on index.html and written to
+/// compile_summary.json (and, with `--report-md`, as Markdown for CI comments)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompileSummary {
+    /// Compilation metrics logged, i.e. compilations including restarted attempts
+    pub total_compiles: u64,
+    /// Distinct compile ids that recompile a frame, i.e. `[x/y]` with y > 0
+    pub recompiles: u64,
+    /// Restarts of Dynamo's analysis, most of which are graph breaks
+    pub graph_breaks: u64,
+    /// Compilations that failed outright
+    pub failures: u64,
+    pub cumulative_compile_time_s: f64,
+    /// Slowest first
+    pub slowest_compiles: Vec<SlowCompile>,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// None if the log has no cache hits or misses
+    pub cache_hit_rate: Option<f64>,
+    /// Guards installed by all compilations
+    #[serde(default)]
+    pub total_guards: u64,
+    /// Inductor and Dynamo config options of the run, see inductor_config.html
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub config: BTreeMap<String, String>,
+}
+
+/// One row of the comparison against a `--baseline` summary
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryDelta {
+    pub metric: &'static str,
+    pub baseline: String,
+    pub current: String,
+    pub delta: String,
+    /// Whether the change is for the worse
+    pub regressed: bool,
+}
+
+impl CompileSummary {
+    pub fn new(
+        metrics_index: &CompilationMetricsIndex,
+        directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    ) -> Self {
+        let mut summary = CompileSummary::default();
+        let mut recompiles = std::collections::HashSet::new();
+        for (compile_id, metrics) in metrics_index {
+            if let Some(CompileId {
+                compiled_autograd_id,
+                frame_id,
+                frame_compile_id: Some(frame_compile_id),
+                ..
+            }) = compile_id
+            {
+                if *frame_compile_id > 0 {
+                    recompiles.insert((*compiled_autograd_id, *frame_id, *frame_compile_id));
+                }
+            }
+            let mut compile_time_s = None;
+            for m in metrics {
+                summary.total_compiles += 1;
+                summary.graph_breaks += m.restart_reasons.as_ref().map_or(0, |r| r.len()) as u64;
+                summary.failures += m.fail_type.is_some() as u64;
+                summary.total_guards += m.guard_count.unwrap_or(0);
+                if let Some(t) = m.entire_frame_compile_time_s {
+                    *compile_time_s.get_or_insert(0.0) += t;
+                }
+            }
+            if let Some(compile_time_s) = compile_time_s {
+                summary.cumulative_compile_time_s += compile_time_s;
+                summary.slowest_compiles.push(SlowCompile {
+                    compile_id: compile_id
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    compile_time_s: round_s(compile_time_s),
+                    co_name: metrics.iter().find_map(|m| m.co_name.clone()),
+                });
+            }
+        }
+        summary.recompiles = recompiles.len() as u64;
+        summary.cumulative_compile_time_s = round_s(summary.cumulative_compile_time_s);
+        summary
+            .slowest_compiles
+            .sort_by(|a, b| b.compile_time_s.total_cmp(&a.compile_time_s));
+        summary.slowest_compiles.truncate(NUM_SLOWEST);
+
+        // The same suffixes the index uses to mark cache hits and misses
+        for file in directory.values().flatten() {
+            match file.suffix.as_str() {
+                "✅" => summary.cache_hits += 1,
+                "❌" => summary.cache_misses += 1,
+                _ => {}
+            }
+        }
+        let lookups = summary.cache_hits + summary.cache_misses;
+        if lookups > 0 {
+            summary.cache_hit_rate = Some(summary.cache_hits as f64 / lookups as f64);
+        }
+        summary
+    }
+
+    pub fn cache_miss_rate(&self) -> Option<f64> {
+        self.cache_hit_rate.map(|r| 1.0 - r)
+    }
+
+    /// How this summary differs from `baseline`, a summary of an earlier run
+    pub fn compare(&self, baseline: &CompileSummary) -> Vec<SummaryDelta> {
+        // For all of these but cache hits, more is worse
+        let counts = [
+            (
+                "Compiles",
+                baseline.total_compiles,
+                self.total_compiles,
+                true,
+            ),
+            ("Recompiles", baseline.recompiles, self.recompiles, true),
+            (
+                "Graph breaks",
+                baseline.graph_breaks,
+                self.graph_breaks,
+                true,
+            ),
+            ("Failures", baseline.failures, self.failures, true),
+            ("Cache hits", baseline.cache_hits, self.cache_hits, false),
+            (
+                "Cache misses",
+                baseline.cache_misses,
+                self.cache_misses,
+                true,
+            ),
+            ("Guards", baseline.total_guards, self.total_guards, true),
+        ];
+        let mut deltas: Vec<SummaryDelta> = counts
+            .into_iter()
+            .map(|(metric, baseline, current, more_is_worse)| {
+                let delta = current as i64 - baseline as i64;
+                SummaryDelta {
+                    metric,
+                    baseline: baseline.to_string(),
+                    current: current.to_string(),
+                    delta: format!("{delta:+}"),
+                    regressed: if more_is_worse { delta > 0 } else { delta < 0 },
+                }
+            })
+            .collect();
+        let delta_s = self.cumulative_compile_time_s - baseline.cumulative_compile_time_s;
+        deltas.insert(
+            4,
+            SummaryDelta {
+                metric: "Cumulative compile time (s)",
+                baseline: baseline.cumulative_compile_time_s.to_string(),
+                current: self.cumulative_compile_time_s.to_string(),
+                delta: match self.compile_time_change(baseline) {
+                    Some(change) => format!("{:+.3} ({:+.1}%)", delta_s, change * 100.0),
+                    None => format!("{delta_s:+.3}"),
+                },
+                regressed: delta_s > 0.0,
+            },
+        );
+        deltas
+    }
+
+    /// Relative change in cumulative compile time, e.g. 0.1 for 10% slower than `baseline`
+    pub fn compile_time_change(&self, baseline: &CompileSummary) -> Option<f64> {
+        (baseline.cumulative_compile_time_s > 0.0)
+            .then(|| self.cumulative_compile_time_s / baseline.cumulative_compile_time_s - 1.0)
+    }
+
+    /// A short report, e.g. for CI to post as a PR comment
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        writeln!(md, "## torch.compile report\n").unwrap();
+        writeln!(md, "| Metric | Value |").unwrap();
+        writeln!(md, "| --- | --- |").unwrap();
+        writeln!(md, "| Compiles | {} |", self.total_compiles).unwrap();
+        writeln!(md, "| Recompiles | {} |", self.recompiles).unwrap();
+        writeln!(md, "| Graph breaks | {} |", self.graph_breaks).unwrap();
+        writeln!(md, "| Failures | {} |", self.failures).unwrap();
+        writeln!(
+            md,
+            "| Cumulative compile time | {:.2} s |",
+            self.cumulative_compile_time_s
+        )
+        .unwrap();
+        writeln!(
+            md,
+            "| Cache hit rate | {} |",
+            self.cache_hit_rate.map_or("n/a".to_string(), |r| format!(
+                "{:.1}% ({} hits, {} misses)",
+                r * 100.0,
+                self.cache_hits,
+                self.cache_misses
+            ))
+        )
+        .unwrap();
+        if !self.slowest_compiles.is_empty() {
+            writeln!(md, "\n### Slowest compiles\n").unwrap();
+            writeln!(md, "| Compile Id | Compile time |").unwrap();
+            writeln!(md, "| --- | --- |").unwrap();
+            for c in &self.slowest_compiles {
+                writeln!(md, "| `{}` | {:.2} s |", c.compile_id, c.compile_time_s).unwrap();
+            }
+        }
+        md
+    }
+
+    /// A plain text version of the report, for the terminal
+    pub fn to_table(&self) -> String {
+        let mut table = String::new();
+        let cache = match self.cache_hit_rate {
+            Some(r) => format!(
+                "{} / {} ({:.1}% hit rate)",
+                self.cache_hits,
+                self.cache_misses,
+                r * 100.0
+            ),
+            None => "n/a".to_string(),
+        };
+        for (metric, value) in [
+            ("Compiles", self.total_compiles.to_string()),
+            ("Recompiles", self.recompiles.to_string()),
+            ("Graph breaks", self.graph_breaks.to_string()),
+            ("Failures", self.failures.to_string()),
+            ("Cache hits / misses", cache),
+            (
+                "Total compile time",
+                format!("{:.3} s", self.cumulative_compile_time_s),
+            ),
+        ] {
+            writeln!(table, "{metric:<22}{value}").unwrap();
+        }
+        if !self.slowest_compiles.is_empty() {
+            writeln!(table, "\nSlowest compiles").unwrap();
+            let width = self
+                .slowest_compiles
+                .iter()
+                .map(|c| c.compile_id.len())
+                .max()
+                .unwrap_or_default();
+            for c in &self.slowest_compiles {
+                writeln!(
+                    table,
+                    "  {:<width$}  {:>9.3} s  {}",
+                    c.compile_id,
+                    c.compile_time_s,
+                    c.co_name.as_deref().unwrap_or_default()
+                )
+                .unwrap();
+            }
+        }
+        table
+    }
+
+    /// A plain text version of `compare`, for the terminal, marking regressions with a `!`
+    pub fn compare_table(&self, baseline: &CompileSummary) -> String {
+        let deltas = self.compare(baseline);
+        let width = |column: fn(&SummaryDelta) -> &str, header: &str| {
+            deltas
+                .iter()
+                .map(|d| column(d).len())
+                .chain([header.len()])
+                .max()
+                .unwrap_or_default()
+        };
+        let metric_width = width(|d| d.metric, "");
+        let baseline_width = width(|d| &d.baseline, "Baseline");
+        let current_width = width(|d| &d.current, "Current");
+        let mut table = String::new();
+        writeln!(
+            table,
+            "{:<metric_width$}  {:>baseline_width$}  {:>current_width$}  Delta",
+            "", "Baseline", "Current"
+        )
+        .unwrap();
+        for d in &deltas {
+            writeln!(
+                table,
+                "{:<metric_width$}  {:>baseline_width$}  {:>current_width$}  {}{}",
+                d.metric,
+                d.baseline,
+                d.current,
+                d.delta,
+                if d.regressed { " !" } else { "" }
+            )
+            .unwrap();
+        }
+        let changes = config_changes(&baseline.config, &self.config);
+        if !changes.is_empty() {
+            writeln!(table, "\nConfig changes").unwrap();
+            for c in &changes {
+                writeln!(table, "  {}: {} -> {}", c.key, c.baseline, c.current).unwrap();
+            }
+        }
+        table
+    }
+}
+
+/// A compact snapshot of a run for dashboards, written to trend_record.json on one line so that
+/// the records of many runs can be appended to a JSONL file and loaded into a time-series store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendRecord {
+    pub tlparse_version: String,
+    /// Identify the run, e.g. the commit the job ran at, from `--git-sha` and `--job-id`
+    pub git_sha: Option<String>,
+    pub job_id: Option<String>,
+    /// Rank of the log, for the records of a multi-rank report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+    /// When the report was written, in seconds since the Unix epoch; left out with
+    /// `--deterministic`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_at: Option<u64>,
+    pub total_compiles: u64,
+    pub recompiles: u64,
+    pub graph_breaks: u64,
+    pub failures: u64,
+    pub cumulative_compile_time_s: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub total_guards: u64,
+}
+
+impl TrendRecord {
+    pub fn new(
+        summary: &CompileSummary,
+        git_sha: Option<String>,
+        job_id: Option<String>,
+        rank: Option<u32>,
+        recorded_at: Option<u64>,
+    ) -> Self {
+        TrendRecord {
+            tlparse_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha,
+            job_id,
+            rank,
+            recorded_at,
+            total_compiles: summary.total_compiles,
+            recompiles: summary.recompiles,
+            graph_breaks: summary.graph_breaks,
+            failures: summary.failures,
+            cumulative_compile_time_s: summary.cumulative_compile_time_s,
+            cache_hits: summary.cache_hits,
+            cache_misses: summary.cache_misses,
+            total_guards: summary.total_guards,
+        }
+    }
+}
+
+/// Compile health limits for CI, e.g. `--fail-on-recompiles`.  A limit is exceeded when the
+/// summary's value is greater than it.
+#[derive(Debug, Clone, Default)]
+pub struct CompileThresholds {
+    pub recompiles: Option<u64>,
+    pub graph_breaks: Option<u64>,
+    pub cache_miss_rate: Option<f64>,
+    /// Relative to `--baseline`, e.g. 0.1 to allow compiling up to 10% slower
+    pub compile_time_regression: Option<f64>,
+    /// Relative to `--baseline`
+    pub compile_count_increase: Option<u64>,
+}
+
+impl CompileThresholds {
+    pub fn is_empty(&self) -> bool {
+        self.recompiles.is_none()
+            && self.graph_breaks.is_none()
+            && self.cache_miss_rate.is_none()
+            && self.compile_time_regression.is_none()
+            && self.compile_count_increase.is_none()
+    }
+
+    /// Descriptions of the limits `summary` exceeds.  Limits relative to a baseline are only
+    /// checked if there is one.
+    pub fn violations(
+        &self,
+        summary: &CompileSummary,
+        baseline: Option<&CompileSummary>,
+    ) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(max) = self.recompiles.filter(|max| summary.recompiles > *max) {
+            violations.push(format!("{} recompiles (limit {max})", summary.recompiles));
+        }
+        if let Some(max) = self.graph_breaks.filter(|max| summary.graph_breaks > *max) {
+            violations.push(format!(
+                "{} graph breaks (limit {max})",
+                summary.graph_breaks
+            ));
+        }
+        if let (Some(max), Some(rate)) = (self.cache_miss_rate, summary.cache_miss_rate()) {
+            if rate > max {
+                violations.push(format!("cache miss rate {rate:.3} (limit {max})"));
+            }
+        }
+        if let Some(baseline) = baseline {
+            if let (Some(max), Some(change)) = (
+                self.compile_time_regression,
+                summary.compile_time_change(baseline),
+            ) {
+                if change > max {
+                    violations.push(format!(
+                        "compile time {:+.1}% over baseline (limit {:+.1}%)",
+                        change * 100.0,
+                        max * 100.0
+                    ));
+                }
+            }
+            let increase = summary
+                .total_compiles
+                .saturating_sub(baseline.total_compiles);
+            if let Some(max) = self.compile_count_increase.filter(|max| increase > *max) {
+                violations.push(format!(
+                    "{increase} more compiles than baseline (limit {max})"
+                ));
+            }
+        }
+        violations
+    }
+}
+
+// Name, help and value of a metric of metrics.prom
+type PrometheusMetric = (&'static str, &'static str, fn(&CompileSummary) -> f64);
+
+/// The compile summaries of ranks, or of a single log with no rank, in the Prometheus text
+/// format, e.g. for CI jobs to push to a Pushgateway or for node_exporter's textfile collector
+pub fn prometheus_metrics(summaries: &[(Option<u32>, &CompileSummary)]) -> String {
+    let metrics: [PrometheusMetric; 8] = [
+        (
+            "compiles_total",
+            "Compilations, including restarted attempts",
+            |s| s.total_compiles as f64,
+        ),
+        (
+            "recompiles_total",
+            "Compile ids that recompile a frame",
+            |s| s.recompiles as f64,
+        ),
+        ("graph_breaks_total", "Restarts of Dynamo's analysis", |s| {
+            s.graph_breaks as f64
+        }),
+        ("compile_failures_total", "Compilations that failed", |s| {
+            s.failures as f64
+        }),
+        (
+            "compile_seconds_total",
+            "Cumulative compile time in seconds",
+            |s| s.cumulative_compile_time_s,
+        ),
+        ("cache_hits_total", "Compile cache hits", |s| {
+            s.cache_hits as f64
+        }),
+        ("cache_misses_total", "Compile cache misses", |s| {
+            s.cache_misses as f64
+        }),
+        (
+            "guards_total",
+            "Guards installed by all compilations",
+            |s| s.total_guards as f64,
+        ),
+    ];
+    let mut prom = String::new();
+    for (name, help, value) in metrics {
+        writeln!(prom, "# HELP tlparse_{name} {help}").unwrap();
+        writeln!(prom, "# TYPE tlparse_{name} counter").unwrap();
+        for (rank, summary) in summaries {
+            let labels = rank.map_or(String::new(), |rank| format!("{{rank=\"{rank}\"}}"));
+            writeln!(prom, "tlparse_{name}{labels} {}", value(summary)).unwrap();
+        }
+    }
+    prom
+}
+
+// A CSV field, quoted if it has to be
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The compilation metrics as CSV, one row per compilation with every numeric field as a column,
+/// e.g. for `pandas.read_csv`
+pub fn metrics_csv(metrics_index: &CompilationMetricsIndex) -> String {
+    let rows: Vec<(
+        String,
+        Option<String>,
+        serde_json::Map<String, serde_json::Value>,
+    )> = metrics_index
+        .iter()
+        .flat_map(|(compile_id, metrics)| {
+            let compile_id = compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string());
+            metrics.iter().map(move |m| {
+                let fields = match serde_json::to_value(m) {
+                    Ok(serde_json::Value::Object(fields)) => fields,
+                    _ => serde_json::Map::new(),
+                };
+                (compile_id.clone(), m.co_name.clone(), fields)
+            })
+        })
+        .collect();
+    let mut columns: Vec<&str> = rows
+        .iter()
+        .flat_map(|(_, _, fields)| fields.iter())
+        .filter(|(_, value)| value.is_number())
+        .map(|(name, _)| name.as_str())
+        .collect();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let mut csv = String::new();
+    writeln!(csv, "compile_id,co_name,{}", columns.join(",")).unwrap();
+    for (compile_id, co_name, fields) in &rows {
+        let mut row = vec![
+            csv_field(compile_id),
+            csv_field(co_name.as_deref().unwrap_or_default()),
+        ];
+        row.extend(columns.iter().map(|column| match fields.get(*column) {
+            Some(value) if value.is_number() => value.to_string(),
+            _ => String::new(),
+        }));
+        writeln!(csv, "{}", row.join(",")).unwrap();
+    }
+    csv
+}
@@ -15,11 +15,28 @@ table td { vertical-align: top; }
 }
 .stack-trie a { text-decoration: none; }
 .stack-trie a:hover { text-decoration: underline; }
+.stack-trie .weight { color: gray; }
+.stack-trie .source-snippet { margin: 2px 0 4px 4ch; color: gray; }
+.stack-trie .source-snippet .current { color: black; font-weight: bold; }
+
+.frame-locals td { padding: 2px 8px; }
+.frame-locals ul { margin: 0; padding-left: 2ch; list-style-type: none; }
+.frame-locals .badge {
+    display: inline-block; margin-left: 4px; padding: 0 6px; border-radius: 8px;
+    background-color: #e1ecf4; color: #1c4f7c; font-family: monospace; font-size: 0.9em;
+}
+.frame-locals .tensor { font-family: monospace; text-decoration: underline dotted; }
+.frame-locals .long-repr pre { white-space: pre-wrap; }
 .status-missing { background-color: purple; color: white; }
 .status-error { background-color: red; color: white; }
 .status-empty { background-color: white; color: black; }
 .status-ok { background-color: green; color: white; }
 .status-break { background-color: lime; color: black; }
+.log-line { color: gray; font-size: smaller; }
+.attempt { color: gray; }
+.step { color: #2a6f97; font-size: smaller; }
+.graph-size { color: gray; font-size: smaller; }
+.restart-reason { list-style: none; color: #b35900; font-style: italic; }
 summary::-webkit-details-marker { color: #00ACF3; font-size: 125%; margin-right: 2px; }
 summary:focus { outline-style: none; }
 article > details > summary { font-size: 28px; margin-top: 16px; }
@@ -44,6 +61,15 @@ pub static JAVASCRIPT: &str = r#"
       toggleItem.classList.toggle('collapsed');
     }
   }
+
+  // When the build products are paginated, links to compile ids listed on other pages (e.g. from
+  // the stack trie) go to the page that lists them
+  function followToIndexPage() {
+    const compileId = decodeURIComponent(window.location.hash.slice(1));
+    if (compileId in indexPageOf && !document.getElementById(compileId)) {
+      window.location.href = indexPageOf[compileId] + window.location.hash;
+    }
+  }
 "#;
 
 pub static EXPORT_CSS: &str = r#"
@@ -82,151 +108,1505 @@ td:first-child {
 
 pub static TEMPLATE_DYNAMO_GUARDS: &str = r#"
 <html>
+<head>
+<link rel="stylesheet" href="{root}theme.css">
+</head>
 <body>
 <h2>Guards</h2>
 <ul>
 {{ for guard in guards }}
-    <li><code>{guard.code}</code></li>
+    <li id="guard-{@index}"><code>{guard.code}</code></li>
+{{ endfor }}
+</ul>
+{qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_INDEX: &str = r#"
+<html>
+<head>
+  <meta charset="UTF-8">
+</head>
+<style>
+{css | format_unescaped}
+</style>
+<link rel="stylesheet" href="theme.css">
+<script>
+{javascript | format_unescaped}
+</script>
+<body>
+<div>
+{custom_header_html | format_unescaped}
+{{ if distributed_info }}
+<p>
+Rank <strong>{distributed_info.rank}</strong>{{ if distributed_info.world_size }} of {distributed_info.world_size}{{ endif }}{{ if distributed_info.hostname }} on <code>{distributed_info.hostname}</code>{{ endif }}{{ if distributed_info.device_type }} ({distributed_info.device_type}){{ endif }}
+</p>
+{{ endif }}
+<p><a href="search.html">Search</a> all artifacts, compile ids, guards, failures and kernels.</p>
+{{ if truncated_line }}
+<div class="warning-box">
+<p><strong>Warning:</strong> The log is truncated: it ends in the middle of the envelope on line {truncated_line}, e.g. because the job crashed.
+The report covers everything logged before it.</p>
+</div>
+{{ endif }}
+{{ if has_first_failure }}
+<p>Parsing stopped at the <a href='first_failure.html'>first failed compilation</a>, so this report only has its artifacts.</p>
+{{ endif }}
+{{ if findings }}
+<h2>Findings</h2>
+<style>
+.finding-high \{ border-left: 4px solid #cc0000; padding-left: 8px; }
+.finding-medium \{ border-left: 4px solid #e69500; padding-left: 8px; }
+.finding-low \{ border-left: 4px solid #999999; padding-left: 8px; }
+</style>
+<p>Checks of the usual causes of slow or unstable compilation flagged these, most severe first:</p>
+<ul>
+{{ for finding in findings }}
+<li class="finding-{finding.severity}"><strong>{finding.title}</strong> ({finding.severity}). {finding.detail}
+Evidence: {{ for e in finding.evidence }}<a href='{e.url}'>{e.text}</a>{{ if not @last }}, {{ endif }}{{ endfor }}</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+{{ if completeness_hints }}
+<h2>Missing Artifacts</h2>
+<p>The log lacks records that parts of this report are built from:</p>
+<ul>
+{{ for h in completeness_hints }}
+<li><strong>{h.title}.</strong> {h.hint | format_unescaped}</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+<h2>Summary</h2>
+<p>
+<strong>{summary.total_compiles}</strong> compile(s), <strong>{summary.recompiles}</strong> recompile(s),
+<strong>{summary.graph_breaks}</strong> restart(s) (mostly graph breaks) and <strong>{summary.failures}</strong> failure(s),
+taking <strong>{summary.cumulative_compile_time_s}</strong> s in total.
+{{ if has_cache_stats }}Cache hit rate: {summary.cache_hits} hit(s), {summary.cache_misses} miss(es).{{ endif }}
+The numbers are also in <a href="compile_summary.json">compile_summary.json</a>.
+{{ if has_baseline }}See how they compare to the baseline in <a href="baseline_comparison.html">baseline_comparison.html</a>.{{ endif }}
+</p>
+{{ if summary.slowest_compiles }}
+<p>Slowest compiles:
+{{ for c in summary.slowest_compiles }}<a href='#{c.compile_id}'>{c.compile_id}</a> ({c.compile_time_s} s){{ if not @last }}, {{ endif }}{{ endfor }}
+</p>
+{{ endif }}
+<h2>Stack trie</h2>
+<p>
+The <strong>stack trie</strong> is a way of getting a quick orientation on where all the
+compilations in a model take place, esp., if you are compiling a codebase you are unfamiliar with.
+It is a tree of stack frames, for all stacks that triggered PT2 compilation.  If only a single
+stack is in the tree, you will simply see a plain list of frames (most recent call last).  With
+multiple stacks, at every point where two stacks diverge from having a common prefix, we increase
+the indentation of the list and have a separate sub-list per sub-tree.
+</p>
+<p>
+Links to particular compilation are color coded by status:
+<span class="status-ok">[Success]</span>,
+<span class="status-break">[Success with restart (e.g., graph break)]</span>,
+<span class="status-empty">[Empty graph]</span>,
+<span class="status-error">[Error]</span>,
+<span class="status-missing">[Metrics were missing]</span>
+</p>
+{stack_trie_html | format_unescaped}
+</div>
+{{ if hot_entry_points }}
+<div>
+<h2> Hot Compilation Entry Points </h2>
+<p>
+The user functions that most often triggered compilation, i.e. the innermost frames of the stacks above.
+A function compiled from several distinct stacks has more than one caller.
+</p>
+<table>
+<tr> <th> Function </th> <th> Location </th> <th> Compiles </th> <th> Distinct stacks </th> <th> Compile time (s) </th> <th> Compile ids </th> </tr>
+{{ for entry_point in hot_entry_points }}
+<tr> <td> <code>{entry_point.name}</code> </td> <td> {entry_point.filename}:{entry_point.line} </td> <td> {entry_point.num_compiles} </td> <td> {entry_point.num_stacks} </td> <td> {entry_point.compile_time_s} </td> <td> {{ for compile_id in entry_point.compile_ids }}<a href='#{compile_id}'>{compile_id}</a> {{ endfor }}</td> </tr>
+{{ endfor }}
+</table>
+</div>
+{{ endif }}
+<div>
+{{ if num_breaks }}
+<h2> Failures and Restarts </h2>
+<p>
+Various issues may cause Dynamo to restart its analysis or give up on compilation entirely, causing graph breaks and fallbacks to eager mode.
+This run had <strong><a href="failures_and_restarts.html">{num_breaks} restart(s) and/or compilation failure(s)</a></strong>.
+</p>
+{{ endif }}
+<h2>IR dumps</h2>
+<p>
+The <strong>IR dumps</strong> collected dumped intermediate products from various points of the PT2
+compilation process.  The products are organized by compile id, and then sorted in chronological
+order.
+</p>
+<p>
+A <strong>compile id</strong> uniquely identifies are particular compilation inside a PT2
+program.  It is traditionally written as <code>[x/y]</code>, where the <strong>frame id</strong> x
+identifies the particular Python frame which we are compiling, and <strong>frame compile
+id</strong> y identifies how many times we've recompiled this same frame.  For example,
+<code>[0/0]</code> refers to the very first frame compiled by PT2; <code>[0/1]</code> refers to the
+first recompilation of this frame, while <code>[1/0]</code> refers to a different frame, within
+distinct code cache, which we are compiling next (perhaps because of a graph break).  Although
+Dynamo treats distinct frames as completely unrelated, a frame compilation could overlap with another
+frame; for example, if you graph break in an inlined function, Dynamo will typically try to compile
+the nested frame again on an inner frame.  You can identify the hierarchical relationship between
+frames by looking at the stack trie above.
+</p>
+<p>
+In some situations, the compile id will have an extra signifier <code>[x/y_z]</code>, where z is the
+<strong>attempt</strong> for this particular (re)compilation.  Certain conditions will cause Dynamo to
+restart analysis, when Dynamo discovers that it needs to undo a decision it previously made.  The most
+common cause of recompilation is a graph break in an inlined function call, which forces to restart
+and avoid inlining the function in the first place.
+</p>
+<p>
+When compiled autograd is enabled, the compile id will include a prefix signifier <code>[!a/x/y]</code>,
+where a is the <strong>compiled autograd id</strong>. For instance, <code>[!0/-/-]</code> refers 
+to the first graph captured by compiled autograd. It is then traced by torch.compile as <code>[!0/x/y_z]</code>.
+</p>
+<p>
+Here is a high level description of PT2's compilation phases, and the intermediate products each
+phase generates:
+</p>
+<ol>
+<li><em>Optional:</em> If compiled autograd is enabled, and we are processing a backward call, compiled autograd will trace the autograd graph from the autograd engine, and produce an FX graph <code>compiled_autograd_graph</code> that will be Dynamo traced.  Otherwise, Dynamo will directly trace user's bytecode.</li>
+<li>Dynamo symbolically evaluates the Python bytecode of a program, producing <code>dynamo_output_graph</code></li>
+<li><em>Optional:</em> If <code>optimize_ddp</code> is enabled, the DDPOptimizer will split the Dynamo output graph to improve pipelining communications.  Each split subgraph is <code>optimize_ddp_split_child_submod</code>, and the high level graph that plumbs the graphs together is <code>optimize_ddp_split_graph</code>.  If there are multiple splits, each subsequent build product will be produced multiple times, one for each split.</li>
+<li>AOTAutograd traces the (possibly split) Dynamo output graph, producing a <code>aot_joint_graph</code> if backwards is enabled.  It then partitions the graph into <code>aot_forward_graph</code> and <code>aot_backward_graph</code>.  If training is not needed, there may only be an <code>aot_inference_graph</code>.</li>
+<li>Inductor will apply some post grad FX passes, producing <code>inductor_post_grad_graph</code></li>
+<li>Inductor will perform code generation, producing the final <code>inductor_output_code</code> which will be executed at runtime.  This output is a valid Python program and can be directly run.</li>
+</ol>
+
+{{ if has_chromium_events }}
+<h2> Chromium Events </h2>
+PT2 generates <a href='chromium_events.json'>Chromium Trace Events</a> in JSON on specific events during compilation.
+You can download and view them in a tool like <a href='https://ui.perfetto.dev/'>Perfetto</a>.
+{{ endif  }}
+{{ if has_pass_timings }}
+<h2> Inductor Pass Timings </h2>
+Inductor logged the duration of its graph passes; <a href='pass_timings.html'>pass_timings.html</a> aggregates
+them across compile ids to help spot pathologically slow passes.
+{{ endif }}
+{{ if memory_peaks }}
+<h2> Estimated Peak Memory </h2>
+<p>
+Inductor planned the memory of these compile ids, highest estimated peak first:
+</p>
+<ul>
+{{ for peak in memory_peaks }}
+<li><a href="{peak.url}">{peak.compile_id}</a>: {peak.peak_memory}</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+{{ if has_counters }}
+<h2> Counters </h2>
+<a href='counters.html'>counters.html</a> tabulates the counter artifacts that were logged, with
+how much each counter changed at every compile id.
+{{ endif }}
+{{ if has_graph_partitions }}
+<h2> Graph Partitions </h2>
+Some graphs were split into regions or partitions that are compiled separately;
+<a href='graph_partitions.html'>graph_partitions.html</a> shows how each frame was split.
+{{ endif }}
+{{ if has_graph_stats }}
+<h2> Graph Statistics </h2>
+Compile ids below are annotated with the number of nodes in their Dynamo graph.
+<a href='graph_stats.json'>graph_stats.json</a> breaks down every logged graph by op, with its
+inputs, outputs and largest fan-out.
+{{ endif }}
+{{ if num_duplicate_graphs }}
+<h2> Duplicate Graphs </h2>
+{num_duplicate_graphs} graph(s) were produced by more than one compile id, which often points at
+unnecessary recompilations or a misconfigured cache; see <a href='duplicate_graphs.html'>duplicate_graphs.html</a>.
+{{ endif }}
+{{ if has_module_map }}
+<h2> Module Map </h2>
+<a href='module_map.html'>module_map.html</a> maps the nodes of each pre-grad graph to the
+nn.Module of the model that they come from, e.g. layers.3.attn.
+{{ endif }}
+{{ if cudagraph_skips }}
+<h2> CUDA Graph Fallbacks </h2>
+<p>
+CUDA graphs were skipped for {num_cudagraph_skips} graph(s), which run their kernels without CUDA graphs instead:
+</p>
+<ul>
+{{ for skip in cudagraph_skips }}
+<li><a href="{skip.url}">{skip.compile_id}</a>: <code>{skip.reason}</code></li>
+{{ endfor }}
+</ul>
+{{ endif }}
+{{ if data_dependent_specializations }}
+<h2> Data-Dependent Specializations </h2>
+<p>
+{num_data_dependent_specializations} data-dependent specialization(s) occurred: these expressions
+depended on the values of tensors, so they were evaluated with real tensors and asserts
+(<code>torch._check</code>) were inserted into the graph, which fail if a later input disagrees:
+</p>
+<ul>
+{{ for s in data_dependent_specializations }}
+<li><a href="{s.url}">{s.compile_id}</a>: <code>{s.expr}</code> evaluated to <code>{s.result}</code></li>
+{{ endfor }}
+</ul>
+{{ endif }}
+{{ if guard_failures }}
+<h2> Guard Failures </h2>
+<p>
+These recompiles happened because guards of earlier compiles failed.  Each links to a Markdown
+summary of the failed guards, ready to paste into an issue:
+</p>
+<ul>
+{{ for failure in guard_failures }}
+<li><a href='{failure.url}'>{failure.compile_id}</a> <code>{failure.co_name}</code>{{ for g in failure.failed_guards }}{{ if @first }}: <code>{g.guard}</code>{{ endif }}{{ endfor }}</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+{{ if shape_churn }}
+<h2> Dynamic Shape Churn </h2>
+<p>
+These sizes were specialized to a different value by different compiles, so every new value
+recompiled the frame, e.g. a batch size that varies.  Marking them dynamic before calling the
+compiled function compiles a single graph for all of them instead:
+</p>
+<table>
+<tr> <th> Frame </th> <th> Source </th> <th> Values </th> <th> Suggestion </th> </tr>
+{{ for churn in shape_churn }}
+<tr> <td> {churn.frame} </td> <td> <code>{churn.source}</code> </td> <td> {{ for v in churn.values }}<code>{v.value}</code> ({{ for compile_id in v.compile_ids }}<a href='#{compile_id}'>{compile_id}</a>{{ if not @last }} {{ endif }}{{ endfor }}){{ if not @last }}, {{ endif }}{{ endfor }} </td> <td> <code>{churn.hint}</code> </td> </tr>
+{{ endfor }}
+</table>
+{{ endif }}
+{{ if guard_latency }}
+<h2> Guard Latency </h2>
+Evaluating guards took {guard_latency.total_latency_us} us for {guard_latency.total_guard_count} guards across all compiled frames.
+<a href='guard_latency.html'>guard_latency.html</a> ranks compile ids by their guard overhead.
+{{ endif }}
+{{ if has_runtime_profile_correlation }}
+<h2> Runtime Estimation Accuracy </h2>
+<a href='runtime_profile_correlation.html'>runtime_profile_correlation.html</a> compares Inductor's estimated
+runtime of each op with the kernel durations measured in the profile passed with <code>--profile-trace</code>.
+{{ endif }}
+{{ if has_code_size }}
+<h2> Generated Code Size </h2>
+<a href='code_size.html'>code_size.html</a> shows how much code was generated for each compile id, to help
+find the graphs that contribute most to binary size or warm start time.
+{{ endif }}
+{{ if num_threads }}
+<h2> Threads </h2>
+The log was written by {num_threads} threads.  <a href='threads.html'>threads.html</a> shows what each of them
+compiled and when{{ if num_overlapping_compiles }}; {num_overlapping_compiles} compile(s) ran at the same time as a compile on another thread{{ endif }}.
+{{ endif }}
+{{ if envelope_stats }}
+<h2> Log Records </h2>
+<a href='trace_size.html'>trace_size.html</a> breaks down the size of the log by record type and compile id,
+and <a href='envelope_stats.json'>envelope_stats.json</a> counts the records of each type.
+<details>
+<summary>Records in the log by type, largest payloads first</summary>
+<table>
+<tr><th>Type</th><th>Count</th><th>Payload bytes</th></tr>
+{{ for s in envelope_stats }}
+<tr><td><code>{s.envelope_type}</code></td><td>{s.count}</td><td>{s.payload_bytes}</td></tr>
+{{ endfor }}
+</table>
+</details>
+{{ endif }}
+{{ if parser_timings }}
+<h2> Parser Profile </h2>
+Time spent in each parser, including writing its outputs, is in <a href='parser_profile.json'>parser_profile.json</a>.
+<details>
+<summary>Parsers by total time, slowest first</summary>
+<table>
+<tr><th>Parser</th><th>Calls</th><th>Total (s)</th><th>Mean (ms)</th><th>Outputs</th><th>Output bytes</th></tr>
+{{ for t in parser_timings }}
+<tr><td><code>{t.parser}</code></td><td>{t.calls}</td><td>{t.total_s}</td><td>{t.mean_ms}</td><td>{t.outputs}</td><td>{t.output_bytes}</td></tr>
+{{ endfor }}
+</table>
+</details>
+{{ endif }}
+{{ if has_compile_flamegraph }}
+<h2> Compile Time Flame Graph </h2>
+The <a href='compile_flamegraph.svg'>compile time flame graph</a> shows which user code is responsible
+for compilation time: each compilation is charged to the stack that triggered it, and the width of a frame
+is the total compile time of everything below it.  The underlying data is also available in
+<a href='compile_flamegraph.folded'>folded stacks format</a>, e.g. for rendering with inferno.
+{{ endif }}
+<p>
+Build products below:
+</p>
+{{ if has_index_pages }}
+<p>Pages: <strong>1</strong>{{ for page in index_pages }} <a href="{page.url}">{page.number}</a>{{ endfor }}</p>
+<script>
+const indexPageOf = {index_page_of_json | format_unescaped};
+window.addEventListener("hashchange", followToIndexPage);
+window.addEventListener("DOMContentLoaded", followToIndexPage);
+</script>
+{{ endif }}
+<ul>
+{{ for compile_directory in directory }}
+{{ if compile_directory.2.first_attempt }}
+    <li><span class="attempt-frame">{compile_directory.2.frame}</span> ({compile_directory.2.num_attempts} attempts)
+    <ul class="attempt-tree">
+{{ endif }}
+{{ for restart_reason in compile_directory.2.restart_reasons }}
+    <li class="restart-reason">restarted: {restart_reason}</li>
+{{ endfor }}
+    <li><a id="{compile_directory.0}">{compile_directory.0}</a>{{ if compile_directory.2.frame }} <span class="attempt">attempt {compile_directory.2.attempt}</span>{{ endif }}{{ if compile_directory.2.step }} <span class="step">step {compile_directory.2.step}</span>{{ endif }}{{ if compile_directory.2.graph_nodes }} <span class="graph-size" title="Nodes of dynamo_output_graph">{compile_directory.2.graph_nodes} nodes</span>{{ endif }}
+    <ul>
+        {{ for path_idx in compile_directory.1 }}
+            <li><a href="{path_idx.url}">{path_idx.name}</a>{{ if path_idx.readable_url }} (<a href="{path_idx.readable_url}">readable_html</a>){{ endif }} {path_idx.suffix} ({path_idx.number}){{ if path_idx.log_line }} <span class="log-line" title="Line of raw.log this was parsed from">raw.log:{path_idx.log_line}</span>{{ endif }}</li>
+        {{ endfor }}
+    </ul>
+    </li>
+{{ if compile_directory.2.last_attempt }}
+    </ul>
+    </li>
+{{ endif }}
 {{ endfor }}
 </ul>
+{{ if has_overflow_compile_ids }}
+<p>
+The artifacts of {num_overflow_compile_ids} less expensive compile ids were omitted to keep this page
+loadable; their compilation metrics are summarized in <a href="overflow_compile_ids.html">overflow_compile_ids.html</a>.
+</p>
+{{ endif }}
+</div>
+
+
+{{ if has_inductor_provenance }}
+<h2>Provenance Tracking</h2>
+<div>
+    <p>View detailed provenance tracking information for each rank and frame:</p>
+    <ul>
+    {{ for directory_name in directory_names }}
+        <li><a href='provenance_tracking_{directory_name}.html'>provenance_tracking_{directory_name}</a></li>
+    {{ endfor }}
+    </ul>
+</div>
+{{ endif }}
+
+{{ if has_unknown_stack_trie }}
+<div>
+<h2>Unknown stacks</h2>
+<p>
+  Sometimes, logs are made without a compile id.  This makes it difficult to correlate related
+  logs.  This stack trie shows all places where log entries occurred without compile context; to
+  fix, look an appropriate place in the stack where compile id should have been specified.
+</p>
+{unknown_stack_trie_html | format_unescaped}
+</div>
+{{ endif }}
 {qps | format_unescaped}
 </body>
 </html>
 "#;
 
-pub static TEMPLATE_INDEX: &str = r#"
-<html>
-<head>
-  <meta charset="UTF-8">
-</head>
-<style>
-{css | format_unescaped}
-</style>
-<script>
-{javascript | format_unescaped}
-</script>
-<body>
-<div>
-{custom_header_html | format_unescaped}
-<h2>Stack trie</h2>
-<p>
-The <strong>stack trie</strong> is a way of getting a quick orientation on where all the
-compilations in a model take place, esp., if you are compiling a codebase you are unfamiliar with.
-It is a tree of stack frames, for all stacks that triggered PT2 compilation.  If only a single
-stack is in the tree, you will simply see a plain list of frames (most recent call last).  With
-multiple stacks, at every point where two stacks diverge from having a common prefix, we increase
-the indentation of the list and have a separate sub-list per sub-tree.
-</p>
-<p>
-Links to particular compilation are color coded by status:
-<span class="status-ok">[Success]</span>,
-<span class="status-break">[Success with restart (e.g., graph break)]</span>,
-<span class="status-empty">[Empty graph]</span>,
-<span class="status-error">[Error]</span>,
-<span class="status-missing">[Metrics were missing]</span>
-</p>
-{stack_trie_html | format_unescaped}
-</div>
-<div>
-{{ if num_breaks }}
-<h2> Failures and Restarts </h2>
-<p>
-Various issues may cause Dynamo to restart its analysis or give up on compilation entirely, causing graph breaks and fallbacks to eager mode.
-This run had <strong><a href="failures_and_restarts.html">{num_breaks} restart(s) and/or compilation failure(s)</a></strong>.
-</p>
-{{ endif }}
-<h2>IR dumps</h2>
-<p>
-The <strong>IR dumps</strong> collected dumped intermediate products from various points of the PT2
-compilation process.  The products are organized by compile id, and then sorted in chronological
-order.
-</p>
-<p>
-A <strong>compile id</strong> uniquely identifies are particular compilation inside a PT2
-program.  It is traditionally written as <code>[x/y]</code>, where the <strong>frame id</strong> x
-identifies the particular Python frame which we are compiling, and <strong>frame compile
-id</strong> y identifies how many times we've recompiled this same frame.  For example,
-<code>[0/0]</code> refers to the very first frame compiled by PT2; <code>[0/1]</code> refers to the
-first recompilation of this frame, while <code>[1/0]</code> refers to a different frame, within
-distinct code cache, which we are compiling next (perhaps because of a graph break).  Although
-Dynamo treats distinct frames as completely unrelated, a frame compilation could overlap with another
-frame; for example, if you graph break in an inlined function, Dynamo will typically try to compile
-the nested frame again on an inner frame.  You can identify the hierarchical relationship between
-frames by looking at the stack trie above.
-</p>
-<p>
-In some situations, the compile id will have an extra signifier <code>[x/y_z]</code>, where z is the
-<strong>attempt</strong> for this particular (re)compilation.  Certain conditions will cause Dynamo to
-restart analysis, when Dynamo discovers that it needs to undo a decision it previously made.  The most
-common cause of recompilation is a graph break in an inlined function call, which forces to restart
-and avoid inlining the function in the first place.
-</p>
-<p>
-When compiled autograd is enabled, the compile id will include a prefix signifier <code>[!a/x/y]</code>,
-where a is the <strong>compiled autograd id</strong>. For instance, <code>[!0/-/-]</code> refers 
-to the first graph captured by compiled autograd. It is then traced by torch.compile as <code>[!0/x/y_z]</code>.
-</p>
-<p>
-Here is a high level description of PT2's compilation phases, and the intermediate products each
-phase generates:
-</p>
-<ol>
-<li><em>Optional:</em> If compiled autograd is enabled, and we are processing a backward call, compiled autograd will trace the autograd graph from the autograd engine, and produce an FX graph <code>compiled_autograd_graph</code> that will be Dynamo traced.  Otherwise, Dynamo will directly trace user's bytecode.</li>
-<li>Dynamo symbolically evaluates the Python bytecode of a program, producing <code>dynamo_output_graph</code></li>
-<li><em>Optional:</em> If <code>optimize_ddp</code> is enabled, the DDPOptimizer will split the Dynamo output graph to improve pipelining communications.  Each split subgraph is <code>optimize_ddp_split_child_submod</code>, and the high level graph that plumbs the graphs together is <code>optimize_ddp_split_graph</code>.  If there are multiple splits, each subsequent build product will be produced multiple times, one for each split.</li>
-<li>AOTAutograd traces the (possibly split) Dynamo output graph, producing a <code>aot_joint_graph</code> if backwards is enabled.  It then partitions the graph into <code>aot_forward_graph</code> and <code>aot_backward_graph</code>.  If training is not needed, there may only be an <code>aot_inference_graph</code>.</li>
-<li>Inductor will apply some post grad FX passes, producing <code>inductor_post_grad_graph</code></li>
-<li>Inductor will perform code generation, producing the final <code>inductor_output_code</code> which will be executed at runtime.  This output is a valid Python program and can be directly run.</li>
-</ol>
+pub static AOT_PARTITION_CSS: &str = r#"
+pre { line-height: 1.4; }
+.partition-forward { background-color: #dbeafe; }
+.partition-backward { background-color: #fde2c8; }
+.partition-recomputed { background-color: #eadcfb; }
+.partition-unused { color: gray; }
+"#;
+
+pub static TEMPLATE_COMPILED_AUTOGRAD: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Compiled Autograd {compile_id}</title>
+</head>
+<body>
+    <h1>Compiled Autograd {compile_id}</h1>
+    <p>
+    {{ if is_compiled_autograd }}
+    This compile id was traced by compiled autograd.  Based on the stack that triggered it, it runs the backward of:
+    {{ else }}
+    The backward of this compile id was compiled by compiled autograd, as part of:
+    {{ endif }}
+    </p>
+    <ul>
+    {{ for r in related }}
+    <li><a href="{r.url}">{r.compile_id}</a></li>
+    {{ endfor }}
+    </ul>
+    <p>
+    Compiled autograd graphs are matched to forward graphs by the longest common prefix of their stacks,
+    so this is a best guess when several models are trained from the same function.
+    </p>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_DDP_SPLITS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>DDPOptimizer Splits {compile_id}</title>
+</head>
+<body>
+    <h1>DDPOptimizer Splits {compile_id}</h1>
+    <p>
+    DDPOptimizer split the Dynamo graph into the submodules below, so that gradient communication can
+    overlap with the backward of later submodules.  Each submodule is compiled separately.
+    </p>
+    {{ if unbalanced }}
+    <p><strong>Warning:</strong> some submodules have more than twice the average number of nodes, which
+    limits how much communication can overlap with compute.  Consider adjusting the DDP bucket size.</p>
+    {{ endif }}
+    <table>
+    <tr> <th> Submodule </th> <th> Nodes </th> <th> % of Nodes </th> </tr>
+    {{ for child in children }}
+    <tr> <td> <code>{child.name}</code>{{ if child.unbalanced }} <strong>(unbalanced)</strong>{{ endif }} </td> <td> {child.num_nodes} </td> <td> {child.percent} </td> </tr>
+    {{ endfor }}
+    </table>
+    <h2>Split graph</h2>
+    <pre>{split_graph}</pre>
+    {{ for child in children }}
+    <details>
+    <summary>{child.name}</summary>
+    <pre>{child.graph}</pre>
+    </details>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_GRAPH_PARTITIONS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Graph Partitions</title>
+</head>
+<body>
+    <h1>Graph Partitions</h1>
+    <p>
+    How regional compilation and Inductor's graph partitioning split the graph of each compile id.
+    Each region or partition is compiled separately.
+    </p>
+    {{ for entry in compile_ids }}
+    <h2><a href='index.html#{entry.compile_id}'>{entry.compile_id}</a></h2>
+    {{ for artifact in entry.artifacts }}
+    <h3><code>{artifact.name}</code></h3>
+    <p>{artifact.description}: {artifact.num_nodes} nodes in total.</p>
+    <table>
+    <tr> <th> Region </th> <th> Nodes </th> <th> Reason </th> </tr>
+    {{ for region in artifact.regions }}
+    <tr> <td> {region.name} </td> <td> <details><summary>{region.num_nodes}</summary><code>{region.nodes}</code></details> </td> <td> {{ if region.reason }}{region.reason}{{ endif }} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endfor }}
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_AOT_PARTITION: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>AOT Autograd Partition {compile_id}</title>
+</head>
+<body>
+    <h1>AOT Autograd Partition {compile_id}</h1>
+    <p>
+    Nodes of the joint graph, colored by the graph the partitioner put them in (matched by node name):
+    <span class="partition-forward">forward only ({num_forward})</span>,
+    <span class="partition-backward">backward only ({num_backward})</span>,
+    <span class="partition-recomputed">both, i.e. recomputed in the backward ({num_recomputed})</span>,
+    <span class="partition-unused">neither ({num_unused})</span>.
+    </p>
+    <h2>Joint graph</h2>
+    <pre>{joint_graph_html | format_unescaped}</pre>
+    <details>
+    <summary>Forward graph</summary>
+    <pre>{forward_graph}</pre>
+    </details>
+    <details>
+    <summary>Backward graph</summary>
+    <pre>{backward_graph}</pre>
+    </details>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static SYMBOLIC_EXPRESSIONS_CSS: &str = r#"
+.sym-expr {
+    margin: 8px 0;
+    padding: 8px 16px;
+    border: 1px solid #ccc;
+    border-radius: 8px;
+}
+.sym-expr:target { background-color: #fff3a0; }
+"#;
+
+pub static TEMPLATE_SYMBOLIC_EXPRESSIONS: &str = r##"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Symbolic Expressions {compile_id}</title>
+</head>
+<body>
+    <h1>Symbolic Expressions {compile_id}</h1>
+    <p>
+    Every symbolic expression created while tracing, with the expressions it was computed from
+    (inputs) and the expressions computed from it (users).
+    </p>
+    {{ if unbacked_symbols }}
+    <h2>Unbacked symbols</h2>
+    <ul>
+    {{ for s in unbacked_symbols }}
+    <li><a href="#node-{s.id}"><code>{s.result}</code></a></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if roots }}
+    <h2>Final expressions</h2>
+    <ul>
+    {{ for s in roots }}
+    <li><a href="#node-{s.id}"><code>{s.result}</code></a></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    <h2>All expressions</h2>
+    {{ for node in nodes }}
+    <div class="sym-expr" id="node-{node.id}">
+    <h3><code>{node.result}</code></h3>
+    {{ if node.method }}
+    <p>Method: <code>{node.method}</code>({node.arguments})</p>
+    {{ else }}
+    <p>Unbacked symbol</p>
+    {{ endif }}
+    {{ if node.inputs }}
+    <p>Inputs: {{ for i in node.inputs }}<a href="#node-{i.id}"><code>{i.result}</code></a> {{ endfor }}</p>
+    {{ endif }}
+    {{ if node.users }}
+    <p>Users: {{ for u in node.users }}<a href="#node-{u.id}"><code>{u.result}</code></a> {{ endfor }}</p>
+    {{ endif }}
+    {node.user_stack_html | format_unescaped}
+    </div>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"##;
+
+pub static TEMPLATE_PASS_TIMINGS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Inductor Pass Timings</title>
+</head>
+<body>
+    <h1>Inductor Pass Timings</h1>
+    <p>
+    Time spent in each Inductor pass, summed over every compile id that ran it, slowest passes first.
+    A pass with a large total but small mean runs often; a large max points at a single pathological graph.
+    </p>
+    <table>
+    <tr> <th> Stage </th> <th> Pass </th> <th> Runs </th> <th> Total (s) </th> <th> Mean (s) </th> <th> Max (s) </th> <th> Slowest Compile Id </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> {row.stage} </td> <td> <code>{row.pass}</code> </td> <td> {row.count} </td> <td> {row.total_s} </td> <td> {row.mean_s} </td> <td> {row.max_s} </td> <td> {row.slowest_compile_id} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COUNTERS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Counters</title>
+</head>
+<body>
+    <h1>Counters</h1>
+    <p>
+    The counters PyTorch logged, e.g. Dynamo's counters and wait counters.  Each artifact is a
+    snapshot of running totals, so the change since the snapshot before it is what happened in
+    between, usually during the compile id that logged it.
+    </p>
+    <h2>Final Values</h2>
+    <table>
+    <tr> <th> Artifact </th> <th> Counter </th> <th> Value </th> <th> Changed In </th> </tr>
+    {{ for row in global }}
+    <tr> <td> {row.source} </td> <td> <code>{row.counter}</code> </td> <td> {row.value} </td> <td> {row.num_changes} snapshot(s) </td> </tr>
+    {{ endfor }}
+    </table>
+    <h2>By Compile Id</h2>
+    {{ for snapshot in compiles }}
+    <details>
+    <summary>{snapshot.compile_id} <code>{snapshot.source}</code>: {snapshot.num_changed} counter(s) changed</summary>
+    <table>
+    <tr> <th> Counter </th> <th> Value </th> <th> Change </th> </tr>
+    {{ for row in snapshot.rows }}
+    <tr> <td> <code>{row.counter}</code> </td> <td> {row.value} </td> <td> {row.delta} </td> </tr>
+    {{ endfor }}
+    </table>
+    </details>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_MEMORY_REPORT: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    .lifetime \{
+        position: relative;
+        width: 400px;
+        height: 10px;
+        background-color: #eee;
+    }
+    .lifetime div \{
+        position: absolute;
+        height: 100%;
+        background-color: #80b1d3;
+    }
+    .live-at-peak .lifetime div \{
+        background-color: #fb8072;
+    }
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Memory Report</title>
+</head>
+<body>
+    <h1>Memory Report {compile_id}</h1>
+    <p>
+    Inductor estimated a peak memory of <b>{peak_memory}</b>{{ if peak_step }} at step {peak_step}{{ endif }}
+    for the {num_buffers} buffer(s) it planned, which allocate {total_allocated} in total.  Buffers
+    live at the peak are highlighted; freeing one of them earlier, or not materializing it at all,
+    lowers the peak.
+    </p>
+    <h2>Largest Allocations</h2>
+    <table>
+    <tr> <th> Buffer </th> <th> Size </th> <th> Live </th> </tr>
+    {{ for b in largest }}
+    <tr{{ if b.live_at_peak }} class="live-at-peak"{{ endif }}> <td> <code>{b.name}</code> </td> <td> {b.size} </td> <td> steps {b.start}-{b.end} </td> </tr>
+    {{ endfor }}
+    </table>
+    <h2>Buffer Lifetimes</h2>
+    <table>
+    <tr> <th> Buffer </th> <th> Size </th> <th> Lifetime </th> </tr>
+    {{ for b in buffers }}
+    <tr{{ if b.live_at_peak }} class="live-at-peak"{{ endif }}> <td> <code>{b.name}</code> </td> <td> {b.size} </td> <td> <div class="lifetime" title="steps {b.start}-{b.end}"><div style="left: {b.left}%; width: {b.width}%"></div></div> </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_DUPLICATE_GRAPHS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Duplicate Graphs</title>
+</head>
+<body>
+    <h1>Duplicate Graphs</h1>
+    <p>
+    Graphs that more than one compile id produced.  Compiling the same graph again usually means a
+    recompilation that didn't need to happen, e.g. because of an overly specific guard, or that the
+    compile caches are misconfigured.  Graphs are identical if their dumps are equal after dropping
+    comments; graphs identical up to node names only differ in what their nodes are called.
+    </p>
+    <table>
+    <tr> <th> Graph </th> <th> Match </th> <th> Compile Ids </th> <th> Identical Dumps </th> </tr>
+    {{ for cluster in clusters }}
+    <tr> <td> {cluster.graph} </td> <td> {cluster.kind} </td> <td> {cluster.num_compile_ids} </td> <td> {{ for ids in cluster.identical_compile_ids }}{ids}<br>{{ endfor }} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_MODULE_MAP: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Module Map</title>
+</head>
+<body>
+    <h1>Module Map</h1>
+    <p>
+    The nodes of each pre-grad graph by the nn.Module of the model that they come from.  Modules are
+    taken from the nn_module_stack of the nodes where the graph dump has it, and otherwise from the
+    parameters and buffers that the nodes use.  Counts include the nodes of submodules.
+    </p>
+    {{ for graph in graphs }}
+    <h2><a href='{graph.directory_name}/'>{graph.directory_name}</a></h2>
+    {graph.tree_html | format_unescaped}
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_CUDAGRAPHS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>CUDA Graphs</title>
+</head>
+<body>
+    <h1>CUDA Graphs for {compile_id}</h1>
+    <p>
+    CUDA graphs were requested, but Inductor fell back to running this graph without them.  The graph
+    still runs correctly, but each kernel is launched individually, which is usually much slower for
+    small, launch bound graphs.
+    </p>
+    <h2>Skip reasons</h2>
+    {{ for reason in skip_reasons }}
+    <pre>{reason}</pre>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_GUARD_LATENCY: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Guard Evaluation Latency</title>
+</head>
+<body>
+    <h1>Guard Evaluation Latency</h1>
+    <p>
+    Time Dynamo measured to evaluate the guards of each compiled frame, {total_latency_us} us for
+    {total_guard_count} guards in total.  Guards run on every call, so slow guards add overhead at runtime.
+    The cumulative latency also counts the guards of earlier compilations of the same frame, which are
+    checked first when looking the frame up in the cache; compile ids are ranked by it.
+    </p>
+    <table>
+    <tr> <th> Compile Id </th> <th> Guards </th> <th> Latency (us) </th> <th> Cumulative Latency (us) </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> <a href="index.html#{row.compile_id}">{row.compile_id}</a> </td> <td> {row.guard_count} </td> <td> {row.latency_us} </td> <td> {row.cumulative_latency_us} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_INDEX_PAGE: &str = r#"
+<html>
+<head>
+  <meta charset="UTF-8">
+    <title>Build Products (page {number} of {num_pages})</title>
+</head>
+<style>
+{css | format_unescaped}
+</style>
+<link rel="stylesheet" href="theme.css">
+<body>
+<p><a href="index.html">Back to the summary</a></p>
+<p>Pages:{{ for page in index_pages }} {{ if page.current }}<strong>{page.number}</strong>{{ else }}<a href="{page.url}">{page.number}</a>{{ endif }}{{ endfor }}</p>
+<ul>
+{{ for compile_directory in directory }}
+{{ if compile_directory.2.first_attempt }}
+    <li><span class="attempt-frame">{compile_directory.2.frame}</span> ({compile_directory.2.num_attempts} attempts)
+    <ul class="attempt-tree">
+{{ endif }}
+{{ for restart_reason in compile_directory.2.restart_reasons }}
+    <li class="restart-reason">restarted: {restart_reason}</li>
+{{ endfor }}
+    <li><a id="{compile_directory.0}">{compile_directory.0}</a>{{ if compile_directory.2.frame }} <span class="attempt">attempt {compile_directory.2.attempt}</span>{{ endif }}{{ if compile_directory.2.step }} <span class="step">step {compile_directory.2.step}</span>{{ endif }}{{ if compile_directory.2.graph_nodes }} <span class="graph-size" title="Nodes of dynamo_output_graph">{compile_directory.2.graph_nodes} nodes</span>{{ endif }}
+    <ul>
+        {{ for path_idx in compile_directory.1 }}
+            <li><a href="{path_idx.url}">{path_idx.name}</a>{{ if path_idx.readable_url }} (<a href="{path_idx.readable_url}">readable_html</a>){{ endif }} {path_idx.suffix} ({path_idx.number}){{ if path_idx.log_line }} <span class="log-line" title="Line of raw.log this was parsed from">raw.log:{path_idx.log_line}</span>{{ endif }}</li>
+        {{ endfor }}
+    </ul>
+    </li>
+{{ if compile_directory.2.last_attempt }}
+    </ul>
+    </li>
+{{ endif }}
+{{ endfor }}
+</ul>
+{qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_BASELINE_COMPARISON: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    .regressed \{ color: red; font-weight: bold; }
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Baseline Comparison</title>
+</head>
+<body>
+    <h1>Baseline Comparison</h1>
+    <p>
+    How this run compares to the baseline summary passed with <code>--baseline</code>.  Changes for the
+    worse are highlighted.
+    </p>
+    <table>
+    <tr> <th> Metric </th> <th> Baseline </th> <th> Current </th> <th> Change </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> {row.metric} </td> <td> {row.baseline} </td> <td> {row.current} </td> <td{{ if row.regressed }} class="regressed"{{ endif }}> {row.delta} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ if config_changes }}
+    <h2>Config Changes</h2>
+    <p>
+    Config options that differ from the baseline, which often explain cache misses and performance
+    regressions.
+    </p>
+    <table>
+    <tr> <th> Option </th> <th> Baseline </th> <th> Current </th> </tr>
+    {{ for change in config_changes }}
+    <tr> <td> <code>{change.key}</code> </td> <td> <code>{change.baseline}</code> </td> <td> <code>{change.current}</code> </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endif }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COMPARE_RUNS: &str = r#"
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+    {css}
+    .differs \{ background-color: #fff3cd; }
+    .failed \{ color: red; font-weight: bold; }
+    .missing \{ color: #999; }
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Run Comparison</title>
+</head>
+<body>
+    {custom_header_html | format_unescaped}
+    <h1>Run Comparison</h1>
+    <p>
+    The compilations of each run, aligned by the frame they compile and the user stack that led to
+    them (hover over a frame to see it), rather than by compile id, which depends on the order frames
+    happen to be compiled in.  Rows where the runs differ in what they compiled, their cache hits and
+    misses (✅ hit, ❌ miss, ❓ bypass) or their failures are highlighted.
+    </p>
+    <table>
+    <tr> <th> Run </th> <th> Compiles </th> <th> Failures </th> <th> Compile time (s) </th> </tr>
+    {{ for run in runs }}
+    <tr> <td> <a href="{run.url}">{run.name}</a> </td> <td> {run.compiles} </td> <td> {run.failures} </td> <td> {run.compile_time_s} </td> </tr>
+    {{ endfor }}
+    </table>
+    <table>
+    <tr>
+    <th> Frame </th>
+    {{ for run in runs }}
+    <th> {run.name} </th>
+    {{ endfor }}
+    </tr>
+    {{ for row in rows }}
+    <tr id="compile-{@index}"{{ if row.differs }} class="differs"{{ endif }}>
+    <td title="{row.stack}"> <code>{row.frame}</code> </td>
+    {{ for cell in row.cells }}
+    {{ if cell.present }}
+    <td>
+    {{ if cell.url }}<a href="{cell.url}">{cell.compile_id}</a>{{ else }}{cell.compile_id}{{ endif }}
+    {{ if cell.compile_time_s }} {cell.compile_time_s} s{{ endif }} {cell.cache}
+    {{ if cell.fail_type }}<br><span class="failed">{cell.fail_type}</span>{{ endif }}
+    </td>
+    {{ else }}
+    <td class="missing"> not compiled </td>
+    {{ endif }}
+    {{ endfor }}
+    </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_RUNTIME_PROFILE_CORRELATION: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Runtime Estimation Accuracy</title>
+</head>
+<body>
+    <h1>Runtime Estimation Accuracy</h1>
+    <p>
+    Inductor's estimated runtime of each op, next to the mean duration of the matching events in the profiler
+    trace passed with <code>--profile-trace</code>.  Ops are matched by name, e.g. <code>extern_kernels.mm</code>
+    with <code>aten::mm</code>; the ratio is measured over estimated time, and the error is the mean absolute
+    percentage error of the matched ops.  The data is also in
+    <a href="runtime_profile_correlation.json">runtime_profile_correlation.json</a>.
+    </p>
+    <table>
+    <tr> <th> Compile Id </th> <th> Matched ops </th> <th> Unmatched ops </th> <th> Estimated (us) </th> <th> Measured (us) </th> <th> Error (%) </th> </tr>
+    {{ for graph in graphs }}
+    <tr> <td> <a href='#{graph.compile_id}'>{graph.compile_id}</a> </td> <td> {graph.num_matched} </td> <td> {graph.num_unmatched} </td> <td> {graph.estimated_total_us} </td> <td> {graph.measured_total_us} </td> <td> {graph.mean_abs_pct_error} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ for graph in graphs }}
+    <h2 id="{graph.compile_id}">{graph.compile_id}</h2>
+    <table>
+    <tr> <th> Op </th> <th> Estimated (us) </th> <th> Measured (us) </th> <th> Ratio </th> </tr>
+    {{ for op in graph.ops }}
+    <tr> <td> <code>{op.name}</code> </td> <td> {op.estimated_us} </td> <td> {op.measured_us} </td> <td> {op.ratio} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_RUNTIME_ESTIMATION: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Runtime Estimation {compile_id}</title>
+</head>
+<body>
+    <h1>Runtime Estimation {compile_id}</h1>
+    <p>
+    Inductor's estimated runtime of each op of the graph, slowest first.  The kernel links point to where the op
+    runs in the output code.
+    </p>
+    <table>
+    <tr> <th> Op </th> <th> Estimated (us) </th> <th> Share (%) </th> <th> Kernel </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> <code>{row.name}</code> </td> <td> {row.estimated_us} </td> <td> {row.percent} </td> <td> {{ if row.url }}<a href="{row.url}"><code>{row.kernel}</code></a>{{ endif }} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_GUARD_DIFF: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Guard Diff {diff.compile_id}</title>
+</head>
+<body>
+    <h1>Guard Diff {diff.compile_id}</h1>
+    <p>
+    How the guards of {diff.compile_id} differ from those of the previous compile of the same frame,
+    {{ if previous_guards_url }}<a href='{previous_guards_url}'>{diff.previous_compile_id}</a>{{ else }}{diff.previous_compile_id}{{ endif }}.
+    Guards that were added usually show what changed to force the recompile, e.g. a new size or
+    type of an input; {diff.num_unchanged} guard(s) are the same in both.
+    </p>
+    <table>
+    <tr> <th> </th> <th> Guard </th> </tr>
+    {{ for guard in diff.added }}
+    <tr style="background-color: #e6ffed"> <td> + </td> <td> <code>{guard}</code> </td> </tr>
+    {{ endfor }}
+    {{ for guard in diff.removed }}
+    <tr style="background-color: #ffeef0"> <td> - </td> <td> <code>{guard}</code> </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_INDUCTOR_CONFIG: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Config {compile_id}</title>
+</head>
+<body>
+    <h1>Config {compile_id}</h1>
+    <p>
+    The Inductor and Dynamo config options {compile_id} was compiled with, as logged in config dumps
+    and the keys of the compile caches.  Options that differ from the previous compile id that logged
+    its config are highlighted.
+    </p>
+    <table>
+    <tr> <th> Option </th> <th> Value </th> </tr>
+    {{ for option in options }}
+    <tr{{ if option.changed }} style="background-color: #fff5b1"{{ endif }}> <td> <code>{option.key}</code> </td> <td> <code>{option.value}</code> </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COMPILE_ID_SUMMARY: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Summary {compile_id}</title>
+</head>
+<body>
+    <h1>Summary {compile_id}</h1>
+    <p><a href="{root}index.html#{compile_id}">Back to the index</a></p>
+    <table>
+    {{ if co_name }}<tr> <td> Function </td> <td> <code>{co_name}</code>{{ if location }} ({location}){{ endif }} </td> </tr>{{ endif }}
+    <tr> <td> Status </td> <td>{{ if failed }} <strong>{status}</strong>{{ else }} {status}{{ endif }} </td> </tr>
+    {{ if compile_time_s }}<tr> <td> Compile time </td> <td> {compile_time_s} s </td> </tr>{{ endif }}
+    {{ if guard_count }}<tr> <td> Guards </td> <td> {guard_count} </td> </tr>{{ endif }}
+    {{ if graph_nodes }}<tr> <td> Graph nodes </td> <td> {graph_nodes} </td> </tr>{{ endif }}
+    </table>
+    {{ if fail_reason }}
+    <h2>Failure</h2>
+    <pre>{fail_reason}</pre>
+    {{ endif }}
+    {{ if recompile_reason }}
+    <h2>Recompile reason</h2>
+    <pre>{recompile_reason}</pre>
+    {{ endif }}
+    {{ if restart_reasons }}
+    <h2>Restart reasons</h2>
+    <ul>
+    {{ for reason in restart_reasons }}
+    <li><code>{reason}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if cache }}
+    <h2>Caches</h2>
+    <table>
+    <tr> <th> Artifact </th> <th> Status </th> </tr>
+    {{ for c in cache }}
+    <tr> <td> <a href="{c.url}">{c.artifact}</a> </td> <td> {c.status} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endif }}
+    <h2>Artifacts</h2>
+    <ul>
+    {{ for artifact in artifacts }}
+    <li><a href="{artifact.url}">{artifact.name}</a></li>
+    {{ endfor }}
+    </ul>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_FIRST_FAILURE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>First Failure {compile_id}</title>
+</head>
+<body>
+    <h1>First Failure {compile_id}</h1>
+    <p>
+    Parsing stopped at the first compilation that failed (<code>--first-failure</code>), so the report
+    only has the artifacts of {compile_id}.
+    </p>
+    <table>
+    <tr> <td> Function </td> <td> <code>{co_name}</code>{{ if location }} ({location}){{ endif }} </td> </tr>
+    <tr> <td> Failure </td> <td> <code>{fail_type}</code> </td> </tr>
+    {{ if user_frame }}<tr> <td> User code </td> <td> {user_frame} </td> </tr>{{ endif }}
+    </table>
+    <h2>Reason</h2>
+    <pre>{fail_reason}</pre>
+    <h2>Artifacts</h2>
+    <ul>
+    {{ for artifact in artifacts }}
+    <li><a href='{artifact.url}'>{artifact.name}</a></li>
+    {{ endfor }}
+    </ul>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_OVERFLOW_COMPILE_IDS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Omitted Compile Ids</title>
+</head>
+<body>
+    <h1>Omitted Compile Ids</h1>
+    <p>
+    This log has more than {max_compile_ids} compile ids, so only the artifacts of the {max_compile_ids} most
+    expensive ones (by entire frame compile time, or the most recent ones on ties) were kept.  The compilation
+    metrics of the other compile ids are summarized below.
+    </p>
+    <table>
+    <tr> <th> Compile Id </th> <th> Compile Time (s) </th> <th> Graph Ops </th> <th> Guards </th> <th> Failure </th> <th> Omitted Artifacts </th> </tr>
+    {{ for row in rows }}
+    <tr id="{row.compile_id}"> <td> {row.compile_id} </td> <td> {row.compile_time_s} </td> <td> {row.graph_op_count} </td> <td> {row.guard_count} </td> <td> {row.fail_type} </td> <td> {row.num_artifacts} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static CODE_SIZE_CSS: &str = r#"
+.treemap {
+    position: relative;
+    width: 100%;
+    height: 480px;
+    border: 1px solid #999;
+    margin-bottom: 20px;
+}
+.treemap a {
+    position: absolute;
+    box-sizing: border-box;
+    border: 1px solid #fff;
+    overflow: hidden;
+    padding: 2px 4px;
+    font-size: 12px;
+    color: #000;
+    text-decoration: none;
+}
+"#;
+
+pub static TEMPLATE_CODE_SIZE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    {treemap_css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Generated Code Size</title>
+</head>
+<body>
+    <h1>Generated Code Size</h1>
+    <p>
+    Bytes of code Inductor generated for each compile id ({total_size} in total), counting
+    <code>inductor_output_code</code> and AOTInductor wrapper and kernel code.  The area of each tile is
+    proportional to the size of its code.  The raw numbers are available in <a href="code_size.json">code_size.json</a>.
+    </p>
+    <div class="treemap">
+    {{ for tile in tiles }}
+    <a href="index.html#{tile.compile_id}" title="{tile.compile_id}: {tile.size}" style="left: {tile.left}; top: {tile.top}; width: {tile.width}; height: {tile.height}; background: {tile.color};">{tile.compile_id}<br>{tile.size}</a>
+    {{ endfor }}
+    </div>
+    <table>
+    <tr> <th> Compile Id </th> <th> Total </th> <th> Breakdown </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> <a href="index.html#{row.compile_id}">{row.compile_id}</a> </td> <td> {row.total} </td> <td> {row.breakdown} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_TRACE_SIZE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Trace Size</title>
+</head>
+<body>
+    <h1>Trace Size</h1>
+    <p>
+    Where the {total_size} of the log go, to help pick which artifacts to trace when logging in
+    production.  Sizes include the records' payloads and glog prefixes.  Counts of each record type are
+    available in <a href="envelope_stats.json">envelope_stats.json</a>.
+    </p>
+    <h2>By record type</h2>
+    <table>
+    <tr> <th> Type </th> <th> Size </th> <th> Share </th> <th> Records </th> </tr>
+    {{ for row in types }}
+    <tr> <td> <code>{row.name}</code> </td> <td> {row.size} </td> <td> {row.percent} </td> <td> {row.breakdown} </td> </tr>
+    {{ endfor }}
+    </table>
+    <h2>By compile id</h2>
+    <table>
+    <tr> <th> Compile Id </th> <th> Size </th> <th> Share </th> <th> Biggest record types </th> </tr>
+    {{ for row in compile_ids }}
+    <tr> <td> <a href="index.html#{row.name}">{row.name}</a> </td> <td> {row.size} </td> <td> {row.percent} </td> <td> {row.breakdown} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_THREADS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    .timeline \{ position: relative; height: 20px; background-color: #f2f2f2; min-width: 400px; }
+    .timeline a \{ position: absolute; top: 2px; height: 16px; min-width: 2px; background-color: #0066cc; }
+    .timeline a.overlapping \{ background-color: #cc0000; }
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Threads</title>
+</head>
+<body>
+    <h1>Threads</h1>
+    <p>
+    Compile ids logged by each thread of the process, from the first to the last record of each.
+    {{ if num_overlapping }}
+    <strong>{num_overlapping}</strong> compile(s), in red, ran at the same time as a compile on another
+    thread, so their compile times include time spent waiting for each other.
+    {{ endif }}
+    The same data is available in <a href="threads.json">threads.json</a>.
+    </p>
+    <table>
+    <tr> <th> Thread </th> <th> Records </th> <th> Compile ids </th> <th style="width: 60%"> Timeline </th> </tr>
+    {{ for row in rows }}
+    <tr>
+        <td> {row.thread} </td>
+        <td> {row.num_records} </td>
+        <td> {row.num_compiles} </td>
+        <td><div class="timeline">
+        {{ for bar in row.bars }}<a href="index.html#{bar.compile_id}" title="{bar.title}" style="left: {bar.left}; width: {bar.width};"{{ if bar.overlapping }} class="overlapping"{{ endif }}></a>{{ endfor }}
+        </div></td>
+    </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_SEARCH: &str = r#"
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Search</title>
+</head>
+<body>
+    <h1>Search</h1>
+    <p>
+    Search artifact names, compile ids, guard expressions, failure reasons and kernel names.  All
+    terms must match; a term matches any word containing it, e.g. <code>fused_mul 0/0</code>.
+    The index is also available as <a href="search_index.json">search_index.json</a>.
+    </p>
+    <input id="query" type="search" size="80" placeholder="e.g. triton_poi_fused_mul_1" autofocus>
+    <p id="summary"></p>
+    <table id="results"></table>
+    <script>
+    const SEARCH_INDEX = {search_index_json | format_unescaped};
+    </script>
+    <script>
+    {lookup_javascript | format_unescaped}
+    {javascript | format_unescaped}
+    </script>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_CROSS_RANK_SEARCH: &str = r#"
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="theme.css">
+    <title>Cross-Rank Search</title>
+</head>
+<body>
+    <h1>Cross-Rank Search</h1>
+    <p>
+    Search the guard expressions, kernel names and failure reasons of all {num_ranks} rank(s), to see
+    which ranks have them without opening each rank's report.  All terms must match; a term matches
+    any word containing it, e.g. <code>fused_mul</code>.
+    </p>
+    <input id="query" type="search" size="80" placeholder="e.g. triton_poi_fused_mul_1" autofocus>
+    <p id="summary"></p>
+    <table id="results"></table>
+    <script>
+    const SEARCH_INDEX = {search_index_json | format_unescaped};
+    </script>
+    <script>
+    {lookup_javascript | format_unescaped}
+    {javascript | format_unescaped}
+    </script>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static CROSS_RANK_SEARCH_JAVASCRIPT: &str = r#"
+function render() {
+    const query = document.getElementById("query").value;
+    const ids = search(query);
+    const table = document.getElementById("results");
+    table.replaceChildren();
+    document.getElementById("summary").textContent =
+        query.trim() === "" ? "" : ids.length + " result(s)" + (ids.length > MAX_RESULTS ? ", showing the first " + MAX_RESULTS : "");
+    if (ids.length === 0) return;
+    const header = table.insertRow();
+    for (const name of ["Kind", "Match", "Ranks", "Compile Ids"]) {
+        const th = document.createElement("th");
+        th.textContent = name;
+        header.appendChild(th);
+    }
+    for (const id of ids.slice(0, MAX_RESULTS)) {
+        const entry = SEARCH_INDEX.entries[id];
+        const row = table.insertRow();
+        row.insertCell().textContent = entry.kind.replace("_", " ");
+        const code = document.createElement("code");
+        code.textContent = entry.text;
+        row.insertCell().appendChild(code);
+        row.insertCell().textContent = [...new Set(entry.hits.map(hit => hit.rank))].join(", ");
+        const hits = row.insertCell();
+        entry.hits.forEach((hit, i) => {
+            if (i > 0) hits.appendChild(document.createTextNode(", "));
+            const link = document.createElement("a");
+            link.href = hit.url;
+            link.textContent = "rank " + hit.rank + " " + hit.compile_id;
+            hits.appendChild(link);
+        });
+    }
+}
 
-{{ if has_chromium_events }}
-<h2> Chromium Events </h2>
-PT2 generates <a href='chromium_events.json'>Chromium Trace Events</a> in JSON on specific events during compilation.
-You can download and view them in a tool like <a href='https://ui.perfetto.dev/'>Perfetto</a>.
-{{ endif  }}
-<p>
-Build products below:
-</p>
-<ul>
-{{ for compile_directory in directory }}
-    <li><a id="{compile_directory.0}">{compile_directory.0}</a>
-    <ul>
-        {{ for path_idx in compile_directory.1 }}
-            <li><a href="{path_idx.url}">{path_idx.name}</a>{{ if path_idx.readable_url }} (<a href="{path_idx.readable_url}">readable_html</a>){{ endif }} {path_idx.suffix} ({path_idx.number})</li>
-        {{ endfor }}
-    </ul>
-    </li>
-{{ endfor }}
-</ul>
-</div>
+document.getElementById("query").addEventListener("input", render);
+const initialQuery = new URLSearchParams(window.location.search).get("q");
+if (initialQuery) {
+    document.getElementById("query").value = initialQuery;
+    render();
+}
+"#;
 
+// The lookup of search.html and cross_rank_search.html, over the index in SEARCH_INDEX
+pub static SEARCH_LOOKUP_JAVASCRIPT: &str = r#"
+const MAX_RESULTS = 500;
 
-{{ if has_inductor_provenance }}
-<h2>Provenance Tracking</h2>
-<div>
-    <p>View detailed provenance tracking information for each rank and frame:</p>
-    <ul>
-    {{ for directory_name in directory_names }}
-        <li><a href='provenance_tracking_{directory_name}.html'>provenance_tracking_{directory_name}</a></li>
-    {{ endfor }}
-    </ul>
-</div>
-{{ endif }}
+function tokenize(text) {
+    return text.toLowerCase().split(/[^\p{L}\p{N}_\/]+/u).filter(t => t.length > 0);
+}
 
-{{ if has_unknown_stack_trie }}
-<div>
-<h2>Unknown stacks</h2>
-<p>
-  Sometimes, logs are made without a compile id.  This makes it difficult to correlate related
-  logs.  This stack trie shows all places where log entries occurred without compile context; to
-  fix, look an appropriate place in the stack where compile id should have been specified.
-</p>
-{unknown_stack_trie_html | format_unescaped}
-</div>
-{{ endif }}
-{qps | format_unescaped}
-</body>
-</html>
+function search(query) {
+    let result = null;
+    for (const term of tokenize(query)) {
+        const matched = new Set();
+        for (const [token, ids] of Object.entries(SEARCH_INDEX.index)) {
+            if (token.includes(term)) ids.forEach(id => matched.add(id));
+        }
+        result = result === null ? matched : new Set([...result].filter(id => matched.has(id)));
+    }
+    return result === null ? [] : [...result].sort((a, b) => a - b);
+}
+"#;
+
+pub static SEARCH_JAVASCRIPT: &str = r#"
+function render() {
+    const query = document.getElementById("query").value;
+    const ids = search(query);
+    const table = document.getElementById("results");
+    table.replaceChildren();
+    document.getElementById("summary").textContent =
+        query.trim() === "" ? "" : ids.length + " result(s)" + (ids.length > MAX_RESULTS ? ", showing the first " + MAX_RESULTS : "");
+    if (ids.length === 0) return;
+    const header = table.insertRow();
+    for (const name of ["Kind", "Match", "Compile Id"]) {
+        const th = document.createElement("th");
+        th.textContent = name;
+        header.appendChild(th);
+    }
+    for (const id of ids.slice(0, MAX_RESULTS)) {
+        const entry = SEARCH_INDEX.entries[id];
+        const row = table.insertRow();
+        row.insertCell().textContent = entry.kind.replace("_", " ");
+        const link = document.createElement("a");
+        link.href = entry.url;
+        link.textContent = entry.text;
+        const code = document.createElement("code");
+        code.appendChild(link);
+        row.insertCell().appendChild(code);
+        row.insertCell().textContent = entry.compile_id;
+    }
+}
+
+document.getElementById("query").addEventListener("input", render);
+const initialQuery = new URLSearchParams(window.location.search).get("q");
+if (initialQuery) {
+    document.getElementById("query").value = initialQuery;
+    render();
+}
+"#;
+
+pub static MODULE_MAP_CSS: &str = r#"
+.module-tree {
+    list-style: none;
+    padding-left: 1.5em;
+    font-family: monospace;
+}
+.module-size {
+    color: #666;
+}
+.module-nodes {
+    color: #666;
+    font-size: 0.9em;
+}
 "#;
 
 pub static TEMPLATE_FAILURES_CSS: &str = r#"
@@ -254,6 +1634,10 @@ a {
 a:hover {
     text-decoration: underline;
 }
+.step {
+    color: #2a6f97;
+    font-size: smaller;
+}
 "#;
 
 pub static TEMPLATE_FAILURES_AND_RESTARTS: &str = r#"
@@ -262,14 +1646,26 @@ pub static TEMPLATE_FAILURES_AND_RESTARTS: &str = r#"
     <style>
     {css}
     </style>
+<link rel="stylesheet" href="theme.css">
 </head>
 <body>
     <h1>Failures and Restarts</h1>
+    <h2>By reason</h2>
+    <table>
+    <tr> <th> Count </th> <th> Failure Type </th> <th> Failure Description </th> <th> Failure Source (compilation failures only) </th> <th> Compile Ids </th> </tr>
+    {{ for group in groups }}
+    <tr> <td> {group.count} </td>{group.reason | format_unescaped}<td> {{ for compile_id in group.compile_ids }}{compile_id | format_unescaped}{{ endfor }} </td></tr>
+    {{ endfor }}
+    </table>
+    <details>
+    <summary>Chronological list</summary>
     <table>
     <tr> <th> Compile Id </th> <th> Failure Type </th> <th> Failure Description </th> <th> Failure Source (compilation failures only) </th> </tr>
     {{ for failure in failures }}
     <tr> <td> {failure.0 | format_unescaped} </td>{failure.1 | format_unescaped}</tr>
     {{ endfor }}
+    </table>
+    </details>
     {qps | format_unescaped}
 </body>
 </html>
@@ -281,8 +1677,9 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     <style>
     {css}
     </style>
+<link rel="stylesheet" href="theme.css">
     <title>Compilation Metrics</title>
-    <base href="..">
+    <base href="{root}">
 </head>
 <body>
     <h1>Compilation Info for {compile_id}</h1>
@@ -295,7 +1692,12 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     </ul>
     <h2>Stack</h2>
     {stack_html | format_unescaped}
-    <h2>Compile Time(seconds)</h2>
+    {{ if locals_html }}
+    <h2 id="frame-locals">Frame Locals</h2>
+    <p>Locals and symbols of the frame, captured with its guards:</p>
+    {locals_html | format_unescaped}
+    {{ endif }}
+    <h2 id="compile-time">Compile Time(seconds)</h2>
     <p>Entire Frame <abbr title="Total time spent in convert_frame function">[?]</abbr>: {m.entire_frame_compile_time_s}</div>
     <p>Backend <abbr title="Time spent running the backend compiler">[?]</abbr>: {m.backend_compile_time_s}</div>
     {{ if m.inductor_compile_time_s }}
@@ -305,7 +1707,7 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     <p>Code Gen Time: {m.code_gen_time_s}</p>
     {{ endif}}
     <div>Dynamo Time Before Restart <abbr title="Total time spent restarting dynamo analysis">[?]</abbr>: {m.dynamo_time_before_restart_s}</div>
-    <h2>Restarts and Failures</h2>
+    <h2 id="failures">Restarts and Failures</h2>
     {{ if m.fail_type }}
     <p>Failure Exception: <pre>{m.fail_type}</pre></p>
     <p>Failure Reason: <pre>{m.fail_reason}</pre></p>
@@ -323,10 +1725,14 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     {{ else }}
     <p> No restarts! </p>
     {{ endif }}
-    <h2>Cache Metrics</h2>
+    {{ if m.recompile_reason }}
+    <p>Recompiled because these guards failed:<p>
+    <pre>{m.recompile_reason}</pre>
+    {{ endif }}
+    <h2 id="cache-metrics">Cache Metrics</h2>
     <p>Cache Size: {m.cache_size}</p>
     <p>Accumulated Cache Size: {m.accumulated_cache_size}</p>
-    <h2>Graph Metrics</h2>
+    <h2 id="graph-metrics">Graph Metrics</h2>
     <p>Guard Count: {m.guard_count}</p>
     <p>Shape Env Guards: {m.shape_env_guard_count}</p>
     <p>Graph Ops: {m.graph_op_count}</p>
@@ -345,13 +1751,23 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     <li> <code> {op} </code> </li>
     {{ endfor }}
     {{ endif }}
+    {{ if extra_metrics }}
+    <h2>Other Metrics</h2>
+    <p>Metrics logged by this version of PyTorch that tlparse doesn't render specially yet:</p>
+    <table>
+    <tr> <th>Key</th> <th>Value</th> </tr>
+    {{ for metric in extra_metrics }}
+    <tr id="metric-{metric.key}"> <td><code>{metric.key}</code></td> <td>{metric.value}</td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endif }}
     <h2>Symbolic shape specializations</h2>
     <table>
     <tr>
         <th>Sym</th> <th>Source(s)</th> <th>Value</th> <th>User stack</th> <th>Framework stack</th>
     </tr>
     {{ for spec in symbolic_shape_specializations }}
-    <tr>
+    <tr id="spec-{spec.symbol}">
         <td>{spec.symbol}</td>
         <td>{{ for source in spec.sources }}{source}<br>{{ endfor }}</td>
         <td>{spec.value}</td>
@@ -384,6 +1800,7 @@ pub static TEMPLATE_AOT_AUTOGRAD_BACKWARD_COMPILATION_METRICS: &str = r#"
     <style>
     {css}
     </style>
+<link rel="stylesheet" href="{root}theme.css">
     <title>AOT Autograd Backward Compilation Metrics</title>
 </head>
 <body>
@@ -406,6 +1823,7 @@ pub static TEMPLATE_BWD_COMPILATION_METRICS: &str = r#"
     <style>
     {css}
     </style>
+<link rel="stylesheet" href="{root}theme.css">
     <title>Backward Compilation Metrics</title>
 </head>
 <body>
@@ -431,13 +1849,48 @@ pub static TEMPLATE_BWD_COMPILATION_METRICS: &str = r#"
 
 // NB: Invariant for generated HTML: all links must show up in the initial HTML for this to be applied.
 //     Links dynamically generated/added after document load (i.e. using JS) will not get this applied.
+// Sorts the rows of a `table.sortable` by the column whose header is clicked, numerically if the
+// cells are numbers.  Clicking the same header again reverses the order.
+pub static TEMPLATE_SORTABLE_TABLE_SCRIPT: &str = r#"
+<script>
+document.querySelectorAll('table.sortable').forEach((table) => {
+    const headers = table.rows[0].cells;
+    for (let col = 0; col < headers.length; col++) {
+        headers[col].style.cursor = 'pointer';
+        headers[col].addEventListener('click', () => {
+            const ascending = table.dataset.sortCol !== String(col) || table.dataset.sortDir !== 'asc';
+            const rows = Array.from(table.rows).slice(1);
+            const key = (row) => {
+                const text = row.cells[col].textContent.trim();
+                const num = parseFloat(text);
+                return isNaN(num) ? text : num;
+            };
+            rows.sort((a, b) => {
+                const [x, y] = [key(a), key(b)];
+                const cmp = typeof x === 'number' && typeof y === 'number'
+                    ? x - y : String(x).localeCompare(String(y));
+                return ascending ? cmp : -cmp;
+            });
+            rows.forEach((row) => row.parentNode.appendChild(row));
+            table.dataset.sortCol = String(col);
+            table.dataset.sortDir = ascending ? 'asc' : 'desc';
+        });
+    }
+});
+</script>
+"#;
+
 pub static TEMPLATE_QUERY_PARAM_SCRIPT: &str = r#"
     <script>
     document.addEventListener('DOMContentLoaded', function() {
 
-        // Append the current URL's query parameters to all relative links on the page
+        // Append the current URL's query parameters to all relative links on the page, except
+        // those of deep links to this page
         const queryParams = new URLSearchParams(window.location.search);
-        if (queryParams.size === 0) return url; // No query params, return original URL
+        for (const param of ['line', 'row', 'artifact']) {
+            queryParams.delete(param);
+        }
+        if (queryParams.size === 0) return; // No query params, leave the links as they are
 
         function appendQueryParams(url) {
             const newURL = new URL((new Request(url)).url);  // new URL(<relative URL>) but it actually works
@@ -473,6 +1926,7 @@ pub static TEMPLATE_EXPORT_INDEX: &str = r#"
 <style>
 {css | format_unescaped}
 </style>
+<link rel="stylesheet" href="theme.css">
 <script>
 {javascript | format_unescaped}
 </script>
@@ -487,19 +1941,40 @@ produce a graph. You can now change back to torch.export.export()
 </p>
 {{ else }}
 <b>{num_failures} issue(s) were found during export</b>, and it was not able to
-soundly produce a graph. The following is a list of all the issues found and how
-you may address them.
+soundly produce a graph. The following is a list of all the issues found, grouped by type, and how
+you may address them.  A machine readable summary is available in <a href="export_failures.json">export_failures.json</a>.
 <table>
-<tr> <th> Failure Type </th> <th> Reason </th> <th> Additional Info </th> </tr>
-{{ for failure in failures }}
-<tr> 
-    <td>{failure.failure_type | format_unescaped}</td>
-    <td>{failure.reason | format_unescaped}</td>
-    <td>{failure.additional_info | format_unescaped}</td>
-</tr>
+<tr> <th> Failure Type </th> <th> Count </th> </tr>
+{{ for group in groups }}
+<tr> <td>{group.failure_type}</td> <td>{group.count}</td> </tr>
+{{ endfor }}
+</table>
+{{ for group in groups }}
+<h2>{group.failure_type} ({group.count})</h2>
+{{ if group.ops }}
+<table>
+<tr> <th> Operator </th> <th> Count </th> </tr>
+{{ for op in group.ops }}
+<tr> <td><code>torch.ops.{op.op}</code></td> <td>{op.count}</td> </tr>
 {{ endfor }}
 </table>
 {{ endif }}
+{{ for failure in group.failures }}
+<details>
+<summary>{failure.reason | format_unescaped}</summary>
+<p>{failure.additional_info | format_unescaped}</p>
+{{ if failure.suggestions }}
+<p>Suggested fixes:</p>
+<ul>
+{{ for suggestion in failure.suggestions }}
+<li>{suggestion.explanation}<pre>{suggestion.code}</pre></li>
+{{ endfor }}
+</ul>
+{{ endif }}
+</details>
+{{ endfor }}
+{{ endfor }}
+{{ endif }}
 
 Here is the resulting exported program: <a href="{exported_program_url}">link</a>.
 {qps | format_unescaped}
@@ -507,14 +1982,64 @@ Here is the resulting exported program: <a href="{exported_program_url}">link</a
 </html>
 "#;
 
+pub static EXPORTED_PROGRAM_CSS: &str = r#"
+pre { line-height: 1.4; padding: 0.5em; }
+.line:target { background-color: #fff3a0; }
+.lineno {
+    display: inline-block;
+    width: 3em;
+    margin-right: 1em;
+    text-align: right;
+    color: gray;
+    text-decoration: none;
+    user-select: none;
+}
+tr:target { background-color: #fff3a0; }
+"#;
+
+pub static TEMPLATE_EXPORTED_PROGRAM: &str = r##"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+<link rel="stylesheet" href="{root}theme.css">
+    <title>Exported Program</title>
+</head>
+<body>
+    <h1>Exported Program</h1>
+    {{ if text_url }}<p>The raw output is also available as <a href="{text_url}">text</a>.</p>{{ endif }}
+    <h2>Graph</h2>
+    {pre_open | format_unescaped}{{ for line in graph_lines }}<span class="line" id="L{line.lineno}"><a class="lineno" href="#L{line.lineno}">{line.lineno}</a>{line.html | format_unescaped}</span>{{ endfor }}</pre>
+    {{ if graph_signature }}
+    <h2>Graph signature</h2>
+    <pre>{graph_signature}</pre>
+    {{ endif }}
+    <h2>Range constraints</h2>
+    {{ if range_constraints }}
+    <table>
+    <tr> <th> Symbol </th> <th> Range </th> </tr>
+    {{ for c in range_constraints }}
+    <tr id="sym-{c.symbol}"> <td> <code>{c.symbol}</code> </td> <td> <code>{c.range}</code> </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ else }}
+    <p>No range constraints.</p>
+    {{ endif }}
+    {qps | format_unescaped}
+</body>
+</html>
+"##;
+
 pub static TEMPLATE_SYMBOLIC_GUARD_INFO: &str = r#"
 <html>
 <head>
     <style>
     {css}
     </style>
+<link rel="stylesheet" href="theme.css">
     <title>Symbolic Shapes Information</title>
-    <base href="..">
+    <base href="{root}">
 </head>
 <body>
     <h1>More detailed information on <code>{expr}</code></h1>
@@ -544,6 +2069,7 @@ pub static TEMPLATE_MULTI_RANK_INDEX: &str = r#"
 <style>
 {css | format_unescaped}
 </style>
+<link rel="stylesheet" href="theme.css">
 <body>
 <div>
 {custom_header_html | format_unescaped}
@@ -569,13 +2095,43 @@ pub static TEMPLATE_MULTI_RANK_INDEX: &str = r#"
         {{ endfor }}
     </ul>
     {{ endif }}
+    {{ if diagnostics.divergence.graph_content }}
+    <p><strong>Warning:</strong> Graphs with the same compile id have different content across ranks, which suggests nondeterministic compilation. Graphs and the groups of ranks with identical content:</p>
+    <ul>
+        {{ for graph in diagnostics.graph_content_divergence }}
+            <li>{graph.compile_id} <code>{graph.artifact}</code>: {{ for group in graph.groups }}Ranks {group.ranks}{{ if not @last }}; {{ endif }}{{ endfor }}</li>
+        {{ endfor }}
+    </ul>
+    {{ endif }}
 </div>
 {{ endif }}
 <h2>Multi-Rank TLParse Report</h2>
 <p>
 This report contains TLParse links from <strong>{num_ranks}</strong> rank(s). Click on any rank below
-to view its detailed compilation report.
+to view its detailed compilation report, or <a href="cross_rank_search.html">search all ranks</a> for
+a guard, kernel or failure.
+</p>
+{{ if findings }}
+<h2>Findings</h2>
+<style>
+.finding-high \{ border-left: 4px solid #cc0000; padding-left: 8px; }
+.finding-medium \{ border-left: 4px solid #e69500; padding-left: 8px; }
+.finding-low \{ border-left: 4px solid #999999; padding-left: 8px; }
+</style>
+<p>Checks of the usual causes of slow or unstable compilation flagged these, most severe first.  Each rank's report has the findings of that rank.</p>
+<ul>
+{{ for finding in findings }}
+<li class="finding-{finding.severity}"><strong>{finding.title}</strong> ({finding.severity}). {finding.detail}
+Evidence: {{ for e in finding.evidence }}<a href='{e.url}'>{e.text}</a>{{ if not @last }}, {{ endif }}{{ endfor }}</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+{{ if diagnostics.skipped_ranks }}
+<p>
+<strong>Note:</strong> Only a subset of the ranks was parsed (<code>--ranks</code> or <code>--sample-ranks</code>), so
+the comparisons below don't cover ranks {diagnostics.skipped_ranks}.
 </p>
+{{ endif }}
 {{ if has_chromium_events }}
 <h3> Chromium Events </h3>
 <p>
@@ -595,11 +2151,75 @@ You can download and view this trace in <a href='https://ui.perfetto.dev/'>Perfe
 <p>
 Individual rank reports:
 </p>
+{{ if has_distributed_info }}
+<table>
+<tr> <th>Rank</th> <th>World Size</th> <th>Device</th> <th>Host</th> </tr>
+{{ for r in rank_summaries }}
+<tr>
+    <td><a href="rank_{r.rank}/index.html">Rank {r.rank}</a>{{ if r.rank_mismatch }} (logged as rank {r.info.rank}){{ endif }}</td>
+    <td>{{ if r.info }}{r.info.world_size}{{ endif }}</td>
+    <td>{{ if r.info }}{r.info.device_type}{{ endif }}</td>
+    <td>{{ if r.info }}{r.info.hostname}{{ endif }}</td>
+</tr>
+{{ endfor }}
+</table>
+{{ else }}
 <ul>
 {{ for rank in ranks }}
     <li><a href="rank_{rank}/index.html">Rank {rank}</a></li>
 {{ endfor }}
 </ul>
+{{ endif }}
+{{ if has_rank_stats }}
+<h3>Rank Statistics</h3>
+<p>
+Headline numbers of each rank's log; click a column header to sort by it.
+</p>
+<table class="sortable">
+<tr> <th>Rank</th> <th>Log entries</th> <th>Compiles</th> <th>Failures</th> <th>Compile time (s)</th> <th>Cache hit rate</th> </tr>
+{{ for r in rank_summaries }}
+{{ if r.stats }}
+<tr>
+    <td><a href="rank_{r.rank}/index.html">{r.rank}</a></td>
+    <td>{r.stats.log_entries}</td>
+    <td>{r.stats.compiles}</td>
+    <td>{r.stats.failures}</td>
+    <td>{r.stats.compile_time_s}</td>
+    <td>{{ if r.stats.cache_hit_rate }}{r.stats.cache_hit_rate}{{ else }}n/a{{ endif }}</td>
+</tr>
+{{ endif }}
+{{ endfor }}
+</table>
+{sortable_table_script | format_unescaped}
+{{ endif }}
+{{ if diagnostics.compile_time_skew }}
+<h3>Compile Time Skew</h3>
+<style>
+.straggler \{ background-color: #ffe0e0; font-weight: bold; }
+.skew-bar \{ height: 12px; background-color: #0066cc; }
+.straggler .skew-bar \{ background-color: #cc0000; }
+</style>
+<p>
+Total compile time of each rank; the median is {diagnostics.compile_time_skew.median_s} s.
+{{ if diagnostics.compile_time_skew.num_stragglers }}
+<strong>{diagnostics.compile_time_skew.num_stragglers}</strong> rank(s) deviate from the median by more than {diagnostics.compile_time_skew.threshold_pct}%.
+Ranks that compile much longer than the others stall the first step's collectives until they catch up.
+{{ else }}
+No rank deviates from the median by more than {diagnostics.compile_time_skew.threshold_pct}%.
+{{ endif }}
+</p>
+<table>
+<tr> <th>Rank</th> <th>Compile time (s)</th> <th>vs. median</th> <th style="width: 40%"></th> </tr>
+{{ for r in diagnostics.compile_time_skew.ranks }}
+<tr{{ if r.straggler }} class="straggler"{{ endif }}>
+    <td><a href="rank_{r.rank}/index.html">{r.rank}</a></td>
+    <td>{r.compile_time_s}</td>
+    <td>{r.deviation}</td>
+    <td><div class="skew-bar" style="width: {r.bar_pct}%"></div></td>
+</tr>
+{{ endfor }}
+</table>
+{{ endif }}
 {{ if diagnostics.analysis }}
 {{ if diagnostics.analysis.has_mismatched_graph_counts }}
 <h3>Graph Runtime Analysis</h3>
@@ -618,6 +2238,31 @@ desync issues on specific ranks.
 {{ endfor }}
 {{ endif }}
 {{ endif }}
+{{ if diagnostics.memory_analysis }}
+<h3>Peak Memory Analysis</h3>
+<style>
+.diverging-memory \{ background-color: #ffe0e0; font-weight: bold; }
+</style>
+<p>
+Estimated peak memory of each graph from Inductor's memory planning, compared across ranks.  Ranks
+running the same program should plan the same memory; a rank whose estimate diverges from the
+others compiled a different graph and may run out of memory before the rest.
+{{ if diagnostics.memory_analysis.num_diverging_graphs }}
+<strong>{diagnostics.memory_analysis.num_diverging_graphs} graph(s) have diverging estimates.</strong>
+{{ endif }}
+</p>
+<table>
+<tr><th>Graph</th><th>Peak Memory</th><th>Delta</th><th>Diverging Ranks</th></tr>
+{{ for graph in diagnostics.memory_analysis.graphs }}
+<tr>
+    <td>{graph.graph_id}</td>
+    <td>{graph.peak_memory}</td>
+    <td>{graph.delta}</td>
+    <td>{{ for r in graph.diverging_ranks }}<span class="diverging-memory">Rank {r.rank}: {r.peak_memory}</span>{{ if not @last }}, {{ endif }}{{ endfor }}</td>
+</tr>
+{{ endfor }}
+</table>
+{{ endif }}
 <h3>Tensor Metadata Analysis</h3>
 <p>
 Compares inductor tensor metadata (shapes, dtypes, strides) across ranks to detect compilation divergence.
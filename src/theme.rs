@@ -0,0 +1,132 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Color theme for the generated report.  Each page embeds its own layout CSS and then links
+/// `theme.css`, which only sets colors, so a theme can restyle every page without having to
+/// know about its structure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    fn variables(&self) -> &'static str {
+        match self {
+            Theme::Light => LIGHT_VARIABLES,
+            Theme::Dark => DARK_VARIABLES,
+            Theme::HighContrast => HIGH_CONTRAST_VARIABLES,
+        }
+    }
+
+    /// Syntect theme used to highlight generated code
+    pub fn syntect_theme(&self) -> &'static str {
+        match self {
+            Theme::Light => "InspiredGitHub",
+            Theme::Dark | Theme::HighContrast => "base16-ocean.dark",
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            "high-contrast" => Ok(Theme::HighContrast),
+            _ => Err(format!(
+                "unknown theme {s}; expected one of: light, dark, high-contrast"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Contents of theme.css: the theme's color variables, the rules that apply them, and then
+/// any user supplied css so it can override either.
+pub fn theme_css(theme: Theme, custom_css: &str) -> String {
+    let mut css = String::new();
+    css.push_str(theme.variables());
+    css.push_str(THEME_RULES);
+    if !custom_css.is_empty() {
+        css.push_str("\n/* custom css */\n");
+        css.push_str(custom_css);
+        css.push('\n');
+    }
+    css
+}
+
+static LIGHT_VARIABLES: &str = r#"
+:root {
+    --bg: #ffffff;
+    --fg: #000000;
+    --link: #0066cc;
+    --border: #999999;
+    --header-bg: #d3d3d3;
+    --row-alt-bg: #f2f2f2;
+    --panel-bg: #f0f0f0;
+    --highlight: #ffff00;
+    --highlight-fg: #000000;
+    --muted: #888888;
+}
+"#;
+
+static DARK_VARIABLES: &str = r#"
+:root {
+    --bg: #1e1e1e;
+    --fg: #d4d4d4;
+    --link: #4fa3ff;
+    --border: #555555;
+    --header-bg: #333333;
+    --row-alt-bg: #252526;
+    --panel-bg: #2d2d2d;
+    --highlight: #665c00;
+    --highlight-fg: #ffffff;
+    --muted: #9a9a9a;
+}
+"#;
+
+static HIGH_CONTRAST_VARIABLES: &str = r#"
+:root {
+    --bg: #000000;
+    --fg: #ffffff;
+    --link: #ffff00;
+    --border: #ffffff;
+    --header-bg: #000000;
+    --row-alt-bg: #000000;
+    --panel-bg: #000000;
+    --highlight: #00ffff;
+    --highlight-fg: #000000;
+    --muted: #ffffff;
+}
+"#;
+
+static THEME_RULES: &str = r#"
+body { background-color: var(--bg); color: var(--fg); }
+a { color: var(--link); }
+table, th, td { border-color: var(--border); }
+th { background-color: var(--header-bg); }
+tr:nth-child(odd) { background-color: var(--row-alt-bg); }
+.url-inputs { background-color: var(--panel-bg); }
+.editor { border-color: var(--border); }
+.divider { background-color: var(--border); }
+.highlight { background-color: var(--highlight); color: var(--highlight-fg); }
+.line-number { color: var(--muted); }
+.stack-trie .weight { color: var(--muted); }
+pre span:before { color: var(--muted); }
+pre span:target { background-color: var(--highlight); color: var(--highlight-fg); }
+"#;
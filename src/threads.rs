@@ -0,0 +1,204 @@
+use fxhash::FxHashSet;
+use serde::Serialize;
+
+use crate::glog::{timestamp_us, GlogPrefix};
+use crate::types::{CompileId, FxIndexMap};
+
+#[derive(Debug, Default)]
+struct Span {
+    first_us: i64,
+    last_us: i64,
+    num_records: u64,
+}
+
+impl Span {
+    fn new(t: i64) -> Self {
+        Span {
+            first_us: t,
+            last_us: t,
+            num_records: 0,
+        }
+    }
+
+    fn extend(&mut self, t: i64) {
+        self.first_us = self.first_us.min(t);
+        self.last_us = self.last_us.max(t);
+        self.num_records += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct ThreadActivity {
+    span: Option<Span>,
+    // From the first to the last record of each compile id the thread logged
+    compile_ids: FxIndexMap<String, Span>,
+}
+
+/// What each thread of the logging process (by its glog thread id) compiled and when, to tell
+/// apart compiles that ran at the same time, e.g. on async compile workers
+#[derive(Debug, Default)]
+pub struct ThreadIndex {
+    threads: FxIndexMap<u64, ThreadActivity>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThreadCompile {
+    pub compile_id: String,
+    pub start_s: f64,
+    pub duration_s: f64,
+    pub num_records: u64,
+    /// Whether a compile on another thread ran at the same time
+    pub overlapping: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThreadSummary {
+    pub thread: u64,
+    pub num_records: u64,
+    /// Seconds since the first record of the log
+    pub start_s: f64,
+    pub duration_s: f64,
+    pub compiles: Vec<ThreadCompile>,
+}
+
+// Report times to the millisecond
+fn seconds(us: i64) -> f64 {
+    (us as f64 / 1000.0).round() / 1000.0
+}
+
+impl ThreadIndex {
+    pub fn record(&mut self, prefix: &GlogPrefix, compile_id: &Option<CompileId>) {
+        let t = timestamp_us(prefix);
+        let activity = self.threads.entry(prefix.thread).or_default();
+        activity.span.get_or_insert_with(|| Span::new(t)).extend(t);
+        if let Some(compile_id) = compile_id {
+            activity
+                .compile_ids
+                .entry(compile_id.to_string())
+                .or_insert_with(|| Span::new(t))
+                .extend(t);
+        }
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Compile ids of each thread that ran at the same time as a compile on another thread
+    fn overlapping(&self) -> FxHashSet<(u64, &str)> {
+        let mut spans: Vec<(u64, &str, &Span)> = self
+            .threads
+            .iter()
+            .flat_map(|(thread, activity)| {
+                activity
+                    .compile_ids
+                    .iter()
+                    .map(move |(compile_id, span)| (*thread, compile_id.as_str(), span))
+            })
+            .collect();
+        spans.sort_by_key(|(_, _, span)| span.first_us);
+        // Sweep the spans in order of their start, keeping those that haven't ended yet
+        let mut overlapping = FxHashSet::default();
+        let mut active: Vec<(u64, &str, &Span)> = Vec::new();
+        for (thread, compile_id, span) in spans {
+            active.retain(|(_, _, other)| other.last_us >= span.first_us);
+            for (other_thread, other_id, _) in &active {
+                if *other_thread != thread && *other_id != compile_id {
+                    overlapping.insert((thread, compile_id));
+                    overlapping.insert((*other_thread, *other_id));
+                }
+            }
+            active.push((thread, compile_id, span));
+        }
+        overlapping
+    }
+
+    pub fn summary(&self) -> Vec<ThreadSummary> {
+        let start = self
+            .threads
+            .values()
+            .filter_map(|a| a.span.as_ref())
+            .map(|s| s.first_us)
+            .min()
+            .unwrap_or(0);
+        let overlapping = self.overlapping();
+        self.threads
+            .iter()
+            .filter_map(|(thread, activity)| {
+                let span = activity.span.as_ref()?;
+                Some(ThreadSummary {
+                    thread: *thread,
+                    num_records: span.num_records,
+                    start_s: seconds(span.first_us - start),
+                    duration_s: seconds(span.last_us - span.first_us),
+                    compiles: activity
+                        .compile_ids
+                        .iter()
+                        .map(|(compile_id, s)| ThreadCompile {
+                            compile_id: compile_id.clone(),
+                            start_s: seconds(s.first_us - start),
+                            duration_s: seconds(s.last_us - s.first_us),
+                            num_records: s.num_records,
+                            overlapping: overlapping.contains(&(*thread, compile_id.as_str())),
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineBar {
+    pub compile_id: String,
+    /// Position and size in % of the whole log's duration
+    pub left: String,
+    pub width: String,
+    pub title: String,
+    pub overlapping: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineRow {
+    pub thread: u64,
+    pub num_records: u64,
+    pub num_compiles: usize,
+    pub bars: Vec<TimelineBar>,
+}
+
+/// A row per thread, with a bar per compile id spanning its records
+pub fn timeline_rows(summary: &[ThreadSummary]) -> Vec<TimelineRow> {
+    let end = summary
+        .iter()
+        .map(|t| t.start_s + t.duration_s)
+        .fold(0.0, f64::max);
+    let percent = |s: f64| {
+        if end > 0.0 {
+            format!("{:.2}%", s / end * 100.0)
+        } else {
+            "0%".to_string()
+        }
+    };
+    summary
+        .iter()
+        .map(|t| TimelineRow {
+            thread: t.thread,
+            num_records: t.num_records,
+            num_compiles: t.compiles.len(),
+            bars: t
+                .compiles
+                .iter()
+                .map(|c| TimelineBar {
+                    compile_id: c.compile_id.clone(),
+                    left: percent(c.start_s),
+                    width: percent(c.duration_s),
+                    title: format!(
+                        "{} from {:.3} s for {:.3} s ({} records)",
+                        c.compile_id, c.start_s, c.duration_s, c.num_records
+                    ),
+                    overlapping: c.overlapping,
+                })
+                .collect(),
+        })
+        .collect()
+}
@@ -5,12 +5,14 @@ use indexmap::IndexMap;
 use regex::Regex;
 use serde_json::Value;
 
+use std::collections::BTreeMap;
 use std::fmt::{self, Display, Write};
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+
+use crate::source_snippets::SourceSnippets;
 
 // Main function returns a list of files to save
 pub type ParseOutput = Vec<(PathBuf, String)>;
@@ -20,15 +22,85 @@ pub type SymbolicShapeSpecializationIndex =
     FxHashMap<Option<CompileId>, Vec<SymbolicShapeSpecializationMetadata>>;
 pub type GuardAddedFastIndex = FxHashMap<Option<CompileId>, Vec<GuardAddedFastMetadata>>;
 pub type SymExprInfoIndex = FxHashMap<u64, SymExprInfoMetadata>;
+pub type AOTGraphsIndex = FxIndexMap<Option<CompileId>, AOTGraphs>;
+pub type DDPSplitsIndex = FxIndexMap<Option<CompileId>, DDPSplits>;
+pub type GraphPartitionsIndex = FxIndexMap<Option<CompileId>, Vec<GraphPartitionArtifact>>;
+// Stats of each dump of an FX graph, by compile id and the name of the graph, e.g.
+// dynamo_output_graph
+pub type GraphStatsIndex = FxIndexMap<(Option<CompileId>, &'static str), Vec<GraphStats>>;
+pub type SymbolicExpressionsIndex = FxIndexMap<Option<CompileId>, SymbolicExpressions>;
+// (stage, pass name) => duration of the pass in seconds for each compile id that ran it
+pub type PassTimingsIndex = FxIndexMap<(String, String), Vec<(Option<CompileId>, f64)>>;
+// Estimated peak memory in bytes of each compile id with a memory plan
+pub type MemoryIndex = FxIndexMap<Option<CompileId>, u64>;
+// Snapshots of counter artifacts, in the order they were logged
+pub type CountersIndex = Vec<CounterSnapshot>;
 
 pub type FxIndexMap<K, V> = IndexMap<K, V, BuildHasherDefault<FxHasher>>;
 
-/// Per-rank metadata collected during multi-rank aggregation.
-#[derive(Debug)]
-pub struct RankMetaData {
-    pub rank: u32,
+/// What the multi-rank report compares across ranks about a parsed log, see
+/// `parse_path_with_summary`
+#[derive(Debug, Default)]
+pub struct RankParseSummary {
+    pub stats: Stats,
+    pub compile_summary: crate::summary::CompileSummary,
     pub compile_ids: FxHashSet<String>,
+    /// Cache hit/miss markers of the artifacts, in log order
     pub cache_sequence: String,
+    /// md5 of each graph artifact (e.g. dynamo_output_graph), by compile id and artifact name
+    pub graph_hashes: FxHashMap<(String, String), String>,
+    pub num_chromium_events: usize,
+    /// The compilations of the log, for comparing runs with `--compare-runs`, see
+    /// `ParseConfig::compare_runs`
+    pub compiles: Vec<RunCompile>,
+}
+
+/// A compilation of a run, with what identifies it across runs: the frame it compiled and the
+/// user stack that led to it
+#[derive(Debug, Clone)]
+pub struct RunCompile {
+    pub compile_id: String,
+    /// e.g. forward (/home/me/model.py:12)
+    pub frame: String,
+    /// The user stack of the compile, innermost frame last
+    pub stack: Vec<String>,
+    /// Link to its compilation metrics, relative to the run's report
+    pub url: Option<String>,
+    pub compile_time_s: Option<f64>,
+    /// Cache hit/miss markers of its artifacts
+    pub cache: String,
+    pub fail_type: Option<String>,
+}
+
+/// Describes the process that wrote a log, from the `distributed_info` artifact PyTorch logs
+/// once the process group is initialized.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DistributedInfo {
+    pub rank: Option<u32>,
+    pub world_size: Option<u32>,
+    pub device_type: Option<String>,
+    pub hostname: Option<String>,
+}
+
+/// A rank of a multi-rank report, along with what it logged about itself
+#[derive(Debug, Serialize)]
+pub struct RankSummary {
+    pub rank: u32,
+    pub info: Option<DistributedInfo>,
+    /// The rank in the log's distributed info doesn't match the one in its filename
+    pub rank_mismatch: bool,
+    pub stats: Option<RankStats>,
+}
+
+/// Headline numbers of a rank's log, for comparing ranks on the multi-rank landing page
+#[derive(Debug, Serialize)]
+pub struct RankStats {
+    pub log_entries: u64,
+    pub compiles: u64,
+    pub failures: u64,
+    pub compile_time_s: f64,
+    /// e.g. "87.5%", None if the rank had no cache hits or misses
+    pub cache_hit_rate: Option<String>,
 }
 
 /// Grouping of ranks that share the same sequence pattern (cache, collective ops, etc.).
@@ -45,6 +117,15 @@ pub struct CollectiveSchedule {
     pub ops: Vec<String>,
 }
 
+/// A graph of a compile id with different content on different ranks, which compile the "same"
+/// graph nondeterministically.  Each group is the ranks with one version of the graph.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GraphContentDivergence {
+    pub compile_id: String,
+    pub artifact: String,
+    pub groups: Vec<DivergenceGroup>,
+}
+
 /// Canonical fingerprint for tensor meta JSON for a given graph on a rank
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TensorMetaFingerprint {
@@ -68,6 +149,38 @@ pub struct GraphRuntime {
     pub ops: Vec<OpRuntime>,
 }
 
+/// Estimated peak memory of 1 graph on a given rank, from its memory plan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphMemory {
+    pub rank: u32,
+    pub graph: String,
+    pub peak_bytes: u64,
+}
+
+/// A rank whose estimated peak memory for a graph differs from most ranks
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RankMemory {
+    pub rank: u32,
+    pub peak_memory: String,
+}
+
+/// Estimated peak memory of a graph across the ranks that compiled it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GraphMemoryComparison {
+    pub graph_id: String,
+    /// The estimate of most ranks
+    pub peak_memory: String,
+    pub delta: String,
+    pub diverging_ranks: Vec<RankMemory>,
+}
+
+/// Peak memory analysis results across ranks for all graphs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemoryAnalysis {
+    pub graphs: Vec<GraphMemoryComparison>,
+    pub num_diverging_graphs: usize,
+}
+
 /// Details for a specific rank at a graph index
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RuntimeRankDetail {
@@ -91,6 +204,29 @@ pub struct RuntimeAnalysis {
     pub has_mismatched_graph_counts: bool,
 }
 
+/// Total compile time of a rank, compared to the median of all ranks
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RankCompileTime {
+    pub rank: u32,
+    pub compile_time_s: f64,
+    /// e.g. "+35.0%"
+    pub deviation: String,
+    /// Width of the rank's bar, relative to the slowest rank
+    pub bar_pct: f64,
+    /// Deviates from the median by more than the threshold
+    pub straggler: bool,
+}
+
+/// How much the ranks' compile times differ: ranks that take much longer to compile than the
+/// others stall the first step's collectives until they catch up
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompileTimeSkew {
+    pub median_s: f64,
+    pub threshold_pct: f64,
+    pub num_stragglers: usize,
+    pub ranks: Vec<RankCompileTime>,
+}
+
 pub fn extract_eval_with_key_id(filename: &str) -> Option<u64> {
     let re = Regex::new(r"<eval_with_key>\.([0-9]+)").unwrap();
     re.captures(filename)
@@ -98,8 +234,36 @@ pub fn extract_eval_with_key_id(filename: &str) -> Option<u64> {
         .and_then(|m| m.as_str().parse::<u64>().ok())
 }
 
-pub static INTERN_TABLE: Lazy<Mutex<FxHashMap<u32, String>>> =
-    Lazy::new(|| Mutex::new(FxHashMap::default()));
+/// Strings interned by `{"str": [string, index]}` log entries, e.g. the filenames of stack frames.
+/// Each parse has its own table, since indices are only meaningful within one log.
+#[derive(Debug, Default)]
+pub struct InternTable {
+    strings: FxHashMap<u32, String>,
+}
+
+impl InternTable {
+    pub fn insert(&mut self, index: u32, s: String) {
+        self.strings.insert(index, s);
+    }
+
+    pub fn get(&self, index: u32) -> &str {
+        self.lookup(index).unwrap_or("(unknown)")
+    }
+
+    pub fn lookup(&self, index: u32) -> Option<&str> {
+        self.strings.get(&index).map(|s| s.as_str())
+    }
+
+    /// All strings by index, with nulls for missing indices
+    pub fn string_table(&self) -> Vec<Option<String>> {
+        let max_index = self.strings.keys().max().copied().unwrap_or(0) as usize;
+        let mut string_table: Vec<Option<String>> = vec![None; max_index + 1];
+        for (&index, value) in self.strings.iter() {
+            string_table[index as usize] = Some(value.clone());
+        }
+        string_table
+    }
+}
 
 #[derive(Default)]
 pub struct StackTrieNode {
@@ -130,77 +294,186 @@ impl StackTrieNode {
 
     pub fn fmt(
         &self,
+        intern_table: &InternTable,
         metrics_index: Option<&CompilationMetricsIndex>,
+        sources: Option<&SourceSnippets>,
         caption: &str,
         open: bool,
     ) -> Result<String, fmt::Error> {
         let mut f = String::new();
         write!(f, "<details{}>", if open { " open" } else { "" })?;
-        write!(f, "<summary>{}</summary>", caption)?;
+        match metrics_index {
+            Some(metrics_index) if !self.is_empty() => {
+                let (compiles, compile_time_s) = self.weight(metrics_index);
+                write!(
+                    f,
+                    "<summary>{caption} {}</summary>",
+                    fmt_weight(compiles, compile_time_s)
+                )?;
+            }
+            _ => write!(f, "<summary>{}</summary>", caption)?,
+        }
         write!(f, "<div class='stack-trie'>")?;
         write!(f, "<ul>")?;
-        self.fmt_inner(&mut f, metrics_index)?;
+        self.fmt_inner(&mut f, intern_table, metrics_index, sources)?;
         write!(f, "</ul>")?;
         write!(f, "</div>")?;
         write!(f, "</details>")?;
         Ok(f)
     }
 
+    // Links to the compilations whose stack ends exactly at this node, colored by status
+    fn terminal_links(
+        &self,
+        mb_metrics_index: Option<&CompilationMetricsIndex>,
+    ) -> Result<String, fmt::Error> {
+        let mut star = String::new();
+        for t in &self.terminal {
+            if let Some(c) = t {
+                let ok_class = mb_metrics_index.map_or("status-missing", |metrics_index| {
+                    metrics_index.get(t).map_or("status-missing", |m| {
+                        if m.iter().any(|n| n.fail_type.is_some()) {
+                            "status-error"
+                        } else if m.iter().any(|n| n.graph_op_count.unwrap_or(0) == 0) {
+                            "status-empty"
+                        } else if m
+                            .iter()
+                            .any(|n| !n.restart_reasons.as_ref().is_some_and(|o| o.is_empty()))
+                        {
+                            "status-break"
+                        } else {
+                            "status-ok"
+                        }
+                    })
+                });
+                write!(
+                    star,
+                    "<a href='#{cid}' class='{ok_class}'>{cid}</a> ",
+                    cid = c,
+                    ok_class = ok_class
+                )?;
+            } else {
+                write!(star, "(unknown) ")?;
+            }
+        }
+        Ok(star)
+    }
+
+    // Number of distinct compilations in this subtree and their total compile time
+    fn weight(&self, metrics_index: &CompilationMetricsIndex) -> (usize, f64) {
+        fn collect<'a>(node: &'a StackTrieNode, compile_ids: &mut FxHashSet<&'a CompileId>) {
+            compile_ids.extend(node.terminal.iter().flatten());
+            for child in node.children.values() {
+                collect(child, compile_ids);
+            }
+        }
+        let mut compile_ids = FxHashSet::default();
+        collect(self, &mut compile_ids);
+        let compile_time_s = compile_ids
+            .iter()
+            .filter_map(|cid| metrics_index.get(&Some((*cid).clone())))
+            .flatten()
+            .filter_map(|m| m.entire_frame_compile_time_s)
+            .fold(0.0, |acc, t| acc + t);
+        (compile_ids.len(), compile_time_s)
+    }
+
     pub fn fmt_inner(
         &self,
         f: &mut String,
+        intern_table: &InternTable,
         mb_metrics_index: Option<&CompilationMetricsIndex>,
+        sources: Option<&SourceSnippets>,
     ) -> fmt::Result {
-        for (frame, node) in self.children.iter() {
-            let mut star = String::new();
-            for t in &node.terminal {
-                if let Some(c) = t {
-                    let ok_class = mb_metrics_index.map_or("status-missing", |metrics_index| {
-                        metrics_index.get(t).map_or("status-missing", |m| {
-                            if m.iter().any(|n| n.fail_type.is_some()) {
-                                "status-error"
-                            } else if m.iter().any(|n| n.graph_op_count.unwrap_or(0) == 0) {
-                                "status-empty"
-                            } else if m.iter().any(|n| {
-                                !n.restart_reasons.as_ref().map_or(false, |o| o.is_empty())
-                            }) {
-                                "status-break"
-                            } else {
-                                "status-ok"
-                            }
-                        })
-                    });
-                    write!(
-                        star,
-                        "<a href='#{cid}' class='{ok_class}'>{cid}</a> ",
-                        cid = c,
-                        ok_class = ok_class
-                    )?;
-                } else {
-                    write!(star, "(unknown) ")?;
+        if self.children.len() == 1 {
+            // Follow the chain of frames that have only one child.  Frames in the middle of a
+            // long chain carry no information about where compilations diverge, so we fold
+            // them away.
+            let mut chain: Vec<(&FrameSummary, &StackTrieNode)> =
+                vec![self.children.first().unwrap()];
+            while let Some((_, last)) = chain.last() {
+                if last.children.len() != 1 {
+                    break;
+                }
+                let next = last.children.first().unwrap();
+                chain.push(next);
+                if !next.1.terminal.is_empty() {
+                    break;
                 }
             }
-
-            if self.children.len() > 1 {
-                // If the node has multiple children, increase the indent and print a hyphen
+            let (first, rest) = chain.split_first().unwrap();
+            let (middle, last) = match rest.split_last() {
+                Some((last, middle)) => (middle, Some(last)),
+                None => (rest, None),
+            };
+            // If the node has only one child, don't increase the indent and don't print a hyphen
+            let write_frame = |f: &mut String, (frame, node): &(&FrameSummary, &StackTrieNode)| {
                 writeln!(
                     f,
-                    "<li><span onclick='toggleList(this)' class='marker'></span>{star}",
-                    star = star
+                    "<li>{star}{}</li>",
+                    frame.display_with_source(intern_table, sources),
+                    star = node.terminal_links(mb_metrics_index)?
+                )
+            };
+            write_frame(f, first)?;
+            if middle.len() >= MIN_COLLAPSED_FRAMES {
+                write!(
+                    f,
+                    "<li><details><summary>{} more frames</summary><ul>",
+                    middle.len()
                 )?;
-                writeln!(f, "{}<ul>", frame)?;
-                node.fmt_inner(f, mb_metrics_index)?;
-                write!(f, "</ul></li>")?;
+                for entry in middle {
+                    write_frame(f, entry)?;
+                }
+                write!(f, "</ul></details></li>")?;
             } else {
-                // If the node has only one child, don't increase the indent and don't print a hyphen
-                writeln!(f, "<li>{star}{}</li>", frame, star = star)?;
-                node.fmt_inner(f, mb_metrics_index)?;
+                for entry in middle {
+                    write_frame(f, entry)?;
+                }
+            }
+            if let Some(last) = last {
+                write_frame(f, last)?;
             }
+            let (_, end) = last.unwrap_or(first);
+            return end.fmt_inner(f, intern_table, mb_metrics_index, sources);
+        }
+
+        for (frame, node) in self.children.iter() {
+            let star = node.terminal_links(mb_metrics_index)?;
+            let weight = match mb_metrics_index {
+                Some(metrics_index) => {
+                    let (compiles, compile_time_s) = node.weight(metrics_index);
+                    format!("{} ", fmt_weight(compiles, compile_time_s))
+                }
+                None => String::new(),
+            };
+            // If the node has multiple children, increase the indent and print a hyphen
+            writeln!(
+                f,
+                "<li><span onclick='toggleList(this)' class='marker'></span>{weight}{star}",
+            )?;
+            writeln!(
+                f,
+                "{}<ul>",
+                frame.display_with_source(intern_table, sources)
+            )?;
+            node.fmt_inner(f, intern_table, mb_metrics_index, sources)?;
+            write!(f, "</ul></li>")?;
         }
         Ok(())
     }
 }
 
+// Chains of single-child frames at least this long are collapsed in the stack trie
+const MIN_COLLAPSED_FRAMES: usize = 3;
+
+fn fmt_weight(compiles: usize, compile_time_s: f64) -> String {
+    format!(
+        "<span class='weight'>[{compiles} compile{}, {compile_time_s:.2}s]</span>",
+        if compiles == 1 { "" } else { "s" }
+    )
+}
+
 #[derive(Eq, PartialEq, Hash, Deserialize, Serialize, Debug, Clone)]
 pub struct CompileId {
     pub compiled_autograd_id: Option<u32>,
@@ -232,6 +505,41 @@ impl fmt::Display for CompileId {
     }
 }
 
+/// Parses a compile id as it is displayed, e.g. [!1/3/0_2], with or without the brackets.  The
+/// attempt is None if it is left out, as it is for attempt 0.
+impl FromStr for CompileId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid compile id {s}; expected e.g. 3/1, 3/1_2 or [!0/3/1]");
+        let number = |n: &str| n.parse::<u32>().map_err(|_| invalid());
+        let id = s.strip_prefix('[').unwrap_or(s);
+        let id = id.strip_suffix(']').unwrap_or(id);
+        let (compiled_autograd_id, id) = match id.strip_prefix('!') {
+            Some(id) => {
+                let (compiled_autograd_id, id) = id.split_once('/').ok_or_else(invalid)?;
+                (Some(number(compiled_autograd_id)?), id)
+            }
+            None => (None, id),
+        };
+        let (id, attempt) = match id.split_once('_') {
+            Some((id, attempt)) => (id, Some(number(attempt)?)),
+            None => (id, None),
+        };
+        let (frame_id, frame_compile_id) = id.split_once('/').ok_or_else(invalid)?;
+        let optional = |n: &str| match n {
+            "-" => Ok(None),
+            n => number(n).map(Some),
+        };
+        Ok(CompileId {
+            compiled_autograd_id,
+            frame_id: optional(frame_id)?,
+            frame_compile_id: optional(frame_compile_id)?,
+            attempt,
+        })
+    }
+}
+
 impl CompileId {
     pub fn as_directory_name(&self) -> String {
         let compiled_autograd_id_str = self
@@ -247,7 +555,7 @@ impl CompileId {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Serialize)]
 pub struct Stats {
     pub ok: u64,
     pub other_rank: u64,
@@ -259,6 +567,65 @@ pub struct Stats {
     pub fail_key_conflict: u64,
     pub fail_json_serialization: u64,
     pub unknown: u64,
+    /// Bytes of payloads not written again because an identical payload already was
+    pub deduplicated_payload_bytes: u64,
+    /// chromium_events that were malformed beyond repair
+    pub chromium_events_dropped: u64,
+    pub chromium_events_repaired: u64,
+    pub chromium_events_deduplicated: u64,
+    /// Payloads put back together from lines interleaved with other threads' log lines
+    pub recovered_payloads: u64,
+    /// Malformed payloads written to corrupt_payloads/ instead of being parsed
+    pub quarantined_payloads: u64,
+    /// The log ends in the middle of an envelope or its payload, e.g. because the job crashed
+    pub truncated_at_eof: u64,
+    /// Byte sequences of the log that aren't UTF-8, replaced with U+FFFD
+    pub invalid_utf8: u64,
+    /// Payloads with bytes that aren't UTF-8.  Their md5 is of the bytes as logged, so they still
+    /// verify.
+    pub invalid_utf8_payloads: u64,
+    /// Renderings of payloads cut short at `ParseConfig::max_artifact_bytes`
+    pub truncated_artifacts: u64,
+}
+
+/// A malformed payload block, written to `url` instead of being parsed.  See
+/// `ParseConfig::recover_payloads`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CorruptPayload {
+    /// Line of the log the block starts on
+    pub line: usize,
+    /// Thread that logged the envelope, if the block has one
+    pub thread: Option<u64>,
+    pub reason: String,
+    pub expected_md5: Option<String>,
+    pub actual_md5: String,
+    pub num_lines: usize,
+    /// Lines of other threads found in the middle of the block
+    pub interleaved_lines: Vec<usize>,
+    pub url: String,
+}
+
+/// How many records of one type a log has, e.g. dynamo_start or artifact:fx_graph_cache_miss,
+/// and how many bytes their payloads take up
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EnvelopeTypeStats {
+    pub envelope_type: String,
+    pub count: u64,
+    pub payload_bytes: u64,
+    /// Bytes of the log the records take up, i.e. their payloads and envelopes
+    pub log_bytes: u64,
+}
+
+/// Time spent in one parser over the whole log, for parser_profile.json
+#[derive(Debug, Clone, Serialize)]
+pub struct ParserTiming {
+    pub parser: String,
+    /// Records the parser ran on
+    pub calls: u64,
+    pub outputs: u64,
+    pub output_bytes: u64,
+    pub total_s: f64,
+    pub mean_ms: f64,
 }
 
 impl std::fmt::Display for Stats {
@@ -301,6 +668,55 @@ impl std::fmt::Display for Stats {
         if self.unknown > 0 {
             fields.push(format!("unknown: {}", self.unknown));
         }
+        if self.deduplicated_payload_bytes > 0 {
+            fields.push(format!(
+                "deduplicated_payload_bytes: {}",
+                self.deduplicated_payload_bytes
+            ));
+        }
+        if self.chromium_events_dropped > 0 {
+            fields.push(format!(
+                "chromium_events_dropped: {}",
+                self.chromium_events_dropped
+            ));
+        }
+        if self.chromium_events_repaired > 0 {
+            fields.push(format!(
+                "chromium_events_repaired: {}",
+                self.chromium_events_repaired
+            ));
+        }
+        if self.chromium_events_deduplicated > 0 {
+            fields.push(format!(
+                "chromium_events_deduplicated: {}",
+                self.chromium_events_deduplicated
+            ));
+        }
+
+        if self.recovered_payloads > 0 {
+            fields.push(format!("recovered_payloads: {}", self.recovered_payloads));
+        }
+        if self.quarantined_payloads > 0 {
+            fields.push(format!(
+                "quarantined_payloads: {}",
+                self.quarantined_payloads
+            ));
+        }
+        if self.truncated_at_eof > 0 {
+            fields.push(format!("truncated_at_eof: {}", self.truncated_at_eof));
+        }
+        if self.invalid_utf8 > 0 {
+            fields.push(format!("invalid_utf8: {}", self.invalid_utf8));
+        }
+        if self.invalid_utf8_payloads > 0 {
+            fields.push(format!(
+                "invalid_utf8_payloads: {}",
+                self.invalid_utf8_payloads
+            ));
+        }
+        if self.truncated_artifacts > 0 {
+            fields.push(format!("truncated_artifacts: {}", self.truncated_artifacts));
+        }
 
         if fields.is_empty() {
             write!(f, "Stats {{ }}")
@@ -333,41 +749,71 @@ pub fn simplify_filename<'a>(filename: &'a str) -> &'a str {
     return filename;
 }
 
-pub fn unintern_str(interned_str: u32) -> String {
-    let intern_table = INTERN_TABLE.lock().unwrap();
-    let filename = intern_table
-        .get(&interned_str)
-        .map_or("(unknown)", |s| s.as_str());
-    return filename.to_string();
+impl FrameSummary {
+    pub fn filename<'a>(&'a self, intern_table: &'a InternTable) -> &'a str {
+        match &self.uninterned_filename {
+            Some(f) => f.as_str(),
+            None => intern_table.get(self.filename),
+        }
+    }
+
+    /// Renders the frame as html
+    pub fn display<'a>(&'a self, intern_table: &'a InternTable) -> FrameDisplay<'a> {
+        self.display_with_source(intern_table, None)
+    }
+
+    /// Renders the frame as html, with the source around its line if `sources` can find its file
+    pub fn display_with_source<'a>(
+        &'a self,
+        intern_table: &'a InternTable,
+        sources: Option<&'a SourceSnippets>,
+    ) -> FrameDisplay<'a> {
+        FrameDisplay {
+            frame: self,
+            intern_table,
+            sources,
+        }
+    }
+}
+
+pub struct FrameDisplay<'a> {
+    frame: &'a FrameSummary,
+    intern_table: &'a InternTable,
+    sources: Option<&'a SourceSnippets>,
 }
 
-impl fmt::Display for FrameSummary {
+impl fmt::Display for FrameDisplay<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let intern_table = INTERN_TABLE.lock().unwrap();
-        let filename = if let Some(f) = &self.uninterned_filename {
-            f.as_str()
-        } else {
-            intern_table
-                .get(&self.filename)
-                .map_or("(unknown)", |s| s.as_str())
-        };
+        let frame = self.frame;
+        let filename = frame.filename(self.intern_table);
         if let Some(fx_id) = extract_eval_with_key_id(filename) {
             write!(
                 f,
                 "<a href='dump_file/eval_with_key_{fx_id}.html#L{line}'>{filename}:{line}</a> in {name}",
                 fx_id = fx_id,
                 filename = encode_text(simplify_filename(filename)),
-                line = self.line,
-                name = encode_text(&self.name)
+                line = frame.line,
+                name = encode_text(&frame.name)
+            )?;
+        } else if let Some(snippet) = self
+            .sources
+            .and_then(|sources| sources.snippet_html(filename, frame.line))
+        {
+            write!(
+                f,
+                "{}:{} in {}{snippet}",
+                encode_text(simplify_filename(filename)),
+                frame.line,
+                encode_text(&frame.name),
             )?;
         } else {
             write!(
                 f,
                 "{}:{} in {}<br>&nbsp;&nbsp;&nbsp;&nbsp;{}",
                 encode_text(simplify_filename(filename)),
-                self.line,
-                encode_text(&self.name),
-                encode_text(&self.loc.clone().unwrap_or("".to_string()))
+                frame.line,
+                encode_text(&frame.name),
+                encode_text(&frame.loc.clone().unwrap_or("".to_string()))
             )?;
         }
         Ok(())
@@ -459,6 +905,14 @@ pub struct CompilationMetricsMetadata {
     pub compliant_custom_ops: Option<Vec<String>>,
     pub restart_reasons: Option<Vec<String>>,
     pub dynamo_time_before_restart_s: Option<f64>,
+    pub guard_latency_us: Option<f64>,
+    pub cudagraph_skip_reason: Option<String>,
+    /// The guards of earlier compiles of the frame that failed, e.g.
+    /// `0/0: tensor 'L['x']' size mismatch at index 0. expected 3, actual 4`
+    pub recompile_reason: Option<String>,
+    /// Fields PyTorch logs that we don't know about yet, kept so they still show up in reports
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -489,35 +943,11 @@ pub struct SymbolicShapeSpecializationMetadata {
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct FrameLocals {
+    /// Source line the locals were captured at
+    pub loc: Option<String>,
     pub locals: Option<FxHashMap<String, Option<String>>>,
     pub symbols: Option<FxHashMap<String, Option<String>>>,
 }
-impl Display for FrameLocals {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(locals) = &self.locals {
-            write!(f, "Locals:<pre>\n")?;
-            for (name, value) in locals {
-                match value {
-                    Some(v) => write!(f, "    {}: {}\n", name, v),
-                    None => Ok(()),
-                }?
-            }
-            write!(f, "</pre>")?;
-        }
-        if let Some(symbols) = &self.symbols {
-            write!(f, "Symbols:<pre>\n")?;
-            for (name, value) in symbols {
-                match value {
-                    Some(v) => write!(f, "    {}: {}\n", name, v),
-                    None => Ok(()),
-                }?
-            }
-            write!(f, "</pre>")?;
-        }
-        Ok(())
-    }
-}
-
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SymbolicShapePropagateRealTensorMetadata {
     pub expr: Option<String>,
@@ -539,7 +969,7 @@ pub struct UnbackedSymbolMetadata {
     pub vr: Option<String>,
 }
 
-#[derive(Default, Debug, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct SymExprInfoMetadata {
     pub method: Option<String>,
     pub result: Option<String>,
@@ -558,6 +988,7 @@ pub struct FakeKernelMetadata {
 
 #[derive(Debug, Serialize)]
 pub struct BwdCompilationMetricsContext<'e> {
+    pub root: String,
     pub m: &'e BwdCompilationMetricsMetadata,
     pub css: &'static str,
     pub compile_id: String,
@@ -566,6 +997,7 @@ pub struct BwdCompilationMetricsContext<'e> {
 
 #[derive(Debug, Serialize)]
 pub struct AOTAutogradBackwardCompilationMetricsContext<'e> {
+    pub root: String,
     pub m: &'e AOTAutogradBackwardCompilationMetricsMetadata,
     pub css: &'static str,
     pub compile_id: String,
@@ -580,10 +1012,60 @@ pub struct OutputFile {
     pub suffix: String,
     /// URL to a human-readable HTML version of inductor_provenance_tracking_kernel_stack_traces.json
     pub readable_url: Option<String>,
+    /// Line of the log where the envelope this was parsed from starts, None for pages
+    /// generated after parsing
+    pub log_line: Option<usize>,
+}
+
+/// Version of the shape of compile_directory.json, bumped whenever a field is removed or changes
+/// meaning, so that tools reading it can tell which shape they got
+pub const COMPILE_DIRECTORY_SCHEMA_VERSION: u32 = 1;
+
+/// compile_directory.json: the artifacts of each compile id in log order, keyed by the compile id,
+/// e.g. "[0/0]", or "unknown" for artifacts without one
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompileDirectory {
+    /// `COMPILE_DIRECTORY_SCHEMA_VERSION`
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub compile_ids: FxIndexMap<String, CompileDirectoryEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompileDirectoryEntry {
+    pub artifacts: Vec<CompileDirectoryArtifact>,
+    /// Where the artifacts of the compile id are, relative to the root of the report, e.g.
+    /// -_0_0_0 or frame_0/compile_0/attempt_0, None for artifacts without a compile id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    /// The `DirectoryLayout` of the report, e.g. flat
+    pub layout: String,
+    /// Training step the compilation happened at, with --steps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompileDirectoryArtifact {
+    /// Relative to the root of the report
+    pub url: String,
+    /// File name of the artifact, without its directory
+    pub name: String,
+    /// Position of the artifact among those of the log
+    pub number: i32,
+    /// e.g. ❌ for failed compilations
+    pub suffix: String,
+    /// Human-readable version of the artifact, if any
+    pub readable_url: Option<String>,
+    /// Line of the log the artifact was parsed from, None for pages generated after parsing
+    pub log_line: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct CompilationMetricsContext<'e> {
+    pub root: String,
     pub m: &'e CompilationMetricsMetadata,
     pub css: &'static str,
     pub compile_id: String,
@@ -593,11 +1075,21 @@ pub struct CompilationMetricsContext<'e> {
     pub output_files: &'e Vec<OutputFile>,
     pub compile_id_dir: &'e PathBuf,
     pub mini_stack_html: String,
+    pub extra_metrics: Vec<ExtraMetric>,
+    /// Frame locals captured with the guards of the compile, empty if there are none
+    pub locals_html: String,
     pub qps: &'static str,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ExtraMetric {
+    pub key: String,
+    pub value: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SymbolicGuardContext {
+    pub root: String,
     pub css: &'static str,
     pub expr: String,
     pub user_stack_html: String,
@@ -644,11 +1136,40 @@ impl Display for FailureReason {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExportFailure {
     pub failure_type: String,
     pub reason: String,
     pub additional_info: String,
+    /// Operator the failure is about, for fake kernel failures
+    pub op: Option<String>,
+    /// Guard expression, for guard and data dependent failures
+    pub expr: Option<String>,
+    pub suggestions: Vec<ConstraintSuggestion>,
+}
+
+/// A suggested fix for an export guard failure
+#[derive(Debug, Clone, Serialize)]
+pub struct ConstraintSuggestion {
+    /// "dynamic_shapes" or "torch._check"
+    pub kind: &'static str,
+    pub code: String,
+    pub explanation: String,
+}
+
+/// Export failures of one type, with per-operator counts for fake kernel failures
+#[derive(Debug, Serialize)]
+pub struct ExportFailureGroup {
+    pub failure_type: String,
+    pub count: usize,
+    pub ops: Vec<ExportFailureOp>,
+    pub failures: Vec<ExportFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportFailureOp {
+    pub op: String,
+    pub count: usize,
 }
 impl Display for ExportFailure {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -667,10 +1188,21 @@ impl Display for ExportFailure {
 pub struct RestartsAndFailuresContext {
     // Serialized versions of (CompileId, FailureReason)
     pub failures: Vec<(String, String)>,
+    // Identical failure reasons grouped together, most frequent first
+    pub groups: Vec<FailureGroup>,
     pub css: &'static str,
     pub qps: &'static str,
 }
 
+#[derive(Debug, Serialize)]
+pub struct FailureGroup {
+    // Serialized FailureReason shared by the whole group
+    pub reason: String,
+    pub count: usize,
+    // Serialized CompileIds, without duplicates
+    pub compile_ids: Vec<String>,
+}
+
 #[derive(Debug)]
 pub enum Metadata<'e> {
     Empty(&'e EmptyMetadata),
@@ -688,6 +1220,77 @@ pub enum Metadata<'e> {
     DumpFile(&'e DumpFileMetadata),
     GuardAddedFast(&'e GuardAddedFastMetadata),
     SymbolicShapePropagateRealTensor(&'e SymbolicShapePropagateRealTensorMetadata),
+    AOTGraph(AOTGraphKind),
+    /// Name of an FX graph dump
+    FxGraph(&'static str),
+    DDPSplit(Option<&'e OptimizeDdpSplitChildMetadata>),
+}
+
+#[derive(Debug, Default)]
+pub struct AOTGraphs {
+    // Line of the last graph seen, only used to name the output directory if there's no compile id
+    pub lineno: usize,
+    pub joint: Option<String>,
+    pub forward: Option<String>,
+    pub backward: Option<String>,
+}
+
+/// Graphs DDPOptimizer logged for one compile id: the split graph plumbing the submodules
+/// together, and each submodule by name
+#[derive(Debug, Default)]
+pub struct DDPSplits {
+    pub lineno: usize,
+    pub split_graph: Option<String>,
+    pub children: Vec<(String, String)>,
+}
+
+/// Size and shape of an FX graph, see graph_stats.json
+#[derive(Debug, Serialize)]
+pub struct GraphStats {
+    /// Nodes besides the inputs and output
+    pub num_nodes: usize,
+    pub num_inputs: usize,
+    pub num_outputs: usize,
+    /// Most users of a single node
+    pub max_fan_out: usize,
+    /// How many nodes call each target, e.g. aten.mul.Tensor
+    pub op_counts: BTreeMap<String, usize>,
+    /// Of the dump without comments, equal for byte-identical graphs
+    pub hash: String,
+    /// Equal for graphs that only differ in the names of their nodes
+    pub structure_hash: String,
+}
+
+/// How a graph_region* or graph_partition* artifact split the graph of a compile id
+#[derive(Debug, Serialize)]
+pub struct GraphPartitionArtifact {
+    pub name: String,
+    pub description: &'static str,
+    pub num_nodes: usize,
+    pub regions: Vec<GraphRegion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphRegion {
+    pub name: String,
+    pub num_nodes: usize,
+    pub nodes: String,
+    /// e.g. why a partition was split off or can't be cudagraphed, if logged
+    pub reason: Option<String>,
+}
+
+/// Ids (into SymExprInfoIndex) of the symbolic expressions created by one compile id, in log order
+#[derive(Debug)]
+pub struct SymbolicExpressions {
+    pub lineno: usize,
+    pub node_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AOTGraphKind {
+    Joint,
+    Forward,
+    Backward,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -843,6 +1446,7 @@ pub struct DynamoGuard {
 
 #[derive(Debug, Serialize)]
 pub struct DynamoGuardsContext {
+    pub root: String,
     pub guards: Vec<DynamoGuard>,
     pub qps: &'static str,
 }
@@ -851,16 +1455,629 @@ pub struct DynamoGuardsContext {
 pub struct IndexContext {
     pub css: &'static str,
     pub javascript: &'static str,
-    pub directory: Vec<(String, Vec<OutputFile>)>,
+    /// Most severe first
+    pub findings: Vec<crate::findings::Finding>,
+    /// Records missing from the log, with the settings that log them
+    pub completeness_hints: Vec<crate::completeness::CompletenessHint>,
+    /// Parsing stopped at the first failure, see first_failure.html
+    pub has_first_failure: bool,
+    /// Line of the envelope the log was cut off in, if it was
+    pub truncated_line: Option<usize>,
+    pub directory: Vec<(String, Vec<OutputFile>, IndexCompileId)>,
     pub stack_trie_html: String,
     pub unknown_stack_trie_html: String,
     pub has_unknown_stack_trie: bool,
     pub num_breaks: usize,
     pub custom_header_html: String,
     pub has_chromium_events: bool,
+    pub has_compile_flamegraph: bool,
+    pub has_pass_timings: bool,
+    pub has_counters: bool,
+    /// Highest estimated peak memory first
+    pub memory_peaks: Vec<MemoryPeak>,
+    pub has_code_size: bool,
+    pub has_graph_partitions: bool,
+    pub has_graph_stats: bool,
+    /// Graphs produced by more than one compile id, see duplicate_graphs.html
+    pub num_duplicate_graphs: usize,
+    pub has_module_map: bool,
+    /// Most compiled first
+    pub hot_entry_points: Vec<crate::entry_points::EntryPoint>,
+    pub guard_latency: Option<GuardLatencySummary>,
+    pub cudagraph_skips: Vec<CudagraphSkip>,
+    pub num_cudagraph_skips: usize,
+    /// Asserts inserted by propagate_real_tensors outside of export
+    pub data_dependent_specializations: Vec<DataDependentSpecialization>,
+    pub num_data_dependent_specializations: usize,
+    /// Recompiles caused by guards failing, each with a guard_failure_<n>.md
+    pub guard_failures: Vec<crate::guard_failure::GuardFailure>,
+    /// Sources specialized to different values by different compiles
+    pub shape_churn: Vec<crate::shape_churn::ShapeChurn>,
+    pub distributed_info: Option<DistributedInfo>,
     pub qps: &'static str,
     pub has_inductor_provenance: bool,
     pub directory_names: Vec<String>,
+    pub has_overflow_compile_ids: bool,
+    pub num_overflow_compile_ids: usize,
+    pub summary: crate::summary::CompileSummary,
+    /// Whether any cache was looked up; the hit rate may well be 0
+    pub has_cache_stats: bool,
+    pub has_baseline: bool,
+    pub has_runtime_profile_correlation: bool,
+    pub has_index_pages: bool,
+    /// The pages after the first, when the directory is paginated
+    pub index_pages: Vec<IndexPageLink>,
+    pub index_page_of_json: String,
+    /// Largest payloads first
+    pub envelope_stats: Vec<EnvelopeTypeStats>,
+    /// Empty with `ParseConfig::deterministic`, as timings differ between runs
+    pub parser_timings: Vec<ParserTiming>,
+    /// Zero if the whole log was written by one thread
+    pub num_threads: usize,
+    pub num_overlapping_compiles: usize,
+}
+
+/// What the index shows about a compile id besides its artifacts, notably where it sits among the
+/// attempts of its frame.  Frames that were restarted are shown as a tree of attempts, with why
+/// each one restarted between them.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct IndexCompileId {
+    /// The frame, e.g. "[0/0]", if it has several attempts
+    pub frame: Option<String>,
+    pub num_attempts: usize,
+    pub attempt: u32,
+    /// Why the attempt before this one was restarted
+    pub restart_reasons: Vec<String>,
+    /// Whether this opens or closes the frame's list of attempts on its page
+    pub first_attempt: bool,
+    pub last_attempt: bool,
+    /// Training step the compilation started in, with `--steps`
+    pub step: Option<String>,
+    /// Nodes of the compile id's dynamo_output_graph
+    pub graph_nodes: Option<String>,
+    // Position of the frame in the index, shared by all of its attempts
+    #[serde(skip)]
+    pub frame_index: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IndexPageLink {
+    pub number: usize,
+    pub url: String,
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexPageContext {
+    pub css: &'static str,
+    pub number: usize,
+    pub num_pages: usize,
+    pub index_pages: Vec<IndexPageLink>,
+    pub directory: Vec<(String, Vec<OutputFile>, IndexCompileId)>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AOTPartitionContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub num_forward: usize,
+    pub num_backward: usize,
+    pub num_recomputed: usize,
+    pub num_unused: usize,
+    pub joint_graph_html: String,
+    pub forward_graph: String,
+    pub backward_graph: String,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelatedCompileId {
+    pub compile_id: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompiledAutogradContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    /// Whether this page is for the compiled autograd side of the link
+    pub is_compiled_autograd: bool,
+    pub related: Vec<RelatedCompileId>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphPartitionsCompileId {
+    pub compile_id: String,
+    pub artifacts: Vec<GraphPartitionArtifact>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphPartitionsContext {
+    pub css: &'static str,
+    pub compile_ids: Vec<GraphPartitionsCompileId>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DDPSplitChild {
+    pub name: String,
+    pub num_nodes: usize,
+    pub percent: String,
+    pub unbalanced: bool,
+    pub graph: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedProgramLine {
+    pub lineno: usize,
+    pub html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RangeConstraint {
+    pub symbol: String,
+    pub range: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedProgramContext {
+    pub root: String,
+    /// exported_program.txt, as it is named in the output
+    pub text_url: Option<String>,
+    pub css: &'static str,
+    // Opening <pre> tag carrying the syntect theme background
+    pub pre_open: String,
+    pub graph_lines: Vec<ExportedProgramLine>,
+    pub graph_signature: Option<String>,
+    pub range_constraints: Vec<RangeConstraint>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymExprLink {
+    pub id: u64,
+    pub result: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymExprNode {
+    pub id: u64,
+    pub result: String,
+    // Empty for unbacked symbols, which aren't computed from other expressions
+    pub method: String,
+    pub arguments: String,
+    pub inputs: Vec<SymExprLink>,
+    pub users: Vec<SymExprLink>,
+    pub user_stack_html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolicExpressionsContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub unbacked_symbols: Vec<SymExprLink>,
+    pub roots: Vec<SymExprLink>,
+    pub nodes: Vec<SymExprNode>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DDPSplitsContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub split_graph: String,
+    pub children: Vec<DDPSplitChild>,
+    pub unbalanced: bool,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PassTimingRow {
+    pub stage: String,
+    pub pass: String,
+    pub count: usize,
+    pub total_s: String,
+    pub mean_s: String,
+    pub max_s: String,
+    pub slowest_compile_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PassTimingsContext {
+    pub css: &'static str,
+    pub rows: Vec<PassTimingRow>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryBuffer {
+    pub name: String,
+    pub size: String,
+    pub start: usize,
+    pub end: usize,
+    // Position and length of the lifetime bar, as a percentage of the steps
+    pub left: String,
+    pub width: String,
+    pub live_at_peak: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryReportContext {
+    pub css: &'static str,
+    pub root: String,
+    pub compile_id: String,
+    pub peak_memory: String,
+    /// The step with the most bytes live, empty if there are no buffers
+    pub peak_step: String,
+    pub num_buffers: usize,
+    pub total_allocated: String,
+    pub largest: Vec<MemoryBuffer>,
+    /// By the step they are allocated at
+    pub buffers: Vec<MemoryBuffer>,
+    pub qps: &'static str,
+}
+
+/// A compile id's estimated peak memory, on index.html
+#[derive(Debug, Serialize)]
+pub struct MemoryPeak {
+    pub compile_id: String,
+    pub url: String,
+    pub peak_memory: String,
+}
+
+/// The values of the counters of one counter artifact, by their dotted names
+#[derive(Debug)]
+pub struct CounterSnapshot {
+    pub compile_id: Option<CompileId>,
+    /// The name of the artifact, e.g. dynamo_counters
+    pub source: String,
+    pub values: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CounterRow {
+    pub counter: String,
+    pub value: String,
+    /// Change since the previous snapshot of the same artifact, empty if none
+    pub delta: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileCounters {
+    pub compile_id: String,
+    pub source: String,
+    pub num_changed: usize,
+    pub rows: Vec<CounterRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GlobalCounterRow {
+    pub source: String,
+    pub counter: String,
+    /// The value in the last snapshot
+    pub value: String,
+    /// How many snapshots changed the counter
+    pub num_changes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountersContext {
+    pub css: &'static str,
+    pub global: Vec<GlobalCounterRow>,
+    pub compiles: Vec<CompileCounters>,
+    pub qps: &'static str,
+}
+
+/// Compile ids that produced the same graph
+#[derive(Debug, Serialize)]
+pub struct DuplicateGraphCluster {
+    /// e.g. dynamo_output_graph
+    pub graph: String,
+    pub kind: &'static str,
+    pub num_compile_ids: usize,
+    /// Comma separated compile ids, one entry per byte-identical dump
+    pub identical_compile_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGraphsContext {
+    pub css: &'static str,
+    pub clusters: Vec<DuplicateGraphCluster>,
+    pub qps: &'static str,
+}
+
+/// The nn.Module hierarchy of the pre-grad graph of a compile id
+#[derive(Debug, Serialize)]
+pub struct ModuleMapGraph {
+    pub directory_name: String,
+    pub tree_html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleMapContext {
+    pub css: &'static str,
+    pub graphs: Vec<ModuleMapGraph>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuardLatencyRow {
+    pub compile_id: String,
+    pub guard_count: String,
+    pub latency_us: String,
+    pub cumulative_latency_us: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CudagraphsContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub skip_reasons: Vec<String>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CudagraphSkip {
+    pub compile_id: String,
+    pub url: String,
+    pub reason: String,
+}
+
+/// An expression that couldn't be decided without real tensors, so it was specialized to its
+/// result and an assert was inserted into the graph
+#[derive(Debug, Serialize)]
+pub struct DataDependentSpecialization {
+    pub compile_id: String,
+    /// symbolic_guard_information.html of the expression
+    pub url: String,
+    pub expr: String,
+    pub result: String,
+}
+
+/// Totals shown on the landing page
+#[derive(Debug, Serialize)]
+pub struct GuardLatencySummary {
+    pub total_latency_us: String,
+    pub total_guard_count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuardLatencyContext {
+    pub css: &'static str,
+    pub rows: Vec<GuardLatencyRow>,
+    pub total_latency_us: String,
+    pub total_guard_count: u64,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BaselineComparisonContext {
+    pub css: &'static str,
+    pub rows: Vec<crate::summary::SummaryDelta>,
+    pub config_changes: Vec<crate::inductor_config::ConfigChange>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuntimeProfileCorrelationContext {
+    pub css: &'static str,
+    pub graphs: Vec<crate::profile::GraphCorrelation>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuntimeEstimationContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub rows: Vec<crate::runtime_estimation::RuntimeEstimationRow>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuardDiffContext {
+    pub root: String,
+    pub css: &'static str,
+    pub diff: crate::guard_diff::GuardDiff,
+    /// dynamo_guards.html of the previous compile
+    pub previous_guards_url: Option<String>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigOption {
+    pub key: String,
+    pub value: String,
+    /// Whether the previous compile id with a config had another value, or didn't log it
+    pub changed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InductorConfigContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub options: Vec<ConfigOption>,
+    pub qps: &'static str,
+}
+
+/// An artifact of a compile id, linked from its summary.html
+#[derive(Debug, Serialize)]
+pub struct SummaryArtifact {
+    pub name: String,
+    pub url: String,
+}
+
+/// Whether a cache artifact of a compile id was a hit, miss or bypass
+#[derive(Debug, Serialize)]
+pub struct SummaryCacheStatus {
+    pub artifact: String,
+    pub url: String,
+    pub status: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileIdSummaryContext {
+    pub root: String,
+    pub css: &'static str,
+    pub compile_id: String,
+    pub co_name: Option<String>,
+    /// e.g. /home/me/model.py:12
+    pub location: Option<String>,
+    pub failed: bool,
+    /// e.g. "Compiled" or "Failed with BackendCompilerFailed"
+    pub status: String,
+    pub fail_reason: Option<String>,
+    pub compile_time_s: Option<String>,
+    pub guard_count: Option<String>,
+    /// Nodes of the Dynamo graph
+    pub graph_nodes: Option<String>,
+    pub restart_reasons: Vec<String>,
+    pub recompile_reason: Option<String>,
+    pub cache: Vec<SummaryCacheStatus>,
+    pub artifacts: Vec<SummaryArtifact>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FirstFailureContext {
+    pub css: &'static str,
+    pub compile_id: String,
+    pub co_name: String,
+    /// e.g. /home/me/model.py:12
+    pub location: Option<String>,
+    pub fail_type: String,
+    pub fail_reason: String,
+    /// Line of user code the failure happened at
+    pub user_frame: Option<String>,
+    pub artifacts: Vec<OutputFile>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverflowCompileIdRow {
+    pub compile_id: String,
+    pub compile_time_s: String,
+    pub graph_op_count: String,
+    pub guard_count: String,
+    pub fail_type: String,
+    pub num_artifacts: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverflowCompileIdsContext {
+    pub css: &'static str,
+    pub max_compile_ids: usize,
+    pub rows: Vec<OverflowCompileIdRow>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeSizeRow {
+    pub compile_id: String,
+    pub total: String,
+    pub breakdown: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSizeRow {
+    pub name: String,
+    pub size: String,
+    pub percent: String,
+    pub breakdown: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSizeContext {
+    pub css: &'static str,
+    pub total_size: String,
+    pub types: Vec<TraceSizeRow>,
+    pub compile_ids: Vec<TraceSizeRow>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThreadsContext {
+    pub css: &'static str,
+    pub rows: Vec<crate::threads::TimelineRow>,
+    pub num_overlapping: usize,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeSizeContext {
+    pub css: &'static str,
+    pub treemap_css: &'static str,
+    pub total_size: String,
+    pub tiles: Vec<crate::code_size::TreemapTile>,
+    pub rows: Vec<CodeSizeRow>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchContext {
+    pub css: &'static str,
+    pub lookup_javascript: &'static str,
+    pub javascript: &'static str,
+    pub search_index_json: String,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrossRankSearchContext {
+    pub css: &'static str,
+    pub num_ranks: usize,
+    pub lookup_javascript: &'static str,
+    pub javascript: &'static str,
+    pub search_index_json: String,
+    pub qps: &'static str,
+}
+
+/// The compilations of one run on compare_runs.html
+#[derive(Debug, Serialize)]
+pub struct ComparedRun {
+    pub name: String,
+    pub url: String,
+    pub compiles: usize,
+    pub failures: usize,
+    pub compile_time_s: String,
+}
+
+/// One compilation of a run, in a row of compare_runs.html
+#[derive(Debug, Default, Serialize)]
+pub struct ComparedCompileCell {
+    pub present: bool,
+    pub compile_id: String,
+    pub url: Option<String>,
+    pub compile_time_s: String,
+    pub cache: String,
+    pub fail_type: Option<String>,
+}
+
+/// The compilations of the same frame and stack in each run
+#[derive(Debug, Serialize)]
+pub struct ComparedCompile {
+    pub frame: String,
+    pub stack: String,
+    /// The runs disagree on whether it was compiled, hit the cache or failed
+    pub differs: bool,
+    pub cells: Vec<ComparedCompileCell>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareRunsContext {
+    pub css: &'static str,
+    pub custom_header_html: String,
+    pub runs: Vec<ComparedRun>,
+    pub rows: Vec<ComparedCompile>,
+    pub qps: &'static str,
 }
 
 #[derive(Debug, Serialize)]
@@ -869,6 +2086,7 @@ pub struct ExportIndexContext {
     pub javascript: &'static str,
     pub directory: Vec<(String, Vec<OutputFile>)>,
     pub failures: Vec<ExportFailure>,
+    pub groups: Vec<ExportFailureGroup>,
     pub custom_header_html: String,
     pub num_failures: usize,
     pub success: bool,
@@ -898,9 +2116,50 @@ pub struct ProvenanceContext<'a> {
     pub js: &'a str,
     pub pre_grad_graph_content: String,
     pub post_grad_graph_content: String,
+    /// Of the first of `code_files`
     pub output_code_content: String,
+    pub has_multiple_code_files: bool,
+    /// inductor_output_code of the compile id, one per graph partition or subgraph
+    pub code_files: Vec<ProvenanceCodeFile>,
     pub aot_code_content: String,
+    /// For the first of `code_files`
     pub line_mappings_content: String,
+    /// For each of `code_files`
+    pub code_file_line_mappings_content: String,
+    /// inductor_provenance_tracking_kernel_stack_traces, or {} if it wasn't logged
+    pub kernel_stack_traces_content: String,
+    /// Whether the generated code has kernels to show in the kernel source pane
+    pub has_kernel_sources: bool,
+    /// The source of each kernel and its post-grad graph lines, as JSON
+    pub kernel_sources_content: String,
+    pub num_unmapped: usize,
+    pub diagnostics: ProvenanceDiagnostics,
+    /// The nn.Module hierarchy of the pre-grad graph, see `ModuleMap::tree_html`
+    pub module_tree_html: String,
+    pub has_module_tree: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvenanceCodeFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// Names in the provenance node mappings that couldn't be located in the graphs or generated
+/// code, so the provenance page can't highlight them
+#[derive(Debug, Default, Serialize)]
+pub struct ProvenanceDiagnostics {
+    pub unmapped_pre_grad_nodes: Vec<String>,
+    pub unmapped_post_grad_nodes: Vec<String>,
+    pub unmapped_kernels: Vec<String>,
+}
+
+impl ProvenanceDiagnostics {
+    pub fn num_unmapped(&self) -> usize {
+        self.unmapped_pre_grad_nodes.len()
+            + self.unmapped_post_grad_nodes.len()
+            + self.unmapped_kernels.len()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
@@ -908,6 +2167,8 @@ pub struct DivergenceFlags {
     pub cache: bool,
     pub collective: bool,
     pub tensor_meta: bool,
+    #[serde(default)]
+    pub graph_content: bool,
 }
 
 #[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
@@ -923,6 +2184,15 @@ pub struct Diagnostics {
     pub cache_groups: Vec<DivergenceGroup>,
     pub collective_groups: Vec<DivergenceGroup>,
     pub tensor_meta_groups: Vec<DivergenceGroup>,
+    #[serde(default)]
+    pub graph_content_divergence: Vec<GraphContentDivergence>,
+    #[serde(default)]
+    pub compile_time_skew: Option<CompileTimeSkew>,
+    #[serde(default)]
+    pub memory_analysis: Option<MemoryAnalysis>,
+    /// Ranks with logs that weren't parsed because of `--ranks` or `--sample-ranks`, e.g. "1, 2"
+    #[serde(default)]
+    pub skipped_ranks: String,
 }
 
 #[derive(Serialize)]
@@ -930,10 +2200,16 @@ pub struct MultiRankContext<'a> {
     pub css: &'a str,
     pub custom_header_html: &'a str,
     pub num_ranks: usize,
+    pub has_rank_stats: bool,
     pub ranks: Vec<String>,
+    pub rank_summaries: Vec<RankSummary>,
+    pub has_distributed_info: bool,
     pub qps: &'a str,
+    pub sortable_table_script: &'a str,
     pub has_chromium_events: bool,
     pub show_desync_warning: bool,
     pub compile_id_divergence: bool,
     pub diagnostics: Diagnostics,
+    /// Most severe first
+    pub findings: Vec<crate::findings::Finding>,
 }
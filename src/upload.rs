@@ -0,0 +1,35 @@
+// Uploads a report to S3 once it is written, enabled with the `upload` feature.
+//
+// Shells out to the AWS CLI rather than linking an SDK, so that uploads use whatever
+// credentials, profiles and endpoints the user already configured for it.
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context};
+
+/// Uploads the report in `dir` to `destination`, e.g. s3://bucket/reports/1234, returning the
+/// URL of its index.html
+pub fn upload_report(dir: &Path, destination: &str) -> anyhow::Result<String> {
+    let Some(location) = destination.strip_prefix("s3://") else {
+        bail!("Don't know how to upload to {destination}; expected s3://bucket/prefix");
+    };
+    let location = location.trim_end_matches('/');
+    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+    if bucket.is_empty() {
+        bail!("{destination} has no bucket");
+    }
+    let status = Command::new("aws")
+        .args(["s3", "cp", "--recursive", "--only-show-errors"])
+        .arg(dir)
+        .arg(format!("s3://{location}/"))
+        .status()
+        .context("Couldn't run the AWS CLI (aws) to upload the report")?;
+    if !status.success() {
+        bail!("Uploading the report to {destination} failed ({status})");
+    }
+    Ok(if prefix.is_empty() {
+        format!("https://{bucket}.s3.amazonaws.com/index.html")
+    } else {
+        format!("https://{bucket}.s3.amazonaws.com/{prefix}/index.html")
+    })
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use crate::types::ParseOutput;
+
+static LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\b(href|src)=(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static BASE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<base\s+href=(?:"([^"]*)"|'([^']*)')"#).unwrap());
+// e.g. https:, data:, mailto:
+static SCHEME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:").unwrap());
+
+/// Whether `link` is relative to the page it is on, rather than e.g. https://... or /...
+pub(crate) fn is_relative(link: &str) -> bool {
+    !link.starts_with('/') && !SCHEME.is_match(link)
+}
+
+/// `link` resolved against the directory `dir` of the report, e.g. ["-_0_0_0"], and put under
+/// `url_prefix`, or None if it isn't a relative link into the report
+pub(crate) fn resolve(link: &str, dir: &[&str], url_prefix: &str) -> Option<String> {
+    if link.is_empty() || link.starts_with(['#', '/', '{']) || SCHEME.is_match(link) {
+        return None;
+    }
+    let end = link.find(['?', '#']).unwrap_or(link.len());
+    let (path, rest) = link.split_at(end);
+    let mut segments: Vec<&str> = dir.to_vec();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    let is_dir = path.is_empty() || path.ends_with('/') || path.ends_with("..");
+    let mut url = format!("{url_prefix}{}", segments.join("/"));
+    if is_dir && !segments.is_empty() {
+        url.push('/');
+    }
+    url.push_str(rest);
+    Some(url)
+}
+
+/// Replaces each link of the page at `page` (relative to the root of the report) with what
+/// `map_link` returns for it and the directory it's relative to, e.g. ["-_0_0_0"], if anything.
+/// Links of pages with a `<base>` are relative to it, as browsers resolve them.
+pub(crate) fn map_links(
+    html: &str,
+    page: &Path,
+    map_link: impl Fn(&str, &[&str]) -> Option<String>,
+) -> String {
+    let page_dir: Vec<String> = page
+        .parent()
+        .into_iter()
+        .flat_map(|dir| dir.iter())
+        .map(|segment| segment.to_string_lossy().to_string())
+        .collect();
+    let page_dir: Vec<&str> = page_dir.iter().map(String::as_str).collect();
+    let base = BASE
+        .captures(html)
+        .and_then(|c| c.get(1).or(c.get(2)))
+        .and_then(|base| resolve(base.as_str(), &page_dir, ""));
+    let base_dir: Vec<&str> = match &base {
+        Some(base) => base.split('/').filter(|s| !s.is_empty()).collect(),
+        None => page_dir.clone(),
+    };
+    LINK.replace_all(html, |c: &Captures| {
+        let (link, quote) = match c.get(2) {
+            Some(link) => (link.as_str(), '"'),
+            None => (c.get(3).map_or("", |l| l.as_str()), '\''),
+        };
+        // The base itself is relative to the page
+        let dir = if c
+            .get(0)
+            .is_some_and(|m| html[..m.start()].trim_end().ends_with("<base"))
+        {
+            &page_dir
+        } else {
+            &base_dir
+        };
+        match map_link(link, dir) {
+            Some(url) => format!("{}={quote}{url}{quote}", &c[1]),
+            None => c[0].to_string(),
+        }
+    })
+    .to_string()
+}
+
+/// Makes the relative links of the page at `page` (relative to the root of the report) absolute
+/// under `url_prefix`, which ends with a slash
+pub fn root_links(html: &str, page: &Path, url_prefix: &str) -> String {
+    map_links(html, page, |link, dir| resolve(link, dir, url_prefix))
+}
+
+/// `root_links` for every page of `output`
+pub fn root_output_links(output: ParseOutput, url_prefix: &str) -> ParseOutput {
+    output
+        .into_iter()
+        .map(|(path, content)| {
+            if path.extension().is_some_and(|e| e == "html") {
+                let content = root_links(&content, &path, url_prefix);
+                (path, content)
+            } else {
+                (path, content)
+            }
+        })
+        .collect()
+}
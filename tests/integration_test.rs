@@ -1,7 +1,7 @@
 use assert_cmd::Command;
 use predicates::boolean::PredicateBooleanExt;
 use predicates::str;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
@@ -13,6 +13,31 @@ fn prefix_exists(map: &HashMap<PathBuf, String>, prefix: &str) -> bool {
         .any(|key| key.to_str().map_or(false, |s| s.starts_with(prefix)))
 }
 
+fn md5_hex(bytes: &[u8]) -> String {
+    use md5::{Digest, Md5};
+    Md5::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+// The envelope of an artifact with `payload`, which is logged on the lines after it
+fn artifact_envelope(
+    thread: u32,
+    frame_id: u32,
+    name: &str,
+    encoding: &str,
+    payload: &str,
+) -> String {
+    format!(
+        "V1206 15:18:21.452000 {thread} torch/_logging/structured.py:27] \
+         {{\"artifact\": {{\"name\": \"{name}\", \"encoding\": \"{encoding}\"}}, \
+         \"frame_id\": {frame_id}, \"frame_compile_id\": 0, \"attempt\": 0, \
+         \"has_payload\": \"{}\"}}\n",
+        md5_hex(payload.as_bytes())
+    )
+}
+
 #[test]
 fn test_parse_simple() {
     let expected_files = [
@@ -312,9 +337,9 @@ fn test_parse_chromium_event() {
 #[test]
 fn test_cache_hit_miss() {
     let expected_files = [
-        "-_1_0_0/fx_graph_cache_miss_33.json",
-        "-_1_0_0/fx_graph_cache_miss_9.json",
-        "-_1_0_0/fx_graph_cache_hit_20.json",
+        "-_1_0_0/fx_graph_cache_miss_0.json",
+        "-_1_0_0/fx_graph_cache_miss_1.json",
+        "-_1_0_0/fx_graph_cache_hit_0.json",
         "compile_directory.json",
         "index.html",
     ];
@@ -337,6 +362,63 @@ fn test_cache_hit_miss() {
     }
 }
 
+#[test]
+fn test_crlf_and_prefixed_lines() {
+    // CRLF line endings, and glog prefixes that don't start the line, parse like the original
+    let original = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let mangled: String = original
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            if i == 0 {
+                format!("[rank0]: {l}\r\n")
+            } else {
+                format!("{l}\r\n")
+            }
+        })
+        .collect();
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("simple_crlf.log");
+    fs::write(&path, mangled).unwrap();
+
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let expected: HashMap<PathBuf, String> =
+        tlparse::parse_path(&PathBuf::from("tests/inputs/simple.log"), &config)
+            .unwrap()
+            .into_iter()
+            .collect();
+    let output: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let mut expected_files: Vec<&PathBuf> = expected.keys().collect();
+    let mut output_files: Vec<&PathBuf> = output.keys().collect();
+    expected_files.sort();
+    output_files.sort();
+    assert_eq!(expected_files, output_files);
+}
+
+#[test]
+fn test_payload_verification() {
+    // This log has payloads that don't match their md5 hash
+    let path = Path::new("tests/inputs/multi_rank_runtime/dedicated_log_torch_trace_rank_3.log")
+        .to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(tlparse::parse_path(&path, &config).is_err());
+    let config = tlparse::ParseConfig {
+        strict: true,
+        verify_payloads: tlparse::PayloadVerification::None,
+        ..Default::default()
+    };
+    assert!(tlparse::parse_path(&path, &config).is_ok());
+}
+
 #[test]
 fn test_export_report() {
     let expected_files = [
@@ -367,6 +449,95 @@ fn test_export_report() {
     }
 }
 
+#[test]
+fn test_exported_program_html() {
+    let path = Path::new("tests/inputs/export.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        export: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let program = &map[&PathBuf::from("-_-_-_-/exported_program_0.html")];
+    // Graph lines are anchored, the signature is one spec per line, and constraints per symbol
+    assert!(program.contains(r#"id="L5""#));
+    assert!(program.contains("\n        InputSpec(kind="));
+    assert!(program.contains(r#"<tr id="sym-s0">"#));
+    // The text is linked by the name it is written under
+    assert!(program.contains(r#"<a href="exported_program_0.txt">text</a>"#));
+    assert!(map.contains_key(Path::new("-_-_-_-/exported_program_0.txt")));
+    // Guard failures link to the symbols they constrain
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("exported_program_0.html#sym-u0"));
+    assert!(index.contains(r#"<a href="-_-_-_-/exported_program_0.html">link</a>"#));
+
+    let config = tlparse::ParseConfig {
+        strict: true,
+        export: true,
+        dedup_payloads: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let program = &map[&PathBuf::from("-_-_-_-/exported_program_0.html")];
+    let text = &program[program.find(r#"<a href="../blobs/"#).unwrap() + 12..];
+    assert!(map.contains_key(Path::new(&text[..text.find('"').unwrap()])));
+}
+
+#[test]
+fn test_export_constraint_suggestions() {
+    let config = tlparse::ParseConfig {
+        strict: true,
+        export: true,
+        ..Default::default()
+    };
+    // Data dependent error on an unbacked symbol: suggest a torch._check on the user's local
+    let path = Path::new("tests/inputs/export.log").to_path_buf();
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<pre>torch._check(a != 0)</pre>"));
+    assert!(index.contains("u0 comes from `a = x.item()`"));
+
+    // Guard specializing a backed size: suggest marking the dimension static
+    let path = Path::new("tests/inputs/export_guard_added.log").to_path_buf();
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let failures: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("export_failures.json")]).unwrap();
+    let suggestion = &failures["failure_types"][0]["failures"][0]["suggestions"][0];
+    assert_eq!(suggestion["kind"], "dynamic_shapes");
+    assert_eq!(suggestion["code"], "Dim.STATIC");
+}
+
+#[test]
+fn test_symbolic_expressions_page() {
+    // Symbolic expressions are rendered outside of export mode too
+    let path = Path::new("tests/inputs/export.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let (_, page) = map
+        .iter()
+        .find(|(path, _)| {
+            path.to_string_lossy()
+                .starts_with("-_-_-_-/symbolic_expressions")
+        })
+        .expect("symbolic_expressions page not found");
+    // u0 is an unbacked symbol, used by the negation that starts the chain to the final guard
+    assert!(page.contains(r##"<li><a href="#node-140536506030432"><code>u0</code></a></li>"##));
+    assert!(page.contains(r##"Users: <a href="#node-140536506789616"><code>-u0</code></a>"##));
+    assert!(page
+        .contains(r##"<a href="#node-140538758781280"><code>Eq((((-u0)//3)) + 5, 0)</code></a>"##));
+}
+
 #[test]
 fn test_export_guard_report() {
     let expected_files = [
@@ -397,13 +568,48 @@ fn test_export_guard_report() {
     }
 }
 
+#[test]
+fn test_export_failures_grouped() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/export_fake_kernels.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        export: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let failures: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("export_failures.json")])?;
+    assert_eq!(failures["success"], false);
+    assert_eq!(failures["num_failures"], 4);
+    let missing = &failures["failure_types"][0];
+    assert_eq!(missing["failure_type"], "Missing Fake Kernel");
+    assert_eq!(missing["count"], 3);
+    assert_eq!(
+        missing["ops"],
+        serde_json::json!([
+            {"op": "mylib.foo.default", "count": 2},
+            {"op": "mylib.bar.default", "count": 1},
+        ])
+    );
+    assert_eq!(
+        missing["failures"][0]["reason"],
+        "torch.ops.mylib.foo.default is missing a fake kernel implementation"
+    );
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<h2>Missing Fake Kernel (3)</h2>"));
+    assert!(
+        index.contains("<tr> <td><code>torch.ops.mylib.foo.default</code></td> <td>2</td> </tr>")
+    );
+    Ok(())
+}
+
 #[test]
 fn test_provenance_tracking_aot_cuda() {
     let expected_files = [
         "-_-_-_-/before_pre_grad_graph_0.txt",
-        "-_-_-_-/after_post_grad_graph_6.txt",
+        "-_-_-_-/after_post_grad_graph_0.txt",
         "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_12.json",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_0.json",
     ];
     // Read the test file
     let path = Path::new("tests/inputs/inductor_provenance_aot_cuda_log.txt").to_path_buf();
@@ -501,9 +707,9 @@ fn test_provenance_tracking_aot_cuda() {
 fn test_provenance_tracking_aot_debug_handle() {
     let expected_files = [
         "-_-_-_-/before_pre_grad_graph_0.txt",
-        "-_-_-_-/after_post_grad_graph_6.txt",
+        "-_-_-_-/after_post_grad_graph_0.txt",
         "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_10.json",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_0.json",
     ];
 
     let path = Path::new("tests/inputs/inductor_provenance_aot_debug_handle_log.txt").to_path_buf();
@@ -687,9 +893,9 @@ fn test_provenance_tracking_aot_debug_handle() {
 fn test_provenance_tracking_aot_log() {
     let expected_files = [
         "-_-_-_-/before_pre_grad_graph_0.txt",
-        "-_-_-_-/after_post_grad_graph_6.txt",
+        "-_-_-_-/after_post_grad_graph_0.txt",
         "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_11.json",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_0.json",
     ];
 
     let path = Path::new("tests/inputs/inductor_provenance_aot_log.txt").to_path_buf();
@@ -863,9 +1069,9 @@ fn test_provenance_tracking_aot_log() {
 fn test_provenance_tracking_aot_log_old() {
     let expected_files = [
         "-_-_-_-/inductor_pre_grad_graph_0.txt",
-        "-_-_-_-/inductor_post_grad_graph_8.txt",
+        "-_-_-_-/inductor_post_grad_graph_0.txt",
         "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_11.json",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_0.json",
     ];
 
     let path = Path::new("tests/inputs/inductor_provenance_aot_log_old.txt").to_path_buf();
@@ -1010,10 +1216,10 @@ fn test_provenance_tracking_aot_log_old() {
 #[test]
 fn test_provenance_tracking_jit_cuda() {
     let expected_files = [
-        "-_0_0_0/before_pre_grad_graph_1.txt",
-        "-_0_0_0/after_post_grad_graph_8.txt",
+        "-_0_0_0/before_pre_grad_graph_0.txt",
+        "-_0_0_0/after_post_grad_graph_0.txt",
         "provenance_tracking_-_0_0_0.html",
-        "-_0_0_0/inductor_provenance_tracking_node_mappings_14.json",
+        "-_0_0_0/inductor_provenance_tracking_node_mappings_0.json",
     ];
 
     let path = Path::new("tests/inputs/inductor_provenance_jit_cuda_log.txt").to_path_buf();
@@ -1204,10 +1410,10 @@ fn test_provenance_tracking_jit_cuda() {
 #[test]
 fn test_provenance_tracking_jit_log() {
     let expected_files = [
-        "-_0_0_0/before_pre_grad_graph_1.txt",
-        "-_0_0_0/after_post_grad_graph_8.txt",
+        "-_0_0_0/before_pre_grad_graph_0.txt",
+        "-_0_0_0/after_post_grad_graph_0.txt",
         "provenance_tracking_-_0_0_0.html",
-        "-_0_0_0/inductor_provenance_tracking_node_mappings_13.json",
+        "-_0_0_0/inductor_provenance_tracking_node_mappings_0.json",
     ];
 
     let path = Path::new("tests/inputs/inductor_provenance_jit_log.txt").to_path_buf();
@@ -1380,10 +1586,10 @@ fn test_provenance_tracking_jit_log() {
 #[test]
 fn test_provenance_tracking_jit_debug_handle() {
     let expected_files = [
-        "-_0_0_0/before_pre_grad_graph_1.txt",
-        "-_0_0_0/after_post_grad_graph_11.txt",
+        "-_0_0_0/before_pre_grad_graph_0.txt",
+        "-_0_0_0/after_post_grad_graph_0.txt",
         "provenance_tracking_-_0_0_0.html",
-        "-_0_0_0/inductor_provenance_tracking_node_mappings_14.json",
+        "-_0_0_0/inductor_provenance_tracking_node_mappings_0.json",
     ];
 
     let path = Path::new("tests/inputs/inductor_provenance_jit_debug_handle_log.txt").to_path_buf();
@@ -1634,6 +1840,133 @@ fn test_all_ranks_basic() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_cross_rank_search() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_content = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(landing_content.contains(r#"<a href="cross_rank_search.html">"#));
+    let search = fs::read_to_string(out_dir.join("cross_rank_search.html"))?;
+    let index_json = search
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("const SEARCH_INDEX = "))
+        .and_then(|l| l.strip_suffix(';'))
+        .unwrap();
+    let index: serde_json::Value = serde_json::from_str(index_json)?;
+    // Ranks 0 and 2 use the broken backend, and their failures are merged into one entry
+    let failure = index["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["kind"] == "failure" && e["text"].as_str().unwrap().contains("broken backend"))
+        .unwrap();
+    let ranks: Vec<u64> = failure["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["rank"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ranks, [0, 2]);
+    assert_eq!(
+        failure["hits"][1]["url"],
+        "rank_2/-_0_0_0/compilation_metrics_0.html"
+    );
+    // Artifacts and compile ids are left to each rank's search
+    assert!(index["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| ["guard", "kernel", "failure"].contains(&e["kind"].as_str().unwrap())));
+    Ok(())
+}
+
+#[test]
+fn test_compare_runs() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    // Ranks 0 and 2 compile the same frame, rank 3 other ones
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("--runs")
+        .args([0, 2, 3].map(|r| input_dir.join(format!("dedicated_log_torch_trace_rank_{r}.log"))))
+        .arg("--compare-runs")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    for run in 0..3 {
+        assert!(out_dir.join(format!("run_{run}/index.html")).exists());
+    }
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(
+        landing.contains(r#"<a href="run_2/index.html">dedicated_log_torch_trace_rank_3.log</a>"#)
+    );
+    // The failed compile of `fn` is on one row for the first two runs
+    let row = landing
+        .split("<tr id=\"compile-")
+        .find(|row| row.contains("<code>fn ("))
+        .unwrap();
+    assert!(row.contains(r#"<a href="run_0/-_0_0_0/compilation_metrics_0.html">[0/0]</a>"#));
+    assert!(row.contains(r#"<a href="run_1/-_0_0_0/compilation_metrics_0.html">[0/0]</a>"#));
+    assert_eq!(row.matches("BackendCompilerFailed").count(), 2);
+    assert!(row.contains("not compiled"));
+    assert!(row.contains(r#"class="differs""#));
+
+    // Lines moved by an edit at the top of the file still align by file and function
+    let log = fs::read_to_string(input_dir.join("dedicated_log_torch_trace_rank_0.log"))?;
+    let mut shifted = String::new();
+    let mut rest = log.as_str();
+    while let Some(i) = ["\"line\": ", "\"co_firstlineno\": "]
+        .iter()
+        .filter_map(|key| rest.find(key))
+        .min()
+    {
+        let start = i + rest[i..].find(' ').unwrap() + 1;
+        let end = start + rest[start..].find(|c: char| !c.is_ascii_digit()).unwrap();
+        let line: u64 = rest[start..end].parse()?;
+        shifted.push_str(&rest[..start]);
+        shifted.push_str(&(line + 5).to_string());
+        rest = &rest[end..];
+    }
+    shifted.push_str(rest);
+    let shifted_path = temp_dir.path().join("shifted.log");
+    fs::write(&shifted_path, shifted)?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("--runs")
+        .arg(input_dir.join("dedicated_log_torch_trace_rank_0.log"))
+        .arg(&shifted_path)
+        .arg("--compare-runs")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    let row = landing
+        .split("<tr id=\"compile-")
+        .find(|row| row.contains("<code>fn ("))
+        .unwrap();
+    assert!(row.contains(r#"<a href="run_0/-_0_0_0/compilation_metrics_0.html">[0/0]</a>"#));
+    assert!(row.contains(r#"<a href="run_1/-_0_0_0/compilation_metrics_0.html">[0/0]</a>"#));
+    assert!(!row.contains("not compiled"));
+    assert!(!landing.contains("not compiled"));
+    Ok(())
+}
+
 #[test]
 fn test_all_ranks_messy_input() -> Result<(), Box<dyn std::error::Error>> {
     let input_dir = PathBuf::from("tests/inputs/multi_rank_messy_input");
@@ -1771,7 +2104,7 @@ fn test_all_ranks_chromium_events_combined() -> Result<(), Box<dyn std::error::E
         .filter_map(|event| event.get("pid").and_then(|v| v.as_u64()))
         .collect();
 
-    let expected_pids: std::collections::HashSet<u64> = [0, 2, 3].iter().cloned().collect();
+    let expected_pids: std::collections::HashSet<u64> = [0, 1, 2, 3].iter().cloned().collect();
     assert_eq!(pids, expected_pids);
 
     // verify each rank-specific chromium_events.json file
@@ -1806,7 +2139,8 @@ fn test_all_ranks_chromium_events_combined() -> Result<(), Box<dyn std::error::E
 
 #[test]
 fn test_all_ranks_chromium_events_sparse() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_in_dir = tempdir()?;
+    let input_dir = temp_in_dir.path();
     let temp_out_dir = tempdir()?;
     let out_dir = temp_out_dir.path();
 
@@ -1828,6 +2162,11 @@ fn test_all_ranks_chromium_events_sparse() -> Result<(), Box<dyn std::error::Err
         input_dir.join("dedicated_log_torch_trace_rank_2.log"),
     )?;
 
+    fs::copy(
+        "tests/inputs/multi_rank_logs/dedicated_log_torch_trace_rank_3.log",
+        input_dir.join("dedicated_log_torch_trace_rank_3.log"),
+    )?;
+
     let mut cmd = Command::cargo_bin("tlparse")?;
     cmd.arg(input_dir)
         .arg("--all-ranks-html")
@@ -1956,6 +2295,42 @@ fn test_no_compile_id_divergence() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+// Ranks whose memory plan estimates a different peak for a graph are flagged
+#[test]
+fn test_multi_rank_peak_memory() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_in = tempdir()?;
+    let log = fs::read_to_string("tests/inputs/memory_planning.log")?;
+    fs::write(
+        temp_in.path().join("dedicated_log_torch_trace_rank_0.log"),
+        &log,
+    )?;
+    // Rank 1 plans a larger peak for [0/0]; the payload hash isn't checked without --strict
+    fs::write(
+        temp_in.path().join("dedicated_log_torch_trace_rank_1.log"),
+        log.replace("12582912", "16777216"),
+    )?;
+    let temp_out = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(temp_in.path())
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_out.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing = fs::read_to_string(temp_out.path().join("index.html"))?;
+    assert!(landing.contains("<h3>Peak Memory Analysis</h3>"));
+    assert!(landing.contains("1 graph(s) have diverging estimates."));
+    // A tie between ranks goes to the smaller estimate
+    assert!(landing.contains("<td>-_0_0_0</td>\n    <td>12.0 MiB</td>\n    <td>4.0 MiB</td>\n    <td><span class=\"diverging-memory\">Rank 1: 16.0 MiB</span></td>"));
+    assert!(
+        landing.contains("<td>-_1_0_0</td>\n    <td>3.0 KiB</td>\n    <td>0 B</td>\n    <td></td>")
+    );
+    Ok(())
+}
+
 // Detect diverging cache hit/miss patterns: should raise warning
 #[test]
 fn test_diverging_cache_events_warning() -> Result<(), Box<dyn std::error::Error>> {
@@ -2177,7 +2552,8 @@ fn test_collective_schedule_with_divergence() -> Result<(), Box<dyn std::error::
 #[test]
 fn test_runtime_estimation_parsing() -> Result<(), Box<dyn std::error::Error>> {
     let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
-    let out_dir = input_dir.join("out");
+    let temp_out = tempdir()?;
+    let out_dir = temp_out.path();
 
     Command::cargo_bin("tlparse")?
         .arg(&input_dir)
@@ -2365,3 +2741,3512 @@ fn test_tensor_meta_divergence_groups() -> Result<(), Box<dyn std::error::Error>
 
     Ok(())
 }
+
+#[test]
+fn test_template_dir_override() -> Result<(), Box<dyn std::error::Error>> {
+    let template_dir = tempdir()?;
+    fs::write(
+        template_dir.path().join("index.html"),
+        "<html><body><h1>Custom report</h1>{{ for compile_directory in directory }}<p>{compile_directory.0}</p>{{ endfor }}</body></html>",
+    )?;
+    let config = tlparse::ParseConfig {
+        strict: true,
+        template_overrides: tlparse::load_template_overrides(template_dir.path())?,
+        ..Default::default()
+    };
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert!(index_html.contains("<h1>Custom report</h1>"));
+    assert!(index_html.contains("<p>[0/0]</p>"));
+    // Templates that were not overridden still use the built-in version
+    assert!(map[&PathBuf::from("failures_and_restarts.html")].contains("Failures and Restarts"));
+    Ok(())
+}
+
+#[test]
+fn test_template_dir_rejects_unknown_template() -> Result<(), Box<dyn std::error::Error>> {
+    let template_dir = tempdir()?;
+    fs::write(template_dir.path().join("indx.html"), "<html></html>")?;
+    let temp_out = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/simple.log")
+        .arg("--template-dir")
+        .arg(template_dir.path())
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_out.path())
+        .arg("--no-browser");
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("Unknown template indx.html"));
+
+    fs::remove_file(template_dir.path().join("indx.html"))?;
+    fs::write(template_dir.path().join("index.html"), "{{ if }}")?;
+    let err = tlparse::load_template_overrides(template_dir.path()).unwrap_err();
+    assert!(err.to_string().contains("Invalid template"));
+    Ok(())
+}
+
+#[test]
+fn test_theme() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())?
+            .into_iter()
+            .collect();
+    assert!(map[&PathBuf::from("theme.css")].contains("--bg: #ffffff;"));
+    assert!(
+        map[&PathBuf::from("index.html")].contains(r#"<link rel="stylesheet" href="theme.css">"#)
+    );
+
+    let css_file = tempdir()?;
+    let css_path = css_file.path().join("extra.css");
+    fs::write(&css_path, "body { font-size: 20px; }")?;
+    let temp_dir = tempdir()?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&path)
+        .arg("--theme")
+        .arg("dark")
+        .arg("--theme-css")
+        .arg(&css_path)
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_dir.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+    let theme = fs::read_to_string(temp_dir.path().join("theme.css"))?;
+    assert!(theme.contains("--bg: #1e1e1e;"));
+    assert!(theme.ends_with("body { font-size: 20px; }\n"));
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&path)
+        .arg("--theme")
+        .arg("sepia")
+        .arg("--no-browser");
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("unknown theme sepia"));
+    Ok(())
+}
+
+#[test]
+fn test_search_index() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    assert!(map.contains_key(&PathBuf::from("search.html")));
+    let index: serde_json::Value = serde_json::from_str(&map[&PathBuf::from("search_index.json")])?;
+    let entries = index["entries"].as_array().unwrap();
+
+    // Kernels link to the output code of the compile id that produced them
+    let ids = index["index"]["triton_poi_fused_baz_gelu_native_dropout_1"]
+        .as_array()
+        .unwrap();
+    assert_eq!(ids.len(), 1);
+    let kernel = &entries[ids[0].as_u64().unwrap() as usize];
+    assert_eq!(kernel["kind"], "kernel");
+    assert_eq!(kernel["compile_id"], "[0/0]");
+    assert!(kernel["url"]
+        .as_str()
+        .unwrap()
+        .starts_with("-_0_0_0/inductor_output_code_"));
+
+    // Every artifact of a compile id can be found by the compile id
+    let artifacts = index["index"]["0/0"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|id| entries[id.as_u64().unwrap() as usize]["kind"] == "artifact")
+        .count();
+    assert!(artifacts > 5);
+    Ok(())
+}
+
+#[test]
+fn test_failures_grouped_by_reason() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let failures = &map[&PathBuf::from("failures_and_restarts.html")];
+    let (grouped, chronological) = failures.split_once("Chronological list").unwrap();
+    // Both restarts have the same reason, so they are a single group listing both compile ids
+    assert_eq!(grouped.matches("skip function graph_break").count(), 1);
+    assert!(grouped.contains("<td> 2 </td>"));
+    assert!(grouped.contains(">[0/0_1]</a>"));
+    assert!(grouped.contains(">[1/0_1]</a>"));
+    assert_eq!(
+        chronological.matches("skip function graph_break").count(),
+        2
+    );
+    Ok(())
+}
+
+#[test]
+fn test_stack_trie_weights() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index_html = &map[&PathBuf::from("index.html")];
+    // The common prefix of all stacks is folded away
+    assert!(index_html.contains("more frames</summary>"));
+    // Branches are annotated with how many compilations happened below them
+    assert!(index_html.contains("<span class='weight'>[2 compiles, "));
+    assert!(index_html.contains("<span class='weight'>[1 compile, "));
+    Ok(())
+}
+
+#[test]
+fn test_compile_flamegraph() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let folded = &map[&PathBuf::from("compile_flamegraph.folded")];
+    // One line per compilation: user stack, then the compile id, then compile time in ms
+    assert_eq!(folded.lines().count(), 3);
+    for line in folded.lines() {
+        let (stack, ms) = line.rsplit_once(' ').unwrap();
+        assert!(stack.starts_with("<module> ("));
+        assert!(stack.ends_with("/0]"));
+        ms.parse::<u64>()?;
+    }
+    let svg = &map[&PathBuf::from("compile_flamegraph.svg")];
+    assert!(svg.contains("<svg"));
+    assert!(svg.contains("test_graph_break_compilation_metrics"));
+    assert!(map[&PathBuf::from("index.html")].contains("compile_flamegraph.svg"));
+    Ok(())
+}
+
+#[test]
+fn test_pass_timings() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/pass_timings.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    // The raw artifacts are still dumped per compile id
+    assert!(map.keys().any(
+        |p| p.starts_with("-_1_0_0") && p.to_string_lossy().contains("post_grad_pass_timings")
+    ));
+    let pass_timings = &map[&PathBuf::from("pass_timings.html")];
+    // Sorted by total time, with the slowest compile id of each pass
+    let post_grad = pass_timings
+        .find("<td> post_grad </td> <td> <code>pattern_matcher</code> </td> <td> 2 </td> <td> 4.7500 </td> <td> 2.3750 </td> <td> 3.5000 </td> <td> [1/0] </td>")
+        .unwrap();
+    let joint_graph = pass_timings
+        .find("<td> joint_graph </td> <td> <code>pattern_matcher</code> </td>")
+        .unwrap();
+    assert!(post_grad < joint_graph);
+    assert!(map[&PathBuf::from("index.html")].contains("pass_timings.html"));
+    Ok(())
+}
+
+#[test]
+fn test_counters() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/counters.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let counters = &map[&PathBuf::from("counters.html")];
+    // Nested categories are flattened, with the final value of each counter
+    assert!(counters.contains("<td> dynamo_counters </td> <td> <code>stats.calls_captured</code> </td> <td> 7 </td> <td> 2 snapshot(s) </td>"));
+    // Each snapshot shows the change since the one before it
+    assert!(counters
+        .contains("<summary>[1/0] <code>dynamo_counters</code>: 3 counter(s) changed</summary>"));
+    assert!(counters
+        .contains("<td> <code>stats.calls_captured</code> </td> <td> 7 </td> <td> +4 </td>"));
+    assert!(map[&PathBuf::from("index.html")].contains("counters.html"));
+    Ok(())
+}
+
+#[test]
+fn test_memory_report() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/memory_planning.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let report = &map[&PathBuf::from("-_0_0_0/memory_report_0.html")];
+    assert!(report.contains("estimated a peak memory of <b>12.0 MiB</b> at step 1"));
+    // Largest first, highlighted when live at the peak
+    let buf1 = report
+        .find(r#"<tr class="live-at-peak"> <td> <code>buf1</code> </td> <td> 8.0 MiB </td> <td> steps 1-3 </td>"#)
+        .unwrap();
+    let buf2 = report
+        .find("<tr> <td> <code>buf2</code> </td> <td> 1.0 MiB </td> <td> steps 3-4 </td>")
+        .unwrap();
+    assert!(buf1 < buf2);
+    // Without a logged peak, it is computed from the buffer lifetimes
+    let index = &map[&PathBuf::from("index.html")];
+    let first = index
+        .find(r#"<a href="-_0_0_0/memory_report_0.html">[0/0]</a>: 12.0 MiB"#)
+        .unwrap();
+    let second = index
+        .find(r#"<a href="-_1_0_0/memory_report_0.html">[1/0]</a>: 3.0 KiB"#)
+        .unwrap();
+    assert!(first < second);
+    // With the field names of torch/_inductor/memory.py; buffers live past usize::MAX are left out
+    let report = &map[&PathBuf::from("-_2_0_0/memory_report_0.html")];
+    assert!(report.contains("estimated a peak memory of <b>512 B</b> at step 0"));
+    assert!(report.contains("for the 1 buffer(s) it planned"));
+    assert!(!report.contains("<code>buf1</code>"));
+    assert!(report.contains(r#"href="../theme.css""#));
+    Ok(())
+}
+
+#[test]
+fn test_aot_partition() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/aot_partition.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let (_, partition) = map
+        .iter()
+        .find(|(p, _)| p.starts_with("-_0_0_0") && p.to_string_lossy().contains("aot_partition"))
+        .unwrap();
+    assert!(partition.contains("forward only (1)"));
+    assert!(partition.contains("backward only (4)"));
+    assert!(partition.contains("recomputed in the backward (1)"));
+    assert!(partition.contains(r#"<span class="partition-forward">        sin: "#));
+    assert!(partition.contains(r#"<span class="partition-recomputed">        mul: "#));
+    Ok(())
+}
+
+#[test]
+fn test_code_size() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let code_size: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("code_size.json")])?;
+    let compile_ids = code_size["compile_ids"].as_array().unwrap();
+    assert!(!compile_ids.is_empty());
+    let total: u64 = compile_ids
+        .iter()
+        .map(|c| c["total_bytes"].as_u64().unwrap())
+        .sum();
+    assert!(total > 0);
+    assert_eq!(code_size["total_bytes"].as_u64(), Some(total));
+    assert!(
+        compile_ids[0]["artifacts"]["inductor_output_code"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+    let html = &map[&PathBuf::from("code_size.html")];
+    assert!(html.contains(&format!(
+        r#"href="index.html#{}""#,
+        compile_ids[0]["compile_id"].as_str().unwrap()
+    )));
+    assert!(map[&PathBuf::from("index.html")].contains("code_size.html"));
+    Ok(())
+}
+
+#[test]
+fn test_compilation_metrics_extra_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let (_, metrics) = map
+        .iter()
+        .find(|(p, _)| {
+            p.starts_with("-_0_0_1") && p.to_string_lossy().contains("compilation_metrics")
+        })
+        .unwrap();
+    // frame_key is logged by PyTorch but not modeled by CompilationMetricsMetadata
+    assert!(metrics.contains("<h2>Other Metrics</h2>"));
+    assert!(metrics.contains("<td><code>frame_key</code></td>"));
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_distributed_info() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_distributed_info");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_content = fs::read_to_string(out_dir.join("index.html")).unwrap();
+    assert!(landing_content.contains("<th>World Size</th>"));
+    assert!(landing_content.contains("<td>trainer-1.example.com</td>"));
+    assert!(!landing_content.contains("logged as rank"));
+
+    let rank1_content = fs::read_to_string(out_dir.join("rank_1/index.html")).unwrap();
+    assert!(rank1_content
+        .contains("Rank <strong>1</strong> of 2 on <code>trainer-1.example.com</code> (cuda)"));
+    Ok(())
+}
+
+#[test]
+fn test_guard_latency() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/multi_rank_runtime/dedicated_log_torch_trace_rank_3.log")
+        .to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let guard_latency = &map[&PathBuf::from("guard_latency.html")];
+    // Later recompiles of frame 0 pay for the guards of every earlier entry
+    let last = guard_latency
+        .find(r#"<a href="index.html#[0/3]">[0/3]</a> </td> <td> 74 </td> <td> 76.0 </td> <td> 335.0 </td>"#)
+        .unwrap();
+    let first = guard_latency
+        .find(r#"<a href="index.html#[0/0]">"#)
+        .unwrap();
+    assert!(last < first);
+    assert!(map[&PathBuf::from("index.html")]
+        .contains("Evaluating guards took 335.0 us for 275 guards across all compiled frames."));
+    Ok(())
+}
+
+#[test]
+fn test_cudagraph_skips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/cudagraphs.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    // Only the compile id that fell back gets a page
+    assert!(!map
+        .keys()
+        .any(|p| p.starts_with("-_0_0_0") && p.to_string_lossy().contains("cudagraphs")));
+    let (cudagraphs_path, cudagraphs) = map
+        .iter()
+        .find(|(p, _)| p.starts_with("-_1_0_0") && p.to_string_lossy().contains("cudagraphs"))
+        .unwrap();
+    assert!(cudagraphs
+        .contains("<pre>skipping cudagraphs due to mutated inputs (1 instances). Found from : \n"));
+    assert!(cudagraphs.contains("    x.add_(1)\n</pre>"));
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("CUDA graphs were skipped for 1 graph(s)"));
+    assert!(index.contains(&format!(
+        r#"<a href="{}">[1/0]</a>"#,
+        cudagraphs_path.to_string_lossy()
+    )));
+    Ok(())
+}
+
+#[test]
+fn test_data_dependent_specializations_outside_export() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/export.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let (page_path, page) = map
+        .iter()
+        .find(|(p, _)| p.to_string_lossy().contains("symbolic_guard_information"))
+        .unwrap();
+    assert!(page.contains("Eq((((-u0)//3)) + 5, 0)"));
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("1 data-dependent specialization(s) occurred"));
+    assert!(index.contains(&format!(r#"<a href="{}">"#, page_path.to_string_lossy())));
+    assert!(index.contains("<code>Eq((((-u0)//3)) + 5, 0)</code> evaluated to <code>False</code>"));
+    Ok(())
+}
+
+#[test]
+fn test_ddp_splits() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/ddp_splits.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    // The graphs are still dumped individually
+    assert!(map.contains_key(&PathBuf::from("-_0_0_0/optimize_ddp_split_graph_0.txt")));
+    assert!(map.contains_key(&PathBuf::from(
+        "-_0_0_0/optimize_ddp_split_child_submod_2_0.txt"
+    )));
+    let splits = &map[&PathBuf::from("-_0_0_0/ddp_splits_0.html")];
+    assert!(splits.contains("<td> <code>submod_0</code> </td> <td> 2 </td> <td> 14.3 </td>"));
+    assert!(splits.contains(
+        "<td> <code>submod_2</code> <strong>(unbalanced)</strong> </td> <td> 10 </td> <td> 71.4 </td>"
+    ));
+    assert!(splits.contains("<strong>Warning:</strong>"));
+    Ok(())
+}
+
+#[test]
+fn test_compiled_autograd_correlation() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/compiled_autograd.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    // The compiled autograd compile id shares the train_step frame with [0/0] only
+    let backward = &map[&PathBuf::from("0_2_0_0/compiled_autograd_0.html")];
+    assert!(
+        backward.contains(r#"<li><a href="../-_0_0_0/compilation_metrics_0.html">[0/0]</a></li>"#)
+    );
+    assert!(!backward.contains("[1/0]"));
+    let forward = &map[&PathBuf::from("-_0_0_0/compiled_autograd_0.html")];
+    assert!(forward
+        .contains(r#"<li><a href="../0_2_0_0/compilation_metrics_0.html">[!0/2/0]</a></li>"#));
+    assert!(!map
+        .keys()
+        .any(|p| p.starts_with("-_1_0_0") && p.to_string_lossy().contains("compiled_autograd")));
+    Ok(())
+}
+
+#[test]
+fn test_intern_table_per_parse() -> Result<(), Box<dyn std::error::Error>> {
+    let string_table = |path: &str| -> String {
+        let config = tlparse::ParseConfig {
+            strict: true,
+            ..Default::default()
+        };
+        let map: HashMap<PathBuf, String> =
+            tlparse::parse_path(&Path::new(path).to_path_buf(), &config)
+                .unwrap()
+                .into_iter()
+                .collect();
+        map[&PathBuf::from("raw.jsonl")]
+            .lines()
+            .next()
+            .unwrap()
+            .to_string()
+    };
+    // Logs can be parsed concurrently, and strings interned by one don't leak into the other
+    let handle = std::thread::spawn(move || string_table("tests/inputs/simple.log"));
+    let cudagraphs = string_table("tests/inputs/cudagraphs.log");
+    let simple = handle.join().unwrap();
+    assert_eq!(cudagraphs, r#"{"string_table":[null]}"#);
+    assert!(simple.contains("torch/_dynamo/convert_frame.py"));
+    assert_eq!(string_table("tests/inputs/cudagraphs.log"), cudagraphs);
+    Ok(())
+}
+
+#[test]
+fn test_legacy_artifact_numbering() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        legacy_artifact_numbering: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    for file in [
+        "-_1_0_0/fx_graph_cache_miss_33.json",
+        "-_1_0_0/fx_graph_cache_miss_9.json",
+        "-_1_0_0/fx_graph_cache_hit_20.json",
+    ] {
+        assert!(map.contains_key(&PathBuf::from(file)), "{file} not found");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_dedup_payloads() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        dedup_payloads: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let directory: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("compile_directory.json")])?;
+    let blob_urls: Vec<&str> = directory
+        .as_object()
+        .unwrap()
+        .values()
+        .filter_map(|v| v["artifacts"].as_array())
+        .flatten()
+        .filter_map(|a| a["url"].as_str())
+        .filter(|url| url.starts_with("blobs/"))
+        .collect();
+    let unique_blobs: HashSet<&str> = blob_urls.iter().copied().collect();
+    // The same graphs are dumped by several compiles, but written only once
+    assert!(unique_blobs.len() < blob_urls.len());
+    for blob in &unique_blobs {
+        assert!(map.contains_key(&PathBuf::from(blob)), "{blob} not written");
+    }
+    assert_eq!(
+        map.keys().filter(|p| p.starts_with("blobs")).count(),
+        unique_blobs.len()
+    );
+    // Payloads aren't also written into the compile directories
+    assert!(!map.contains_key(&PathBuf::from("-_1_0_0/inductor_post_grad_graph_0.txt")));
+    Ok(())
+}
+
+// Writes string artifacts a second time, as .py
+struct PythonArtifactParser;
+impl tlparse::parsers::StructuredLogParser for PythonArtifactParser {
+    fn name(&self) -> &'static str {
+        "python_artifact"
+    }
+    fn get_metadata<'e>(
+        &self,
+        e: &'e tlparse::parsers::Envelope,
+    ) -> Option<tlparse::parsers::Metadata<'e>> {
+        e.artifact
+            .as_ref()
+            .filter(|m| m.encoding == "string")
+            .map(tlparse::parsers::Metadata::Artifact)
+    }
+    fn parse<'e>(
+        &self,
+        _lineno: usize,
+        metadata: tlparse::parsers::Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<tlparse::CompileId>,
+        _payload: &str,
+    ) -> anyhow::Result<tlparse::parsers::ParserResults> {
+        let tlparse::parsers::Metadata::Artifact(metadata) = metadata else {
+            anyhow::bail!("Expected Artifact metadata");
+        };
+        let dir = compile_id.as_ref().unwrap().as_directory_name();
+        Ok(vec![tlparse::parsers::ParserOutput::PayloadFile(
+            PathBuf::from(dir).join(format!("{}.py", metadata.name)),
+        )])
+    }
+}
+
+#[test]
+fn test_dedup_payloads_extensions() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let path = temp_dir.path().join("log.txt");
+    fs::write(
+        &path,
+        "V0601 10:00:00.000000 1 torch/_dynamo/convert_frame.py:1] {\"artifact\": {\"name\": \"graph_source\", \"encoding\": \"string\"}, \"frame_id\": 0, \"frame_compile_id\": 0, \"attempt\": 0, \"has_payload\": \"525ae5ddef01f7025d4af97b62ba4026\"}\n\tx = torch.ones(3)\n",
+    )?;
+    let config = tlparse::ParseConfig {
+        dedup_payloads: true,
+        custom_parsers: vec![Box::new(PythonArtifactParser)],
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    // The same content under two extensions is two blobs
+    for blob in [
+        "blobs/525ae5ddef01f7025d4af97b62ba4026.txt",
+        "blobs/525ae5ddef01f7025d4af97b62ba4026.py",
+    ] {
+        assert_eq!(map[&PathBuf::from(blob)], "x = torch.ones(3)", "{blob}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_max_compile_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        max_compile_ids: Some(1),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    // Only [2/0] has a compile time, so it is the one that's kept
+    assert!(prefix_exists(&map, "-_2_0_0/compilation_metrics"));
+    assert!(!prefix_exists(&map, "-_0_0_1/"));
+    assert!(!prefix_exists(&map, "-_1_0_1/"));
+    let overflow = &map[&PathBuf::from("overflow_compile_ids.html")];
+    assert!(overflow.contains(r#"<tr id="[0/0_1]"> <td> [0/0_1] </td>"#));
+    assert!(!overflow.contains("[2/0]"));
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(r#"<a href="overflow_compile_ids.html">"#));
+    assert!(!index.contains("-_1_0_1/"));
+
+    // The summary still counts the cache lookups of the compile ids that were dropped
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        max_compile_ids: Some(0),
+        ..config
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    assert!(map.contains_key(&PathBuf::from("overflow_compile_ids.html")));
+    let summary: tlparse::CompileSummary =
+        serde_json::from_str(&map[&PathBuf::from("compile_summary.json")])?;
+    assert_eq!((summary.cache_hits, summary.cache_misses), (1, 2));
+    Ok(())
+}
+
+#[test]
+fn test_paginated_index() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        index_page_size: Some(2),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index = &map[&PathBuf::from("index.html")];
+    // The summary stays on the first page, along with the first compile ids
+    assert!(index.contains("<h2>Stack trie</h2>"));
+    assert!(index.contains(r#"<a id="[0/0_1]">"#));
+    assert!(!index.contains(r#"<a id="[2/0]">"#));
+    assert!(index.contains(r#"<a href="index_2.html">3</a>"#));
+    assert!(index.contains(r#""[2/0]":"index_2.html""#));
+    let last_page = &map[&PathBuf::from("index_2.html")];
+    assert!(last_page.contains(r#"<a id="[2/0]">"#));
+    assert!(last_page.contains("-_2_0_0/compilation_metrics_0.html"));
+    assert!(!last_page.contains("<h2>Stack trie</h2>"));
+    assert!(!map.contains_key(&PathBuf::from("index_3.html")));
+    Ok(())
+}
+
+#[test]
+fn test_compile_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let summary: tlparse::CompileSummary =
+        serde_json::from_str(&map[&PathBuf::from("compile_summary.json")])?;
+    assert_eq!(summary.total_compiles, 6);
+    assert_eq!((summary.cache_hits, summary.cache_misses), (1, 2));
+    assert!(map[&PathBuf::from("index.html")].contains("Cache hit rate: 1 hit(s), 2 miss(es)."));
+    let md = summary.to_markdown();
+    assert!(md.contains("| Compiles | 6 |"));
+    assert!(md.contains("| Cache hit rate | 33.3% (1 hits, 2 misses) |"));
+
+    // A hit rate of 0 is shown too
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    assert!(map[&PathBuf::from("index.html")].contains("Cache hit rate: 0 hit(s), 1 miss(es)."));
+
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let summary: tlparse::CompileSummary =
+        serde_json::from_str(&map[&PathBuf::from("compile_summary.json")])?;
+    assert_eq!(summary.graph_breaks, 2);
+    assert_eq!(summary.slowest_compiles[0].compile_id, "[0/0]");
+    assert!(summary.to_markdown().contains("| `[0/0]` | 0.01 s |"));
+    Ok(())
+}
+
+#[test]
+fn test_fail_on_thresholds() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+    let report = temp_dir.path().join("report.md");
+    let run = |graph_breaks: &str| -> Result<Command, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("tlparse")?;
+        cmd.arg("tests/inputs/comp_metrics.log")
+            .arg("--overwrite")
+            .arg("-o")
+            .arg(&out_dir)
+            .arg("--no-browser")
+            .arg("--report-md")
+            .arg(&report)
+            .arg("--fail-on-graph-breaks")
+            .arg(graph_breaks)
+            .arg("--fail-on-recompiles")
+            .arg("0");
+        Ok(cmd)
+    };
+    // comp_metrics.log restarts twice, and never recompiles
+    run("2")?.assert().success();
+    assert!(fs::read_to_string(&report)?.contains("| Graph breaks | 2 |"));
+    run("1")?
+        .assert()
+        .failure()
+        .stderr(str::contains("2 graph breaks (limit 1)"));
+    Ok(())
+}
+
+#[test]
+fn test_baseline_comparison() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let baseline = tlparse::CompileSummary {
+        total_compiles: 2,
+        graph_breaks: 3,
+        cumulative_compile_time_s: 0.01,
+        ..Default::default()
+    };
+    let config = tlparse::ParseConfig {
+        strict: true,
+        baseline: Some(baseline.clone()),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let comparison = &map[&PathBuf::from("baseline_comparison.html")];
+    assert!(comparison.contains(
+        r#"<tr> <td> Compiles </td> <td> 2 </td> <td> 3 </td> <td class="regressed"> +1 </td> </tr>"#
+    ));
+    assert!(comparison
+        .contains("<tr> <td> Graph breaks </td> <td> 3 </td> <td> 2 </td> <td> -1 </td> </tr>"));
+    assert!(map[&PathBuf::from("index.html")].contains("baseline_comparison.html"));
+
+    let summary: tlparse::CompileSummary =
+        serde_json::from_str(&map[&PathBuf::from("compile_summary.json")])?;
+    let thresholds = tlparse::CompileThresholds {
+        compile_time_regression: Some(0.5),
+        compile_count_increase: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(
+        thresholds.violations(&summary, Some(&baseline)),
+        vec!["compile time +130.0% over baseline (limit +50.0%)".to_string()]
+    );
+    assert!(thresholds.violations(&summary, None).is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_chromium_events_repair() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/chromium_events_malformed.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let events: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("chromium_events.json")])?;
+    // The duplicate, the event without a timestamp and the truncated event are dropped
+    let names: Vec<&str> = events.iter().map(|e| e["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["dynamo", "missing_ph", "dynamo"]);
+    // A complete event missing its phase
+    assert_eq!(events[1]["ph"], "X");
+    // The end can't come before the beginning
+    assert_eq!(events[2]["ts"].as_f64(), Some(1000.0));
+    Ok(())
+}
+
+#[test]
+fn test_per_compile_id_chromium_events() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let events: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("-_0_0_0/chromium_events.json")])?;
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|e| e["args"]["compile_id"] == "0/0"));
+    let begins = events.iter().filter(|e| e["ph"] == "B").count();
+    let ends = events.iter().filter(|e| e["ph"] == "E").count();
+    assert_eq!(begins, ends);
+    assert!(map[&PathBuf::from("index.html")]
+        .contains(r#"<a href="-_0_0_0/chromium_events.json">-_0_0_0/chromium_events.json</a>"#));
+    Ok(())
+}
+
+#[test]
+fn test_runtime_profile_correlation() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/multi_rank_runtime/dedicated_log_torch_trace_rank_0.log")
+        .to_path_buf();
+    let trace = r#"{"traceEvents": [
+        {"ph": "X", "name": "aten::mm", "ts": 0, "dur": 6000},
+        {"ph": "X", "name": "aten::mm", "ts": 7000, "dur": 8000},
+        {"ph": "X", "name": "_c10d_functional::all_reduce_", "ts": 16000, "dur": 60},
+        {"ph": "i", "name": "op1", "ts": 17000}
+    ]}"#;
+    let config = tlparse::ParseConfig {
+        profile: Some(tlparse::MeasuredDurations::from_trace(trace)?),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let correlation = &map[&PathBuf::from("runtime_profile_correlation.html")];
+    // extern_kernels.mm matches both aten::mm events, and all_reduce_ matches by op name
+    assert!(correlation.contains(
+        "<tr> <td> <code>extern_kernels.mm</code> </td> <td> 6885.8 </td> <td> 7000.0 </td> <td> 1.02 </td> </tr>"
+    ));
+    assert!(correlation.contains("<tr> <td> <code>torch.ops._c10d_functional.all_reduce_.default</code> </td> <td> 30.1 </td> <td> 60.0 </td> <td> 2.0 </td> </tr>"));
+    assert!(correlation
+        .contains("<tr> <td> <a href='#[0/0]'>[0/0]</a> </td> <td> 3 </td> <td> 3 </td>"));
+    assert!(map[&PathBuf::from("index.html")].contains("runtime_profile_correlation.html"));
+    Ok(())
+}
+
+#[test]
+fn test_runtime_estimation_page() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/multi_rank_runtime/dedicated_log_torch_trace_rank_0.log")
+        .to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let page = &map[&PathBuf::from("-_0_0_0/runtime_estimation_0.html")];
+    let output_code =
+        "-_0_0_0/inductor_output_code_cvklj7mq6mj7yvispxo37sxtyhv7txkklhaxntykqhqgcspopin5_0.html";
+    // Slowest first, and fused ops link to the kernel named in the provenance mappings
+    let mm = page
+        .find("<code>extern_kernels.mm</code> </td> <td> 6885.8")
+        .unwrap();
+    let op5 = page.find("<code>op5</code>").unwrap();
+    assert!(mm < op5);
+    assert!(page.contains(&format!(
+        r#"<a href="../{output_code}#L152"><code>triton_poi_fused_relu_1</code></a>"#
+    )));
+    let code = &map[&PathBuf::from(output_code)];
+    let line =
+        &code[code.find(r#"<span id="L152">"#).unwrap()..code.find(r#"<span id="L153">"#).unwrap()];
+    assert!(line.contains("triton_poi_fused_relu_1"));
+    Ok(())
+}
+
+#[test]
+fn test_graph_content_divergence() -> Result<(), Box<dyn std::error::Error>> {
+    let log = fs::read_to_string("tests/inputs/simple.log")?;
+    let render = |rank_1_log: String| -> Result<String, Box<dyn std::error::Error>> {
+        let temp_in = tempdir()?;
+        fs::write(
+            temp_in.path().join("dedicated_log_torch_trace_rank_0.log"),
+            &log,
+        )?;
+        fs::write(
+            temp_in.path().join("dedicated_log_torch_trace_rank_1.log"),
+            rank_1_log,
+        )?;
+        let temp_out = tempdir()?;
+        Command::cargo_bin("tlparse")?
+            .arg(temp_in.path())
+            .args(["--all-ranks-html", "--overwrite", "--no-browser", "-o"])
+            .arg(temp_out.path())
+            .assert()
+            .success();
+        Ok(fs::read_to_string(temp_out.path().join("index.html"))?)
+    };
+
+    // Each rank compiles for its own device
+    let landing = render(log.replace("cuda:0", "cuda:1"))?;
+    assert!(!landing.contains("have different content across ranks"));
+
+    let landing = render(log.replacen("torch._C._nn.gelu(l_x_)", "torch.relu(l_x_)", 1))?;
+    assert!(landing.contains("have different content across ranks"));
+    assert!(
+        landing.contains("<li>[0/0] <code>dynamo_output_graph_0.txt</code>: Ranks 0; Ranks 1</li>")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_multi_rank_stats_table() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_in = tempdir()?;
+    fs::copy(
+        "tests/inputs/cache_hit_miss.log",
+        temp_in.path().join("dedicated_log_torch_trace_rank_0.log"),
+    )?;
+    fs::copy(
+        "tests/inputs/simple.log",
+        temp_in.path().join("dedicated_log_torch_trace_rank_1.log"),
+    )?;
+    let temp_out = tempdir()?;
+    Command::cargo_bin("tlparse")?
+        .arg(temp_in.path())
+        .args(["--all-ranks-html", "--overwrite", "--no-browser", "-o"])
+        .arg(temp_out.path())
+        .assert()
+        .success();
+
+    let landing = fs::read_to_string(temp_out.path().join("index.html"))?;
+    assert!(landing.contains("<h3>Rank Statistics</h3>"));
+    assert!(landing.contains(r#"<table class="sortable">"#));
+    // Log entries, compiles, failures, compile time and cache hit rate of rank 0
+    assert!(landing.contains(
+        "<td><a href=\"rank_0/index.html\">0</a></td>\n    <td>369</td>\n    <td>6</td>\n    <td>0</td>\n    <td>0.0</td>\n    <td>33.3%</td>"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_parse_path_with_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let (output, summary) = tlparse::parse_path_with_summary(&path, &config)?;
+    assert_eq!(summary.cache_sequence, "❌❓✅❓❌❓");
+    assert_eq!(summary.stats.ok, 369);
+    assert_eq!(summary.compile_summary.total_compiles, 6);
+    assert_eq!(summary.num_chromium_events, 128);
+    assert!(summary.graph_hashes.contains_key(&(
+        "[1/0]".to_string(),
+        "inductor_post_grad_graph_0.txt".to_string()
+    )));
+
+    // The same compile ids as the output's compile_directory.json
+    let map: HashMap<PathBuf, String> = output.into_iter().collect();
+    let directory: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("compile_directory.json")])?;
+    let compile_ids: HashSet<String> = directory
+        .as_object()
+        .unwrap()
+        .keys()
+        .filter(|k| *k != "unknown" && *k != "schema_version")
+        .cloned()
+        .collect();
+    assert_eq!(
+        summary.compile_ids.into_iter().collect::<HashSet<_>>(),
+        compile_ids
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rank_manifest() -> Result<(), Box<dyn std::error::Error>> {
+    // Logs in unrelated places, under names the directory scan wouldn't find
+    let mount_a = tempdir()?;
+    let mount_b = tempdir()?;
+    fs::copy("tests/inputs/simple.log", mount_a.path().join("job0.log"))?;
+    fs::copy(
+        "tests/inputs/cache_hit_miss.log",
+        mount_b.path().join("job1.log"),
+    )?;
+    let manifest = mount_b.path().join("manifest.json");
+    fs::write(
+        &manifest,
+        serde_json::json!({
+            "0": mount_a.path().join("job0.log"),
+            "1": "job1.log",
+        })
+        .to_string(),
+    )?;
+
+    let temp_out = tempdir()?;
+    Command::cargo_bin("tlparse")?
+        .arg("--manifest")
+        .arg(&manifest)
+        .args(["--all-ranks-html", "--overwrite", "--no-browser", "-o"])
+        .arg(temp_out.path())
+        .assert()
+        .success();
+    let landing = fs::read_to_string(temp_out.path().join("index.html"))?;
+    assert!(landing.contains("rank_0/index.html"));
+    assert!(landing.contains("rank_1/index.html"));
+    assert!(temp_out
+        .path()
+        .join("rank_1/compile_directory.json")
+        .exists());
+
+    fs::write(&manifest, r#"{"0": "missing.log"}"#)?;
+    Command::cargo_bin("tlparse")?
+        .arg("--manifest")
+        .arg(&manifest)
+        .args(["--all-ranks-html", "--overwrite", "--no-browser", "-o"])
+        .arg(temp_out.path())
+        .assert()
+        .failure()
+        .stderr(str::contains("Log of rank 0 in manifest"));
+    Ok(())
+}
+
+#[test]
+fn test_compile_time_skew() -> Result<(), Box<dyn std::error::Error>> {
+    let skew =
+        tlparse::analyze_compile_time_skew(&[(0, 10.0), (1, 11.0), (2, 10.5), (3, 20.0)], 0.2)
+            .unwrap();
+    assert_eq!(skew.median_s, 10.75);
+    assert_eq!(skew.num_stragglers, 1);
+    assert!(skew.ranks[3].straggler);
+    assert_eq!(skew.ranks[3].deviation, "+86.0%");
+    assert_eq!(skew.ranks[3].bar_pct, 100.0);
+    assert!(!skew.ranks[0].straggler);
+    // Nothing to compare
+    assert!(tlparse::analyze_compile_time_skew(&[(0, 10.0)], 0.2).is_none());
+
+    let temp_in = tempdir()?;
+    fs::copy(
+        "tests/inputs/comp_metrics.log",
+        temp_in.path().join("dedicated_log_torch_trace_rank_0.log"),
+    )?;
+    fs::copy(
+        "tests/inputs/comp_metrics.log",
+        temp_in.path().join("dedicated_log_torch_trace_rank_1.log"),
+    )?;
+    let temp_out = tempdir()?;
+    Command::cargo_bin("tlparse")?
+        .arg(temp_in.path())
+        .args(["--all-ranks-html", "--overwrite", "--no-browser", "-o"])
+        .arg(temp_out.path())
+        .assert()
+        .success();
+    let landing = fs::read_to_string(temp_out.path().join("index.html"))?;
+    assert!(landing.contains("<h3>Compile Time Skew</h3>"));
+    assert!(landing.contains("No rank deviates from the median by more than 20.0%."));
+    Ok(())
+}
+
+#[test]
+fn test_rank_selection() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_in = tempdir()?;
+    for rank in 0..3 {
+        fs::copy(
+            "tests/inputs/simple.log",
+            temp_in
+                .path()
+                .join(format!("dedicated_log_torch_trace_rank_{rank}.log")),
+        )?;
+    }
+    let run = |args: &[&str]| {
+        let temp_out = tempdir().unwrap();
+        let assert = Command::cargo_bin("tlparse")
+            .unwrap()
+            .arg(temp_in.path())
+            .args(["--all-ranks-html", "--overwrite", "--no-browser", "-o"])
+            .arg(temp_out.path())
+            .args(args)
+            .assert();
+        (temp_out, assert)
+    };
+
+    let (out, assert) = run(&["--ranks", "1"]);
+    assert.success();
+    assert!(out.path().join("rank_1/index.html").exists());
+    assert!(!out.path().join("rank_0").exists());
+    let landing = fs::read_to_string(out.path().join("index.html"))?;
+    assert!(landing.contains("the comparisons below don't cover ranks 0, 2."));
+
+    let (out, assert) = run(&["--sample-ranks", "2"]);
+    assert.success();
+    let parsed = (0..3)
+        .filter(|rank| out.path().join(format!("rank_{rank}")).exists())
+        .count();
+    assert_eq!(parsed, 2);
+
+    let (_, assert) = run(&["--ranks", "0,5"]);
+    assert
+        .failure()
+        .stderr(str::contains("No log found for rank 5 passed to --ranks"));
+    Ok(())
+}
+
+#[test]
+fn test_artifact_log_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let log = fs::read_to_string(&path)?;
+    let log_lines: Vec<&str> = log.lines().collect();
+    let directory: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("compile_directory.json")])?;
+    let artifacts: Vec<&serde_json::Value> = directory
+        .as_object()
+        .unwrap()
+        .values()
+        .filter_map(|v| v["artifacts"].as_array())
+        .flatten()
+        .collect();
+    let graph = artifacts
+        .iter()
+        .find(|a| {
+            a["name"]
+                .as_str()
+                .unwrap()
+                .starts_with("dynamo_output_graph")
+        })
+        .expect("dynamo_output_graph not found");
+    let line = graph["log_line"].as_u64().expect("no log line") as usize;
+    // The envelope the graph was parsed from
+    assert!(log_lines[line - 1].contains(r#"{"dynamo_output_graph""#));
+    // Compilation metrics are rendered once parsing is done, but still from their envelope
+    let metrics = artifacts
+        .iter()
+        .find(|a| {
+            a["name"]
+                .as_str()
+                .unwrap()
+                .starts_with("compilation_metrics")
+        })
+        .expect("compilation_metrics not found");
+    let metrics_line = metrics["log_line"].as_u64().expect("no log line") as usize;
+    assert!(log_lines[metrics_line - 1].contains(r#"{"compilation_metrics""#));
+    assert!(map[&PathBuf::from("index.html")].contains(&format!("raw.log:{line}")));
+    Ok(())
+}
+
+#[test]
+fn test_envelope_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let log = fs::read_to_string(&path)?;
+    let stats: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("envelope_stats.json")])?;
+    let by_type: HashMap<&str, &serde_json::Value> = stats
+        .iter()
+        .map(|s| (s["envelope_type"].as_str().unwrap(), s))
+        .collect();
+    let num_chromium_events = log.matches(r#"{"chromium_event""#).count() as u64;
+    assert_eq!(by_type["chromium_event"]["count"], num_chromium_events);
+    assert_eq!(by_type["dynamo_start"]["count"], 1);
+    assert!(
+        by_type["inductor_output_code"]["payload_bytes"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+    assert!(by_type.contains_key("artifact:fx_graph_cache_miss"));
+    // Largest payloads first
+    let bytes: Vec<u64> = stats
+        .iter()
+        .map(|s| s["payload_bytes"].as_u64().unwrap())
+        .collect();
+    assert!(bytes.windows(2).all(|w| w[0] >= w[1]));
+    assert!(map[&PathBuf::from("index.html")].contains("<code>artifact:fx_graph_cache_miss</code>"));
+    Ok(())
+}
+
+#[test]
+fn test_parser_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())?
+        .into_iter()
+        .collect();
+    let timings: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("parser_profile.json")])?;
+    let by_parser: HashMap<&str, &serde_json::Value> = timings
+        .iter()
+        .map(|t| (t["parser"].as_str().unwrap(), t))
+        .collect();
+    let output_code = by_parser["inductor_output_code"];
+    assert_eq!(output_code["calls"], 1);
+    assert!(output_code["outputs"].as_u64().unwrap() >= 1);
+    assert!(output_code["output_bytes"].as_u64().unwrap() > 0);
+    // Slowest first
+    let total_s: Vec<f64> = timings
+        .iter()
+        .map(|t| t["total_s"].as_f64().unwrap())
+        .collect();
+    assert!(total_s.windows(2).all(|w| w[0] >= w[1]));
+    assert!(map[&PathBuf::from("index.html")].contains("<code>inductor_output_code</code>"));
+
+    // Timings differ between runs, so deterministic output leaves them out
+    let config = tlparse::ParseConfig {
+        deterministic: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    assert!(!map.contains_key(&PathBuf::from("parser_profile.json")));
+    assert!(!map[&PathBuf::from("index.html")].contains("Parser Profile"));
+    Ok(())
+}
+
+#[test]
+fn test_trace_size_report() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let stats: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("envelope_stats.json")])?;
+    // Every line of the log belongs to some record, though the last one may have no newline
+    let log_bytes: u64 = stats.iter().map(|s| s["log_bytes"].as_u64().unwrap()).sum();
+    assert!(log_bytes.abs_diff(fs::metadata(&path)?.len()) <= 1);
+    let html = &map[&PathBuf::from("trace_size.html")];
+    assert!(html.contains("<code>chromium_event</code>"));
+    assert!(html.contains(r#"<a href="index.html#[0/0]">[0/0]</a>"#));
+    assert!(html.contains("(no compile id)"));
+    Ok(())
+}
+
+#[test]
+fn test_parser_selection() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let parse = |disabled: &[&str], enabled: Option<&[&str]>| {
+        let config = tlparse::ParseConfig {
+            strict: true,
+            disabled_parsers: disabled.iter().map(|s| s.to_string()).collect(),
+            enabled_parsers: enabled.map(|e| e.iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        };
+        tlparse::parse_path(&path, &config)
+    };
+    let map: HashMap<PathBuf, String> = parse(&[], None)?.into_iter().collect();
+    assert!(map[Path::new("search_index.json")].contains(r#""kind":"kernel""#));
+
+    let map: HashMap<PathBuf, String> = parse(&["inductor_output_code"], None)?
+        .into_iter()
+        .collect();
+    assert!(!prefix_exists(&map, "-_0_0_0/inductor_output_code"));
+    assert!(prefix_exists(&map, "-_0_0_0/dynamo_output_graph"));
+    // Nor is the output code scanned for kernels to search for
+    assert!(!map[Path::new("search_index.json")].contains(r#""kind":"kernel""#));
+
+    let map: HashMap<PathBuf, String> = parse(&[], Some(&["compilation_metrics"]))?
+        .into_iter()
+        .collect();
+    assert!(prefix_exists(&map, "-_0_0_0/compilation_metrics"));
+    assert!(!prefix_exists(&map, "-_0_0_0/dynamo_output_graph"));
+
+    let err = parse(&["no_such_parser"], None).unwrap_err();
+    assert!(err.to_string().contains("Unknown parser no_such_parser"));
+    Ok(())
+}
+
+#[test]
+fn test_output_code_highlighting() -> Result<(), Box<dyn std::error::Error>> {
+    // Syntect's syntaxes and themes are loaded once and shared by every output code
+    let temp_in = tempdir()?;
+    let path = temp_in.path().join("output_code.log");
+    let mut log = String::new();
+    for i in 0..100 {
+        log.push_str(&format!(
+            "V1206 15:18:21.452000 1500233 torch/_inductor/graph.py:2030] \
+             {{\"inductor_output_code\": {{\"filename\": \"/tmp/k{i}.py\"}}, \"frame_id\": {i}, \
+             \"frame_compile_id\": 0, \"attempt\": 0, \"has_payload\": \"0\"}}\n\
+             \tdef call(args):\n\t    return args\n"
+        ));
+    }
+    fs::write(&path, log)?;
+    let parse = |plain_text: bool| -> HashMap<PathBuf, String> {
+        let config = tlparse::ParseConfig {
+            plain_text,
+            verify_payloads: tlparse::PayloadVerification::None,
+            ..Default::default()
+        };
+        tlparse::parse_path(&path, &config)
+            .unwrap()
+            .into_iter()
+            .collect()
+    };
+
+    let output = parse(false);
+    let first = &output[Path::new("-_0_0_0/inductor_output_code_k0_0.html")];
+    assert!(first.contains("<span id=\"L1\">"));
+    assert!(first.contains("<span id=\"L2\">"));
+    // `def` is highlighted in a color of its own
+    assert!(first.contains(";\">def </span>"));
+    let renderings: Vec<&String> = output
+        .iter()
+        .filter(|(name, _)| name.to_string_lossy().contains("inductor_output_code_k"))
+        .map(|(_, html)| html)
+        .collect();
+    assert_eq!(renderings.len(), 100);
+    assert!(renderings.iter().all(|html| *html == first));
+
+    // Plain text output isn't highlighted at all
+    let output = parse(true);
+    let text = &output[Path::new("-_0_0_0/inductor_output_code_k0_0.txt")];
+    assert_eq!(text, "def call(args):\n    return args");
+    Ok(())
+}
+
+#[cfg(feature = "async-io")]
+#[test]
+fn test_parallel_writes() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        max_parallel_writes: Some(8),
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config)?;
+    let temp_out = tempdir()?;
+    tlparse::write_output(output.clone(), temp_out.path(), &config)?;
+    for (filename, content) in output {
+        assert_eq!(
+            fs::read_to_string(temp_out.path().join(&filename))?,
+            content
+        );
+    }
+
+    let temp_out = tempdir()?;
+    Command::cargo_bin("tlparse")?
+        .arg(&path)
+        .args([
+            "--overwrite",
+            "--no-browser",
+            "--parallel-writes",
+            "4",
+            "-o",
+        ])
+        .arg(temp_out.path())
+        .assert()
+        .success();
+    assert!(temp_out.path().join("index.html").exists());
+    assert!(temp_out.path().join("-_0_0_0").is_dir());
+    Ok(())
+}
+
+#[test]
+fn test_raw_jsonl_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+
+    let temp_out = tempdir()?;
+    let raw_jsonl_path = temp_out.path().join("raw.jsonl");
+    let (output, _) =
+        tlparse::parse_path_with_raw_jsonl_file(&[path.clone()], &config, &raw_jsonl_path)?;
+    assert!(!output.iter().any(|(f, _)| f == Path::new("raw.jsonl")));
+    // Same content, string table first
+    let raw_jsonl = fs::read_to_string(&raw_jsonl_path)?;
+    assert_eq!(raw_jsonl, map[&PathBuf::from("raw.jsonl")]);
+    assert!(raw_jsonl.starts_with(r#"{"string_table""#));
+    let leftovers: Vec<_> = fs::read_dir(temp_out.path())?.collect();
+    assert_eq!(leftovers.len(), 1);
+
+    // Nothing is left behind when parsing fails
+    let temp_out = tempdir()?;
+    let raw_jsonl_path = temp_out.path().join("raw.jsonl");
+    let config = tlparse::ParseConfig {
+        disabled_parsers: vec!["no_such_parser".to_string()],
+        ..Default::default()
+    };
+    assert!(tlparse::parse_path_with_raw_jsonl_file(&[path], &config, &raw_jsonl_path).is_err());
+    assert_eq!(fs::read_dir(temp_out.path())?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_recover_payloads() -> Result<(), Box<dyn std::error::Error>> {
+    let artifact = |thread: u32, name: &str, payload: &str| {
+        artifact_envelope(thread, 0, name, "string", payload)
+    };
+    // Another thread logs in the middle of foo's payload, and the process dies while logging bar
+    let log = [
+        artifact(100, "foo", "a\nb\nc"),
+        "\ta\n".to_string(),
+        "V1206 15:18:21.452001 200 torch/_logging/structured.py:27] {\"str\": [\"x.py\", 0]}\n"
+            .to_string(),
+        "\tb\n\tc\n".to_string(),
+        artifact(100, "bar", "x\ny"),
+        "\tx\n".to_string(),
+    ]
+    .concat();
+    let temp_in = tempdir()?;
+    let path = temp_in.path().join("interleaved.log");
+    fs::write(&path, log)?;
+
+    let parse = |recover_payloads: bool| -> HashMap<PathBuf, String> {
+        let config = tlparse::ParseConfig {
+            recover_payloads,
+            ..Default::default()
+        };
+        tlparse::parse_path(&path, &config)
+            .unwrap()
+            .into_iter()
+            .collect()
+    };
+    let foo = |map: &HashMap<PathBuf, String>| {
+        map.iter()
+            .find(|(f, _)| f.starts_with("-_0_0_0") && f.to_string_lossy().contains("foo"))
+            .map(|(_, content)| content.clone())
+    };
+
+    let map = parse(false);
+    assert_eq!(foo(&map).as_deref(), Some("a"));
+
+    let map = parse(true);
+    assert_eq!(foo(&map).as_deref(), Some("a\nb\nc"));
+    assert_eq!(map[&PathBuf::from("corrupt_payloads/line_6.txt")], "x");
+    let corrupt: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("corrupt_payloads/index.json")])?;
+    assert_eq!(corrupt.len(), 1);
+    assert_eq!(corrupt[0]["thread"], 100);
+    assert_eq!(corrupt[0]["expected_md5"], md5_hex(b"x\ny"));
+    // The truncated payload isn't parsed
+    assert!(!map
+        .keys()
+        .any(|f| f.starts_with("-_0_0_0") && f.to_string_lossy().contains("bar")));
+    Ok(())
+}
+
+#[test]
+fn test_thread_timeline() -> Result<(), Box<dyn std::error::Error>> {
+    let record = |time: &str, thread: u32, frame_id: u32| {
+        format!(
+            "V1206 10:00:{time} {thread} torch/_dynamo/convert_frame.py:1000] \
+             {{\"dynamo_start\": {{\"stack\": []}}, \"frame_id\": {frame_id}, \
+             \"frame_compile_id\": 0, \"attempt\": 0}}\n"
+        )
+    };
+    // Threads 100 and 200 compile frames 0 and 1 at the same time, then 100 compiles frame 2
+    let log = [
+        record("00.000000", 100, 0),
+        record("01.000000", 200, 1),
+        record("02.000000", 100, 0),
+        record("03.000000", 200, 1),
+        record("10.000000", 100, 2),
+        record("11.000000", 100, 2),
+    ]
+    .concat();
+    let temp_in = tempdir()?;
+    let path = temp_in.path().join("threads.log");
+    fs::write(&path, log)?;
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())?
+            .into_iter()
+            .collect();
+    let threads: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("threads.json")])?;
+    assert_eq!(threads.len(), 2);
+    let overlapping: HashSet<&str> = threads
+        .iter()
+        .flat_map(|t| t["compiles"].as_array().unwrap())
+        .filter(|c| c["overlapping"] == true)
+        .map(|c| c["compile_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(overlapping, HashSet::from(["[0/0]", "[1/0]"]));
+    assert_eq!(threads[0]["compiles"][1]["start_s"], 10.0);
+    assert!(map[&PathBuf::from("index.html")].contains("2 compile(s) ran at the same time"));
+    assert!(map[&PathBuf::from("threads.html")].contains(r#"href="index.html#[2/0]""#));
+
+    // No page for a single thread
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(
+        &PathBuf::from("tests/inputs/simple.log"),
+        &tlparse::ParseConfig::default(),
+    )?
+    .into_iter()
+    .collect();
+    assert!(!map.contains_key(&PathBuf::from("threads.html")));
+    Ok(())
+}
+
+#[test]
+fn test_attempt_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index = &map[&PathBuf::from("index.html")];
+    // Both attempts of frame 0 are nested under it, with the restart between them
+    let frame = index
+        .find(r#"<span class="attempt-frame">[0/0]</span> (2 attempts)"#)
+        .expect("frame [0/0] not nested");
+    let first = index.find(r#"<a id="[0/0]">"#).unwrap();
+    let restart = index[first..]
+        .find(r#"<li class="restart-reason">restarted: "#)
+        .unwrap()
+        + first;
+    let second = index.find(r#"<a id="[0/0_1]">"#).unwrap();
+    assert!(frame < first && first < restart && restart < second);
+    assert!(index[restart..second].contains("skip function graph_break"));
+    // A frame compiled in one attempt is listed as before
+    assert!(!index.contains(r#"<span class="attempt-frame">[2/0]</span>"#));
+    assert!(index.contains(r#"<li><a id="[2/0]">[2/0]</a>"#));
+    Ok(())
+}
+
+#[test]
+fn test_graph_partitions() -> Result<(), Box<dyn std::error::Error>> {
+    let artifact = |frame_id: u32, name: &str, payload: &str| {
+        artifact_envelope(100, frame_id, name, "json", payload) + &format!("\t{payload}\n")
+    };
+    let log = [
+        // Two identical regions of frame 0, and frame 1 partitioned around a cpu op
+        artifact(0, "graph_region_groups", r#"[[["mul", "add"], ["mul_1", "add_1"]]]"#),
+        artifact(
+            1,
+            "graph_partition_summary",
+            r#"{"partitions": [{"name": "partition_0", "nodes": ["mm"]}, {"name": "partition_1", "nodes": ["sin", "cos"], "skip_cudagraph_reason": "cpu device"}]}"#,
+        ),
+    ]
+    .concat();
+    let temp_in = tempdir()?;
+    let path = temp_in.path().join("partitions.log");
+    fs::write(&path, log)?;
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let page = &map[&PathBuf::from("graph_partitions.html")];
+    assert!(page.contains("href='index.html#[0/0]'"));
+    assert!(page.contains("<code>mul_1, add_1</code>"));
+    assert!(page.contains("partition_1"));
+    assert!(page.contains("cpu device"));
+    assert!(page.contains("3 nodes in total"));
+    assert!(map[&PathBuf::from("index.html")].contains("graph_partitions.html"));
+    // The artifacts are still dumped as they are
+    assert!(prefix_exists(&map, "-_0_0_0/graph_region_groups"));
+    Ok(())
+}
+
+#[test]
+fn test_hot_entry_points() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/compiled_autograd.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("Hot Compilation Entry Points"));
+    // forward is compiled three times, once from each of three stacks
+    let row = index
+        .lines()
+        .find(|l| l.contains("<code>forward</code>"))
+        .expect("forward not listed");
+    assert!(row.contains("<td> /data/train.py:5 </td> <td> 3 </td> <td> 3 </td>"));
+    assert!(row.contains("<a href='#[!0/2/0]'>[!0/2/0]</a>"));
+    Ok(())
+}
+
+#[test]
+fn test_training_steps() -> Result<(), Box<dyn std::error::Error>> {
+    // Frame 0 compiles during step 100, frames 1 and 2 during step 101
+    let steps = tlparse::TrainingSteps::from_json(
+        r#"[{"_step": 100, "_timestamp": 1712154528.0}, {"_step": 101, "_timestamp": 1712154528.06}]"#,
+    )?;
+    assert_eq!(steps.step_at(1712154527.0), None);
+    let config = tlparse::ParseConfig {
+        strict: true,
+        steps: Some(steps),
+        ..Default::default()
+    };
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let directory: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("compile_directory.json")])?;
+    assert_eq!(directory["[0/0]"]["step"], 100);
+    assert_eq!(directory["[2/0]"]["step"], 101);
+    assert!(map[&PathBuf::from("index.html")]
+        .contains(r#"<a id="[2/0]">[2/0]</a> <span class="step">step 101</span>"#));
+    assert!(map[&PathBuf::from("failures_and_restarts.html")]
+        .contains("<span class='step'>step 100</span>"));
+
+    // Steps are marked on the chromium timeline, given in milliseconds like MLflow does
+    let config = tlparse::ParseConfig {
+        steps: Some(tlparse::TrainingSteps::from_json(
+            r#"{"7": 1733527213900, "8": 1733527214000, "9": 1733527215000}"#,
+        )?),
+        ..Default::default()
+    };
+    let path = Path::new("tests/inputs/chromium_events.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let events: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("chromium_events.json")])?;
+    let step_events: Vec<&str> = events
+        .iter()
+        .filter(|e| e["cat"] == "training_step")
+        .map(|e| e["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(step_events, vec!["step 8"]);
+    Ok(())
+}
+
+#[test]
+fn test_summarize_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.current_dir(temp_dir.path())
+        .arg("summarize")
+        .arg(fs::canonicalize("tests/inputs/comp_metrics.log")?);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let table = String::from_utf8(output)?;
+    assert!(table.contains("Compiles              3\n"));
+    assert!(table.contains("Graph breaks          2\n"));
+    assert!(table.contains("[0/0]      0.012 s  fn\n"));
+    // Nothing is written
+    assert_eq!(fs::read_dir(temp_dir.path())?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_summary_only() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let (full_output, full) = tlparse::parse_path_with_summary(&path, &Default::default())?;
+    let config = tlparse::ParseConfig {
+        summary_only: true,
+        ..Default::default()
+    };
+    let (output, summary) = tlparse::parse_path_with_summary(&path, &config)?;
+    // Nothing is rendered, not even raw.log
+    assert!(output.is_empty());
+    assert!(full_output.iter().any(|(p, _)| p == Path::new("raw.log")));
+    assert_eq!(
+        serde_json::to_value(&summary.compile_summary)?,
+        serde_json::to_value(&full.compile_summary)?
+    );
+    assert_eq!(summary.compile_ids, full.compile_ids);
+    Ok(())
+}
+
+#[test]
+fn test_subcommands() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("parse")
+        .arg("tests/inputs/simple.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+    assert!(out_dir.join("index.html").exists());
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("diff")
+        .arg("tests/inputs/simple.log")
+        .arg("tests/inputs/comp_metrics.log");
+    cmd.assert().success().stdout(str::contains(
+        "Graph breaks                        0        2  +2 !\n",
+    ));
+
+    // Rank selection only makes sense for multi-rank reports
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/simple.log")
+        .arg("--ranks")
+        .arg("0")
+        .arg("--no-browser");
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("need --all-ranks-html"));
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("completions").arg("bash");
+    cmd.assert()
+        .success()
+        .stdout(str::contains("tlparse,multi-rank)"))
+        .stdout(str::contains("--all-ranks-html"))
+        .stdout(str::contains("complete -F _tlparse"));
+    Ok(())
+}
+
+#[test]
+fn test_multi_rank_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("multi-rank")
+        .arg("tests/inputs/multi_rank_logs")
+        .arg("--ranks")
+        .arg("0,2")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+    assert!(out_dir.join("rank_0/index.html").exists());
+    assert!(out_dir.join("rank_2/index.html").exists());
+    assert!(!out_dir.join("rank_1").exists());
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(landing.contains(r#"<a href="rank_2/index.html">"#));
+    assert!(!landing.contains(r#"<a href="rank_1/index.html">"#));
+
+    // Options of single-log reports aren't options of multi-rank
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("multi-rank")
+        .arg("tests/inputs/multi_rank_logs")
+        .arg("--latest")
+        .arg("--no-browser");
+    cmd.assert().failure();
+    Ok(())
+}
+
+#[test]
+fn test_serve() -> Result<(), Box<dyn std::error::Error>> {
+    use assert_cmd::cargo::CommandCargoExt;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+
+    let temp_dir = tempdir()?;
+    let report = temp_dir.path().join("report");
+    fs::create_dir_all(report.join("-_0_0_0"))?;
+    fs::write(report.join("index.html"), "<html>report</html>")?;
+    fs::write(report.join("-_0_0_0/graph.txt"), "graph")?;
+    fs::write(temp_dir.path().join("secret.txt"), "secret")?;
+    let mut server = std::process::Command::cargo_bin("tlparse")?
+        .arg("serve")
+        .arg(&report)
+        .arg("--port")
+        .arg("0")
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    // Serving DIR at http://127.0.0.1:PORT/
+    let mut line = String::new();
+    BufReader::new(server.stdout.take().unwrap()).read_line(&mut line)?;
+    let addr = line
+        .trim()
+        .rsplit_once("http://")
+        .and_then(|(_, a)| a.strip_suffix('/'))
+        .unwrap()
+        .to_string();
+    let get = |target: &str| -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(&addr)?;
+        write!(stream, "GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    };
+
+    // A connection that never sends its request doesn't keep the server from answering others
+    let _idle = TcpStream::connect(&addr)?;
+    let index = get("/")?;
+    let graph = get("/%2D_0_0_0/graph.txt?line=1")?;
+    let outside = get("/-_0_0_0/../../secret.txt")?;
+    server.kill()?;
+    server.wait()?;
+
+    assert!(index.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(index.contains("Content-Type: text/html"));
+    assert!(index.ends_with("<html>report</html>"));
+    assert!(graph.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(graph.ends_with("\r\n\r\ngraph"));
+    assert!(outside.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    Ok(())
+}
+
+#[test]
+fn test_extract_artifact() -> Result<(), Box<dyn std::error::Error>> {
+    let path = PathBuf::from("tests/inputs/comp_metrics.log");
+    // Any attempt of [1/0]
+    let output = tlparse::extract_artifact(&path, "dynamo_output_graph", Some(&"1/0".parse()?))?;
+    assert_eq!(output.len(), 1);
+    assert!(output[0].0.starts_with("-_1_0_1"));
+    assert!(output[0].1.contains("class GraphModule(torch.nn.Module)"));
+    // Named as in the report
+    let report = tlparse::parse_path(&path, &tlparse::ParseConfig::default())?;
+    assert!(report.iter().any(|(f, _)| *f == output[0].0));
+    assert_eq!(
+        tlparse::extract_artifact(&path, "dynamo_output_graph", None)?.len(),
+        3
+    );
+    let compile_id: tlparse::CompileId = "[!1/3/-_2]".parse()?;
+    assert_eq!(compile_id.to_string(), "[!1/3/-_2]");
+    assert!("3".parse::<tlparse::CompileId>().is_err());
+    assert!(tlparse::extract_artifact(&path, "no_such_parser", None)
+        .unwrap_err()
+        .to_string()
+        .contains("Unknown parser no_such_parser"));
+
+    let temp_dir = tempdir()?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("extract")
+        .arg(&path)
+        .arg("dynamo_output_graph")
+        .arg("--compile-id")
+        .arg("[2/0]")
+        .arg("-o")
+        .arg(temp_dir.path());
+    cmd.assert().success();
+    assert!(temp_dir
+        .path()
+        .join("-_2_0_0/dynamo_output_graph_0.txt")
+        .exists());
+    // Nothing else of the report is written
+    assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_progress_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let run = |progress: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("tlparse")?;
+        cmd.arg("tests/inputs/comp_metrics.log")
+            .arg("-o")
+            .arg(temp_dir.path())
+            .arg("--overwrite")
+            .arg("--no-browser")
+            .args(progress);
+        let output = cmd.assert().success().get_output().stderr.clone();
+        Ok(String::from_utf8(output)?)
+    };
+    // stderr is a pipe here, as in CI logs
+    assert!(!run(&[])?.contains('\x1b'));
+    assert!(!run(&["--quiet"])?.contains("KiB/s"));
+    assert!(run(&["--progress", "always"])?.contains("KiB/s"));
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_output() -> Result<(), Box<dyn std::error::Error>> {
+    fn read_tree(dir: &Path, root: &Path, files: &mut HashMap<PathBuf, Vec<u8>>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                read_tree(&path, root, files);
+            } else {
+                let name = path.strip_prefix(root).unwrap().to_path_buf();
+                files.insert(name, fs::read(&path).unwrap());
+            }
+        }
+    }
+
+    // Two separate runs, so that nothing the process keeps between them hides a difference
+    let temp_dir = tempdir()?;
+    let mut outputs = Vec::new();
+    for run in ["first", "second"] {
+        let out_dir = temp_dir.path().join(run);
+        let mut cmd = Command::cargo_bin("tlparse")?;
+        cmd.arg("tests/inputs/comp_metrics.log")
+            .arg("-o")
+            .arg(&out_dir)
+            .arg("--deterministic")
+            .arg("--no-browser");
+        cmd.assert().success();
+        let mut files = HashMap::new();
+        read_tree(&out_dir, &out_dir, &mut files);
+        outputs.push(files);
+    }
+    assert_eq!(
+        outputs[0].keys().collect::<HashSet<_>>(),
+        outputs[1].keys().collect::<HashSet<_>>()
+    );
+    for (name, content) in &outputs[0] {
+        assert!(&outputs[1][name] == content, "{} differs", name.display());
+    }
+
+    let files = &outputs[0];
+    // Timings and wall-clock times are left out
+    assert!(!files.contains_key(Path::new("parser_profile.json")));
+    let trend_record = String::from_utf8_lossy(&files[Path::new("trend_record.json")]);
+    assert!(!trend_record.contains("recorded_at"));
+    // Glog timestamps have no year, which is the current one unless the output is deterministic
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let current_year = 1970 + now.as_secs() / 31_556_952;
+    for (name, content) in files {
+        let content = String::from_utf8_lossy(content);
+        for year in [current_year - 1, current_year, current_year + 1] {
+            assert!(
+                !content.contains(&format!("{year}-")),
+                "{} has a timestamp of {year}",
+                name.display()
+            );
+        }
+    }
+    let raw_jsonl = String::from_utf8_lossy(&files[Path::new("raw.jsonl")]);
+    assert!(raw_jsonl.contains("\"timestamp\":\"2000-04-03T07:28:48.052000Z\""));
+    Ok(())
+}
+
+#[test]
+fn test_url_prefix() {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        url_prefix: Some("https://example.com/reports/1234/".to_string()),
+        ..Default::default()
+    };
+    let output: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &output[Path::new("index.html")];
+    assert!(index
+        .contains("href=\"https://example.com/reports/1234/-_0_0_1/compilation_metrics_0.html\""));
+    // In-page anchors stay as they are
+    assert!(index.contains("href='#[0/0]'"));
+    // Pages of compile ids are resolved against their <base>
+    let metrics = &output[Path::new("-_0_0_1/compilation_metrics_0.html")];
+    assert!(metrics.contains("href=\"https://example.com/reports/1234/theme.css\""));
+    assert!(
+        metrics.contains("href=\"https://example.com/reports/1234/-_0_0_1/dynamo_guards_0.html\"")
+    );
+}
+
+#[cfg(all(feature = "upload", unix))]
+#[test]
+fn test_upload() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Stands in for the AWS CLI, recording how it was called
+    let bin_dir = tempdir()?;
+    let aws = bin_dir.path().join("aws");
+    fs::write(
+        &aws,
+        format!(
+            "#!/bin/sh\necho \"$@\" > {}\n",
+            bin_dir.path().join("args").display()
+        ),
+    )?;
+    fs::set_permissions(&aws, fs::Permissions::from_mode(0o755))?;
+    let path = std::env::join_paths(std::iter::once(bin_dir.path().to_path_buf()).chain(
+        std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()),
+    ))?;
+
+    let temp_out = tempdir()?;
+    Command::cargo_bin("tlparse")?
+        .env("PATH", path)
+        .arg("tests/inputs/simple.log")
+        .args([
+            "--overwrite",
+            "--no-browser",
+            "--upload",
+            "s3://bucket/reports/1234/",
+            "-o",
+        ])
+        .arg(temp_out.path())
+        .assert()
+        .success()
+        .stdout(str::contains(
+            "Uploaded the report to https://bucket.s3.amazonaws.com/reports/1234/index.html",
+        ));
+    let args = fs::read_to_string(bin_dir.path().join("args"))?;
+    assert_eq!(
+        args.trim(),
+        format!(
+            "s3 cp --recursive --only-show-errors {} s3://bucket/reports/1234/",
+            temp_out.path().display()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_graph_dot() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        graph_dot: true,
+        ..Default::default()
+    };
+    let output: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let dot = &output[Path::new("-_0_0_0/dynamo_output_graph_0.dot")];
+    assert!(dot.starts_with("digraph G {"));
+    assert!(dot.contains("\"L_x_\" [label=\"L_x_\\nf32[1, 320, 128, 128]\", shape=ellipse];"));
+    assert!(dot.contains("\"out\" [label=\"out\\ntorch._C._nn.gelu\\nf32[1, 320, 128, 128]\"];"));
+    assert!(dot.contains("\"input_1\" -> \"out_1\";"));
+    assert!(dot.contains("\"out_1\" -> \"output\";"));
+    assert!(output.contains_key(Path::new("-_0_0_0/inductor_post_grad_graph_0.dot")));
+    assert!(output[Path::new("index.html")].contains("dynamo_output_graph_0.dot"));
+
+    let output = tlparse::parse_path(&path, &tlparse::ParseConfig::default()).unwrap();
+    assert!(!output
+        .iter()
+        .any(|(name, _)| name.extension() == Some("dot".as_ref())));
+}
+
+#[test]
+fn test_graph_stats() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let stats: serde_json::Value =
+        serde_json::from_str(&output[Path::new("graph_stats.json")]).unwrap();
+    let graph = |name: &str| {
+        let entry = stats
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|g| g["graph"] == name)
+            .unwrap();
+        assert_eq!(entry["compile_id"], "[0/0]");
+        &entry["stats"]
+    };
+    let dynamo_graph = graph("dynamo_output_graph");
+    assert_eq!(dynamo_graph["num_nodes"], 4);
+    assert_eq!(dynamo_graph["num_inputs"], 1);
+    assert_eq!(dynamo_graph["num_outputs"], 1);
+    assert_eq!(dynamo_graph["max_fan_out"], 1);
+    assert_eq!(
+        dynamo_graph["op_counts"],
+        serde_json::json!({
+            "test.baz": 1,
+            "torch._C._nn.gelu": 1,
+            "torch.nn.functional.dropout": 1,
+        })
+    );
+    let post_grad_graph = graph("inductor_post_grad_graph");
+    assert_eq!(post_grad_graph["op_counts"]["aten.mul.Tensor"], 5);
+    assert_eq!(post_grad_graph["max_fan_out"], 2);
+    assert!(output[Path::new("index.html")].contains(
+        "<span class=\"graph-size\" title=\"Nodes of dynamo_output_graph\">4 nodes</span>"
+    ));
+}
+
+#[test]
+fn test_duplicate_graphs() {
+    let log = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| l.contains("\"dynamo_output_graph\""))
+        .unwrap();
+    let end = start
+        + 1
+        + lines[start + 1..]
+            .iter()
+            .take_while(|l| l.starts_with('\t'))
+            .count();
+    let graph = lines[start..end].join("\n");
+    // Recompiled into the same graph, and into one that only renames a node
+    let identical = graph.replace("\"frame_compile_id\": 0", "\"frame_compile_id\": 1");
+    let renamed = graph
+        .replace("\"frame_compile_id\": 0", "\"frame_compile_id\": 2")
+        .replace("out_1", "res");
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("recompiles.log");
+    fs::write(&path, format!("{log}\n{identical}\n{renamed}\n")).unwrap();
+
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let duplicate_graphs = &output[Path::new("duplicate_graphs.html")];
+    assert!(duplicate_graphs.contains(
+        "<td> dynamo_output_graph </td> <td> Identical up to node names </td> <td> 3 </td> <td> [0/0], [0/1]<br>[0/2]<br> </td>"
+    ));
+    assert!(output[Path::new("index.html")]
+        .contains("1 graph(s) were produced by more than one compile id"));
+    let stats: serde_json::Value =
+        serde_json::from_str(&output[Path::new("graph_stats.json")]).unwrap();
+    let hashes: Vec<&serde_json::Value> = stats
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|g| g["graph"] == "dynamo_output_graph")
+        .map(|g| &g["stats"])
+        .collect();
+    assert_eq!(hashes[0]["hash"], hashes[1]["hash"]);
+    assert_ne!(hashes[0]["hash"], hashes[2]["hash"]);
+    assert_eq!(hashes[0]["structure_hash"], hashes[2]["structure_hash"]);
+}
+
+#[test]
+fn test_graph_stats_repeated_dumps() {
+    let log = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| l.contains("\"dynamo_output_graph\""))
+        .unwrap();
+    let end = start
+        + 1
+        + lines[start + 1..]
+            .iter()
+            .take_while(|l| l.starts_with('\t'))
+            .count();
+    // The same compile id dumps its graph again, with a node renamed
+    let renamed = lines[start..end].join("\n").replace("out_1", "res");
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("repeated.log");
+    fs::write(&path, format!("{log}\n{renamed}\n")).unwrap();
+
+    let config = tlparse::ParseConfig {
+        graph_dot: true,
+        ..Default::default()
+    };
+    let output: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let stats: serde_json::Value =
+        serde_json::from_str(&output[Path::new("graph_stats.json")]).unwrap();
+    let dumps: Vec<&serde_json::Value> = stats
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|g| g["graph"] == "dynamo_output_graph")
+        .map(|g| &g["stats"])
+        .collect();
+    assert_eq!(dumps.len(), 2);
+    assert_ne!(dumps[0]["hash"], dumps[1]["hash"]);
+    assert_eq!(dumps[0]["structure_hash"], dumps[1]["structure_hash"]);
+    // Each dump gets its own .dot file
+    let dots: Vec<&String> = output
+        .iter()
+        .filter(|(name, _)| {
+            name.to_string_lossy()
+                .starts_with("-_0_0_0/dynamo_output_graph")
+                && name.extension() == Some("dot".as_ref())
+        })
+        .map(|(_, dot)| dot)
+        .collect();
+    assert_eq!(dots.len(), 2);
+    assert!(dots.iter().any(|dot| dot.contains("\"res\"")));
+    // Repeating a graph within a compile id doesn't make it a duplicate
+    assert!(!output.contains_key(Path::new("duplicate_graphs.html")));
+}
+
+#[test]
+fn test_guard_failure_markdown() {
+    let log = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let guard_added = r#"V1206 15:18:21.000000 1500233 torch/fx/experimental/symbolic_shapes.py:6000] {"guard_added": {"expr": "Eq(s0, 1)", "prefix": "eval", "expr_node_id": 1, "user_stack": [], "stack": [], "frame_locals": {"loc": "out = F.gelu(x)", "locals": {"x": "Tensor(shape: torch.Size([1, 320, 128, 128]))", "y": "3"}, "symbols": {"s0": "L['x'].size()[0]"}}}, "frame_id": 0, "frame_compile_id": 0, "attempt": 0}"#;
+    let recompile = r#"V1206 15:18:23.000000 1500233 torch/_dynamo/utils.py:1045] {"compilation_metrics": {"co_name": "forward", "co_filename": "/data/test.py", "co_firstlineno": 11123, "recompile_reason": "0/0: tensor 'L['x']' size mismatch at index 0. expected 1, actual 2"}, "frame_id": 0, "frame_compile_id": 1, "attempt": 0}"#;
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("recompile.log");
+    fs::write(&path, format!("{log}\n{guard_added}\n{recompile}\n")).unwrap();
+
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let md = &output[Path::new("guard_failure_0.md")];
+    assert!(md.starts_with("### Recompile of `forward` [0/1]\n"));
+    assert!(md.contains(
+        "`forward` (`/data/test.py:11123`) was recompiled as [0/1] because guards of earlier compiles failed:"
+    ));
+    assert!(md.contains(
+        "Guard of [0/0] that failed:\n```\ntensor 'L['x']' size mismatch at index 0. expected 1, actual 2\n```"
+    ));
+    // Only the locals the guard mentions
+    assert!(md.contains("s0: L['x'].size()[0]\nx: Tensor(shape: torch.Size([1, 320, 128, 128]))\n"));
+    assert!(!md.contains("y: 3"));
+    assert!(output[Path::new("index.html")].contains("<a href='guard_failure_0.md'>[0/1]</a>"));
+    assert!(!output.contains_key(Path::new("guard_failure_1.md")));
+}
+
+#[test]
+fn test_provenance_tracking_kernel_stack_traces() {
+    let path = Path::new("tests/inputs/inductor_provenance_jit_debug_handle_log.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let html_content = &map[Path::new("provenance_tracking_-_0_0_0.html")];
+    let script_start = html_content
+        .find(r#"<script id="kernelStackTraces" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find('>').unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let traces: serde_json::Value =
+        serde_json::from_str(&html_content[json_start..json_end]).unwrap();
+    let trace = traces["triton_poi_fused_addmm_relu_sigmoid_threshold_backward_0:1"][0]
+        .as_str()
+        .unwrap();
+    assert!(trace.contains("test_provenance_tracing.py\", line 81, in forward"));
+    assert!(html_content.contains(r#"<div id="stackTracePanel" class="stack-trace-panel" hidden>"#));
+
+    // Nothing to show without the artifact
+    let path = Path::new("tests/inputs/inductor_provenance_jit_log.txt").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let html_content = &map[Path::new("provenance_tracking_-_0_0_0.html")];
+    assert!(html_content
+        .contains("<script id=\"kernelStackTraces\" type=\"application/json\">\n        {}\n"));
+}
+
+#[test]
+fn test_provenance_tracking_auto_enabled() {
+    let path = Path::new("tests/inputs/inductor_provenance_jit_log.txt").to_path_buf();
+    // Generated whenever the log has node mappings
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    assert!(map.contains_key(Path::new("provenance_tracking_-_0_0_0.html")));
+    assert!(map[Path::new("index.html")].contains("<h2>Provenance Tracking</h2>"));
+
+    let config = tlparse::ParseConfig {
+        no_inductor_provenance: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(Path::new("provenance_tracking_-_0_0_0.html")));
+    assert!(!map[Path::new("index.html")].contains("<h2>Provenance Tracking</h2>"));
+
+    // Logs without node mappings don't get the pages unless asked for
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    assert!(!map
+        .keys()
+        .any(|p| p.to_string_lossy().starts_with("provenance_tracking_")));
+}
+
+#[test]
+fn test_provenance_tracking_unmapped_nodes() {
+    let log = fs::read_to_string("tests/inputs/inductor_provenance_jit_log.txt").unwrap();
+    // Mappings for a node and a kernel that aren't in the graphs or code
+    let log = log.replacen(
+        "{\"preToPost\": {\"x\": [\"permute\", \"addmm\"],",
+        "{\"preToPost\": {\"x\": [\"permute\", \"addmm\", \"missing_node\"],",
+        1,
+    );
+    let log = log.replacen(
+        "\"cppCodeToPost\": {",
+        "\"cppCodeToPost\": {\"missing_kernel:7\": [\"relu\"], ",
+        1,
+    );
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("provenance.log");
+    fs::write(&path, log).unwrap();
+
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let html_content = &map[Path::new("provenance_tracking_-_0_0_0.html")];
+    assert!(html_content.contains("<summary>2 node(s) or kernel(s) of the provenance mappings"));
+    assert!(
+        html_content.contains("<p>Post-grad graph nodes:</p>\n        <pre>missing_node\n</pre>")
+    );
+    assert!(html_content.contains("<p>Kernels:</p>\n        <pre>missing_kernel:7\n</pre>"));
+    assert!(!html_content.contains("<p>Pre-grad graph nodes:</p>"));
+
+    // Logs whose mappings all resolve have no diagnostics
+    let path = Path::new("tests/inputs/inductor_provenance_jit_log.txt").to_path_buf();
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    assert!(
+        !map[Path::new("provenance_tracking_-_0_0_0.html")].contains("provenance-diagnostics\">")
+    );
+}
+
+#[test]
+fn test_provenance_tracking_multiple_code_files() {
+    let log = fs::read_to_string("tests/inputs/inductor_provenance_jit_log.txt").unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| l.contains("{\"inductor_output_code\""))
+        .unwrap();
+    let end = start
+        + 1
+        + lines[start + 1..]
+            .iter()
+            .take_while(|l| l.starts_with('\t'))
+            .count();
+    // The same code again, as if for a second graph partition
+    let partition = lines[start..end].join("\n").replace(
+        "celvaalwlz2ulme27hh3jttevphbo7cueohqpbbvxp4oigd66bqb.py",
+        "partition_1.py",
+    );
+    let log = format!(
+        "{}\n{partition}\n{}\n",
+        lines[..end].join("\n"),
+        lines[end..].join("\n")
+    );
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("partitions.log");
+    fs::write(&path, log).unwrap();
+
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let html_content = &map[Path::new("provenance_tracking_-_0_0_0.html")];
+    assert!(html_content.contains(
+        "<button class=\"code-tab active\" data-index=\"0\">inductor_output_code_celvaalwlz2ulme27hh3jttevphbo7cueohqpbbvxp4oigd66bqb_0.html</button>"
+    ));
+    assert!(html_content.contains(
+        "<button class=\"code-tab\" data-index=\"1\">inductor_output_code_partition_1_0.html</button>"
+    ));
+    assert_eq!(
+        html_content
+            .matches("<div class=\"code-file\" hidden>")
+            .count(),
+        1
+    );
+
+    let script_start = html_content
+        .find(r#"<script id="codeFileLineMappings" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find('>').unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let mappings: serde_json::Value =
+        serde_json::from_str(&html_content[json_start..json_end]).unwrap();
+    let mappings = mappings.as_array().unwrap();
+    assert_eq!(mappings.len(), 2);
+    // Both files have the kernels, so both map to the post-grad graph
+    assert!(!mappings[1]["pyCodeToPost"].as_object().unwrap().is_empty());
+    assert_eq!(mappings[0], mappings[1]);
+}
+
+#[test]
+fn test_provenance_tracking_kernel_sources() {
+    let path = Path::new("tests/inputs/inductor_provenance_jit_cuda_log.txt").to_path_buf();
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let html_content = &map[Path::new("provenance_tracking_-_0_0_0.html")];
+    assert!(html_content.contains(r#"<div id="kernelSource" class="editor"></div>"#));
+    let script_start = html_content
+        .find(r#"<script id="kernelSources" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find('>').unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let kernels: serde_json::Value =
+        serde_json::from_str(&html_content[json_start..json_end]).unwrap();
+    let source = kernels["sources"]["triton_poi_fused_mul_1"]
+        .as_str()
+        .unwrap();
+    assert!(source.starts_with("import triton\n"));
+    assert!(source.contains("def triton_poi_fused_mul_1("));
+    // Up to the end of the kernel
+    assert!(!source.contains("'''"));
+    assert!(!source.contains("triton_poi_fused_addmm_gelu_2"));
+    assert_eq!(
+        kernels["kernelToPost"]["triton_poi_fused_mul_1"],
+        serde_json::json!([17])
+    );
+}
+
+#[test]
+fn test_module_map() {
+    let path = Path::new("tests/inputs/inductor_provenance_jit_log.txt").to_path_buf();
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let module_map = &map[Path::new("module_map.html")];
+    // The linear's weight and bias were lifted from fc1
+    assert!(module_map.contains(r#"<span class="module" data-module="fc1""#));
+    assert!(module_map.contains(r#"<code class="module-nodes">l_self_modules_fc1_parameters_weight_, l_self_modules_fc1_parameters_bias_, x</code>"#));
+    assert!(map[Path::new("index.html")].contains("module_map.html"));
+    let provenance = &map[Path::new("provenance_tracking_-_0_0_0.html")];
+    assert!(provenance.contains(r#"<details class="module-pane" open>"#));
+    assert!(provenance.contains(r#"data-module="fc1""#));
+}
+
+#[test]
+fn test_directory_layout_nested() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        directory_layout: tlparse::DirectoryLayout::Nested,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(map.contains_key(Path::new(
+        "frame_0/compile_0/attempt_0/dynamo_output_graph_0.txt"
+    )));
+    assert!(!map.keys().any(|p| p.starts_with("-_0_0_0")));
+    assert!(map[Path::new("index.html")]
+        .contains(r#"href="frame_0/compile_0/attempt_0/dynamo_output_graph_0.txt""#));
+    // Pages of compile ids link back to the root of the report from deeper down
+    let metrics = &map[Path::new("frame_0/compile_0/attempt_0/compilation_metrics_0.html")];
+    assert!(metrics.contains(r#"<base href="../../../">"#));
+    let directory: serde_json::Value =
+        serde_json::from_str(&map[Path::new("compile_directory.json")]).unwrap();
+    assert_eq!(
+        directory["[0/0]"]["directory"],
+        "frame_0/compile_0/attempt_0"
+    );
+    assert_eq!(directory["[0/0]"]["layout"], "nested");
+    assert_eq!(
+        directory["[0/0]"]["artifacts"][0]["url"],
+        "frame_0/compile_0/attempt_0/dynamo_output_graph_0.txt"
+    );
+
+    // Compiled autograd compile ids are one level deeper, and link across to the others
+    let path = Path::new("tests/inputs/compiled_autograd.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let metrics = &map
+        [Path::new("compiled_autograd_0/frame_2/compile_0/attempt_0/compilation_metrics_0.html")];
+    assert!(metrics.contains(r#"<base href="../../../../">"#));
+    let forward = &map[Path::new("frame_0/compile_0/attempt_0/compiled_autograd_0.html")];
+    assert!(forward.contains(r#"<link rel="stylesheet" href="../../../theme.css">"#));
+    assert!(forward.contains(
+        r#"<a href="../../../compiled_autograd_0/frame_2/compile_0/attempt_0/compilation_metrics_0.html">"#
+    ));
+}
+
+#[test]
+fn test_validate_output() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        validate_output: true,
+        ..Default::default()
+    };
+    let mut output = tlparse::parse_path(&path, &config).unwrap();
+    let position = |output: &[(PathBuf, String)], name: &str| {
+        output
+            .iter()
+            .position(|(path, _)| path == Path::new(name))
+            .unwrap()
+    };
+    let compile_directory = position(&output, "compile_directory.json");
+    let directory: serde_json::Value = serde_json::from_str(&output[compile_directory].1).unwrap();
+    assert_eq!(directory["schema_version"], 1);
+    assert!(directory["[0/0]"].get("schema_version").is_none());
+
+    // A field that downstream tools don't know about
+    let original = output[compile_directory].1.clone();
+    output[compile_directory].1 = original.replacen("\"artifacts\"", "\"files\"", 1);
+    let err = tlparse::validate_output(&output).unwrap_err().to_string();
+    assert!(err.contains("compile_directory.json"));
+    output[compile_directory].1 = original;
+    tlparse::validate_output(&output).unwrap();
+
+    // Every file with a schema is checked against it, not just for being JSON
+    let compile_summary = position(&output, "compile_summary.json");
+    output[compile_summary].1 = output[compile_summary]
+        .1
+        .replacen('{', "{\"unexpected\": 1, ", 1);
+    let err = tlparse::validate_output(&output).unwrap_err().to_string();
+    assert!(err.contains("compile_summary.json"));
+
+    // Compile ids are in log order, and not sorted
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(
+        &PathBuf::from("tests/inputs/compiled_autograd.log"),
+        &config,
+    )
+    .unwrap()
+    .into_iter()
+    .collect();
+    let ordered = &map[Path::new("compile_directory.json")];
+    let positions: Vec<usize> = ["\"[0/0]\"", "\"[1/0]\"", "\"[!0/2/0]\""]
+        .iter()
+        .map(|cid| ordered.find(cid).unwrap())
+        .collect();
+    assert!(positions.is_sorted());
+}
+
+#[test]
+fn test_metrics_prom() {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let summary: tlparse::CompileSummary =
+        serde_json::from_str(&map[Path::new("compile_summary.json")]).unwrap();
+    let prom = &map[Path::new("metrics.prom")];
+    assert!(prom.contains("# TYPE tlparse_compiles_total counter\n"));
+    assert!(prom.contains(&format!(
+        "\ntlparse_compiles_total {}\n",
+        summary.total_compiles
+    )));
+    assert!(prom.contains(&format!(
+        "\ntlparse_compile_seconds_total {}\n",
+        summary.cumulative_compile_time_s
+    )));
+    // Every sample is preceded by its family's HELP and TYPE
+    assert_eq!(
+        prom.matches("# TYPE").count(),
+        prom.matches("# HELP").count()
+    );
+
+    let ranks = tlparse::prometheus_metrics(&[(Some(0), &summary), (Some(1), &summary)]);
+    assert!(ranks.contains("tlparse_recompiles_total{rank=\"1\"}"));
+}
+
+#[test]
+fn test_otel_spans() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        otel_spans: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let request: serde_json::Value =
+        serde_json::from_str(&map[Path::new("otel_spans.json")]).unwrap();
+    let spans = request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+        .as_array()
+        .unwrap();
+    let compile = spans
+        .iter()
+        .find(|s| s["name"] == "torch.compile [0/0]")
+        .unwrap();
+    assert!(compile.get("parentSpanId").is_none());
+    let trace_id = compile["traceId"].as_str().unwrap();
+    assert_eq!(trace_id.len(), 32);
+    // Phases of the chromium events are nested under their compile id, down to the innermost
+    let lowering = spans
+        .iter()
+        .find(|s| s["name"] == "GraphLowering.codegen")
+        .unwrap();
+    let scheduler = spans
+        .iter()
+        .find(|s| s["name"] == "Scheduler.codegen")
+        .unwrap();
+    assert_eq!(scheduler["parentSpanId"], lowering["spanId"]);
+    assert!(spans.iter().all(|s| s["traceId"] == trace_id));
+    let time = |s: &serde_json::Value, key: &str| s[key].as_str().unwrap().parse::<u64>().unwrap();
+    assert!(time(scheduler, "startTimeUnixNano") >= time(lowering, "startTimeUnixNano"));
+    assert!(time(scheduler, "endTimeUnixNano") <= time(lowering, "endTimeUnixNano"));
+    assert!(
+        !tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .iter()
+            .any(|(path, _)| path == Path::new("otel_spans.json"))
+    );
+
+    // Ranks compile the same compile ids at the same time, but their spans are their own
+    let rank_span_ids = |rank: u32| -> HashSet<String> {
+        let config = tlparse::ParseConfig {
+            otel_spans: true,
+            rank: Some(rank),
+            ..Default::default()
+        };
+        let output = tlparse::parse_path(&path, &config).unwrap();
+        let (_, spans) = output
+            .iter()
+            .find(|(path, _)| path == Path::new("otel_spans.json"))
+            .unwrap();
+        let request: serde_json::Value = serde_json::from_str(spans).unwrap();
+        request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|s| [&s["spanId"], &s["traceId"]])
+            .map(|id| id.as_str().unwrap().to_string())
+            .collect()
+    };
+    assert!(rank_span_ids(0).is_disjoint(&rank_span_ids(1)));
+}
+
+#[cfg(feature = "otlp")]
+#[test]
+fn test_otlp_endpoint() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let endpoint = format!("http://{}", listener.local_addr()?);
+    // A collector that takes one request
+    let collector = std::thread::spawn(move || -> std::io::Result<(String, String)> {
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header)?;
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}")?;
+        Ok((request_line, String::from_utf8(body).unwrap()))
+    });
+
+    let temp_dir = tempdir()?;
+    Command::cargo_bin("tlparse")?
+        .arg("tests/inputs/simple.log")
+        .args(["--overwrite", "--no-browser", "-o"])
+        .arg(temp_dir.path())
+        .arg("--otlp-endpoint")
+        .arg(&endpoint)
+        .assert()
+        .success()
+        .stdout(str::contains(format!("to {endpoint}")));
+    let (request_line, body) = collector.join().unwrap()?;
+    assert!(request_line.starts_with("POST /v1/traces "));
+    assert_eq!(
+        body,
+        fs::read_to_string(temp_dir.path().join("otel_spans.json"))?
+    );
+
+    // Nothing listens on the port anymore
+    Command::cargo_bin("tlparse")?
+        .arg("tests/inputs/simple.log")
+        .args(["--overwrite", "--no-browser", "-o"])
+        .arg(temp_dir.path())
+        .arg("--otlp-endpoint")
+        .arg(&endpoint)
+        .assert()
+        .failure()
+        .stderr(str::contains("Sending the spans to"));
+    Ok(())
+}
+
+#[test]
+fn test_metrics_csv() {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        metrics_csv: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let csv = &map[Path::new("compilation_metrics.csv")];
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+    assert_eq!(header[..2], ["compile_id", "co_name"]);
+    assert!(header.contains(&"entire_frame_compile_time_s"));
+    assert!(header.contains(&"guard_count"));
+    // Strings other than the compile id and co_name aren't columns
+    assert!(!header.contains(&"co_filename"));
+    let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+    assert_eq!(row.len(), header.len());
+    assert_eq!(row[..2], ["[0/0]", "fn"]);
+    let guard_count = header.iter().position(|c| *c == "guard_count").unwrap();
+    assert_eq!(row[guard_count], "9");
+}
+
+#[test]
+fn test_metrics_csv_all_ranks() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/multi_rank_logs")
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .arg("--metrics-csv")
+        .arg(temp_dir.path().join("metrics.csv"));
+    cmd.assert().success();
+    for rank in 0..4 {
+        let csv = fs::read_to_string(temp_dir.path().join(format!("metrics_rank_{rank}.csv")))?;
+        assert_eq!(
+            csv,
+            fs::read_to_string(out_dir.join(format!("rank_{rank}/compilation_metrics.csv")))?
+        );
+        assert!(csv.starts_with("compile_id,co_name,"));
+    }
+    assert!(!temp_dir.path().join("metrics.csv").exists());
+    Ok(())
+}
+
+#[test]
+fn test_append_trend() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+    let trends = temp_dir.path().join("trends.jsonl");
+    for job_id in ["1", "2"] {
+        let mut cmd = Command::cargo_bin("tlparse")?;
+        cmd.arg("tests/inputs/comp_metrics.log")
+            .arg("--overwrite")
+            .arg("-o")
+            .arg(&out_dir)
+            .arg("--no-browser")
+            .arg("--git-sha")
+            .arg("abc123")
+            .arg("--job-id")
+            .arg(job_id)
+            .arg("--append-trend")
+            .arg(&trends);
+        cmd.assert().success();
+    }
+    let record = fs::read_to_string(out_dir.join("trend_record.json"))?;
+    assert_eq!(record.lines().count(), 1);
+    let lines: Vec<serde_json::Value> = fs::read_to_string(&trends)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["job_id"], "1");
+    assert_eq!(lines[1]["job_id"], "2");
+    assert_eq!(lines[1]["git_sha"], "abc123");
+    // comp_metrics.log restarts twice
+    assert_eq!(lines[1]["graph_breaks"], 2);
+    assert!(lines[1]["recorded_at"].is_u64());
+    Ok(())
+}
+
+#[test]
+fn test_append_trend_all_ranks() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+    let trends = temp_dir.path().join("trends.jsonl");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/multi_rank_logs")
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .arg("--job-id")
+        .arg("1")
+        .arg("--append-trend")
+        .arg(&trends);
+    cmd.assert().success();
+    let lines: Vec<serde_json::Value> = fs::read_to_string(&trends)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let ranks: Vec<&serde_json::Value> = lines.iter().map(|l| &l["rank"]).collect();
+    assert_eq!(ranks, [0, 1, 2, 3]);
+    assert!(lines.iter().all(|l| l["job_id"] == "1"));
+    Ok(())
+}
+
+#[test]
+fn test_guard_diff() {
+    let log = fs::read_to_string("tests/inputs/comp_metrics.log").unwrap();
+    // The guards of [0/0_1], recompiled as [0/1] with a guard on a different size
+    let guards: Vec<&str> = log
+        .lines()
+        .skip_while(|line| !line.contains(r#"{"dynamo_guards": {}, "frame_id": 0"#))
+        .enumerate()
+        .take_while(|(i, line)| *i == 0 || line.starts_with('\t'))
+        .map(|(_, line)| line)
+        .collect();
+    let recompile = guards
+        .join("\n")
+        .replace(
+            r#""frame_compile_id": 0, "attempt": 1"#,
+            r#""frame_compile_id": 1, "attempt": 0"#,
+        )
+        .replace("size=[4, 4]", "size=[8, 4]");
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("recompile.log");
+    fs::write(&path, format!("{log}\n{recompile}\n")).unwrap();
+
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let diff = &output[Path::new("-_0_1_0/guard_diff.html")];
+    assert!(diff.contains("[0/0_1]"));
+    assert!(diff.contains("size=[8, 4]"));
+    assert!(diff.contains("size=[4, 4]"));
+    assert!(diff.contains("href='../-_0_0_1/dynamo_guards"));
+    // The first compile of a frame has nothing to diff against
+    assert!(!prefix_exists(&output, "-_0_0_1/guard_diff.html"));
+    assert!(!prefix_exists(&output, "-_1_0_1/guard_diff.html"));
+}
+
+#[test]
+fn test_shape_churn() {
+    let log = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let specialization = |frame_id: u32, frame_compile_id: u32, value: u32| {
+        format!(
+            r#"V1206 15:18:22.000000 1500233 torch/fx/experimental/symbolic_shapes.py:6000] {{"symbolic_shape_specialization": {{"symbol": "s0", "sources": ["L['x'].size()[0]"], "value": "{value}", "reason": "Eq(s0, {value})", "stack": [], "user_stack": []}}, "frame_id": {frame_id}, "frame_compile_id": {frame_compile_id}, "attempt": 0}}"#
+        )
+    };
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("churn.log");
+    fs::write(
+        &path,
+        format!(
+            "{log}\n{}\n{}\n{}\n{}\n",
+            specialization(0, 0, 8),
+            specialization(0, 1, 16),
+            specialization(0, 2, 8),
+            // The x of another frame is another tensor
+            specialization(1, 0, 32)
+        ),
+    )
+    .unwrap();
+
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let index = &output[Path::new("index.html")];
+    assert!(index.contains("Dynamic Shape Churn"));
+    assert!(index.contains("<td> 0 </td> <td> <code>L[&#39;x&#39;].size()[0]</code> </td>"));
+    assert!(index.contains(
+        "<code>8</code> (<a href='#[0/0]'>[0/0]</a> <a href='#[0/2]'>[0/2]</a>), <code>16</code> (<a href='#[0/1]'>[0/1]</a>) </td>"
+    ));
+    assert!(!index.contains("<td> 1 </td> <td> <code>L[&#39;x&#39;].size()[0]</code>"));
+    assert!(index.contains("torch._dynamo.mark_dynamic(x, 0)"));
+}
+
+#[test]
+fn test_inductor_config() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let mut baseline = tlparse::CompileSummary::default();
+    baseline
+        .config
+        .insert("inductor_config[debug]".to_string(), "False".to_string());
+    let config = tlparse::ParseConfig {
+        baseline: Some(baseline.clone()),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let page = &map[Path::new("-_0_0_0/inductor_config.html")];
+    assert!(page
+        .contains("<td> <code>inductor_config[debug]</code> </td> <td> <code>True</code> </td>"));
+    let comparison = &map[Path::new("baseline_comparison.html")];
+    assert!(comparison.contains("Config Changes"));
+    assert!(comparison.contains(
+        "<tr> <td> <code>inductor_config[debug]</code> </td> <td> <code>False</code> </td> <td> <code>True</code> </td> </tr>"
+    ));
+
+    // Diffing against the compile_summary.json of an earlier run
+    let temp_dir = tempdir()?;
+    let baseline_path = temp_dir.path().join("compile_summary.json");
+    fs::write(&baseline_path, serde_json::to_string(&baseline)?)?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("diff").arg(&baseline_path).arg(&path);
+    cmd.assert().success().stdout(str::contains(
+        "Config changes\n  inductor_config[debug]: False -> True\n",
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_findings() {
+    let log = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let recompile = |frame_compile_id: u32, guard_count: u32, compile_time_s: f64| {
+        format!(
+            r#"V1206 15:18:23.000000 1500233 torch/_dynamo/utils.py:1045] {{"compilation_metrics": {{"co_name": "forward", "co_filename": "/data/test.py", "co_firstlineno": 11123, "guard_count": {guard_count}, "entire_frame_compile_time_s": {compile_time_s}}}, "frame_id": 0, "frame_compile_id": {frame_compile_id}, "attempt": 0}}"#
+        )
+    };
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("findings.log");
+    fs::write(
+        &path,
+        format!(
+            "{log}\n{}\n{}\n{}\n",
+            recompile(1, 1500, 0.1),
+            recompile(2, 10, 30.0),
+            recompile(3, 10, 0.2)
+        ),
+    )
+    .unwrap();
+
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let index = &output[Path::new("index.html")];
+    let findings =
+        &index[index.find("<h2>Findings</h2>").unwrap()..index.find("<h2>Summary</h2>").unwrap()];
+    assert!(findings.contains("<strong>`forward` was compiled 4 times</strong> (medium)"));
+    assert!(findings.contains("<strong>[0/1] installed 1500 guards</strong> (medium)"));
+    assert!(findings.contains("<strong>[0/2] took 30.0 s to compile</strong> (medium)"));
+    assert!(findings.contains("<a href='-_0_2_0/compilation_metrics"));
+
+    // Nothing to find in a single quick compile
+    let output: HashMap<PathBuf, String> = tlparse::parse_path(
+        &PathBuf::from("tests/inputs/simple.log"),
+        &tlparse::ParseConfig::default(),
+    )
+    .unwrap()
+    .into_iter()
+    .collect();
+    assert!(!output[Path::new("index.html")].contains("<h2>Findings</h2>"));
+}
+
+#[test]
+fn test_completeness_hints() -> Result<(), Box<dyn std::error::Error>> {
+    // --inductor-provenance without node mappings gets a hint instead of empty pages
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&PathBuf::from("tests/inputs/simple.log"), &config)?
+            .into_iter()
+            .collect();
+    assert!(!output
+        .keys()
+        .any(|p| p.to_string_lossy().contains("provenance_tracking")));
+    let index = &output[Path::new("index.html")];
+    assert!(index.contains("<h2>Missing Artifacts</h2>"));
+    assert!(index.contains("torch._inductor.config.trace.provenance_tracking"));
+
+    // Compilation metrics alone can't show what was compiled
+    let temp_dir = tempdir()?;
+    let path = temp_dir.path().join("metrics_only.log");
+    let log = fs::read_to_string("tests/inputs/comp_metrics.log")?;
+    let metrics_only: Vec<&str> = log
+        .lines()
+        .filter(|l| l.contains("\"compilation_metrics\""))
+        .collect();
+    fs::write(&path, metrics_only.join("\n"))?;
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())?
+            .into_iter()
+            .collect();
+    let index = &output[Path::new("index.html")];
+    assert!(index.contains("<strong>The log has compilation metrics but no graphs.</strong>"));
+    assert!(index.contains("<strong>The log has no dynamo_start records.</strong>"));
+    assert!(!index.contains("Provenance tracking was requested"));
+    Ok(())
+}
+
+#[test]
+fn test_first_failure() -> Result<(), Box<dyn std::error::Error>> {
+    let log = fs::read_to_string("tests/inputs/comp_failure.log")?;
+    let later = r#"V1206 15:20:15.000000 1543231 torch/_dynamo/utils.py:1045] {"compilation_metrics": {"co_name": "later", "co_filename": "/data/test.py", "co_firstlineno": 1}, "frame_id": 1, "frame_compile_id": 0, "attempt": 0}"#;
+    let temp_dir = tempdir()?;
+    let path = temp_dir.path().join("failure.log");
+    fs::write(&path, format!("{log}\n{later}\n"))?;
+
+    let config = tlparse::ParseConfig {
+        first_failure: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let page = &map[Path::new("first_failure.html")];
+    assert!(page.contains("<h1>First Failure [0/0]</h1>"));
+    assert!(page.contains("<code>BackendCompilerFailed</code>"));
+    assert!(page.contains("<a href='-_0_0_0/dynamo_error_0.txt'>"));
+    assert!(map[Path::new("index.html")].contains("href='first_failure.html'"));
+    // Nothing after the failure was parsed
+    assert!(!prefix_exists(&map, "-_1_0_0"));
+
+    let map: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())?
+            .into_iter()
+            .collect();
+    assert!(prefix_exists(&map, "-_1_0_0"));
+    assert!(!map.contains_key(Path::new("first_failure.html")));
+    Ok(())
+}
+
+#[test]
+fn test_log_fragments() -> Result<(), Box<dyn std::error::Error>> {
+    let log = fs::read_to_string("tests/inputs/simple.log")?;
+    let lines: Vec<&str> = log.lines().collect();
+    // Named so that the later fragment sorts first
+    let temp_in = tempdir()?;
+    fs::write(temp_in.path().join("trace.b.log"), lines[..913].join("\n"))?;
+    fs::write(temp_in.path().join("trace.a.log"), lines[913..].join("\n"))?;
+
+    let temp_out = tempdir()?;
+    let whole = temp_out.path().join("whole");
+    Command::cargo_bin("tlparse")?
+        .arg("tests/inputs/simple.log")
+        .args(["--no-browser", "-o"])
+        .arg(&whole)
+        .assert()
+        .success();
+    let fragments = temp_out.path().join("fragments");
+    Command::cargo_bin("tlparse")?
+        .arg(temp_in.path().join("trace.*.log"))
+        .args(["--no-browser", "-o"])
+        .arg(&fragments)
+        .assert()
+        .success();
+    let directory = |out: &Path| fs::read_to_string(out.join("compile_directory.json")).unwrap();
+    assert_eq!(directory(&fragments), directory(&whole));
+
+    // Fragments can also be listed one by one, in any order
+    let listed = temp_out.path().join("listed");
+    Command::cargo_bin("tlparse")?
+        .arg(temp_in.path().join("trace.a.log"))
+        .arg(temp_in.path().join("trace.b.log"))
+        .args(["--no-browser", "-o"])
+        .arg(&listed)
+        .assert()
+        .success();
+    assert_eq!(directory(&listed), directory(&whole));
+    // The fragments are read in place, as one log with continuing line numbers
+    let raw_log = |out: &Path| fs::read_to_string(out.join("raw.log")).unwrap();
+    assert_eq!(raw_log(&listed).trim_end(), raw_log(&whole).trim_end());
+
+    Command::cargo_bin("tlparse")?
+        .arg(temp_in.path().join("nothing.*.log"))
+        .arg("--no-browser")
+        .assert()
+        .failure()
+        .stderr(str::contains("No files match"));
+    Ok(())
+}
+
+#[test]
+fn test_truncated_log() -> Result<(), Box<dyn std::error::Error>> {
+    let log = fs::read_to_string("tests/inputs/comp_metrics.log")?;
+    let lines: Vec<&str> = log.lines().collect();
+    // Line 25 is the envelope of the dynamo guards of [0/0_1], followed by their payload
+    let mid_payload = lines[..28].join("\n") + "\n";
+    let mid_envelope = lines[..24].join("\n") + "\n" + &lines[24][..120];
+    let temp_dir = tempdir()?;
+    for (name, truncated) in [("mid_payload", mid_payload), ("mid_envelope", mid_envelope)] {
+        let path = temp_dir.path().join(format!("{name}.log"));
+        fs::write(&path, truncated)?;
+        let (output, summary) =
+            tlparse::parse_path_with_summary(&path, &tlparse::ParseConfig::default())?;
+        assert_eq!(summary.stats.truncated_at_eof, 1, "{name}");
+        assert_eq!(summary.stats.fail_json, 0, "{name}");
+        assert_eq!(summary.stats.fail_payload_md5, 0, "{name}");
+        let map: HashMap<PathBuf, String> = output.into_iter().collect();
+        assert!(map[Path::new("index.html")]
+            .contains("it ends in the middle of the envelope on line 25"));
+        assert!(prefix_exists(&map, "-_0_0_1/dynamo_output_graph"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_invalid_utf8() -> Result<(), Box<dyn std::error::Error>> {
+    let log = fs::read("tests/inputs/simple.log")?;
+    let mut lines: Vec<Vec<u8>> = log.split(|b| *b == b'\n').map(|l| l.to_vec()).collect();
+    // Corrupt a line of the payload of the first dynamo_output_graph
+    let corrupted = lines
+        .iter()
+        .position(|l| l == b"\t        l_x_ = L_x_")
+        .unwrap();
+    lines[corrupted].extend_from_slice(b" \xff");
+    // and hash the payload as logged, like PyTorch would have
+    let envelope = (0..corrupted)
+        .rev()
+        .find(|i| !lines[*i].starts_with(b"\t"))
+        .unwrap();
+    let payload: Vec<&[u8]> = lines[envelope + 1..]
+        .iter()
+        .take_while(|l| l.starts_with(b"\t"))
+        .map(|l| &l[1..])
+        .collect();
+    let md5 = md5_hex(&payload.join(&b'\n'));
+    let envelope_line = String::from_utf8(lines[envelope].clone())?;
+    let (before, after) = envelope_line.split_once("\"has_payload\": \"").unwrap();
+    lines[envelope] = format!("{before}\"has_payload\": \"{md5}{}", &after[32..]).into_bytes();
+    let temp_dir = tempdir()?;
+    let path = temp_dir.path().join("invalid_utf8.log");
+    fs::write(&path, lines.join(&b'\n'))?;
+
+    let (output, summary) =
+        tlparse::parse_path_with_summary(&path, &tlparse::ParseConfig::default())?;
+    assert_eq!(summary.stats.invalid_utf8, 1);
+    assert_eq!(summary.stats.invalid_utf8_payloads, 1);
+    assert_eq!(summary.stats.fail_payload_md5, 0);
+    let map: HashMap<PathBuf, String> = output.into_iter().collect();
+    // The line is kept, with the bytes replaced
+    let graph = map
+        .iter()
+        .find(|(f, _)| {
+            f.starts_with("-_0_0_0") && f.to_string_lossy().contains("dynamo_output_graph")
+        })
+        .map(|(_, content)| content)
+        .unwrap();
+    assert!(graph.contains("l_x_ = L_x_ \u{FFFD}"));
+    assert!(map[Path::new("raw.log")].contains('\u{FFFD}'));
+    Ok(())
+}
+
+// A pipe can't be memory-mapped, so the log is read into memory instead
+#[cfg(unix)]
+#[test]
+fn test_parse_pipe() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("/dev/stdin")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .write_stdin(fs::read("tests/inputs/simple.log")?);
+    cmd.assert().success();
+
+    let raw_log = fs::read_to_string(out_dir.join("raw.log"))?;
+    assert_eq!(raw_log, fs::read_to_string("tests/inputs/simple.log")?);
+    let index = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(index.contains("dynamo_output_graph"));
+    Ok(())
+}
+
+#[test]
+fn test_raw_jsonl_filenames() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        raw_jsonl_filenames: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let raw_jsonl = &map[&PathBuf::from("raw.jsonl")];
+    let dynamo_start: serde_json::Value = serde_json::from_str(
+        raw_jsonl
+            .lines()
+            .find(|l| l.contains("\"dynamo_start\""))
+            .unwrap(),
+    )?;
+    let frames = dynamo_start["dynamo_start"]["stack"].as_array().unwrap();
+    assert!(!frames.is_empty());
+    assert!(frames.iter().all(|f| f["filename"].is_string()));
+    assert_eq!(
+        frames[0]["filename"],
+        "/data/users/xmfan/a/pytorch/test/inductor/test_torchinductor.py"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_max_artifact_bytes() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        max_artifact_bytes: Some(1000),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let (html_path, html) = map
+        .iter()
+        .find(|(f, _)| {
+            let f = f.to_string_lossy();
+            f.contains("inductor_output_code") && f.ends_with(".html")
+        })
+        .unwrap();
+    let full_path = html_path.with_file_name(format!(
+        "{}_full.txt",
+        html_path.file_stem().unwrap().to_string_lossy()
+    ));
+    let full = &map[&full_path];
+    assert!(full.len() > 1000);
+    assert!(html.contains(&format!(
+        "<a href=\"{}\">The full payload</a>",
+        full_path.file_name().unwrap().to_string_lossy()
+    )));
+    // Only the lines that fit are rendered
+    assert!(!html.contains(&format!("id=\"L{}\"", full.lines().count())));
+
+    // Smaller payloads are rendered in full
+    let full_map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())?
+        .into_iter()
+        .collect();
+    assert!(!full_map
+        .keys()
+        .any(|f| f.to_string_lossy().ends_with("_full.txt")));
+    assert!(!full_map[html_path].contains("The full payload"));
+    // The page is cut short, not the payload the parser renders it from
+    let banner_end = html.find("</div>\n").unwrap() + "</div>\n".len();
+    let shown = &html[banner_end..html.find("<style>\n.deep-link-target").unwrap()];
+    assert!(shown.len() <= 1000);
+    assert!(full_map[html_path].starts_with(shown));
+    // Parsers that only analyze payloads see them whole
+    let graph_stats = PathBuf::from("graph_stats.json");
+    assert_eq!(map[&graph_stats], full_map[&graph_stats]);
+    Ok(())
+}
+
+struct StreamingOutputCodeParser;
+impl tlparse::parsers::StructuredLogParser for StreamingOutputCodeParser {
+    fn name(&self) -> &'static str {
+        "streaming_output_code"
+    }
+    fn get_metadata<'e>(
+        &self,
+        e: &'e tlparse::parsers::Envelope,
+    ) -> Option<tlparse::parsers::Metadata<'e>> {
+        e.inductor_output_code
+            .as_ref()
+            .map(tlparse::parsers::Metadata::InductorOutputCode)
+    }
+    fn parse<'e>(
+        &self,
+        _lineno: usize,
+        _metadata: tlparse::parsers::Metadata<'e>,
+        _rank: Option<u32>,
+        _compile_id: &Option<tlparse::parsers::CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<tlparse::parsers::ParserResults> {
+        let payload = payload.to_string();
+        Ok(vec![tlparse::parsers::ParserOutput::FileWriter(
+            PathBuf::from("streamed_output_code.html"),
+            Box::new(move |w| {
+                w.write_all(b"<html><body>\n<pre>")?;
+                for line in payload.lines() {
+                    writeln!(w, "{}", html_escape::encode_text(line))?;
+                }
+                w.write_all(b"</pre>\n</body></html>\n")
+            }),
+        )])
+    }
+}
+
+#[test]
+fn test_file_writer_output() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        custom_parsers: vec![Box::new(StreamingOutputCodeParser)],
+        ..Default::default()
+    };
+    // Kept in memory without a directory to stream to
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let in_memory = &map[Path::new("streamed_output_code_0.html")];
+    assert!(in_memory.starts_with("<html><body>\n<pre>"));
+    assert!(in_memory.contains("</pre>\n"));
+    assert!(map[Path::new("compile_directory.json")].contains("streamed_output_code_0.html"));
+
+    // Streamed to disk, and linked from the index, but not returned
+    let temp_dir = tempdir().unwrap();
+    let (output, _) = tlparse::parse_path_to_directory(&[path.clone()], &config, temp_dir.path())?;
+    let map: HashMap<PathBuf, String> = output.into_iter().collect();
+    assert!(!map.contains_key(Path::new("streamed_output_code_0.html")));
+    assert!(map[Path::new("compile_directory.json")].contains("streamed_output_code_0.html"));
+    let streamed = fs::read_to_string(temp_dir.path().join("streamed_output_code_0.html"))?;
+    assert!(streamed.ends_with("</pre>\n</body></html>\n"));
+    assert!(in_memory.starts_with(&streamed[..streamed.find("</body>").unwrap()]));
+    assert!(temp_dir.path().join("raw.jsonl").exists());
+
+    // Streamed only up to max_artifact_bytes, with the full payload next to it
+    let config = tlparse::ParseConfig {
+        custom_parsers: vec![Box::new(StreamingOutputCodeParser)],
+        max_artifact_bytes: Some(1000),
+        ..Default::default()
+    };
+    let temp_dir = tempdir().unwrap();
+    let (output, _) = tlparse::parse_path_to_directory(&[path], &config, temp_dir.path())?;
+    let map: HashMap<PathBuf, String> = output.into_iter().collect();
+    let streamed = fs::read_to_string(temp_dir.path().join("streamed_output_code_0.html"))?;
+    let (shown, banner) = streamed.split_at(1000);
+    assert!(in_memory.starts_with(shown));
+    assert!(banner.contains("Truncated: only the first 1000 of"));
+    assert!(!banner.contains("</pre>"));
+    assert!(map.contains_key(Path::new("streamed_output_code_0_full.txt")));
+    Ok(())
+}
+
+#[test]
+fn test_compile_id_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())?
+        .into_iter()
+        .collect();
+    let summary = &map[Path::new("-_0_0_0/summary.html")];
+    assert!(summary.contains("<code>forward</code>"));
+    assert!(summary.contains("<td> Status </td> <td> Compiled </td>"));
+    assert!(summary.contains("<td> Guards </td> <td> 49 </td>"));
+    assert!(summary.contains("<td> Graph nodes </td> <td> 4 </td>"));
+    assert!(summary.contains(
+        r#"<a href="../-_0_0_0/fx_graph_cache_miss_0.json">fx_graph_cache_miss_0.json</a> </td> <td> miss </td>"#
+    ));
+    assert!(summary.contains(r#"<a href="../-_0_0_0/compilation_metrics_0.html">"#));
+    // The index links to it
+    assert!(map[Path::new("index.html")].contains("-_0_0_0/summary.html"));
+
+    let path = Path::new("tests/inputs/comp_failure.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())?
+        .into_iter()
+        .collect();
+    let summary = &map[Path::new("-_0_0_0/summary.html")];
+    assert!(summary.contains("<strong>Failed with BackendCompilerFailed</strong>"));
+    assert!(summary.contains("<h2>Failure</h2>"));
+    Ok(())
+}
+
+#[test]
+fn test_compile_id_summary_external_links() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/links.log").to_path_buf();
+    for (layout, summary_path, root) in [
+        (
+            tlparse::DirectoryLayout::Flat,
+            "-_0_0_0/summary.html",
+            "../",
+        ),
+        (
+            tlparse::DirectoryLayout::Nested,
+            "frame_0/compile_0/attempt_0/summary.html",
+            "../../../",
+        ),
+    ] {
+        let config = tlparse::ParseConfig {
+            directory_layout: layout,
+            ..Default::default()
+        };
+        let map: HashMap<PathBuf, String> =
+            tlparse::parse_path(&path, &config)?.into_iter().collect();
+        let summary = &map[Path::new(summary_path)];
+        // Links of the link parser aren't relative to the report
+        assert!(summary.contains(r#"<a href="https://www.google.com">manifold_url</a>"#));
+        assert!(!summary.contains(&format!("{root}https:")));
+        assert!(summary.contains(&format!(r#"href="{root}"#)));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_deep_links() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())?
+        .into_iter()
+        .collect();
+    // Every page can be deep linked to, including those not rendered from templates
+    for (path, content) in &map {
+        if path.extension().is_some_and(|e| e == "html") {
+            assert_eq!(
+                content.matches(r#"<script id="deep-link-script">"#).count(),
+                1,
+                "{}",
+                path.display()
+            );
+        }
+    }
+    let output_code = map
+        .iter()
+        .find(|(f, _)| f.to_string_lossy().contains("inductor_output_code"))
+        .map(|(_, content)| content)
+        .unwrap();
+    assert!(output_code.contains(r#"<span id="L1">"#));
+    let metrics = map
+        .iter()
+        .find(|(f, _)| f.to_string_lossy().contains("compilation_metrics"))
+        .map(|(_, content)| content)
+        .unwrap();
+    assert!(metrics.contains(r#"<h2 id="graph-metrics">"#));
+    // Graph dumps are plain text, so each has a page with its lines to link to
+    let graph = &map[Path::new("-_0_0_0/dynamo_output_graph_0.txt")];
+    let graph_view = &map[Path::new("-_0_0_0/dynamo_output_graph_0.html")];
+    let num_lines = graph.lines().count();
+    assert!(num_lines > 1);
+    for (i, line) in graph.lines().enumerate() {
+        let line = line
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        assert!(graph_view.contains(&format!("<span id=\"L{}\">{line}\n</span>", i + 1)));
+    }
+    assert!(!graph_view.contains(&format!("id=\"L{}\"", num_lines + 1)));
+    assert!(graph_view.contains(r#"<a href="dynamo_output_graph_0.txt">"#));
+
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())?
+        .into_iter()
+        .collect();
+    let guards = map
+        .iter()
+        .find(|(f, _)| f.to_string_lossy().contains("dynamo_guards"))
+        .map(|(_, content)| content)
+        .unwrap();
+    assert!(guards.contains(r#"<li id="guard-0">"#));
+    Ok(())
+}
+
+#[test]
+fn test_source_root() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let checkout = temp_dir.path().join("pytorch");
+    fs::create_dir_all(checkout.join("test/dynamo"))?;
+    let source: String = (1..=10100).map(|i| format!("source line {i}\n")).collect();
+    fs::write(checkout.join("test/dynamo/test_misc.py"), source)?;
+
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let metrics_page = |source_root: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let config = tlparse::ParseConfig {
+            source_roots: vec![source_root.parse()?],
+            ..Default::default()
+        };
+        let map: HashMap<PathBuf, String> =
+            tlparse::parse_path(&path, &config)?.into_iter().collect();
+        Ok(map
+            .iter()
+            .find(|(f, _)| {
+                f.to_string_lossy()
+                    .starts_with("-_0_0_1/compilation_metrics")
+            })
+            .map(|(_, content)| content.clone())
+            .unwrap())
+    };
+    // By the tail of the path, and by mapping the directory of the logged code
+    for source_root in [
+        checkout.display().to_string(),
+        format!("/data/users/jjwu/a/pytorch={}", checkout.display()),
+    ] {
+        let metrics = metrics_page(&source_root)?;
+        // The frame of the compile and the frame that called it
+        assert!(metrics.contains("<span class='current'>  9549  source line 9549</span>"));
+        assert!(metrics.contains("<span class='current'> 10079  source line 10079</span>"));
+        assert!(metrics.contains("<pre class='source-snippet'> 10077  source line 10077\n"));
+        assert!(!metrics.contains("source line 10076\n"));
+    }
+    // Files that aren't in the checkout fall back to file:line
+    let metrics = metrics_page(&temp_dir.path().join("missing").display().to_string())?;
+    assert!(!metrics.contains("source-snippet'>"));
+    assert!(metrics.contains("test_misc.py:10079 in &lt;module&gt;"));
+    // A file of the same name elsewhere isn't the logged one
+    let other = temp_dir.path().join("other");
+    fs::create_dir_all(&other)?;
+    fs::write(other.join("test_misc.py"), "unrelated\n".repeat(10100))?;
+    let metrics = metrics_page(&other.display().to_string())?;
+    assert!(!metrics.contains("unrelated"));
+
+    // Logged paths can't climb out of the directory they are mapped to
+    fs::create_dir_all(temp_dir.path().join("outside"))?;
+    fs::write(
+        temp_dir.path().join("outside/test_misc.py"),
+        "outside\n".repeat(10100),
+    )?;
+    let log = fs::read_to_string(&path)?.replace(
+        "/data/users/jjwu/a/pytorch/test/dynamo/test_misc.py",
+        "/data/users/jjwu/a/pytorch/../outside/test_misc.py",
+    );
+    let escaping_path = temp_dir.path().join("escaping.log");
+    fs::write(&escaping_path, log)?;
+    let config = tlparse::ParseConfig {
+        source_roots: vec![format!("/data/users/jjwu/a/pytorch={}", checkout.display()).parse()?],
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&escaping_path, &config)?;
+    assert!(output.iter().any(|(f, _)| f.starts_with("-_0_0_1")));
+    assert!(!output
+        .iter()
+        .any(|(_, content)| content.contains("outside\n")));
+    Ok(())
+}
+
+#[test]
+fn test_frame_locals_rendering() {
+    let log = fs::read_to_string("tests/inputs/simple.log").unwrap();
+    let long = "x".repeat(200);
+    let guard_added = format!(
+        r#"V1206 15:18:21.000000 1500233 torch/fx/experimental/symbolic_shapes.py:6000] {{"guard_added": {{"expr": "Eq(s0, 1)", "prefix": "eval", "expr_node_id": 1, "user_stack": [], "stack": [], "frame_locals": {{"loc": "out = F.gelu(x)", "locals": {{"x": "FakeTensor(..., device='cuda:0', size=(s0, 320), dtype=torch.float16)", "ys": "[Tensor(shape: torch.Size([4])), 3]", "name": "'{long}'"}}, "symbols": {{"s0": "L['x'].size()[0]"}}}}}}, "frame_id": 0, "frame_compile_id": 1, "attempt": 0}}"#
+    );
+    let metrics = r#"V1206 15:18:23.000000 1500233 torch/_dynamo/utils.py:1045] {"compilation_metrics": {"co_name": "forward", "co_filename": "/data/test.py", "co_firstlineno": 11123}, "frame_id": 0, "frame_compile_id": 1, "attempt": 0}"#;
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("locals.log");
+    fs::write(&path, format!("{log}\n{guard_added}\n{metrics}\n")).unwrap();
+
+    let output: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())
+            .unwrap()
+            .into_iter()
+            .collect();
+    let page = &output[Path::new("-_0_1_0/compilation_metrics_0.html")];
+    assert!(page.contains(r#"<h2 id="frame-locals">Frame Locals</h2>"#));
+    // Tensors get badges for their shape, dtype and device
+    assert!(page.contains("FakeTensor</span><span class='badge'>[s0, 320]</span><span class='badge'>float16</span><span class='badge'>cuda:0</span>"));
+    // Containers of tensors can be expanded
+    assert!(page.contains(
+        "<details><summary><code>[</code> of 2 item(s)</summary><ul><li><span class='tensor'"
+    ));
+    assert!(
+        page.contains("<span class='badge'>[4]</span></li><li><code>3</code></li></ul></details>")
+    );
+    // Long reprs are cut short
+    assert!(page.contains(&format!(
+        "<details class='long-repr'><summary><code>'{}</code>…</summary>",
+        "x".repeat(119)
+    )));
+    assert!(
+        page.contains("<tr><td><code>s0</code></td><td><code>L['x'].size()[0]</code></td></tr>")
+    );
+    // Compiles without locals don't get the section
+    assert!(
+        !output[Path::new("-_0_0_0/compilation_metrics_0.html")].contains(r#"id="frame-locals""#)
+    );
+}